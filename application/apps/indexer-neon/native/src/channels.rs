@@ -91,6 +91,18 @@ impl<T: 'static + Send + Debug + Serialize> Task for EventEmitterTask<T> {
                 o.set(&mut cx, "total", total)?;
                 o.upcast()
             }
+            Ok(IndexingProgress::Processed { bytes }) => {
+                // the size of the input is not known, a total of 0 tells so
+                let o = cx.empty_object();
+                let event_name = cx.string("Progress");
+                let ticked = cx.number(bytes as f64);
+                let total = cx.number(0);
+
+                o.set(&mut cx, "event", event_name)?;
+                o.set(&mut cx, "ellapsed", ticked)?;
+                o.set(&mut cx, "total", total)?;
+                o.upcast()
+            }
             Ok(IndexingProgress::GotItem { item: chunk }) => {
                 let event_name = cx.string("GotItem");
                 match neon_serde::to_value(&mut cx, &chunk) {
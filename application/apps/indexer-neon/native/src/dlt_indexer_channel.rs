@@ -78,6 +78,8 @@ fn index_dlt_file_with_progress(
         &tx,
         shutdown_receiver,
         fibex_metadata,
+        false,
+        false,
     ) {
         Err(why) => {
             error!("couldn't process: {}", why);
@@ -0,0 +1,109 @@
+use crate::channels::EventEmitterTask;
+use crossbeam_channel as cc;
+use dlt::dlt_lifecycle::LifecycleResults;
+use indexer_base::progress::{Notification, Severity};
+use neon::prelude::*;
+use std::path;
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+pub struct DltLifecycleEventEmitter {
+    pub event_receiver: Arc<Mutex<cc::Receiver<LifecycleResults>>>,
+    pub shutdown_sender: cc::Sender<()>,
+    pub task_thread: Option<std::thread::JoinHandle<()>>,
+}
+impl DltLifecycleEventEmitter {
+    pub fn start_dlt_lifecycles_in_thread(
+        self: &mut DltLifecycleEventEmitter,
+        source_file: path::PathBuf,
+        shutdown_rx: cc::Receiver<()>,
+        chunk_result_sender: cc::Sender<LifecycleResults>,
+    ) {
+        // Spawn a thread to continue running after this method has returned.
+        self.task_thread = Some(thread::spawn(move || {
+            dlt_lifecycles_with_progress(
+                source_file,
+                chunk_result_sender.clone(),
+                Some(shutdown_rx),
+            );
+            debug!("back after finding lifecycles finished!",);
+        }));
+    }
+}
+
+fn dlt_lifecycles_with_progress(
+    source_file: path::PathBuf,
+    tx: cc::Sender<LifecycleResults>,
+    shutdown_receiver: Option<cc::Receiver<()>>,
+) {
+    trace!("calling dlt lifecycles with progress");
+    match dlt::dlt_lifecycle::get_dlt_lifecycles(&source_file, &tx, shutdown_receiver) {
+        Err(why) => {
+            error!("couldn't find lifecycles: {}", why);
+            match tx.send(Err(Notification {
+                severity: Severity::ERROR,
+                content: format!("couldn't find lifecycles: {}", why),
+                line: None,
+            })) {
+                Ok(()) => (),
+                Err(_) => warn!("could not communicate errors to js"),
+            }
+        }
+        Ok(_) => trace!("get_dlt_lifecycles returned ok"),
+    }
+}
+
+// interface of the Rust code for js, exposes the `poll` and `shutdown` methods
+declare_types! {
+    pub class JsDltLifecycleEventEmitter for DltLifecycleEventEmitter {
+        init(mut cx) {
+            trace!("Rust: JsDltLifecycleEventEmitter");
+            let file_name = cx.argument::<JsString>(0)?.value();
+            let file_path = path::PathBuf::from(file_name);
+            let chunk_result_channel: (cc::Sender<LifecycleResults>, cc::Receiver<LifecycleResults>) = cc::unbounded();
+            let shutdown_channel = cc::unbounded();
+            let mut emitter = DltLifecycleEventEmitter {
+                event_receiver: Arc::new(Mutex::new(chunk_result_channel.1)),
+                shutdown_sender: shutdown_channel.0,
+                task_thread: None,
+            };
+            emitter.start_dlt_lifecycles_in_thread(
+                file_path,
+                shutdown_channel.1,
+                chunk_result_channel.0
+            );
+            Ok(emitter)
+        }
+
+        // will be called by JS to receive data in a loop, but care should be taken to only call it once at a time.
+        method poll(mut cx) {
+            // The callback to be executed when data is available
+            let cb = cx.argument::<JsFunction>(0)?;
+            let this = cx.this();
+
+            // Create an asynchronously `EventEmitterTask` to receive data
+            let events = cx.borrow(&this, |emitter| Arc::clone(&emitter.event_receiver));
+            let emitter = EventEmitterTask::new(events);
+
+            // Schedule the task on the `libuv` thread pool
+            emitter.schedule(cb);
+            Ok(JsUndefined::new().upcast())
+        }
+
+        // The shutdown method may be called to stop the Rust thread. It
+        // will error if the thread has already been destroyed.
+        method shutdown(mut cx) {
+            trace!("shutdown called");
+            let this = cx.this();
+
+            // Unwrap the shutdown channel and send a shutdown command
+            cx.borrow(&this, |emitter| {
+                match emitter.shutdown_sender.send(()) {
+                    Err(e) => trace!("error happened when sending: {}", e),
+                    Ok(()) => trace!("sent command Shutdown")
+                }
+            });
+            Ok(JsUndefined::new().upcast())
+        }
+    }
+}
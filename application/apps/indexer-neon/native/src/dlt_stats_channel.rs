@@ -33,7 +33,7 @@ fn dlt_stats_with_progress(
     shutdown_receiver: Option<cc::Receiver<()>>,
 ) {
     trace!("calling dlt stats with progress");
-    match dlt::dlt_parse::get_dlt_file_info(&source_file, &tx, shutdown_receiver) {
+    match dlt::dlt_parse::get_dlt_file_info(&source_file, &tx, shutdown_receiver, None, false) {
         Err(why) => {
             error!("couldn't collect statistics: {}", why);
             match tx.send(Err(Notification {
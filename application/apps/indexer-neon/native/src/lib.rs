@@ -13,8 +13,10 @@ extern crate serde;
 mod channels;
 mod concatenator_channel;
 mod dlt_indexer_channel;
+mod dlt_lifecycle_channel;
 mod dlt_socket_channel;
 mod dlt_stats_channel;
+mod dlt_tcp_channel;
 mod fibex_utils;
 mod indexer_channel;
 mod logging;
@@ -23,8 +25,10 @@ mod timestamp_detector_channel;
 use concatenator_channel::JsConcatenatorEmitter;
 use crossbeam_channel as cc;
 use dlt_indexer_channel::JsDltIndexerEventEmitter;
+use dlt_lifecycle_channel::JsDltLifecycleEventEmitter;
 use dlt_socket_channel::JsDltSocketEventEmitter;
 use dlt_stats_channel::JsDltStatsEventEmitter;
+use dlt_tcp_channel::JsDltTcpEventEmitter;
 use indexer_base::progress::{IndexingProgress, IndexingResults};
 use indexer_base::progress::{Notification, Severity};
 use indexer_channel::JsIndexerEventEmitter;
@@ -120,6 +124,9 @@ fn detect_timestamp_format_in_file(mut cx: FunctionContext) -> JsResult<JsValue>
             Ok(Ok(IndexingProgress::Progress { ticks: t })) => {
                 trace!("progress... ({:.1} %)", (t.0 as f64 / t.1 as f64) * 100.0);
             }
+            Ok(Ok(IndexingProgress::Processed { bytes })) => {
+                trace!("progress... ({} bytes)", bytes);
+            }
             Ok(Ok(IndexingProgress::Finished)) => {
                 trace!("finished...");
             }
@@ -155,7 +162,9 @@ register_module!(mut cx, {
     cx.export_class::<JsIndexerEventEmitter>("RustIndexerEventEmitter")?;
     cx.export_class::<JsDltIndexerEventEmitter>("RustDltIndexerEventEmitter")?;
     cx.export_class::<JsDltStatsEventEmitter>("RustDltStatsEventEmitter")?;
+    cx.export_class::<JsDltLifecycleEventEmitter>("RustDltLifecycleEventEmitter")?;
     cx.export_class::<JsDltSocketEventEmitter>("RustDltSocketEventEmitter")?;
+    cx.export_class::<JsDltTcpEventEmitter>("RustDltTcpEventEmitter")?;
     cx.export_class::<JsTimestampFormatDetectionEmitter>("RustTimestampFormatDetectionEmitter")?;
     cx.export_class::<JsConcatenatorEmitter>("RustConcatenatorEmitter")?;
     cx.export_class::<JsMergerEmitter>("RustMergerEmitter")?;
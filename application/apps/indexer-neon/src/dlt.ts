@@ -1,6 +1,6 @@
 import { log } from './logging';
 import { ITicks, INeonTransferChunk, INeonNotification, IChunk } from './progress';
-import { NativeEventEmitter, RustDltIndexerChannel, RustDltStatsChannel, RustDltSocketChannel } from './emitter';
+import { NativeEventEmitter, RustDltIndexerChannel, RustDltStatsChannel, RustDltLifecycleChannel, RustDltSocketChannel, RustDltTcpChannel } from './emitter';
 import { CancelablePromise } from './promise';
 import { 
 	IDLTFilters,
@@ -10,6 +10,7 @@ import {
 	DltLogLevel,
 	LevelDistribution,
 	StatisticInfo,
+	EcuLifecycle,
 	IFibexConfig
 } from '../../../common/interfaces/interface.dlt';
 
@@ -21,6 +22,7 @@ export {
 	DltLogLevel,
 	LevelDistribution,
 	StatisticInfo,
+	EcuLifecycle,
 	IFibexConfig
 }
 
@@ -38,6 +40,13 @@ export interface ISocketConfig {
     bind_addr: string,
     port: string,
 }
+/// TCP client config
+/// `address` is the ip address or hostname of the dlt server (e.g. a dlt-daemon)
+/// `port` is the port the server listens on (dlt-daemon default is 3490)
+export interface ITcpConfig {
+    address: string,
+    port: string,
+}
 /// Multicast config information.
 /// `multiaddr` address must be a valid multicast address
 /// `interface` is the address of the local interface with which the
@@ -116,6 +125,70 @@ export function dltStatsAsync(
 	});
 }
 
+export type TDltLifecyclesEvents = 'lifecycles' | 'progress' | 'notification';
+export type TDltLifecyclesEventLifecycles = (event: EcuLifecycle[]) => void;
+export type TDltLifecyclesEventProgress = (event: ITicks) => void;
+export type TDltLifecyclesEventNotification = (event: INeonNotification) => void;
+export type TDltLifecyclesEventObject =
+	| TDltLifecyclesEventLifecycles
+	| TDltLifecyclesEventProgress
+	| TDltLifecyclesEventNotification;
+
+export function dltLifecyclesAsync(
+	dltFile: string
+): CancelablePromise<void, void, TDltLifecyclesEvents, TDltLifecyclesEventObject> {
+	return new CancelablePromise<
+		void,
+		void,
+		TDltLifecyclesEvents,
+		TDltLifecyclesEventObject
+	>((resolve, reject, cancel, refCancelCB, self) => {
+		try {
+			// Add cancel callback
+			refCancelCB(() => {
+				// Cancelation is started, but not canceled
+				log(`Get command "break" operation. Starting breaking.`);
+				emitter.requestShutdown();
+			});
+			const channel = new RustDltLifecycleChannel(dltFile);
+			const emitter = new NativeEventEmitter(channel);
+			let total: number = 1;
+			emitter.on(NativeEventEmitter.EVENTS.GotItem, (lifecycles: EcuLifecycle[]) => {
+				self.emit('lifecycles', lifecycles);
+			});
+			emitter.on(NativeEventEmitter.EVENTS.Progress, (ticks: ITicks) => {
+				total = ticks.total;
+				self.emit('progress', ticks);
+			});
+			emitter.on(NativeEventEmitter.EVENTS.Stopped, () => {
+				emitter.shutdownAcknowledged(() => {
+					cancel();
+				});
+			});
+			emitter.on(NativeEventEmitter.EVENTS.Notification, (notification: INeonNotification) => {
+				log('dltLifecycles: we got a notification: ' + JSON.stringify(notification));
+				self.emit('notification', notification);
+			});
+			emitter.on(NativeEventEmitter.EVENTS.Finished, () => {
+				emitter.shutdownAcknowledged(() => {
+					self.emit('progress', { ellapsed: total, total });
+					resolve();
+				});
+			});
+		} catch (err) {
+			if (!(err instanceof Error)) {
+				log(`operation is stopped. Error isn't valid:`);
+				log(err);
+				err = new Error(`operation is stopped. Error isn't valid.`);
+			} else {
+				log(`operation is stopped due error: ${err.message}`);
+			}
+			// Operation is rejected
+			reject(err);
+		}
+	});
+}
+
 export type TIndexDltAsyncEvents = 'chunk' | 'progress' | 'notification';
 export type TIndexDltAsyncEventChunk = (event: IChunk) => void;
 export type TIndexDltAsyncEventProgress = (event: ITicks) => void;
@@ -306,6 +379,93 @@ export function dltOverSocket(
 	});
 }
 
+export function dltOverTcp(
+	ecuId: string,
+	params: IDltSocketParams,
+	tcpConfig: ITcpConfig,
+): CancelablePromise<void, void, TDLTSocketEvents, TDLTSocketEventObject> {
+	return new CancelablePromise<
+		void,
+		void,
+		TDLTSocketEvents,
+		TDLTSocketEventObject
+	>((resolve, reject, cancel, refCancelCB, self) => {
+		log(`dltOverTcp: params: ${JSON.stringify(params)}`);
+		try {
+			log(`dltOverTcp: using tcp-conf: ${JSON.stringify(tcpConfig)}`);
+			// Add cancel callback
+			refCancelCB(() => {
+				// Cancelation is started, but not canceled
+				log(`Get command "break" operation. Starting breaking.`);
+				emitter.requestShutdown();
+			});
+			// Create channel
+			const channel = new RustDltTcpChannel(
+				ecuId,
+				tcpConfig,
+				params.tag,
+				params.out,
+				params.filterConfig,
+				params.fibex
+			);
+			// Create emitter
+			const emitter: NativeEventEmitter = new NativeEventEmitter(channel);
+			let chunks: number = 0;
+			// Add listenters
+			emitter.on(NativeEventEmitter.EVENTS.GotItem, (c: INeonTransferChunk) => {
+				log('received over tcp: ' + JSON.stringify(c));
+				if (c.b[0] === 0 && c.b[1] === 0) {
+					self.emit('connect');
+				} else {
+					self.emit('chunk', {
+						bytesStart: c.b[0],
+						bytesEnd: c.b[1],
+						rowsStart: c.r[0],
+						rowsEnd: c.r[1]
+					});
+					chunks += 1;
+				}
+			});
+			emitter.on(NativeEventEmitter.EVENTS.Progress, (ticks: ITicks) => {
+				self.emit('progress', ticks);
+			});
+			emitter.on(NativeEventEmitter.EVENTS.Stopped, () => {
+				log('we got a stopped event after ' + chunks + ' chunks');
+				emitter.shutdownAcknowledged(() => {
+					log('tcpDlt: shutdown completed after we got stopped');
+					// Operation is canceled.
+					cancel();
+				});
+			});
+			emitter.on(NativeEventEmitter.EVENTS.Notification, (notification: INeonNotification) => {
+				self.emit('notification', notification);
+			});
+			emitter.on(NativeEventEmitter.EVENTS.Finished, () => {
+				log('we got a finished event after ' + chunks + ' chunks');
+				emitter.shutdownAcknowledged(() => {
+					log('tcpDlt: shutdown completed after finish event');
+					// Operation is done.
+					resolve();
+				});
+			});
+			// Handle finale of promise
+			self.finally(() => {
+				log('processing dlt indexing is finished');
+			});
+		} catch (err) {
+			if (!(err instanceof Error)) {
+				log(`operation is stopped. Error isn't valid:`);
+				log(err);
+				err = new Error(`operation is stopped. Error isn't valid.`);
+			} else {
+				log(`operation is stopped due error: ${err.message}`);
+			}
+			// Operation is rejected
+			reject(err);
+		}
+	});
+}
+
 function getDefaultIndexDltProcessingOptions(options: IIndexDltOptions | undefined): IIndexDltOptionsChecked {
 	if (typeof options !== 'object' || options === null) {
 		options = {};
@@ -2,7 +2,9 @@ const {
     RustIndexerEventEmitter: RustIndexerChannel,
     RustDltIndexerEventEmitter: RustDltIndexerChannel,
     RustDltStatsEventEmitter: RustDltStatsChannel,
+    RustDltLifecycleEventEmitter: RustDltLifecycleChannel,
     RustDltSocketEventEmitter: RustDltSocketChannel,
+    RustDltTcpEventEmitter: RustDltTcpChannel,
     RustTimestampFormatDetectionEmitter: RustTimestampChannel,
     RustConcatenatorEmitter: RustConcatenatorChannel,
     RustMergerEmitter: RustMergerChannel,
@@ -13,7 +15,9 @@ export {
     RustIndexerChannel,
     RustDltIndexerChannel,
     RustDltStatsChannel,
+    RustDltLifecycleChannel,
     RustDltSocketChannel,
+    RustDltTcpChannel,
     RustTimestampChannel,
     RustConcatenatorChannel,
     RustMergerChannel,
@@ -10,8 +10,10 @@ export { CancelablePromise, Processor, Progress, DLT, Merge, Units };
 export default {
     // DLT
     dltStatsAsync               : DLT.dltStatsAsync,
+    dltLifecyclesAsync          : DLT.dltLifecyclesAsync,
     indexDltAsync               : DLT.indexDltAsync,
     dltOverSocket               : DLT.dltOverSocket,
+    dltOverTcp                  : DLT.dltOverTcp,
     // Indexing
     indexAsync                  : Processor.indexAsync,
     detectTimestampInString     : Processor.detectTimestampInString,
@@ -5,6 +5,7 @@ export enum AsyncResult {
 }
 export interface ITicks {
     ellapsed: number,
+    // 0 if the size of the input is not known
     total: number,
 }
 export interface IChunk {
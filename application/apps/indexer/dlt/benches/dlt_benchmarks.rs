@@ -5,6 +5,14 @@ extern crate dlt;
 // use dlt::dlt::*;
 use bytes::{BytesMut};
 use criterion::Criterion;
+use crossbeam_channel as cc;
+use dlt::dlt_parse::StatisticsResults;
+use indexer_base::chunks::ChunkResults;
+use indexer_base::config::IndexingConfig;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use tempdir::TempDir;
 
 fn dlt_benchmark(c: &mut Criterion) {
     c.bench_function("format header", |b| {
@@ -73,5 +81,119 @@ fn dlt_parse_benchmark(c: &mut Criterion) {
     });
 }
 
-criterion_group!(benches, dlt_benchmark, dlt_parse_benchmark);
-criterion_main!(benches);
+fn dlt_pattern_benchmark(c: &mut Criterion) {
+    c.bench_function("find storage header after 1 MB of garbage", |b| {
+        // pseudo random bytes without a storage header pattern
+        let mut seed = 0x1234_5678u32;
+        let mut buf: Vec<u8> = (0..1024 * 1024)
+            .map(|_| {
+                seed = seed.wrapping_mul(1_103_515_245).wrapping_add(12345);
+                (seed >> 16) as u8 & 0x7F
+            })
+            .collect();
+        buf.extend_from_slice(&[0x44, 0x4C, 0x54, 0x01]);
+        b.iter(|| dlt::dlt_parse::find_storage_header_pattern(&buf))
+    });
+}
+
+/// a dlt file of some MB in `dir` created from the test file
+fn create_bench_file(dir: &Path) -> PathBuf {
+    let content = fs::read(
+        PathBuf::from("..")
+            .join("../indexer-neon/tests")
+            .join("testfile.dlt"),
+    )
+    .expect("could not read testfile");
+    let bench_file = dir.join("dlt_bench_input.dlt");
+    let mut out = fs::File::create(&bench_file).expect("could not create bench file");
+    for _ in 0..500 {
+        out.write_all(&content).expect("could not write bench file");
+    }
+    bench_file
+}
+
+fn dlt_file_benchmark(c: &mut Criterion) {
+    let tmp_dir = TempDir::new("test_dir").expect("could not create temp dir");
+    let bench_file = create_bench_file(tmp_dir.path());
+    let in_file = bench_file.clone();
+    let out_path = tmp_dir.path().join("dlt_bench_input.dlt.out");
+    c.bench_function("index dlt file", move |b| {
+        b.iter(|| {
+            let (tx, rx): (cc::Sender<ChunkResults>, cc::Receiver<ChunkResults>) = cc::unbounded();
+            dlt::dlt_parse::create_index_and_mapping_dlt(
+                IndexingConfig {
+                    tag: "TAG",
+                    chunk_size: 500,
+                    in_file: in_file.clone(),
+                    out_path: &out_path,
+                    append: false,
+                },
+                None,
+                None,
+                &tx,
+                None,
+                None,
+                false,
+                false,
+            )
+            .expect("indexing failed");
+            drop(rx);
+        })
+    });
+    let in_file = bench_file.clone();
+    let out_path = tmp_dir.path().join("dlt_bench_input.dlt.out");
+    c.bench_function("index memory mapped dlt file", move |b| {
+        b.iter(|| {
+            let (tx, rx): (cc::Sender<ChunkResults>, cc::Receiver<ChunkResults>) = cc::unbounded();
+            dlt::dlt_parse::create_index_and_mapping_dlt(
+                IndexingConfig {
+                    tag: "TAG",
+                    chunk_size: 500,
+                    in_file: in_file.clone(),
+                    out_path: &out_path,
+                    append: false,
+                },
+                None,
+                None,
+                &tx,
+                None,
+                None,
+                true,
+                false,
+            )
+            .expect("indexing failed");
+            drop(rx);
+        })
+    });
+    let in_file = bench_file.clone();
+    c.bench_function("dlt file statistics", move |b| {
+        b.iter(|| {
+            let (tx, rx): (
+                cc::Sender<StatisticsResults>,
+                cc::Receiver<StatisticsResults>,
+            ) = cc::unbounded();
+            dlt::dlt_parse::get_dlt_file_info(&in_file, &tx, None, None, false)
+                .expect("statistics failed");
+            drop(rx);
+        })
+    });
+    c.bench_function("memory mapped dlt file statistics", move |b| {
+        b.iter(|| {
+            let (tx, rx): (
+                cc::Sender<StatisticsResults>,
+                cc::Receiver<StatisticsResults>,
+            ) = cc::unbounded();
+            dlt::dlt_parse::get_dlt_file_info(&bench_file, &tx, None, None, true)
+                .expect("statistics failed");
+            drop(rx);
+        })
+    });
+}
+
+criterion_group!(benches, dlt_benchmark, dlt_parse_benchmark, dlt_pattern_benchmark);
+criterion_group! {
+    name = file_benches;
+    config = Criterion::default().sample_size(10);
+    targets = dlt_file_benchmark
+}
+criterion_main!(benches, file_benches);
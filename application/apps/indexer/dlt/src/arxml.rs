@@ -0,0 +1,826 @@
+// Copyright (c) 2020 E.S.R.Labs. All rights reserved.
+//
+// NOTICE:  All information contained herein is, and remains
+// the property of E.S.R.Labs and its suppliers, if any.
+// The intellectual and technical concepts contained herein are
+// proprietary to E.S.R.Labs and its suppliers and may be covered
+// by German and Foreign Patents, patents in process, and are protected
+// by trade secret or copyright law.
+// Dissemination of this information or reproduction of this material
+// is strictly forbidden unless prior written permission is obtained
+// from E.S.R.Labs.
+//! Non-verbose message descriptions from AUTOSAR ARXML files
+//!
+//! A `DLT-MESSAGE` describes one non-verbose message: its `MESSAGE-ID`, the
+//! log level in `MESSAGE-TYPE-INFO` (e.g. `DLT_LOG_INFO`) and one
+//! `DLT-ARGUMENT` for every value of the payload. The type of an argument
+//! is the `SW-BASE-TYPE` that its `BASE-TYPE-REF` refers to, a
+//! `COMPU-METHOD-REF` converts the value like the compu methods of FIBEX.
+//! A `DLT-LOG-CHANNEL` gives the `APPLICATION-ID` and `CONTEXT-ID` of the
+//! messages it refers to with `DLT-MESSAGE-REF`, messages without log
+//! channel are looked up by their id only.
+//! The messages are read into the same metadata as FIBEX files, so they are
+//! decoded the same way.
+use crate::dlt::{FloatWidth, LogLevel, StringCoding, TypeInfo, TypeInfoKind, TypeLength};
+use crate::fibex::{
+    first_definitions, insert_first, locate_warnings, ApplicationId, CompuCategory, CompuMethod,
+    CompuScale, ContextId, FibexMetadata, FibexReport, FrameId, FrameMetadata, Origin, PduMetadata,
+    PositionedWarning, SignalMetadata,
+};
+use failure::{bail, format_err, Error};
+use quick_xml::{events::Event as XmlEvent, Reader as XmlReader};
+use std::{
+    collections::{hash_map::Entry, HashMap, HashSet},
+    fs::File,
+    io::BufReader,
+    mem,
+    path::{Path, PathBuf},
+    rc::Rc,
+    str::FromStr,
+};
+
+type Result<T = ()> = std::result::Result<T, Error>;
+
+pub(crate) fn is_arxml(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("arxml"))
+}
+
+/// reads the DLT messages of ARXML files, the elements that can't be used
+/// are left out with a warning in the log
+/// only files that can't be opened are an error
+pub fn read_arxmls(files: Vec<PathBuf>) -> Result<FibexMetadata> {
+    let report = read_arxmls_with_report(files)?;
+    for warning in &report.warnings {
+        warn!("{}", warning);
+    }
+    Ok(report.metadata)
+}
+
+/// reads the DLT messages of ARXML files and reports the elements that can't
+/// be used, like `read_fibexes_with_report`
+/// elements are referenced by their path (e.g. `/BaseTypes/uint8`), so the
+/// warnings name them by path as well
+pub fn read_arxmls_with_report(files: Vec<PathBuf>) -> Result<FibexReport> {
+    let mut data = ArxmlReadData::default();
+    let mut warnings = vec![];
+    for f in files {
+        ArxmlReader::from_file(f, &mut data, &mut warnings)?.read();
+    }
+    let ArxmlReadData {
+        messages,
+        channels,
+        definitions,
+    } = data;
+    // files can define the same element again, the first definition is used
+    let messages = first_definitions(
+        messages,
+        B_DLT_MESSAGE,
+        |path, _| path.clone(),
+        &mut warnings,
+    );
+    let channels = first_definitions(
+        channels,
+        B_DLT_LOG_CHANNEL,
+        |path, _| path.clone(),
+        &mut warnings,
+    );
+    let message_paths: HashSet<&String> = messages.iter().map(|(path, _, _)| path).collect();
+    let mut ids_of_message: HashMap<String, Vec<(ApplicationId, ContextId)>> = HashMap::new();
+    for (path, origin, channel) in channels {
+        let (application_id, context_id) = match (channel.application_id, channel.context_id) {
+            (Some(application_id), Some(context_id)) => {
+                (ApplicationId(application_id), ContextId(context_id))
+            }
+            _ => {
+                warnings.push(origin.warning(
+                    B_DLT_LOG_CHANNEL,
+                    &path,
+                    "skipped: APPLICATION-ID and CONTEXT-ID are required".to_string(),
+                ));
+                continue;
+            }
+        };
+        for message_ref in channel.message_refs {
+            if !message_paths.contains(&message_ref) {
+                warnings.push(origin.warning(
+                    B_DLT_LOG_CHANNEL,
+                    &path,
+                    format!("DLT-MESSAGE {} not found", message_ref),
+                ));
+                continue;
+            }
+            ids_of_message
+                .entry(message_ref)
+                .or_default()
+                .push((application_id.clone(), context_id.clone()));
+        }
+    }
+    let mut frame_map_with_key = HashMap::new();
+    let mut frame_map = HashMap::new();
+    let mut frame_count = 0;
+    let mut pdu_count = 0;
+    for (path, origin, message) in messages {
+        let (message_id, frame) = match definitions.frame(message) {
+            Ok(frame) => frame,
+            Err(e) => {
+                warnings.push(origin.warning(B_DLT_MESSAGE, &path, format!("skipped: {}", e)));
+                continue;
+            }
+        };
+        let id = FrameId(format!("ID_{}", message_id));
+        let mut used = false;
+        match ids_of_message.remove(&path) {
+            Some(ids) => {
+                for (application_id, context_id) in ids {
+                    let key = (context_id.clone(), application_id.clone(), id.clone());
+                    match frame_map_with_key.entry(key) {
+                        Entry::Occupied(_) => warnings.push(origin.warning(
+                            B_DLT_MESSAGE,
+                            &path,
+                            format!(
+                                "skipped for {}/{}: ID {} is used by another message",
+                                application_id, context_id, message_id
+                            ),
+                        )),
+                        Entry::Vacant(entry) => {
+                            let frame = Rc::new(FrameMetadata {
+                                application_id: Some(application_id),
+                                context_id: Some(context_id),
+                                ..frame.clone()
+                            });
+                            frame_map.entry(id.clone()).or_insert_with(|| frame.clone());
+                            entry.insert(frame);
+                            used = true;
+                        }
+                    }
+                }
+            }
+            None => match frame_map.entry(id) {
+                Entry::Occupied(_) => warnings.push(origin.warning(
+                    B_DLT_MESSAGE,
+                    &path,
+                    format!("skipped: ID {} is used by another message", message_id),
+                )),
+                Entry::Vacant(entry) => {
+                    entry.insert(Rc::new(frame.clone()));
+                    used = true;
+                }
+            },
+        }
+        if used {
+            frame_count += 1;
+            pdu_count += frame.pdus.len();
+        }
+    }
+    Ok(FibexReport {
+        metadata: FibexMetadata {
+            frame_map_with_key,
+            frame_map,
+            services: HashMap::new(),
+            can_messages: HashMap::new(),
+        },
+        frames: frame_count,
+        pdus: pdu_count,
+        services: 0,
+        can_messages: 0,
+        warnings: locate_warnings(warnings),
+    })
+}
+
+#[derive(Default, PartialEq)]
+struct MessageReadData {
+    short_name: String,
+    message_id: Option<u32>,
+    message_type_info: Option<String>,
+    arguments: Vec<ArgumentReadData>,
+}
+
+#[derive(Default, PartialEq)]
+struct ArgumentReadData {
+    short_name: Option<String>,
+    base_type_ref: Option<String>,
+    compu_method_ref: Option<String>,
+    /// arguments with arguments of their own (structures) can't be decoded
+    nested: bool,
+}
+
+#[derive(Default, PartialEq)]
+struct BaseTypeReadData {
+    size: Option<usize>,
+    encoding: Option<String>,
+}
+
+#[derive(Default)]
+struct CompuMethodReadData {
+    category: Option<String>,
+    unit_ref: Option<String>,
+    scales: Vec<CompuScale>,
+}
+
+/// `None` for methods that don't convert values
+type CompuMethodData = Option<(CompuCategory, Option<String>, Vec<CompuScale>)>;
+
+#[derive(Default, PartialEq)]
+struct ChannelReadData {
+    application_id: Option<String>,
+    context_id: Option<String>,
+    message_refs: Vec<String>,
+}
+
+/// elements that are referenced by their path
+#[derive(Default)]
+struct Definitions {
+    base_types: HashMap<String, BaseTypeReadData>,
+    compu_methods: HashMap<String, CompuMethodData>,
+    units: HashMap<String, String>,
+}
+impl Definitions {
+    /// the frame of a message without application and context id
+    fn frame(&self, message: MessageReadData) -> Result<(u32, FrameMetadata)> {
+        let message_id = message
+            .message_id
+            .ok_or_else(|| format_err!("required MESSAGE-ID is missing"))?;
+        let pdus = message
+            .arguments
+            .iter()
+            .map(|argument| {
+                Ok(Rc::new(PduMetadata {
+                    description: None,
+                    signals: vec![self.signal(argument)?],
+                }))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let message_type = message
+            .message_type_info
+            .as_ref()
+            .and_then(|info| LogLevel::from_fibex_message_info(info))
+            .map(|_| "DLT_TYPE_LOG".to_string());
+        Ok((
+            message_id,
+            FrameMetadata {
+                short_name: message.short_name,
+                pdus,
+                application_id: None,
+                context_id: None,
+                message_type,
+                message_info: message.message_type_info,
+            },
+        ))
+    }
+
+    fn signal(&self, argument: &ArgumentReadData) -> Result<SignalMetadata> {
+        let name = argument.short_name.as_deref().unwrap_or("-");
+        if argument.nested {
+            bail!("structured argument {} is not supported", name);
+        }
+        let base_type_ref = argument
+            .base_type_ref
+            .as_ref()
+            .ok_or_else(|| format_err!("argument {} has no BASE-TYPE-REF", name))?;
+        let base_type = self
+            .base_types
+            .get(base_type_ref)
+            .ok_or_else(|| format_err!("SW-BASE-TYPE {} not found", base_type_ref))?;
+        let type_info = match &base_type.encoding {
+            Some(encoding) => {
+                type_info_for_base_type(encoding, base_type.size).ok_or_else(|| {
+                    format_err!(
+                        "unsupported SW-BASE-TYPE {}: encoding {} with {}",
+                        base_type_ref,
+                        encoding,
+                        match base_type.size {
+                            Some(size) => format!("{} bits", size),
+                            None => "no BASE-TYPE-SIZE".to_string(),
+                        }
+                    )
+                })?
+            }
+            None => bail!("SW-BASE-TYPE {} has no BASE-TYPE-ENCODING", base_type_ref),
+        };
+        let compu_method = match &argument.compu_method_ref {
+            Some(compu_method_ref) => match self
+                .compu_methods
+                .get(compu_method_ref)
+                .ok_or_else(|| format_err!("COMPU-METHOD {} not found", compu_method_ref))?
+            {
+                Some((CompuCategory::Identical, None, _)) | None => None,
+                Some((category, unit_ref, scales)) => Some(Rc::new(CompuMethod {
+                    category: category.clone(),
+                    scales: scales.clone(),
+                    unit: unit_ref
+                        .as_ref()
+                        .and_then(|unit_ref| self.units.get(unit_ref).cloned()),
+                })),
+            },
+            None => None,
+        };
+        Ok(SignalMetadata {
+            type_info,
+            compu_method,
+        })
+    }
+}
+
+/// type of an argument from the `BASE-TYPE-ENCODING` and the
+/// `BASE-TYPE-SIZE` (in bits) of its base type
+fn type_info_for_base_type(encoding: &str, size: Option<usize>) -> Option<TypeInfo> {
+    let length = || match size {
+        Some(8) => Some(TypeLength::BitLength8),
+        Some(16) => Some(TypeLength::BitLength16),
+        Some(32) => Some(TypeLength::BitLength32),
+        Some(64) => Some(TypeLength::BitLength64),
+        Some(128) => Some(TypeLength::BitLength128),
+        _ => None,
+    };
+    let (kind, coding) = match encoding {
+        "BOOLEAN" => (TypeInfoKind::Bool, StringCoding::ASCII),
+        "NONE" => (TypeInfoKind::Unsigned(length()?), StringCoding::ASCII),
+        "2C" => (TypeInfoKind::Signed(length()?), StringCoding::ASCII),
+        "IEEE754" => (
+            TypeInfoKind::Float(match size {
+                Some(16) => FloatWidth::Width16,
+                Some(32) => FloatWidth::Width32,
+                Some(64) => FloatWidth::Width64,
+                _ => return None,
+            }),
+            StringCoding::ASCII,
+        ),
+        "UTF-8" => (TypeInfoKind::StringType, StringCoding::UTF8),
+        "ISO-8859-1" | "WINDOWS-1252" => (TypeInfoKind::StringType, StringCoding::ASCII),
+        _ => return None,
+    };
+    Some(TypeInfo {
+        kind,
+        coding,
+        has_variable_info: false,
+        has_trace_info: false,
+    })
+}
+
+/// elements of all files, they can refer to elements of other files
+#[derive(Default)]
+struct ArxmlReadData {
+    messages: Vec<(String, Origin, MessageReadData)>,
+    channels: Vec<(String, Origin, ChannelReadData)>,
+    definitions: Definitions,
+}
+
+/// an open xml element, the SHORT-NAMEs of all open elements give the path
+/// of the innermost one
+struct OpenElement {
+    name: Vec<u8>,
+    short_name: Option<String>,
+}
+
+/// reads the elements of one file into the data of all files
+/// DLT-MESSAGE, SW-BASE-TYPE, COMPU-METHOD, UNIT and DLT-LOG-CHANNEL don't
+/// contain each other, so only one of them is read at a time
+struct ArxmlReader<'a> {
+    xml_reader: XmlReader<BufReader<File>>,
+    file: Rc<PathBuf>,
+    buf: Vec<u8>,
+    /// text of the element that ends next
+    text: String,
+    open: Vec<OpenElement>,
+    data: &'a mut ArxmlReadData,
+    warnings: &'a mut Vec<PositionedWarning>,
+    /// start of the element that is read
+    origin: Option<Origin>,
+    /// the first error in the element that is read, it is skipped
+    error: Option<String>,
+    message: Option<MessageReadData>,
+    argument: Option<ArgumentReadData>,
+    /// number of open DLT-ARGUMENTs
+    argument_depth: usize,
+    base_type: Option<BaseTypeReadData>,
+    compu_method: Option<CompuMethodReadData>,
+    compu_scale: Option<CompuScale>,
+    /// values of a COMPU-NUMERATOR or COMPU-DENOMINATOR that is read
+    coefficients: Option<Vec<f64>>,
+    /// only the conversion from internal to physical values is used
+    in_phys_to_internal: bool,
+    /// display name of a UNIT that is read
+    unit: Option<Option<String>>,
+    channel: Option<ChannelReadData>,
+}
+
+impl<'a> ArxmlReader<'a> {
+    fn from_file(
+        path: PathBuf,
+        data: &'a mut ArxmlReadData,
+        warnings: &'a mut Vec<PositionedWarning>,
+    ) -> Result<Self> {
+        Ok(ArxmlReader {
+            xml_reader: XmlReader::from_file(&path)?,
+            file: Rc::new(path),
+            buf: vec![],
+            text: String::new(),
+            open: vec![],
+            data,
+            warnings,
+            origin: None,
+            error: None,
+            message: None,
+            argument: None,
+            argument_depth: 0,
+            base_type: None,
+            compu_method: None,
+            compu_scale: None,
+            coefficients: None,
+            in_phys_to_internal: false,
+            unit: None,
+            channel: None,
+        })
+    }
+
+    /// errors in the xml syntax stop the reading of the file, the elements
+    /// before them are kept
+    fn read(mut self) {
+        if let Err(e) = self.read_elements() {
+            let element = self
+                .open
+                .last()
+                .map(|e| String::from_utf8_lossy(&e.name).to_string())
+                .unwrap_or_default();
+            self.warnings.push(PositionedWarning {
+                file: self.file.as_ref().clone(),
+                position: self.xml_reader.buffer_position(),
+                element,
+                id: None,
+                reason: format!("reading of the file stopped: {}", e),
+            });
+        }
+    }
+
+    fn read_elements(&mut self) -> Result {
+        loop {
+            self.buf.clear();
+            match self.xml_reader.read_event(&mut self.buf)? {
+                XmlEvent::Start(e) => {
+                    let name = e.local_name().to_vec();
+                    self.start(name);
+                }
+                XmlEvent::Empty(e) => {
+                    let name = e.local_name().to_vec();
+                    self.start(name);
+                    self.end();
+                }
+                XmlEvent::Text(e) => {
+                    let text = e.unescape_and_decode(&self.xml_reader)?;
+                    self.text.push_str(&text);
+                }
+                XmlEvent::End(_) => self.end(),
+                XmlEvent::Eof => return Ok(()),
+                _ => {}
+            }
+        }
+    }
+
+    /// path of the innermost open element
+    fn path(&self) -> String {
+        self.open
+            .iter()
+            .filter_map(|e| e.short_name.as_ref())
+            .fold(String::new(), |path, name| path + "/" + name)
+    }
+
+    fn start_element(&mut self) {
+        self.origin = Some(Origin {
+            file: self.file.clone(),
+            position: self.xml_reader.buffer_position(),
+        });
+        self.error = None;
+    }
+
+    /// the origin and SHORT-NAME of an element that was read without errors
+    fn end_element(
+        &mut self,
+        element: &[u8],
+        path: &str,
+        short_name: Option<String>,
+    ) -> Option<(Origin, String)> {
+        let origin = self.origin.take()?;
+        let reason = match (self.error.take(), short_name) {
+            (None, Some(short_name)) => return Some((origin, short_name)),
+            (Some(e), _) => e,
+            (None, None) => "required SHORT-NAME is missing".to_string(),
+        };
+        self.warnings
+            .push(origin.warning(element, path, format!("skipped: {}", reason)));
+        None
+    }
+
+    fn parse<T: FromStr>(&mut self, element: &[u8], text: &str) -> Option<T>
+    where
+        T::Err: std::fmt::Display,
+    {
+        match text.trim().parse() {
+            Ok(v) => Some(v),
+            Err(e) => {
+                if self.error.is_none() {
+                    self.error = Some(format!(
+                        "can't parse {} {:?}: {}",
+                        String::from_utf8_lossy(element),
+                        text.trim(),
+                        e
+                    ));
+                }
+                None
+            }
+        }
+    }
+
+    fn start(&mut self, name: Vec<u8>) {
+        match name.as_slice() {
+            B_DLT_MESSAGE => {
+                self.start_element();
+                self.message = Some(MessageReadData::default());
+            }
+            B_DLT_ARGUMENT if self.message.is_some() => {
+                if self.argument_depth == 0 {
+                    self.argument = Some(ArgumentReadData::default());
+                } else if let Some(argument) = self.argument.as_mut() {
+                    argument.nested = true;
+                }
+                self.argument_depth += 1;
+            }
+            B_SW_BASE_TYPE => {
+                self.start_element();
+                self.base_type = Some(BaseTypeReadData::default());
+            }
+            B_COMPU_METHOD => {
+                self.start_element();
+                self.compu_method = Some(CompuMethodReadData::default());
+            }
+            B_COMPU_SCALE if self.compu_method.is_some() => {
+                self.compu_scale = Some(CompuScale::default())
+            }
+            B_COMPU_PHYS_TO_INTERNAL => self.in_phys_to_internal = true,
+            B_COMPU_NUMERATOR | B_COMPU_DENOMINATOR => self.coefficients = Some(vec![]),
+            B_UNIT => {
+                self.start_element();
+                self.unit = Some(None);
+            }
+            B_DLT_LOG_CHANNEL => {
+                self.start_element();
+                self.channel = Some(ChannelReadData::default());
+            }
+            _ => {}
+        }
+        self.text.clear();
+        self.open.push(OpenElement {
+            name,
+            short_name: None,
+        });
+    }
+
+    #[allow(clippy::cognitive_complexity)]
+    fn end(&mut self) {
+        let path = self.path();
+        let element = match self.open.pop() {
+            Some(element) => element,
+            None => return,
+        };
+        let text = mem::take(&mut self.text);
+        let in_compu_method = self.open.last().map(|e| e.name.as_slice()) == Some(B_COMPU_METHOD);
+        match element.name.as_slice() {
+            B_SHORT_NAME => {
+                if let Some(parent) = self.open.last_mut() {
+                    parent.short_name = Some(text.trim().to_string());
+                }
+            }
+            B_MESSAGE_ID if self.message.is_some() => {
+                let message_id = self.parse(B_MESSAGE_ID, &text);
+                if let Some(message) = self.message.as_mut() {
+                    message.message_id = message_id;
+                }
+            }
+            B_MESSAGE_TYPE_INFO => {
+                if let Some(message) = self.message.as_mut() {
+                    message.message_type_info = Some(text.trim().to_string());
+                }
+            }
+            B_BASE_TYPE_REF => {
+                if let Some(argument) = self.argument.as_mut() {
+                    argument.base_type_ref = Some(text.trim().to_string());
+                }
+            }
+            B_COMPU_METHOD_REF => {
+                if let Some(argument) = self.argument.as_mut() {
+                    argument.compu_method_ref = Some(text.trim().to_string());
+                }
+            }
+            B_DLT_ARGUMENT if self.argument_depth > 0 => {
+                self.argument_depth -= 1;
+                if self.argument_depth == 0 {
+                    if let (Some(mut argument), Some(message)) =
+                        (self.argument.take(), self.message.as_mut())
+                    {
+                        argument.short_name = element.short_name;
+                        message.arguments.push(argument);
+                    }
+                }
+            }
+            B_DLT_MESSAGE => {
+                if let Some(mut message) = self.message.take() {
+                    if let Some((origin, short_name)) =
+                        self.end_element(B_DLT_MESSAGE, &path, element.short_name)
+                    {
+                        message.short_name = short_name;
+                        self.data.messages.push((path, origin, message));
+                    }
+                }
+            }
+            B_BASE_TYPE_SIZE if self.base_type.is_some() => {
+                let size = self.parse(B_BASE_TYPE_SIZE, &text);
+                if let Some(base_type) = self.base_type.as_mut() {
+                    base_type.size = size;
+                }
+            }
+            B_BASE_TYPE_ENCODING => {
+                if let Some(base_type) = self.base_type.as_mut() {
+                    base_type.encoding = Some(text.trim().to_string());
+                }
+            }
+            B_SW_BASE_TYPE => {
+                if let Some(base_type) = self.base_type.take() {
+                    if let Some((origin, _)) =
+                        self.end_element(B_SW_BASE_TYPE, &path, element.short_name)
+                    {
+                        if let Some(path) =
+                            insert_first(&mut self.data.definitions.base_types, path, base_type)
+                        {
+                            self.warnings
+                                .push(origin.warning(B_SW_BASE_TYPE, &path, conflict()));
+                        }
+                    }
+                }
+            }
+            B_CATEGORY if in_compu_method => {
+                if let Some(compu_method) = self.compu_method.as_mut() {
+                    compu_method.category = Some(text.trim().to_string());
+                }
+            }
+            B_UNIT_REF => {
+                if let Some(compu_method) = self.compu_method.as_mut() {
+                    compu_method.unit_ref = Some(text.trim().to_string());
+                }
+            }
+            B_LOWER_LIMIT | B_UPPER_LIMIT if self.compu_scale.is_some() => {
+                let limit = self.parse(&element.name, &text);
+                if let Some(scale) = self.compu_scale.as_mut() {
+                    if element.name == B_LOWER_LIMIT {
+                        scale.lower_limit = limit;
+                    } else {
+                        scale.upper_limit = limit;
+                    }
+                }
+            }
+            B_VT => {
+                if let Some(scale) = self.compu_scale.as_mut() {
+                    scale.text = Some(text);
+                }
+            }
+            B_V if self.coefficients.is_some() => {
+                if let Some(v) = self.parse(B_V, &text) {
+                    if let Some(coefficients) = self.coefficients.as_mut() {
+                        coefficients.push(v);
+                    }
+                }
+            }
+            B_COMPU_NUMERATOR | B_COMPU_DENOMINATOR => {
+                let coefficients = self.coefficients.take().unwrap_or_default();
+                if let Some(scale) = self.compu_scale.as_mut() {
+                    if element.name == B_COMPU_NUMERATOR {
+                        scale.numerator = coefficients;
+                    } else {
+                        scale.denominator = coefficients;
+                    }
+                }
+            }
+            B_COMPU_SCALE => {
+                if let (Some(scale), Some(compu_method)) =
+                    (self.compu_scale.take(), self.compu_method.as_mut())
+                {
+                    if !self.in_phys_to_internal {
+                        compu_method.scales.push(scale);
+                    }
+                }
+            }
+            B_COMPU_PHYS_TO_INTERNAL => self.in_phys_to_internal = false,
+            B_COMPU_METHOD => {
+                if let Some(compu_method) = self.compu_method.take() {
+                    if compu_method.category.is_none() && self.error.is_none() {
+                        self.error = Some("required CATEGORY is missing".to_string());
+                    }
+                    if let Some((origin, _)) =
+                        self.end_element(B_COMPU_METHOD, &path, element.short_name)
+                    {
+                        let category = compu_method.category.unwrap_or_default();
+                        // AUTOSAR writes the categories with underscores
+                        let data = match CompuCategory::from_name(&category.replace('_', "-")) {
+                            Some(category) => {
+                                Some((category, compu_method.unit_ref, compu_method.scales))
+                            }
+                            None => {
+                                self.warnings.push(origin.warning(
+                                    B_COMPU_METHOD,
+                                    &path,
+                                    format!(
+                                        "unsupported category {}, values are not converted",
+                                        category
+                                    ),
+                                ));
+                                None
+                            }
+                        };
+                        if let Some(path) =
+                            insert_first(&mut self.data.definitions.compu_methods, path, data)
+                        {
+                            self.warnings
+                                .push(origin.warning(B_COMPU_METHOD, &path, conflict()));
+                        }
+                    }
+                }
+            }
+            B_DISPLAY_NAME => {
+                if let Some(unit) = self.unit.as_mut() {
+                    *unit = Some(text);
+                }
+            }
+            B_UNIT => {
+                if let Some(display_name) = self.unit.take() {
+                    if let Some((origin, short_name)) =
+                        self.end_element(B_UNIT, &path, element.short_name)
+                    {
+                        let display_name = display_name.unwrap_or(short_name);
+                        if let Some(path) =
+                            insert_first(&mut self.data.definitions.units, path, display_name)
+                        {
+                            self.warnings
+                                .push(origin.warning(B_UNIT, &path, conflict()));
+                        }
+                    }
+                }
+            }
+            B_APPLICATION_ID => {
+                if let Some(channel) = self.channel.as_mut() {
+                    channel.application_id = Some(text.trim().to_string());
+                }
+            }
+            B_CONTEXT_ID => {
+                if let Some(channel) = self.channel.as_mut() {
+                    channel.context_id = Some(text.trim().to_string());
+                }
+            }
+            B_DLT_MESSAGE_REF => {
+                if let Some(channel) = self.channel.as_mut() {
+                    channel.message_refs.push(text.trim().to_string());
+                }
+            }
+            B_DLT_LOG_CHANNEL => {
+                if let Some(channel) = self.channel.take() {
+                    if let Some((origin, _)) =
+                        self.end_element(B_DLT_LOG_CHANNEL, &path, element.short_name)
+                    {
+                        self.data.channels.push((path, origin, channel));
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn conflict() -> String {
+    "skipped: conflicts with an earlier definition".to_string()
+}
+
+const B_SHORT_NAME: &[u8] = b"SHORT-NAME";
+const B_DLT_MESSAGE: &[u8] = b"DLT-MESSAGE";
+const B_MESSAGE_ID: &[u8] = b"MESSAGE-ID";
+const B_MESSAGE_TYPE_INFO: &[u8] = b"MESSAGE-TYPE-INFO";
+const B_DLT_ARGUMENT: &[u8] = b"DLT-ARGUMENT";
+const B_BASE_TYPE_REF: &[u8] = b"BASE-TYPE-REF";
+const B_COMPU_METHOD_REF: &[u8] = b"COMPU-METHOD-REF";
+const B_SW_BASE_TYPE: &[u8] = b"SW-BASE-TYPE";
+const B_BASE_TYPE_SIZE: &[u8] = b"BASE-TYPE-SIZE";
+const B_BASE_TYPE_ENCODING: &[u8] = b"BASE-TYPE-ENCODING";
+const B_COMPU_METHOD: &[u8] = b"COMPU-METHOD";
+const B_CATEGORY: &[u8] = b"CATEGORY";
+const B_UNIT_REF: &[u8] = b"UNIT-REF";
+const B_COMPU_SCALE: &[u8] = b"COMPU-SCALE";
+const B_COMPU_PHYS_TO_INTERNAL: &[u8] = b"COMPU-PHYS-TO-INTERNAL";
+const B_LOWER_LIMIT: &[u8] = b"LOWER-LIMIT";
+const B_UPPER_LIMIT: &[u8] = b"UPPER-LIMIT";
+const B_VT: &[u8] = b"VT";
+const B_COMPU_NUMERATOR: &[u8] = b"COMPU-NUMERATOR";
+const B_COMPU_DENOMINATOR: &[u8] = b"COMPU-DENOMINATOR";
+const B_V: &[u8] = b"V";
+const B_UNIT: &[u8] = b"UNIT";
+const B_DISPLAY_NAME: &[u8] = b"DISPLAY-NAME";
+const B_DLT_LOG_CHANNEL: &[u8] = b"DLT-LOG-CHANNEL";
+const B_APPLICATION_ID: &[u8] = b"APPLICATION-ID";
+const B_CONTEXT_ID: &[u8] = b"CONTEXT-ID";
+const B_DLT_MESSAGE_REF: &[u8] = b"DLT-MESSAGE-REF";
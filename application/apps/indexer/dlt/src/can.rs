@@ -0,0 +1,530 @@
+// Copyright (c) 2020 E.S.R.Labs. All rights reserved.
+//
+// NOTICE:  All information contained herein is, and remains
+// the property of E.S.R.Labs and its suppliers, if any.
+// The intellectual and technical concepts contained herein are
+// proprietary to E.S.R.Labs and its suppliers and may be covered
+// by German and Foreign Patents, patents in process, and are protected
+// by trade secret or copyright law.
+// Dissemination of this information or reproduction of this material
+// is strictly forbidden unless prior written permission is obtained
+// from E.S.R.Labs.
+//! CAN frames in network traces
+//!
+//! A traced CAN frame consists of the CAN id (4 bytes big endian, bit 31 is
+//! set for extended ids like in DBC files), the DLC (1 byte) and the data.
+//! The signals of the frame are decoded with the messages of DBC files that
+//! are read together with the FIBEX files. Supported are the `BO_`, `SG_`
+//! (with simple multiplexing), `VAL_` and `SIG_VALTYPE_` entries, all others
+//! are ignored.
+use crate::dlt::{
+    Argument, FloatWidth, StringCoding, TypeInfo, TypeInfoKind, TypeLength, Value,
+    DLT_ARGUMENT_SENTINAL,
+};
+use crate::fibex::{FibexMetadata, FibexWarning};
+use byteorder::{BigEndian, ByteOrder};
+use failure::{bail, format_err, Error};
+use serde::Serialize;
+use std::{
+    collections::{hash_map::Entry, BTreeMap, HashMap},
+    fmt,
+    path::{Path, PathBuf},
+    rc::Rc,
+};
+
+pub const CAN_FRAME_HEADER_LENGTH: usize = 5;
+/// marks extended (29 bit) ids, in traces as well as in DBC files
+pub const CAN_EXTENDED_ID_FLAG: u32 = 0x8000_0000;
+const CAN_EXTENDED_ID_MASK: u32 = 0x1FFF_FFFF;
+
+type Result<T = ()> = std::result::Result<T, Error>;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CanByteOrder {
+    /// little endian, the start bit is the least significant bit
+    Intel,
+    /// big endian, the start bit is the most significant bit
+    Motorola,
+}
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CanValueType {
+    Unsigned,
+    Signed,
+    Float32,
+    Float64,
+}
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Multiplexing {
+    /// the signal selects which multiplexed signals are present
+    Multiplexor,
+    /// the signal is present if the multiplexor has this value
+    Multiplexed(u64),
+}
+
+/// a signal of a DBC message
+#[derive(Debug, Clone, PartialEq)]
+pub struct CanSignalMetadata {
+    pub name: String,
+    pub start_bit: usize,
+    pub size: usize,
+    pub byte_order: CanByteOrder,
+    pub value_type: CanValueType,
+    pub factor: f64,
+    pub offset: f64,
+    pub unit: Option<String>,
+    pub multiplexing: Option<Multiplexing>,
+    /// names of raw values
+    pub value_table: BTreeMap<i64, String>,
+}
+/// a message of a DBC file, extended ids have `CAN_EXTENDED_ID_FLAG` set
+#[derive(Debug, Clone, PartialEq)]
+pub struct CanMessageMetadata {
+    pub id: u32,
+    pub name: String,
+    pub size: usize,
+    pub signals: Vec<CanSignalMetadata>,
+}
+
+/// a decoded CAN frame, the name and signals are only known if a DBC file
+/// describes the message
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CanFrame {
+    /// the id without the extended flag
+    pub id: u32,
+    pub extended: bool,
+    pub dlc: u8,
+    pub data: Vec<u8>,
+    pub name: Option<String>,
+    /// None if the data does not match the signals of the message
+    pub signals: Option<Vec<Argument>>,
+}
+impl fmt::Display for CanFrame {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.extended {
+            write!(f, "CAN 0x{:08X} EXT", self.id)?;
+        } else {
+            write!(f, "CAN 0x{:03X}", self.id)?;
+        }
+        write!(f, " DLC {}", self.dlc)?;
+        if let Some(name) = &self.name {
+            write!(f, " {}", name)?;
+        }
+        match &self.signals {
+            Some(signals) => signals
+                .iter()
+                .try_for_each(|s| write!(f, "{}{}", DLT_ARGUMENT_SENTINAL, s)),
+            None => write!(f, "{}{:02X?}", DLT_ARGUMENT_SENTINAL, self.data),
+        }
+    }
+}
+
+/// the number of data bytes, DLCs above 8 are used by CAN FD
+fn data_length(dlc: u8) -> usize {
+    match dlc {
+        0..=8 => dlc as usize,
+        9 => 12,
+        10 => 16,
+        11 => 20,
+        12 => 24,
+        13 => 32,
+        14 => 48,
+        _ => 64,
+    }
+}
+
+/// decodes a traced CAN frame, None if it is too short for id and DLC
+/// the data is cut to the length given by the DLC
+pub fn decode_can_frame(bytes: &[u8], metadata: Option<&FibexMetadata>) -> Option<CanFrame> {
+    if bytes.len() < CAN_FRAME_HEADER_LENGTH {
+        return None;
+    }
+    let raw_id = BigEndian::read_u32(bytes);
+    let dlc = bytes[4];
+    let available = &bytes[CAN_FRAME_HEADER_LENGTH..];
+    let data = available[..data_length(dlc).min(available.len())].to_vec();
+    let message = metadata.and_then(|m| m.can_message(raw_id));
+    Some(CanFrame {
+        id: raw_id & CAN_EXTENDED_ID_MASK,
+        extended: (raw_id & CAN_EXTENDED_ID_FLAG) != 0,
+        dlc,
+        name: message.map(|m| m.name.clone()),
+        signals: message.and_then(|m| decode_signals(m, &data)),
+        data,
+    })
+}
+
+fn decode_signals(message: &CanMessageMetadata, data: &[u8]) -> Option<Vec<Argument>> {
+    let multiplexor_value = match message
+        .signals
+        .iter()
+        .find(|s| s.multiplexing == Some(Multiplexing::Multiplexor))
+    {
+        Some(multiplexor) => Some(extract_bits(multiplexor, data)?),
+        None => None,
+    };
+    let mut arguments = vec![];
+    for signal in &message.signals {
+        if let Some(Multiplexing::Multiplexed(value)) = signal.multiplexing {
+            if multiplexor_value != Some(value) {
+                continue;
+            }
+        }
+        let raw = extract_bits(signal, data)?;
+        let (kind, value, unit) = physical_value(signal, raw);
+        arguments.push(Argument {
+            type_info: TypeInfo {
+                kind,
+                coding: StringCoding::UTF8,
+                has_variable_info: true,
+                has_trace_info: false,
+            },
+            name: Some(signal.name.clone()),
+            unit,
+            fixed_point: None,
+            value,
+        });
+    }
+    Some(arguments)
+}
+
+/// the raw bits of a signal, None if the signal is not within the data
+fn extract_bits(signal: &CanSignalMetadata, data: &[u8]) -> Option<u64> {
+    let bit_at = |bit: usize| data.get(bit / 8).map(|b| u64::from((b >> (bit % 8)) & 1));
+    let mut raw = 0u64;
+    match signal.byte_order {
+        CanByteOrder::Intel => {
+            for i in 0..signal.size {
+                raw |= bit_at(signal.start_bit + i)? << i;
+            }
+        }
+        CanByteOrder::Motorola => {
+            // bits are counted from the most significant bit down, at the
+            // end of a byte it continues with the next byte
+            let mut bit = signal.start_bit;
+            for i in 0..signal.size {
+                raw = (raw << 1) | bit_at(bit)?;
+                if i + 1 < signal.size {
+                    bit = if bit.is_multiple_of(8) {
+                        bit + 15
+                    } else {
+                        bit - 1
+                    };
+                }
+            }
+        }
+    }
+    Some(raw)
+}
+
+/// the name of the raw value or the scaled value with its unit
+fn physical_value(signal: &CanSignalMetadata, raw: u64) -> (TypeInfoKind, Value, Option<String>) {
+    let integer = match signal.value_type {
+        CanValueType::Unsigned => Some(raw as i128),
+        CanValueType::Signed if signal.size < 64 => {
+            let shift = 64 - signal.size;
+            Some((((raw << shift) as i64) >> shift) as i128)
+        }
+        CanValueType::Signed => Some(raw as i64 as i128),
+        CanValueType::Float32 | CanValueType::Float64 => None,
+    };
+    if let Some(text) = integer.and_then(|v| signal.value_table.get(&(v as i64))) {
+        return (
+            TypeInfoKind::StringType,
+            Value::StringVal(text.clone()),
+            None,
+        );
+    }
+    let (kind, value) = match integer {
+        Some(v) if signal.factor == 1.0 && signal.offset == 0.0 => match signal.value_type {
+            CanValueType::Unsigned => (
+                TypeInfoKind::Unsigned(TypeLength::BitLength64),
+                Value::U64(raw),
+            ),
+            _ => (
+                TypeInfoKind::Signed(TypeLength::BitLength64),
+                Value::I64(v as i64),
+            ),
+        },
+        _ => {
+            let value = match (integer, signal.value_type) {
+                (Some(v), _) => v as f64,
+                (None, CanValueType::Float32) => f64::from(f32::from_bits(raw as u32)),
+                (None, _) => f64::from_bits(raw),
+            };
+            let physical = value * signal.factor + signal.offset;
+            // avoid printing rounding errors like 23.500000000000004
+            (
+                TypeInfoKind::Float(FloatWidth::Width64),
+                Value::F64((physical * 1e9).round() / 1e9),
+            )
+        }
+    };
+    (kind, value, signal.unit.clone())
+}
+
+/// DBC files are read as CAN database instead of FIBEX
+pub(crate) fn is_can_database(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("dbc"))
+}
+
+/// adds the messages of a DBC file, messages are identified by their id and
+/// the first definition is used
+/// only a file that can't be read is an error, entries that can't be used are
+/// skipped with a warning
+pub(crate) fn read_can_database(
+    path: &Path,
+    messages: &mut HashMap<u32, Rc<CanMessageMetadata>>,
+    warnings: &mut Vec<FibexWarning>,
+) -> Result {
+    // DBC files are often not UTF-8 but Windows-1252
+    let content = String::from_utf8_lossy(&std::fs::read(path)?).to_string();
+    let warning = |element: &str, id: Option<String>, line: usize, reason: String| FibexWarning {
+        file: PathBuf::from(path),
+        element: element.to_string(),
+        id,
+        line_and_column: Some((line, 1)),
+        reason,
+    };
+    let mut file_messages: Vec<(usize, CanMessageMetadata)> = vec![];
+    // value tables and value types refer to signals of any message
+    let mut signal_entries: Vec<(usize, &str, &str)> = vec![];
+    // signals of skipped messages are skipped as well
+    let mut in_message = false;
+    for (index, line) in content.lines().enumerate() {
+        let line_number = index + 1;
+        let line = line.trim();
+        let (keyword, rest) = match line.find(char::is_whitespace) {
+            Some(end) => (&line[..end], line[end..].trim()),
+            None => (line, ""),
+        };
+        match keyword {
+            "BO_" => match parse_message(rest) {
+                Ok(Some(message)) => {
+                    file_messages.push((line_number, message));
+                    in_message = true;
+                }
+                // the pseudo message that holds signals of no message
+                Ok(None) => in_message = false,
+                Err(e) => {
+                    warnings.push(warning(
+                        "BO_",
+                        rest.split_whitespace().next().map(str::to_string),
+                        line_number,
+                        format!("skipped: {}", e),
+                    ));
+                    in_message = false;
+                }
+            },
+            "SG_" if in_message => match parse_signal(rest) {
+                Ok(signal) => {
+                    if let Some((_, message)) = file_messages.last_mut() {
+                        message.signals.push(signal);
+                    }
+                }
+                Err(e) => warnings.push(warning(
+                    "SG_",
+                    rest.split_whitespace().next().map(str::to_string),
+                    line_number,
+                    format!("skipped: {}", e),
+                )),
+            },
+            "SG_" => (),
+            "VAL_" | "SIG_VALTYPE_" => {
+                signal_entries.push((line_number, keyword, rest));
+                in_message = false;
+            }
+            "" => (),
+            _ => in_message = false,
+        }
+    }
+    for (line_number, keyword, rest) in signal_entries {
+        let mut tokens = rest.split_whitespace();
+        let (message_id, signal_name) = match (tokens.next(), tokens.next()) {
+            // value tables of environment variables have no message id
+            (Some(id), Some(signal)) if id.parse::<u32>().is_ok() => (id, signal),
+            _ => continue,
+        };
+        let id = Some(format!("{}.{}", message_id, signal_name));
+        let signal = file_messages
+            .iter_mut()
+            .find(|(_, m)| m.id.to_string() == message_id)
+            .and_then(|(_, m)| m.signals.iter_mut().find(|s| s.name == signal_name));
+        let result = match signal {
+            Some(signal) if keyword == "VAL_" => apply_value_table(signal, rest),
+            Some(signal) => apply_value_type(signal, rest),
+            None => Err(format_err!("signal not found")),
+        };
+        if let Err(e) = result {
+            warnings.push(warning(keyword, id, line_number, format!("skipped: {}", e)));
+        }
+    }
+    for (line_number, message) in file_messages {
+        match messages.entry(message.id) {
+            Entry::Occupied(entry) => {
+                if **entry.get() != message {
+                    warnings.push(warning(
+                        "BO_",
+                        Some(message.id.to_string()),
+                        line_number,
+                        "skipped: conflicts with an earlier definition".to_string(),
+                    ));
+                }
+            }
+            Entry::Vacant(entry) => {
+                entry.insert(Rc::new(message));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// `291 EngineData: 8 ECU1`, None for the pseudo message of independent
+/// signals
+fn parse_message(entry: &str) -> Result<Option<CanMessageMetadata>> {
+    let colon = entry
+        .find(':')
+        .ok_or_else(|| format_err!("missing ':' after the name"))?;
+    let mut tokens = entry[..colon].split_whitespace();
+    let id = tokens.next().ok_or_else(|| format_err!("missing id"))?;
+    let id: u32 = id
+        .parse()
+        .map_err(|e| format_err!("can't parse id {:?}: {}", id, e))?;
+    if id & !CAN_EXTENDED_ID_FLAG > CAN_EXTENDED_ID_MASK {
+        return Ok(None);
+    }
+    let name = tokens.next().ok_or_else(|| format_err!("missing name"))?;
+    let size = entry[colon + 1..]
+        .split_whitespace()
+        .next()
+        .ok_or_else(|| format_err!("missing size"))?;
+    Ok(Some(CanMessageMetadata {
+        id,
+        name: name.to_string(),
+        size: size
+            .parse()
+            .map_err(|e| format_err!("can't parse size {:?}: {}", size, e))?,
+        signals: vec![],
+    }))
+}
+
+/// `Temp m3 : 31|8@0- (1,-40) [-40|215] "°C" ECU2`
+fn parse_signal(entry: &str) -> Result<CanSignalMetadata> {
+    let colon = entry
+        .find(':')
+        .ok_or_else(|| format_err!("missing ':' after the name"))?;
+    let mut tokens = entry[..colon].split_whitespace();
+    let name = tokens.next().ok_or_else(|| format_err!("missing name"))?;
+    let multiplexing = match tokens.next() {
+        None => None,
+        Some("M") => Some(Multiplexing::Multiplexor),
+        Some(m) if m.starts_with('m') && m[1..].parse::<u64>().is_ok() => {
+            Some(Multiplexing::Multiplexed(m[1..].parse()?))
+        }
+        Some(m) => bail!("unsupported multiplexing {:?}", m),
+    };
+    let layout = entry[colon + 1..].trim_start();
+    let layout_end = layout.find(char::is_whitespace).unwrap_or(layout.len());
+    let (layout, rest) = layout.split_at(layout_end);
+    let (position, order_and_sign) = layout
+        .split_once('@')
+        .ok_or_else(|| format_err!("can't parse bit position {:?}", layout))?;
+    let (start_bit, size) = position
+        .split_once('|')
+        .ok_or_else(|| format_err!("can't parse bit position {:?}", layout))?;
+    let start_bit: usize = start_bit.parse()?;
+    let size: usize = size.parse()?;
+    if size == 0 || size > 64 {
+        bail!("unsupported size of {} bits", size);
+    }
+    let (byte_order, value_type) = match order_and_sign {
+        "1+" => (CanByteOrder::Intel, CanValueType::Unsigned),
+        "1-" => (CanByteOrder::Intel, CanValueType::Signed),
+        "0+" => (CanByteOrder::Motorola, CanValueType::Unsigned),
+        "0-" => (CanByteOrder::Motorola, CanValueType::Signed),
+        _ => bail!("can't parse byte order and sign {:?}", order_and_sign),
+    };
+    let scaling =
+        enclosed(rest, '(', ')').ok_or_else(|| format_err!("missing factor and offset"))?;
+    let (factor, offset) = scaling
+        .split_once(',')
+        .ok_or_else(|| format_err!("can't parse factor and offset {:?}", scaling))?;
+    let factor: f64 = factor
+        .trim()
+        .parse()
+        .map_err(|e| format_err!("can't parse factor {:?}: {}", factor, e))?;
+    let offset: f64 = offset
+        .trim()
+        .parse()
+        .map_err(|e| format_err!("can't parse offset {:?}: {}", offset, e))?;
+    let unit = enclosed(rest, '"', '"')
+        .filter(|u| !u.is_empty())
+        .map(str::to_string);
+    Ok(CanSignalMetadata {
+        name: name.to_string(),
+        start_bit,
+        size,
+        byte_order,
+        value_type,
+        factor,
+        offset,
+        unit,
+        multiplexing,
+        value_table: BTreeMap::new(),
+    })
+}
+
+/// `291 Gear 0 "Neutral" 1 "First" ;`
+fn apply_value_table(signal: &mut CanSignalMetadata, entry: &str) -> Result {
+    // skip message id and signal name
+    let mut rest = entry
+        .splitn(3, char::is_whitespace)
+        .nth(2)
+        .unwrap_or_default();
+    let mut value_table = BTreeMap::new();
+    loop {
+        rest = rest.trim_start();
+        if rest.is_empty() || rest.starts_with(';') {
+            break;
+        }
+        let (value, after_value) = rest.split_at(
+            rest.find(char::is_whitespace)
+                .ok_or_else(|| format_err!("missing description of {:?}", rest))?,
+        );
+        let value: i64 = value
+            .parse()
+            .map_err(|e| format_err!("can't parse value {:?}: {}", value, e))?;
+        let description = enclosed(after_value, '"', '"')
+            .ok_or_else(|| format_err!("missing description of {}", value))?;
+        value_table.insert(value, description.to_string());
+        rest = &after_value[after_value.find('"').unwrap_or(0) + description.len() + 2..];
+    }
+    signal.value_table = value_table;
+    Ok(())
+}
+
+/// `291 Torque : 1;`
+fn apply_value_type(signal: &mut CanSignalMetadata, entry: &str) -> Result {
+    let value_type = entry
+        .rsplit(':')
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .trim_end_matches(';')
+        .trim();
+    signal.value_type = match (value_type, signal.size) {
+        ("0", _) => return Ok(()),
+        ("1", 32) => CanValueType::Float32,
+        ("2", 64) => CanValueType::Float64,
+        ("1", _) | ("2", _) => bail!("floats need 32 or 64 bits, not {}", signal.size),
+        _ => bail!("unsupported value type {:?}", value_type),
+    };
+    Ok(())
+}
+
+/// the text between the first `start` and the following `end`
+fn enclosed(s: &str, start: char, end: char) -> Option<&str> {
+    let begin = s.find(start)? + start.len_utf8();
+    let length = s[begin..].find(end)?;
+    Some(&s[begin..begin + length])
+}
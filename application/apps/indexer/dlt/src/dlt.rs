@@ -12,7 +12,9 @@
 #![allow(clippy::unit_arg)]
 
 use indexer_base::error_reporter::*;
-use crate::service_id::*;
+use crate::dlt_control::{decode_control_message, ControlMessage};
+use crate::someip::{decode_someip_message, SomeipMessage, SOMEIP_HEADER_LENGTH};
+use crate::can::{decode_can_frame, CanFrame, CAN_FRAME_HEADER_LENGTH};
 use crate::proptest_strategies::*;
 use bytes::{ByteOrder, BytesMut, BufMut};
 use chrono::{NaiveDateTime};
@@ -22,6 +24,7 @@ use std::fmt::{Formatter};
 use std::io;
 use std::io::{Error};
 use std::rc::Rc;
+use serde::ser::{SerializeStruct, Serializer};
 use serde::Serialize;
 use byteorder::{BigEndian, LittleEndian};
 
@@ -30,7 +33,7 @@ use proptest::prelude::*;
 
 use std::str;
 
-use crate::fibex::{FibexMetadata, FrameId, ApplicationId, ContextId, FrameMetadata};
+use crate::fibex::{FibexMetadata, FrameMetadata, PhysicalValue};
 use crate::dlt_parse::{dlt_fixed_point, dlt_uint, dlt_sint, dlt_fint};
 
 #[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, PartialOrd, Ord, Serialize, Arbitrary)]
@@ -54,6 +57,12 @@ impl DltTimeStamp {
             microseconds: (ms % 1000) as u32 * 1000,
         }
     }
+    pub fn as_ms(&self) -> u64 {
+        u64::from(self.seconds) * 1000 + u64::from(self.microseconds) / 1000
+    }
+    pub fn as_micros(&self) -> u64 {
+        u64::from(self.seconds) * 1_000_000 + u64::from(self.microseconds)
+    }
 }
 impl fmt::Display for DltTimeStamp {
     fn fmt(&self, f: &mut Formatter) -> Result<(), fmt::Error> {
@@ -127,10 +136,15 @@ pub struct StandardHeader {
     pub session_id: Option<u32>,
     pub timestamp: Option<u32>,
     pub payload_length: u16,
+    /// fields of version 2 messages, None for version 1
+    pub v2: Option<Box<HeaderV2>>,
 }
 
 impl StandardHeader {
     pub fn header_type_byte(&self) -> u8 {
+        if let Some(v2) = &self.v2 {
+            return self.v2_header_type(v2).0;
+        }
         standard_header_type(
             self.has_extended_header,
             self.endianness,
@@ -141,6 +155,9 @@ impl StandardHeader {
         )
     }
     pub fn overall_length(&self) -> u16 {
+        if let Some(v2) = &self.v2 {
+            return self.v2_headers_length(v2) + self.payload_length;
+        }
         // header length
         let mut length: u16 = HEADER_MIN_LENGTH;
         if self.ecu_id.is_some() {
@@ -212,9 +229,12 @@ impl StandardHeader {
             session_id,
             timestamp,
             payload_length,
+            v2: None,
         }
     }
 
+    /// standard header of a version 1 message, the headers of version 2
+    /// messages depend on the payload and are written by `Message::as_bytes`
     #[allow(dead_code)]
     pub fn header_as_bytes(&self) -> Vec<u8> {
         let header_type_byte = self.header_type_byte();
@@ -251,6 +271,8 @@ impl fmt::Display for StandardHeader {
         )?;
         if let Some(t) = &self.timestamp {
             write!(f, "{}", t)?;
+        } else if let Some(t) = self.v2.as_ref().and_then(|v2| v2.timestamp.as_ref()) {
+            write!(f, "{}", t)?;
         }
         write!(f, "{}", DLT_COLUMN_SENTINAL,)?;
         if let Some(id) = &self.ecu_id {
@@ -260,6 +282,143 @@ impl fmt::Display for StandardHeader {
     }
 }
 
+/// kind of content of a version 2 message (`CNTI` of the header type)
+#[derive(Debug, PartialEq, Clone, Copy, Serialize, Arbitrary)]
+pub enum ContentInfo {
+    Verbose,
+    NonVerbose,
+    Control,
+}
+impl ContentInfo {
+    pub fn value(self) -> u8 {
+        match self {
+            ContentInfo::Verbose => 0,
+            ContentInfo::NonVerbose => 1,
+            ContentInfo::Control => 2,
+        }
+    }
+    pub fn from_value(v: u8) -> Option<Self> {
+        match v {
+            0 => Some(ContentInfo::Verbose),
+            1 => Some(ContentInfo::NonVerbose),
+            2 => Some(ContentInfo::Control),
+            _ => None,
+        }
+    }
+}
+
+/// timestamp of version 2 messages, the seconds are transmitted with 40 bits
+#[derive(Debug, Clone, PartialEq, Arbitrary, Serialize)]
+pub struct DltTimeStampV2 {
+    #[proptest(strategy = "0..(1u64 << 40)")]
+    pub seconds: u64,
+    #[proptest(strategy = "0..1_000_000_000u32")]
+    pub nanoseconds: u32,
+}
+impl fmt::Display for DltTimeStampV2 {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), fmt::Error> {
+        write!(f, "{}.{:09}", self.seconds, self.nanoseconds)
+    }
+}
+
+/// file name and line number of the code that sent a version 2 message
+#[derive(Debug, Clone, PartialEq, Arbitrary, Serialize)]
+pub struct SourceLocation {
+    #[proptest(strategy = "\"[a-z_/]{1,20}\\\\.cpp\"")]
+    pub file_name: String,
+    pub line_number: u32,
+}
+
+/// fields of the base and extension header of version 2 messages (AUTOSAR R19-11)
+/// that version 1 does not have or keeps in the extended header
+///
+/// the ids and the message type are also available as `Message::extended_header`
+/// so that filtering and formatting work the same for both versions
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct HeaderV2 {
+    pub content_info: ContentInfo,
+    /// message type of verbose and control messages
+    pub message_type: Option<MessageType>,
+    /// only verbose and non verbose messages are timestamped
+    pub timestamp: Option<DltTimeStampV2>,
+    pub app_id_context_id: Option<(String, String)>,
+    pub source_location: Option<SourceLocation>,
+    pub tags: Option<Vec<String>>,
+    pub privacy_level: Option<u8>,
+}
+impl HeaderV2 {
+    /// the extended header as version 1 messages have it, non verbose messages
+    /// carry no message type, it is only known from the fibex description
+    pub fn extended_header(&self, argument_count: u8) -> Option<ExtendedHeader> {
+        let (application_id, context_id) = self.app_id_context_id.clone()?;
+        Some(ExtendedHeader {
+            verbose: self.content_info == ContentInfo::Verbose,
+            argument_count,
+            message_type: self
+                .message_type
+                .clone()
+                .unwrap_or(MessageType::Unknown((0, 0))),
+            application_id,
+            context_id,
+        })
+    }
+}
+
+/// length of a string with its length byte, longer strings are cut off
+fn length_prefixed_len(s: &str) -> u16 {
+    1 + s.len().min(u8::MAX as usize) as u16
+}
+
+impl StandardHeader {
+    /// first and second byte of the header type of version 2 messages
+    fn v2_header_type(&self, v2: &HeaderV2) -> (u8, u8) {
+        let mut header_type = v2.content_info.value();
+        if self.ecu_id.is_some() {
+            header_type |= V2_WITH_ECU_ID_FLAG;
+        }
+        if v2.app_id_context_id.is_some() {
+            header_type |= V2_WITH_APP_AND_CONTEXT_ID_FLAG;
+        }
+        if self.session_id.is_some() {
+            header_type |= V2_WITH_SESSION_ID_FLAG;
+        }
+        header_type |= (self.version & 0b111) << 5;
+        let mut extension_type = 0u8;
+        if v2.source_location.is_some() {
+            extension_type |= V2_WITH_SOURCE_LOCATION_FLAG;
+        }
+        if v2.tags.is_some() {
+            extension_type |= V2_WITH_TAGS_FLAG;
+        }
+        if v2.privacy_level.is_some() {
+            extension_type |= V2_WITH_PRIVACY_LEVEL_FLAG;
+        }
+        (header_type, extension_type)
+    }
+    fn v2_headers_length(&self, v2: &HeaderV2) -> u16 {
+        let mut length = calculate_v2_base_header_length(v2.content_info.value());
+        if let Some(id) = &self.ecu_id {
+            length += length_prefixed_len(id);
+        }
+        if let Some((app_id, context_id)) = &v2.app_id_context_id {
+            length += length_prefixed_len(app_id) + length_prefixed_len(context_id);
+        }
+        if self.session_id.is_some() {
+            length += 4;
+        }
+        if let Some(location) = &v2.source_location {
+            length += length_prefixed_len(&location.file_name) + 4;
+        }
+        if let Some(tags) = &v2.tags {
+            length += 1 + tags.iter().map(|tag| length_prefixed_len(tag)).sum::<u16>();
+        }
+        if v2.privacy_level.is_some() {
+            length += 1;
+        }
+        length
+    }
+}
+
 #[derive(Debug, PartialEq, PartialOrd, Clone, Copy, Serialize, Arbitrary)]
 pub enum LogLevel {
     Fatal,
@@ -378,10 +537,25 @@ pub enum MessageType {
     #[proptest(strategy = "((0b100u8..0b111u8),(0..0b1111u8)).prop_map(MessageType::Unknown)")]
     Unknown((u8, u8)),
 }
-
 impl MessageType {
-    fn try_new_from_fibex_message_info(message_info: &str) -> Option<MessageType> {
-        Some(MessageType::Log(match message_info {
+    /// log messages that are less severe than `level` can be skipped
+    pub fn skip_with_level(&self, level: LogLevel) -> bool {
+        match self {
+            MessageType::Log(n) => match (*n, level) {
+                (LogLevel::Invalid(a), LogLevel::Invalid(b)) => a < b,
+                (LogLevel::Invalid(_), _) => false,
+                (_, LogLevel::Invalid(_)) => true,
+                (n, _) => level < n,
+            },
+            _ => false,
+        }
+    }
+}
+
+impl LogLevel {
+    /// the level of a `MESSAGE_INFO` in a fibex frame, e.g. `DLT_LOG_INFO`
+    pub fn from_fibex_message_info(message_info: &str) -> Option<LogLevel> {
+        Some(match message_info {
             "DLT_LOG_FATAL" => LogLevel::Fatal,
             "DLT_LOG_ERROR" => LogLevel::Error,
             "DLT_LOG_WARN" => LogLevel::Warn,
@@ -389,7 +563,13 @@ impl MessageType {
             "DLT_LOG_DEBUG" => LogLevel::Debug,
             "DLT_LOG_VERBOSE" => LogLevel::Verbose,
             _ => return None,
-        }))
+        })
+    }
+}
+
+impl MessageType {
+    fn try_new_from_fibex_message_info(message_info: &str) -> Option<MessageType> {
+        LogLevel::from_fibex_message_info(message_info).map(MessageType::Log)
     }
 }
 
@@ -423,29 +603,23 @@ impl ExtendedHeader {
         buf.to_vec()
     }
     pub fn skip_with_level(self: &ExtendedHeader, level: LogLevel) -> bool {
-        match self.message_type {
-            MessageType::Log(n) => match (n, level) {
-                (LogLevel::Invalid(a), LogLevel::Invalid(b)) => a < b,
-                (LogLevel::Invalid(_), _) => false,
-                (_, LogLevel::Invalid(_)) => true,
-                _ => level < n,
-            },
-            _ => false,
-        }
+        self.message_type.skip_with_level(level)
     }
 }
 
-/// Fixed-Point representation. only supports 32 bit and 64 bit values
-/// according to the spec 128 bit are possible but we don't support it
+/// Fixed-Point representation of the offset, the width of the offset
+/// depends on the length of the value (see `FixedPoint`)
 #[derive(Debug, PartialEq, Clone, Arbitrary, Serialize)]
 pub enum FixedPointValue {
     I32(i32),
     I64(i64),
+    I128(i128),
 }
 pub fn fixed_point_value_width(v: &FixedPointValue) -> usize {
     match v {
         FixedPointValue::I32(_) => 4,
         FixedPointValue::I64(_) => 8,
+        FixedPointValue::I128(_) => 16,
     }
 }
 #[derive(Debug, PartialEq, Clone, Serialize)]
@@ -465,6 +639,13 @@ pub enum Value {
     F64(f64),
     StringVal(String),
     Raw(Vec<u8>),
+    /// all values of an array in row-major order, `dimensions` holds the
+    /// number of entries for each dimension
+    Array {
+        dimensions: Vec<u16>,
+        values: Vec<Value>,
+    },
+    Struct(Vec<Argument>),
 }
 
 #[derive(Debug, Clone, PartialEq, Arbitrary, Serialize)]
@@ -474,15 +655,78 @@ pub enum StringCoding {
 }
 #[derive(Debug, Clone, PartialEq, Copy, Arbitrary, Serialize)]
 pub enum FloatWidth {
+    Width16 = 16,
     Width32 = 32,
     Width64 = 64,
 }
 pub fn float_width_to_type_length(width: FloatWidth) -> TypeLength {
     match width {
+        FloatWidth::Width16 => TypeLength::BitLength16,
         FloatWidth::Width32 => TypeLength::BitLength32,
         FloatWidth::Width64 => TypeLength::BitLength64,
     }
 }
+/// converts an IEEE 754 half precision float (binary16) to a f32
+/// every f16 value can be represented exactly as f32
+pub fn f16_to_f32(bits: u16) -> f32 {
+    let sign = u32::from(bits & 0x8000) << 16;
+    let exponent = u32::from((bits >> 10) & 0x1f);
+    let mantissa = u32::from(bits & 0x3ff);
+    let f32_bits = match exponent {
+        0 if mantissa == 0 => sign,
+        0 => {
+            // subnormal f16 values are normal f32 values
+            let mut exponent = 127 - 15 + 1;
+            let mut mantissa = mantissa;
+            while mantissa & 0x400 == 0 {
+                mantissa <<= 1;
+                exponent -= 1;
+            }
+            sign | (exponent << 23) | ((mantissa & 0x3ff) << 13)
+        }
+        0x1f => sign | 0x7f80_0000 | (mantissa << 13),
+        e => sign | ((e + 127 - 15) << 23) | (mantissa << 13),
+    };
+    f32::from_bits(f32_bits)
+}
+/// converts a f32 to an IEEE 754 half precision float (binary16)
+/// rounds to nearest even, values out of range become infinity
+pub fn f32_to_f16(value: f32) -> u16 {
+    let bits = value.to_bits();
+    let sign = ((bits >> 16) & 0x8000) as u16;
+    let exponent = ((bits >> 23) & 0xff) as i32;
+    let mantissa = bits & 0x7f_ffff;
+    if exponent == 0xff {
+        let nan_bits = if mantissa != 0 {
+            0x200 | (mantissa >> 13) as u16
+        } else {
+            0
+        };
+        return sign | 0x7c00 | nan_bits;
+    }
+    let half_exponent = exponent - 127 + 15;
+    if half_exponent >= 0x1f {
+        return sign | 0x7c00;
+    }
+    let (half_bits, shift) = if half_exponent <= 0 {
+        if half_exponent < -10 {
+            return sign;
+        }
+        let shift = (14 - half_exponent) as u32;
+        ((mantissa | 0x80_0000) >> shift, shift)
+    } else {
+        (((half_exponent as u32) << 10) | (mantissa >> 13), 13)
+    };
+    let round_bit = 1 << (shift - 1);
+    let rest = (mantissa | 0x80_0000) & ((round_bit << 1) - 1);
+    // a carry out of the mantissa correctly increments the exponent
+    let rounded = if rest > round_bit || (rest == round_bit && half_bits & 1 != 0) {
+        half_bits + 1
+    } else {
+        half_bits
+    };
+    sign | rounded as u16
+}
 
 #[derive(Debug, Clone, PartialEq, Copy, Arbitrary, Serialize)]
 pub enum TypeLength {
@@ -498,14 +742,19 @@ pub enum TypeInfoKind {
     Bool,
     #[proptest(strategy = "signed_strategy()")]
     Signed(TypeLength),
-    SignedFixedPoint(FloatWidth),
+    SignedFixedPoint(TypeLength),
     #[proptest(strategy = "unsigned_strategy()")]
     Unsigned(TypeLength),
-    UnsignedFixedPoint(FloatWidth),
+    UnsignedFixedPoint(TypeLength),
     Float(FloatWidth),
-    // Array, NYI
+    /// array of bool, signed, unsigned, float or fixed point values
+    #[proptest(strategy = "array_strategy()")]
+    Array(Box<TypeInfoKind>),
     StringType,
     Raw,
+    /// nested arguments, generated separately (see `argument_strategy`)
+    #[proptest(skip)]
+    Struct,
 }
 
 ///
@@ -539,6 +788,7 @@ pub struct TypeInfo {
 impl TypeInfo {
     pub fn type_length_bits_float(len: FloatWidth) -> u32 {
         match len {
+            FloatWidth::Width16 => 0b010,
             FloatWidth::Width32 => 0b011,
             FloatWidth::Width64 => 0b100,
         }
@@ -563,35 +813,49 @@ impl TypeInfo {
         }
     }
     pub fn is_fixed_point(self: &TypeInfo) -> bool {
-        match self.kind {
+        match &self.kind {
             TypeInfoKind::SignedFixedPoint(_) => true,
             TypeInfoKind::UnsignedFixedPoint(_) => true,
+            TypeInfoKind::Array(element_kind) => matches!(
+                **element_kind,
+                TypeInfoKind::SignedFixedPoint(_) | TypeInfoKind::UnsignedFixedPoint(_)
+            ),
             _ => false,
         }
     }
     pub fn as_bytes<T: ByteOrder>(self: &TypeInfo) -> Vec<u8> {
         // println!("TypeInfo::as_bytes: {:?}", self);
         let mut info: u32 = 0;
+        // arrays are encoded with the type and length of their elements
+        let kind = match &self.kind {
+            TypeInfoKind::Array(element_kind) => {
+                info |= TYPE_INFO_ARRAY_FLAG;
+                element_kind.as_ref()
+            }
+            k => k,
+        };
         // encode length
-        match self.kind {
+        match *kind {
             TypeInfoKind::Float(len) => info |= TypeInfo::type_length_bits_float(len),
             TypeInfoKind::Signed(len) => info |= TypeInfo::type_length_bits(len),
-            TypeInfoKind::SignedFixedPoint(len) => info |= TypeInfo::type_length_bits_float(len),
+            TypeInfoKind::SignedFixedPoint(len) => info |= TypeInfo::type_length_bits(len),
             TypeInfoKind::Unsigned(len) => info |= TypeInfo::type_length_bits(len),
-            TypeInfoKind::UnsignedFixedPoint(len) => info |= TypeInfo::type_length_bits_float(len),
+            TypeInfoKind::UnsignedFixedPoint(len) => info |= TypeInfo::type_length_bits(len),
             TypeInfoKind::Bool => info |= TypeInfo::type_length_bits(TypeLength::BitLength8),
             _ => (),
         }
-        match self.kind {
+        match kind {
             TypeInfoKind::Bool => info |= TYPE_INFO_BOOL_FLAG,
             TypeInfoKind::Signed(_) => info |= TYPE_INFO_SINT_FLAG,
             TypeInfoKind::SignedFixedPoint(_) => info |= TYPE_INFO_SINT_FLAG,
             TypeInfoKind::Unsigned(_) => info |= TYPE_INFO_UINT_FLAG,
             TypeInfoKind::UnsignedFixedPoint(_) => info |= TYPE_INFO_UINT_FLAG,
             TypeInfoKind::Float(_) => info |= TYPE_INFO_FLOAT_FLAG,
-            // TypeInfoKind::Array => info |= TYPE_INFO_ARRAY_FLAG,
+            // nested arrays are not supported by the protocol
+            TypeInfoKind::Array(_) => (),
             TypeInfoKind::StringType => info |= TYPE_INFO_STRING_FLAG,
             TypeInfoKind::Raw => info |= TYPE_INFO_RAW_FLAG,
+            TypeInfoKind::Struct => info |= TYPE_INFO_STRUCT_FLAG,
         }
         if self.has_variable_info {
             info |= TYPE_INFO_VARIABLE_INFO
@@ -646,6 +910,7 @@ impl TryFrom<u32> for TypeInfo {
         }
         fn type_len_float(info: u32) -> Result<FloatWidth, Error> {
             match info & 0b1111 {
+                0x02 => Ok(FloatWidth::Width16),
                 0x03 => Ok(FloatWidth::Width32),
                 0x04 => Ok(FloatWidth::Width64),
                 v => Err(Error::new(
@@ -657,19 +922,24 @@ impl TryFrom<u32> for TypeInfo {
 
         let is_fixed_point = (info & TYPE_INFO_FIXED_POINT_FLAG) != 0;
         let kind = match (info >> 4) & 0b111_1111 {
+            _ if (info & TYPE_INFO_STRUCT_FLAG) != 0 => Ok(TypeInfoKind::Struct),
             0b000_0001 => Ok(TypeInfoKind::Bool),
             0b000_0010 => Ok(if is_fixed_point {
-                TypeInfoKind::SignedFixedPoint(type_len_float(info)?)
+                TypeInfoKind::SignedFixedPoint(type_len(info)?)
             } else {
                 TypeInfoKind::Signed(type_len(info)?)
             }),
             0b000_0100 => Ok(if is_fixed_point {
-                TypeInfoKind::UnsignedFixedPoint(type_len_float(info)?)
+                TypeInfoKind::UnsignedFixedPoint(type_len(info)?)
             } else {
                 TypeInfoKind::Unsigned(type_len(info)?)
             }),
             0b000_1000 => Ok(TypeInfoKind::Float(type_len_float(info)?)),
-            // 0b001_0000 => Ok(TypeInfoKind::Array),
+            0b001_0001 | 0b001_0010 | 0b001_0100 | 0b001_1000 => {
+                // array: parse the element type without the array flag
+                let element_info = TypeInfo::try_from(info & !TYPE_INFO_ARRAY_FLAG)?;
+                Ok(TypeInfoKind::Array(Box::new(element_info.kind)))
+            }
             0b010_0000 => Ok(TypeInfoKind::StringType),
             0b100_0000 => Ok(TypeInfoKind::Raw),
             v => Err(Error::new(
@@ -706,7 +976,7 @@ impl TryFrom<u32> for TypeInfo {
 /// The width depends on the TYLE value
 ///     * i32 bit if Type Length (TYLE) equals 1,2 or 3
 ///     * i64 bit if Type Length (TYLE) equals 4
-///     * i128 bit if Type Length (TYLE) equals 5 (unsupported)
+///     * i128 bit if Type Length (TYLE) equals 5
 #[derive(Debug, Clone, PartialEq, Serialize)]
 pub struct FixedPoint {
     pub quantization: f32,
@@ -720,46 +990,39 @@ pub struct Argument {
     pub fixed_point: Option<FixedPoint>,
     pub value: Value,
 }
-impl Argument {
-    fn value_as_f64(&self) -> Option<f64> {
-        match self.value {
-            Value::I8(v) => Some(v as f64),
-            Value::I16(v) => Some(v as f64),
-            Value::I32(v) => Some(v as f64),
-            Value::I64(v) => Some(v as f64),
-            Value::U8(v) => Some(v as f64),
-            Value::U16(v) => Some(v as f64),
-            Value::U32(v) => Some(v as f64),
-            Value::U64(v) => Some(v as f64),
-            _ => None,
-        }
-    }
-    fn log_v(&self) -> Option<u64> {
-        match &self.fixed_point {
-            Some(FixedPoint {
-                quantization,
-                offset,
-            }) => {
-                if let Some(value) = self.value_as_f64() {
-                    match offset {
-                        FixedPointValue::I32(v) => {
-                            Some((value * *quantization as f64) as u64 + *v as u64)
-                        }
-                        FixedPointValue::I64(v) => {
-                            Some((value * *quantization as f64) as u64 + *v as u64)
-                        }
-                    }
-                } else {
-                    None
-                }
-            }
-            _ => None,
-        }
+fn value_as_f64(value: &Value) -> Option<f64> {
+    match *value {
+        Value::I8(v) => Some(v as f64),
+        Value::I16(v) => Some(v as f64),
+        Value::I32(v) => Some(v as f64),
+        Value::I64(v) => Some(v as f64),
+        Value::I128(v) => Some(v as f64),
+        Value::U8(v) => Some(v as f64),
+        Value::U16(v) => Some(v as f64),
+        Value::U32(v) => Some(v as f64),
+        Value::U64(v) => Some(v as f64),
+        Value::U128(v) => Some(v as f64),
+        _ => None,
     }
-    pub(crate) fn to_real_value(&self) -> Option<u64> {
+}
+/// computed in f64 since offsets can be negative and exceed 64 bit
+fn log_v(fixed_point: &FixedPoint, value: &Value) -> Option<f64> {
+    let FixedPoint {
+        quantization,
+        offset,
+    } = fixed_point;
+    let offset = match offset {
+        FixedPointValue::I32(v) => f64::from(*v),
+        FixedPointValue::I64(v) => *v as f64,
+        FixedPointValue::I128(v) => *v as f64,
+    };
+    value_as_f64(value).map(|value| value * f64::from(*quantization) + offset)
+}
+impl Argument {
+    pub(crate) fn to_real_value(&self) -> Option<f64> {
         match (&self.type_info.kind, &self.fixed_point) {
-            (TypeInfoKind::SignedFixedPoint(_), Some(_)) => self.log_v(),
-            (TypeInfoKind::UnsignedFixedPoint(_), Some(_)) => self.log_v(),
+            (TypeInfoKind::SignedFixedPoint(_), Some(fp)) => log_v(fp, &self.value),
+            (TypeInfoKind::UnsignedFixedPoint(_), Some(fp)) => log_v(fp, &self.value),
             _ => None,
         }
     }
@@ -771,10 +1034,12 @@ impl Argument {
                 Value::Bool(_) => (),
                 _ => valid = false,
             },
-            TypeInfoKind::Float(FloatWidth::Width32) => match self.value {
-                Value::F32(_) => (),
-                _ => valid = false,
-            },
+            TypeInfoKind::Float(FloatWidth::Width16) | TypeInfoKind::Float(FloatWidth::Width32) => {
+                match self.value {
+                    Value::F32(_) => (),
+                    _ => valid = false,
+                }
+            }
             TypeInfoKind::Float(FloatWidth::Width64) => match self.value {
                 Value::F64(_) => (),
                 _ => valid = false,
@@ -836,50 +1101,10 @@ impl Argument {
         let mut buf = BytesMut::with_capacity(capacity);
         buf.extend_from_slice(&info.as_bytes::<T>()[..]);
         if info.has_variable_info {
-            if let Some(n) = name {
-                #[allow(deprecated)]
-                buf.put_u16::<T>(n.len() as u16 + 1);
-            // println!("put name len: {:02X?}", buf.to_vec());
-            } else {
-                #[allow(deprecated)]
-                buf.put_u16::<T>(1u16);
-            }
-            if let Some(u) = unit {
-                #[allow(deprecated)]
-                buf.put_u16::<T>(u.len() as u16 + 1);
-            // println!("put unit len: {:02X?}", buf.to_vec());
-            } else {
-                #[allow(deprecated)]
-                buf.put_u16::<T>(1u16);
-            }
-            if let Some(n) = name {
-                buf.extend_from_slice(n.as_bytes());
-                buf.put_u8(0x0); // null termination
-                                 // println!("put name: {:02X?}", buf.to_vec());
-            } else {
-                buf.put_u8(0x0); // only null termination
-            }
-            if let Some(u) = unit {
-                buf.extend_from_slice(u.as_bytes());
-                buf.put_u8(0x0); // null termination
-                                 // println!("put unit: {:02X?}", buf.to_vec());
-            } else {
-                buf.put_u8(0x0); // only null termination
-            }
+            put_name_and_unit::<T>(&mut buf, name, unit);
         }
         if let Some(fp) = fixed_point {
-            #[allow(deprecated)]
-            buf.put_f32::<T>(fp.quantization);
-            match fp.offset {
-                FixedPointValue::I32(v) => {
-                    #[allow(deprecated)]
-                    buf.put_i32::<T>(v);
-                }
-                FixedPointValue::I64(v) => {
-                    #[allow(deprecated)]
-                    buf.put_i64::<T>(v);
-                }
-            }
+            put_fixed_point::<T>(&mut buf, fp);
         }
         // println!("typeinfo + name + unit as bytes: {:02X?}", buf.to_vec());
         buf
@@ -942,37 +1167,100 @@ impl Argument {
                 dbg_bytes(buf.len(), "unsigned FP argument", &buf.to_vec()[..]);
                 buf.to_vec()
             }
-            TypeInfoKind::Float(_) => {
-                fn write_value<T: ByteOrder>(value: &Value, buf: &mut BytesMut) {
-                    match value {
-                        Value::F32(v) => {
-                            let mut b = [0; 4];
-                            T::write_f32(&mut b, *v);
-                            buf.put_slice(&b)
-                        }
-                        Value::F64(v) => {
-                            let mut b = [0; 8];
-                            T::write_f64(&mut b, *v);
-                            buf.put_slice(&b)
-                        }
-                        _ => (),
-                    }
-                }
+            TypeInfoKind::Float(width) => {
                 let mut buf = self.mut_buf_with_typeinfo_name_unit::<T>(
                     &self.type_info,
                     &self.name,
                     &self.unit,
                     &self.fixed_point,
                 );
-                write_value::<T>(&self.value, &mut buf);
+                put_float_value::<T>(width, &self.value, &mut buf);
                 dbg_bytes(buf.len(), "float argument", &buf.to_vec()[..]);
                 buf.to_vec()
             }
-            // TypeInfoKind::Array => {
-            //     // TODO dlt array type not yet implemented NYI
-            //     eprintln!("found dlt array type...not yet supported");
-            //     BytesMut::with_capacity(STORAGE_HEADER_LENGTH).to_vec()
-            // }
+            TypeInfoKind::Array(ref element_kind) => match &self.value {
+                Value::Array { dimensions, values } => {
+                    let name_and_unit_len = 2 /* length name */ + 2 /* length unit */
+                        + self.name.as_ref().map_or(0, String::len) + 1
+                        + self.unit.as_ref().map_or(0, String::len) + 1;
+                    let mut buf = BytesMut::with_capacity(
+                        TYPE_INFO_LENGTH
+                            + 2 /* number of dimensions */
+                            + 2 * dimensions.len()
+                            + name_and_unit_len
+                            + 4 /* quantization */ + 16 /* offset */
+                            + values.len() * 16,
+                    );
+                    buf.extend_from_slice(&self.type_info.as_bytes::<T>()[..]);
+                    #[allow(deprecated)]
+                    buf.put_u16::<T>(dimensions.len() as u16);
+                    for dimension in dimensions {
+                        #[allow(deprecated)]
+                        buf.put_u16::<T>(*dimension);
+                    }
+                    if self.type_info.has_variable_info {
+                        put_name_and_unit::<T>(&mut buf, &self.name, &self.unit);
+                    }
+                    if let Some(fp) = &self.fixed_point {
+                        put_fixed_point::<T>(&mut buf, fp);
+                    }
+                    for value in values {
+                        match **element_kind {
+                            TypeInfoKind::Bool => buf.put_u8(if *value == Value::Bool(true) {
+                                0x1
+                            } else {
+                                0x0
+                            }),
+                            TypeInfoKind::Signed(_) | TypeInfoKind::SignedFixedPoint(_) => {
+                                put_signed_value::<T>(value, &mut buf)
+                            }
+                            TypeInfoKind::Unsigned(_) | TypeInfoKind::UnsignedFixedPoint(_) => {
+                                put_unsigned_value::<T>(value, &mut buf)
+                            }
+                            TypeInfoKind::Float(width) => {
+                                put_float_value::<T>(width, value, &mut buf)
+                            }
+                            _ => {
+                                error!("found invalid dlt array element type ({:?}", self);
+                                return BytesMut::with_capacity(0).to_vec();
+                            }
+                        }
+                    }
+                    dbg_bytes(buf.len(), "array argument", &buf.to_vec()[..]);
+                    buf.to_vec()
+                }
+                _ => {
+                    error!("found invalid dlt entry for Array ({:?}", self);
+                    BytesMut::with_capacity(0).to_vec()
+                }
+            },
+            TypeInfoKind::Struct => match &self.value {
+                Value::Struct(entries) => {
+                    let name_len = self.name.as_ref().map_or(0, String::len);
+                    let mut buf = BytesMut::with_capacity(
+                        TYPE_INFO_LENGTH + 2 /* number of entries */ + 2 /* length name */ + name_len + 1,
+                    );
+                    buf.extend_from_slice(&self.type_info.as_bytes::<T>()[..]);
+                    #[allow(deprecated)]
+                    buf.put_u16::<T>(entries.len() as u16);
+                    if self.type_info.has_variable_info {
+                        let name = self.name.as_deref().unwrap_or("");
+                        #[allow(deprecated)]
+                        buf.put_u16::<T>(name.len() as u16 + 1);
+                        buf.extend_from_slice(name.as_bytes());
+                        buf.put_u8(0x0); // null termination
+                    }
+                    for entry in entries {
+                        buf.extend_from_slice(&entry.as_bytes::<T>()[..]);
+                    }
+                    dbg_bytes(buf.len(), "struct argument", &buf.to_vec()[..]);
+                    buf.to_vec()
+                }
+                _ => {
+                    error!("found invalid dlt entry for Struct ({:?}", self);
+                    BytesMut::with_capacity(0).to_vec()
+                }
+            },
             TypeInfoKind::StringType => {
                 match (self.type_info.has_variable_info, &self.name) {
                     (true, Some(var_name)) => {
@@ -1100,6 +1388,77 @@ impl Argument {
         }
     }
 }
+fn put_name_and_unit<T: ByteOrder>(
+    buf: &mut BytesMut,
+    name: &Option<String>,
+    unit: &Option<String>,
+) {
+    if let Some(n) = name {
+        #[allow(deprecated)]
+        buf.put_u16::<T>(n.len() as u16 + 1);
+    } else {
+        #[allow(deprecated)]
+        buf.put_u16::<T>(1u16);
+    }
+    if let Some(u) = unit {
+        #[allow(deprecated)]
+        buf.put_u16::<T>(u.len() as u16 + 1);
+    } else {
+        #[allow(deprecated)]
+        buf.put_u16::<T>(1u16);
+    }
+    if let Some(n) = name {
+        buf.extend_from_slice(n.as_bytes());
+        buf.put_u8(0x0); // null termination
+    } else {
+        buf.put_u8(0x0); // only null termination
+    }
+    if let Some(u) = unit {
+        buf.extend_from_slice(u.as_bytes());
+        buf.put_u8(0x0); // null termination
+    } else {
+        buf.put_u8(0x0); // only null termination
+    }
+}
+fn put_fixed_point<T: ByteOrder>(buf: &mut BytesMut, fp: &FixedPoint) {
+    #[allow(deprecated)]
+    buf.put_f32::<T>(fp.quantization);
+    match fp.offset {
+        FixedPointValue::I32(v) => {
+            #[allow(deprecated)]
+            buf.put_i32::<T>(v);
+        }
+        FixedPointValue::I64(v) => {
+            #[allow(deprecated)]
+            buf.put_i64::<T>(v);
+        }
+        FixedPointValue::I128(v) => {
+            let mut b = [0; 16];
+            T::write_i128(&mut b, v);
+            buf.put_slice(&b);
+        }
+    }
+}
+fn put_float_value<T: ByteOrder>(width: FloatWidth, value: &Value, buf: &mut BytesMut) {
+    match value {
+        Value::F32(v) if width == FloatWidth::Width16 => {
+            let mut b = [0; 2];
+            T::write_u16(&mut b, f32_to_f16(*v));
+            buf.put_slice(&b)
+        }
+        Value::F32(v) => {
+            let mut b = [0; 4];
+            T::write_f32(&mut b, *v);
+            buf.put_slice(&b)
+        }
+        Value::F64(v) => {
+            let mut b = [0; 8];
+            T::write_f64(&mut b, *v);
+            buf.put_slice(&b)
+        }
+        _ => (),
+    }
+}
 fn put_unsigned_value<T: ByteOrder>(value: &Value, buf: &mut BytesMut) {
     match value {
         Value::U8(v) => buf.put_u8(*v),
@@ -1269,15 +1628,48 @@ impl Payload2 {
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Serialize)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Message {
     pub storage_header: Option<StorageHeader>,
     pub header: StandardHeader,
     pub extended_header: Option<ExtendedHeader>,
     pub payload: Payload2,
-    #[serde(skip_serializing)]
     pub fibex_metadata: Option<Rc<FibexMetadata>>,
 }
+/// control messages additionally contain the decoded content of the payload,
+/// non verbose messages the arguments described in the fibex metadata and
+/// SOME/IP and CAN network traces the decoded SOME/IP message or CAN frame
+impl Serialize for Message {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let control_message = self.control_message();
+        let nonverbose_arguments = self.nonverbose_arguments();
+        let someip_message = self.someip_message();
+        let can_frame = self.can_frame();
+        let field_cnt = 4
+            + control_message.is_some() as usize
+            + nonverbose_arguments.is_some() as usize
+            + someip_message.is_some() as usize
+            + can_frame.is_some() as usize;
+        let mut state = serializer.serialize_struct("Message", field_cnt)?;
+        state.serialize_field("storage_header", &self.storage_header)?;
+        state.serialize_field("header", &self.header)?;
+        state.serialize_field("extended_header", &self.extended_header)?;
+        state.serialize_field("payload", &self.payload)?;
+        if let Some(control_message) = control_message {
+            state.serialize_field("control_message", &control_message)?;
+        }
+        if let Some(arguments) = nonverbose_arguments {
+            state.serialize_field("nonverbose_arguments", &arguments)?;
+        }
+        if let Some(someip_message) = someip_message {
+            state.serialize_field("someip_message", &someip_message)?;
+        }
+        if let Some(can_frame) = can_frame {
+            state.serialize_field("can_frame", &can_frame)?;
+        }
+        state.end()
+    }
+}
 pub const DLT_COLUMN_SENTINAL: char = '\u{0004}';
 pub const DLT_ARGUMENT_SENTINAL: char = '\u{0005}';
 pub const DLT_NEWLINE_SENTINAL_SLICE: &[u8] = &[0x6];
@@ -1323,6 +1715,9 @@ impl Message {
     ) -> Self {
         // println!("--- Message::new, conf = {:?}", conf);
         // println!("--- Message::new, arg-cnt = {}", conf.payload.arg_count());
+        if conf.version == 2 {
+            return Message::new_v2(conf, fibex, storage_header);
+        }
         let payload_length = if conf.endianness == Endianness::Big {
             conf.payload.as_bytes::<BigEndian>().len()
         } else {
@@ -1338,6 +1733,7 @@ impl Message {
                 timestamp: conf.timestamp,
                 has_extended_header: conf.extended_header_info.is_some(),
                 payload_length,
+                v2: None,
             },
             extended_header: match conf.extended_header_info {
                 Some(ext_info) => Some(ExtendedHeader {
@@ -1355,6 +1751,130 @@ impl Message {
         }
     }
 
+    /// a version 2 message, the payload is big endian and the timestamp
+    /// (in 0.1 milliseconds) is used for verbose and non verbose messages
+    fn new_v2(
+        conf: MessageConfig,
+        fibex: Option<Rc<FibexMetadata>>,
+        storage_header: Option<StorageHeader>,
+    ) -> Self {
+        let content_info = match conf.payload.payload_content {
+            PayloadContent::Verbose(_) => ContentInfo::Verbose,
+            PayloadContent::NonVerbose(_, _) => ContentInfo::NonVerbose,
+            PayloadContent::ControlMsg(_, _) => ContentInfo::Control,
+        };
+        let timestamp = if content_info == ContentInfo::Control {
+            None
+        } else {
+            let ticks = conf.timestamp.unwrap_or(0);
+            Some(DltTimeStampV2 {
+                seconds: u64::from(ticks / 10_000),
+                nanoseconds: ticks % 10_000 * 100_000,
+            })
+        };
+        let (message_type, app_id_context_id) = match conf.extended_header_info {
+            Some(info) => (
+                Some(info.message_type),
+                Some((info.app_id, info.context_id)),
+            ),
+            None => (None, None),
+        };
+        let v2 = HeaderV2 {
+            content_info,
+            message_type: if content_info == ContentInfo::NonVerbose {
+                None
+            } else {
+                message_type
+            },
+            timestamp,
+            app_id_context_id,
+            source_location: None,
+            tags: None,
+            privacy_level: None,
+        };
+        let payload_length = Message::v2_payload_bytes(&conf.payload).len() as u16;
+        Message {
+            header: StandardHeader {
+                version: 2,
+                endianness: Endianness::Big,
+                message_counter: conf.counter,
+                ecu_id: conf.ecu_id,
+                session_id: conf.session_id,
+                timestamp: None,
+                has_extended_header: v2.app_id_context_id.is_some(),
+                payload_length,
+                v2: Some(Box::new(v2.clone())),
+            },
+            extended_header: v2.extended_header(conf.payload.arg_count()),
+            payload: conf.payload,
+            fibex_metadata: fibex,
+            storage_header,
+        }
+    }
+    /// the message id of non verbose version 2 messages is part of the header
+    fn v2_payload_bytes(payload: &Payload2) -> Vec<u8> {
+        match &payload.payload_content {
+            PayloadContent::NonVerbose(_, data) => data.clone(),
+            _ => payload.as_bytes::<BigEndian>(),
+        }
+    }
+    fn v2_headers_as_bytes(&self, v2: &HeaderV2) -> Vec<u8> {
+        fn put_length_prefixed_string(buf: &mut BytesMut, s: &str) {
+            let bytes = &s.as_bytes()[..s.len().min(u8::MAX as usize)];
+            buf.put_u8(bytes.len() as u8);
+            buf.extend_from_slice(bytes);
+        }
+        let header = &self.header;
+        let (header_type, extension_type) = header.v2_header_type(v2);
+        let mut buf = BytesMut::with_capacity(header.v2_headers_length(v2) as usize);
+        buf.put_u8(header_type);
+        buf.put_u8(extension_type);
+        buf.put_u16_be(0);
+        buf.put_u8(header.message_counter);
+        buf.put_u16_be(header.overall_length());
+        if v2.content_info != ContentInfo::NonVerbose {
+            // without message type verbose and control messages are sent as invalid logs
+            buf.put_u8(v2.message_type.as_ref().map(u8::from).unwrap_or(0));
+            buf.put_u8(self.payload.arg_count());
+        }
+        if v2.content_info != ContentInfo::Control {
+            let time = v2.timestamp.clone().unwrap_or(DltTimeStampV2 {
+                seconds: 0,
+                nanoseconds: 0,
+            });
+            buf.put_u32_be(time.nanoseconds);
+            buf.put_u8((time.seconds >> 32) as u8);
+            buf.put_u32_be(time.seconds as u32);
+        }
+        if let PayloadContent::NonVerbose(message_id, _) = &self.payload.payload_content {
+            buf.put_u32_be(*message_id);
+        }
+        if let Some(id) = &header.ecu_id {
+            put_length_prefixed_string(&mut buf, id);
+        }
+        if let Some((app_id, context_id)) = &v2.app_id_context_id {
+            put_length_prefixed_string(&mut buf, app_id);
+            put_length_prefixed_string(&mut buf, context_id);
+        }
+        if let Some(id) = header.session_id {
+            buf.put_u32_be(id);
+        }
+        if let Some(location) = &v2.source_location {
+            put_length_prefixed_string(&mut buf, &location.file_name);
+            buf.put_u32_be(location.line_number);
+        }
+        if let Some(tags) = &v2.tags {
+            buf.put_u8(tags.len() as u8);
+            for tag in tags {
+                put_length_prefixed_string(&mut buf, tag);
+            }
+        }
+        if let Some(level) = v2.privacy_level {
+            buf.put_u8(level);
+        }
+        buf.to_vec()
+    }
+
     pub fn as_bytes(self: &Message) -> Vec<u8> {
         // println!(
         //     "message header overall_length: {}",
@@ -1375,6 +1895,11 @@ impl Message {
             // println!("using capacity: {}", capacity);
             BytesMut::with_capacity(capacity)
         };
+        if let Some(v2) = &self.header.v2 {
+            buf.extend_from_slice(&self.v2_headers_as_bytes(v2)[..]);
+            buf.extend_from_slice(&Message::v2_payload_bytes(&self.payload)[..]);
+            return buf.to_vec();
+        }
         dbg_bytes(buf.len(), "header", &self.header.header_as_bytes()[..]);
         buf.extend_from_slice(&self.header.header_as_bytes()[..]);
         if let Some(ext_header) = &self.extended_header {
@@ -1448,6 +1973,14 @@ impl fmt::Display for Message {
         write!(f, "{}", self.header)?;
         write!(f, "{}", DLT_COLUMN_SENTINAL,)?;
 
+        if let Some(someip_message) = self.someip_message() {
+            self.write_app_id_context_id_and_message_type(f)?;
+            return write!(f, "{}", someip_message);
+        }
+        if let Some(can_frame) = self.can_frame() {
+            self.write_app_id_context_id_and_message_type(f)?;
+            return write!(f, "{}", can_frame);
+        }
         match &self.payload.payload_content {
             PayloadContent::Verbose(arguments) => {
                 self.write_app_id_context_id_and_message_type(f)?;
@@ -1456,32 +1989,164 @@ impl fmt::Display for Message {
                     .try_for_each(|arg| write!(f, "{}{}", DLT_ARGUMENT_SENTINAL, arg))
             }
             PayloadContent::NonVerbose(id, data) => self.format_nonverbose_data(*id, data, f),
-            PayloadContent::ControlMsg(ctrl_id, _data) => {
+            PayloadContent::ControlMsg(ctrl_id, data) => {
                 self.write_app_id_context_id_and_message_type(f)?;
-                match SERVICE_ID_MAPPING.get(&ctrl_id.value()) {
-                    Some((name, _desc)) => write!(f, "[{}]", name),
-                    None => write!(f, "[Unknown CtrlCommand]"),
+                match self.control_message() {
+                    Some(control_message) => write!(f, "{}", control_message),
+                    None => {
+                        let mut payload = vec![ctrl_id.value()];
+                        payload.extend_from_slice(data);
+                        write!(f, "[Unknown CtrlCommand] {:02X?}", payload)
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// the traced message (e.g. a SOME/IP message or CAN frame) in the payload of
+/// a network trace
+///
+/// verbose traces carry it in raw arguments (usually header and payload),
+/// non verbose traces in the payload including the message id, either as is
+/// or as header and payload that each start with their length. the latter
+/// is only assumed if the first part has exactly `header_length` bytes
+pub(crate) fn network_trace_bytes(
+    payload: &PayloadContent,
+    endianness: Endianness,
+    header_length: usize,
+) -> Option<Vec<u8>> {
+    match payload {
+        PayloadContent::Verbose(arguments) => {
+            let mut bytes = vec![];
+            for argument in arguments {
+                match &argument.value {
+                    Value::Raw(raw) => bytes.extend_from_slice(raw),
+                    _ => return None,
                 }
             }
+            Some(bytes)
+        }
+        PayloadContent::NonVerbose(message_id, data) => {
+            let mut bytes = vec![0u8; 4];
+            match endianness {
+                Endianness::Big => BigEndian::write_u32(&mut bytes, *message_id),
+                Endianness::Little => LittleEndian::write_u32(&mut bytes, *message_id),
+            }
+            bytes.extend_from_slice(data);
+            Some(match header_and_payload(&bytes, endianness, header_length) {
+                Some(parts) => parts,
+                None => bytes,
+            })
         }
+        PayloadContent::ControlMsg(_, _) => None,
     }
 }
+/// header and payload without their length prefixes, None if the bytes
+/// don't consist of exactly a header of `header_length` and a payload
+fn header_and_payload(
+    bytes: &[u8],
+    endianness: Endianness,
+    header_length: usize,
+) -> Option<Vec<u8>> {
+    let read_length = |b: &[u8]| match endianness {
+        Endianness::Big => BigEndian::read_u16(b) as usize,
+        Endianness::Little => LittleEndian::read_u16(b) as usize,
+    };
+    if bytes.len() < 2 + header_length + 2 || read_length(bytes) != header_length {
+        return None;
+    }
+    let header = &bytes[2..2 + header_length];
+    let rest = &bytes[2 + header_length..];
+    if read_length(rest) != rest.len() - 2 {
+        return None;
+    }
+    let mut parts = header.to_vec();
+    parts.extend_from_slice(&rest[2..]);
+    Some(parts)
+}
 
 impl Message {
+    /// decoded service id and parameters of a control message
+    pub fn control_message(&self) -> Option<ControlMessage> {
+        match &self.payload.payload_content {
+            PayloadContent::ControlMsg(ctrl_id, data) => {
+                let control_type = match &self.extended_header {
+                    Some(ExtendedHeader {
+                        message_type: MessageType::Control(control_type),
+                        ..
+                    }) => control_type.clone(),
+                    _ => ControlType::Unknown(0),
+                };
+                // the service id starts with the first byte of the payload
+                let mut payload = Vec::with_capacity(data.len() + 1);
+                payload.push(ctrl_id.value());
+                payload.extend_from_slice(data);
+                decode_control_message(&payload, self.header.endianness, &control_type)
+            }
+            _ => None,
+        }
+    }
+    /// decoded SOME/IP message of a SOME/IP network trace, names and
+    /// parameters are resolved with the service mapping of the fibex metadata
+    pub fn someip_message(&self) -> Option<SomeipMessage> {
+        match &self.extended_header {
+            Some(ExtendedHeader {
+                message_type: MessageType::NetworkTrace(NetworkTraceType::Someip),
+                ..
+            }) => {
+                let bytes = network_trace_bytes(
+                    &self.payload.payload_content,
+                    self.header.endianness,
+                    SOMEIP_HEADER_LENGTH,
+                )?;
+                decode_someip_message(&bytes, self.fibex_metadata.as_deref())
+            }
+            _ => None,
+        }
+    }
+    /// decoded CAN frame of a CAN network trace, the signals are decoded with
+    /// the DBC messages of the fibex metadata
+    pub fn can_frame(&self) -> Option<CanFrame> {
+        match &self.extended_header {
+            Some(ExtendedHeader {
+                message_type: MessageType::NetworkTrace(NetworkTraceType::Can),
+                ..
+            }) => {
+                let bytes = network_trace_bytes(
+                    &self.payload.payload_content,
+                    self.header.endianness,
+                    CAN_FRAME_HEADER_LENGTH,
+                )?;
+                decode_can_frame(&bytes, self.fibex_metadata.as_deref())
+            }
+            _ => None,
+        }
+    }
+    fn fibex_frame_metadata(&self, id: u32) -> Option<&Rc<FrameMetadata>> {
+        self.fibex_metadata.as_ref()?.frame(
+            self.extended_header
+                .as_ref()
+                .map(|h| (h.application_id.as_str(), h.context_id.as_str())),
+            id,
+        )
+    }
+    /// arguments of a non verbose message as described in the fibex metadata
+    /// None if the message is not non verbose, the fibex does not contain the
+    /// frame or the payload does not match the frame
+    pub fn nonverbose_arguments(&self) -> Option<Vec<Argument>> {
+        match &self.payload.payload_content {
+            PayloadContent::NonVerbose(id, data) => {
+                let frame_metadata = self.fibex_frame_metadata(*id)?;
+                self.decode_nonverbose_arguments(frame_metadata, data)
+            }
+            _ => None,
+        }
+    }
     fn format_nonverbose_data(&self, id: u32, data: &[u8], f: &mut fmt::Formatter) -> fmt::Result {
         let mut is_written = false;
-        if let Some(fibex_metadata) = &self.fibex_metadata {
-            let id_text = format!("ID_{}", id);
-            let frame_metadata = if let Some(extended_header) = &self.extended_header {
-                fibex_metadata.frame_map_with_key.get(&(
-                    ContextId(extended_header.context_id.clone()),
-                    ApplicationId(extended_header.application_id.clone()),
-                    FrameId(id_text),
-                )) // TODO: avoid cloning here (Cow or Borrow)
-            } else {
-                fibex_metadata.frame_map.get(&FrameId(id_text))
-            };
-            if let Some(frame_metadata) = frame_metadata {
+        if self.fibex_metadata.is_some() {
+            if let Some(frame_metadata) = self.fibex_frame_metadata(id) {
                 let FrameMetadata {
                     application_id,
                     context_id,
@@ -1520,198 +2185,13 @@ impl Message {
                     write!(f, "-")?;
                 }
                 write!(f, "{}", DLT_COLUMN_SENTINAL)?;
-                let mut offset = 0;
-                for pdu in &frame_metadata.pdus {
-                    if let Some(description) = &pdu.description {
-                        let arg = Argument {
-                            type_info: TypeInfo {
-                                kind: TypeInfoKind::StringType,
-                                coding: StringCoding::UTF8,
-                                has_trace_info: false,
-                                has_variable_info: false,
-                            },
-                            name: None,
-                            unit: None,
-                            fixed_point: None,
-                            value: Value::StringVal(description.to_string()),
-                        };
-                        write!(f, "{}{} ", DLT_ARGUMENT_SENTINAL, arg)?;
-                    } else {
-                        for signal_type in &pdu.signal_types {
-                            let mut fixed_point = None;
-                            let value = match signal_type.kind {
-                                TypeInfoKind::StringType | TypeInfoKind::Raw => {
-                                    if data.len() < offset + 2 {
-                                        return fmt::Result::Err(fmt::Error);
-                                    }
-                                    let length = if self.header.endianness == Endianness::Big {
-                                        BigEndian::read_u16(&data[offset..offset + 2]) as usize
-                                    } else {
-                                        LittleEndian::read_u16(&data[offset..offset + 2]) as usize
-                                    };
-                                    offset += 2;
-                                    if data.len() < offset + length {
-                                        return fmt::Result::Err(fmt::Error);
-                                    }
-                                    let v = match signal_type.kind {
-                                        TypeInfoKind::StringType => Value::StringVal(
-                                            String::from_utf8(
-                                                data[offset..offset + length].to_vec(),
-                                            )
-                                            .map_err(|_| fmt::Error)?,
-                                        ),
-                                        TypeInfoKind::Raw => {
-                                            Value::Raw(Vec::from(&data[offset..offset + length]))
-                                        }
-                                        _ => unreachable!(),
-                                    };
-                                    offset += length;
-                                    v
-                                }
-                                TypeInfoKind::Bool => {
-                                    offset += 1;
-                                    if data.len() < offset {
-                                        return fmt::Result::Err(fmt::Error);
-                                    }
-                                    Value::Bool(data[offset - 1] != 0)
-                                }
-                                TypeInfoKind::Float(width) => {
-                                    let length = width as usize / 8;
-                                    if data.len() < offset + length {
-                                        return fmt::Result::Err(fmt::Error);
-                                    }
-                                    let v = if self.header.endianness == Endianness::Big {
-                                        dlt_fint::<BigEndian>(width)(&data[offset..offset + length])
-                                    } else {
-                                        dlt_fint::<LittleEndian>(width)(
-                                            &data[offset..offset + length],
-                                        )
-                                    }
-                                    .map_err(|_| fmt::Error)?
-                                    .1;
-                                    offset += length;
-                                    v
-                                }
-                                TypeInfoKind::Signed(length) => {
-                                    let byte_length = length as usize / 8;
-                                    if data.len() < offset + byte_length {
-                                        return fmt::Result::Err(fmt::Error);
-                                    }
-                                    let value_offset = &data[offset..];
-                                    let (_, v) = if self.header.endianness == Endianness::Big {
-                                        dlt_sint::<BigEndian>(length)(value_offset)
-                                    } else {
-                                        dlt_sint::<LittleEndian>(length)(value_offset)
-                                    }
-                                    .map_err(|_| fmt::Error)?;
-                                    offset += byte_length;
-                                    v
-                                }
-                                TypeInfoKind::SignedFixedPoint(length) => {
-                                    let byte_length = length as usize / 8;
-                                    if data.len() < offset + byte_length {
-                                        return fmt::Result::Err(fmt::Error);
-                                    }
-                                    let (value_offset, fp) =
-                                        if self.header.endianness == Endianness::Big {
-                                            dlt_fixed_point::<BigEndian>(
-                                                &data[offset..offset + byte_length],
-                                                length,
-                                            )
-                                        } else {
-                                            dlt_fixed_point::<LittleEndian>(
-                                                &data[offset..offset + byte_length],
-                                                length,
-                                            )
-                                        }
-                                        .map_err(|_| fmt::Error)?;
-                                    fixed_point = Some(fp);
-                                    let (_, v) =
-                                        if self.header.endianness == Endianness::Big {
-                                            dlt_sint::<BigEndian>(float_width_to_type_length(
-                                                length,
-                                            ))(
-                                                value_offset
-                                            )
-                                        } else {
-                                            dlt_sint::<LittleEndian>(float_width_to_type_length(
-                                                length,
-                                            ))(
-                                                value_offset
-                                            )
-                                        }
-                                        .map_err(|_| fmt::Error)?;
-                                    offset += byte_length;
-                                    v
-                                }
-                                TypeInfoKind::Unsigned(length) => {
-                                    let byte_length = length as usize / 8;
-                                    if data.len() < offset + byte_length {
-                                        return fmt::Result::Err(fmt::Error);
-                                    }
-                                    let value_offset = &data[offset..];
-                                    let (_, v) = if self.header.endianness == Endianness::Big {
-                                        dlt_uint::<BigEndian>(length)(value_offset)
-                                    } else {
-                                        dlt_uint::<LittleEndian>(length)(value_offset)
-                                    }
-                                    .map_err(|_| fmt::Error)?;
-                                    offset += byte_length;
-                                    v
-                                }
-                                TypeInfoKind::UnsignedFixedPoint(length) => {
-                                    let byte_length = length as usize / 8;
-                                    if data.len() < offset + byte_length {
-                                        return fmt::Result::Err(fmt::Error);
-                                    }
-                                    let value_offset = {
-                                        let (r, fp) =
-                                            if self.header.endianness == Endianness::Big {
-                                                dlt_fixed_point::<BigEndian>(
-                                                    &data[offset..offset + byte_length],
-                                                    length,
-                                                )
-                                            } else {
-                                                dlt_fixed_point::<LittleEndian>(
-                                                    &data[offset..offset + byte_length],
-                                                    length,
-                                                )
-                                            }
-                                            .map_err(|_| fmt::Error)?;
-                                        fixed_point = Some(fp);
-                                        r
-                                    };
-                                    let (_, v) =
-                                        if self.header.endianness == Endianness::Big {
-                                            dlt_uint::<BigEndian>(float_width_to_type_length(
-                                                length,
-                                            ))(
-                                                value_offset
-                                            )
-                                        } else {
-                                            dlt_uint::<LittleEndian>(float_width_to_type_length(
-                                                length,
-                                            ))(
-                                                value_offset
-                                            )
-                                        }
-                                        .map_err(|_| fmt::Error)?;
-                                    offset += byte_length;
-                                    v
-                                }
-                            };
-                            let arg = Argument {
-                                type_info: signal_type.clone(),
-                                name: None,
-                                unit: None,
-                                fixed_point,
-                                value,
-                            };
-                            write!(f, "{}{} ", DLT_ARGUMENT_SENTINAL, arg)?;
-                        }
-                    };
-                    is_written = true;
+                let arguments = self
+                    .decode_nonverbose_arguments(frame_metadata, data)
+                    .ok_or(fmt::Error)?;
+                for arg in arguments {
+                    write!(f, "{}{} ", DLT_ARGUMENT_SENTINAL, arg)?;
                 }
+                is_written = true;
             } else {
                 self.write_app_id_context_id_and_message_type(f)?;
             }
@@ -1750,6 +2230,224 @@ impl Message {
         }
         Ok(())
     }
+    /// decodes the signals of the fibex frame from the payload of a non verbose message
+    fn decode_nonverbose_arguments(
+        &self,
+        frame_metadata: &FrameMetadata,
+        data: &[u8],
+    ) -> Option<Vec<Argument>> {
+        let mut offset = 0;
+        let mut arguments = vec![];
+        for pdu in &frame_metadata.pdus {
+            if let Some(description) = &pdu.description {
+                let arg = Argument {
+                    type_info: TypeInfo {
+                        kind: TypeInfoKind::StringType,
+                        coding: StringCoding::UTF8,
+                        has_trace_info: false,
+                        has_variable_info: false,
+                    },
+                    name: None,
+                    unit: None,
+                    fixed_point: None,
+                    value: Value::StringVal(description.to_string()),
+                };
+                arguments.push(arg);
+            } else {
+                for signal in &pdu.signals {
+                    let signal_type = &signal.type_info;
+                    let mut fixed_point = None;
+                    let value = match signal_type.kind {
+                        TypeInfoKind::StringType | TypeInfoKind::Raw => {
+                            if data.len() < offset + 2 {
+                                return None;
+                            }
+                            let length = if self.header.endianness == Endianness::Big {
+                                BigEndian::read_u16(&data[offset..offset + 2]) as usize
+                            } else {
+                                LittleEndian::read_u16(&data[offset..offset + 2]) as usize
+                            };
+                            offset += 2;
+                            if data.len() < offset + length {
+                                return None;
+                            }
+                            let v = match signal_type.kind {
+                                TypeInfoKind::StringType => Value::StringVal(
+                                    String::from_utf8(data[offset..offset + length].to_vec())
+                                        .ok()?,
+                                ),
+                                TypeInfoKind::Raw => {
+                                    Value::Raw(Vec::from(&data[offset..offset + length]))
+                                }
+                                _ => unreachable!(),
+                            };
+                            offset += length;
+                            v
+                        }
+                        TypeInfoKind::Bool => {
+                            offset += 1;
+                            if data.len() < offset {
+                                return None;
+                            }
+                            Value::Bool(data[offset - 1] != 0)
+                        }
+                        TypeInfoKind::Float(width) => {
+                            let length = width as usize / 8;
+                            if data.len() < offset + length {
+                                return None;
+                            }
+                            let v = if self.header.endianness == Endianness::Big {
+                                dlt_fint::<BigEndian>(width)(&data[offset..offset + length])
+                            } else {
+                                dlt_fint::<LittleEndian>(width)(&data[offset..offset + length])
+                            }
+                            .ok()?
+                            .1;
+                            offset += length;
+                            v
+                        }
+                        TypeInfoKind::Signed(length) => {
+                            let byte_length = length as usize / 8;
+                            if data.len() < offset + byte_length {
+                                return None;
+                            }
+                            let value_offset = &data[offset..];
+                            let (_, v) = if self.header.endianness == Endianness::Big {
+                                dlt_sint::<BigEndian>(length)(value_offset)
+                            } else {
+                                dlt_sint::<LittleEndian>(length)(value_offset)
+                            }
+                            .ok()?;
+                            offset += byte_length;
+                            v
+                        }
+                        TypeInfoKind::SignedFixedPoint(length) => {
+                            let byte_length = length as usize / 8;
+                            if data.len() < offset + byte_length {
+                                return None;
+                            }
+                            let (value_offset, fp) = if self.header.endianness == Endianness::Big {
+                                dlt_fixed_point::<BigEndian>(
+                                    &data[offset..offset + byte_length],
+                                    length,
+                                )
+                            } else {
+                                dlt_fixed_point::<LittleEndian>(
+                                    &data[offset..offset + byte_length],
+                                    length,
+                                )
+                            }
+                            .ok()?;
+                            fixed_point = Some(fp);
+                            let (_, v) = if self.header.endianness == Endianness::Big {
+                                dlt_sint::<BigEndian>(length)(value_offset)
+                            } else {
+                                dlt_sint::<LittleEndian>(length)(value_offset)
+                            }
+                            .ok()?;
+                            offset += byte_length;
+                            v
+                        }
+                        TypeInfoKind::Unsigned(length) => {
+                            let byte_length = length as usize / 8;
+                            if data.len() < offset + byte_length {
+                                return None;
+                            }
+                            let value_offset = &data[offset..];
+                            let (_, v) = if self.header.endianness == Endianness::Big {
+                                dlt_uint::<BigEndian>(length)(value_offset)
+                            } else {
+                                dlt_uint::<LittleEndian>(length)(value_offset)
+                            }
+                            .ok()?;
+                            offset += byte_length;
+                            v
+                        }
+                        TypeInfoKind::UnsignedFixedPoint(length) => {
+                            let byte_length = length as usize / 8;
+                            if data.len() < offset + byte_length {
+                                return None;
+                            }
+                            let value_offset = {
+                                let (r, fp) = if self.header.endianness == Endianness::Big {
+                                    dlt_fixed_point::<BigEndian>(
+                                        &data[offset..offset + byte_length],
+                                        length,
+                                    )
+                                } else {
+                                    dlt_fixed_point::<LittleEndian>(
+                                        &data[offset..offset + byte_length],
+                                        length,
+                                    )
+                                }
+                                .ok()?;
+                                fixed_point = Some(fp);
+                                r
+                            };
+                            let (_, v) = if self.header.endianness == Endianness::Big {
+                                dlt_uint::<BigEndian>(length)(value_offset)
+                            } else {
+                                dlt_uint::<LittleEndian>(length)(value_offset)
+                            }
+                            .ok()?;
+                            offset += byte_length;
+                            v
+                        }
+                        TypeInfoKind::Array(_) | TypeInfoKind::Struct => {
+                            // fibex signals are always scalar values
+                            return None;
+                        }
+                    };
+                    let converted = signal.compu_method.as_ref().and_then(|method| {
+                        let raw = match value {
+                            Value::Bool(v) => Some(if v { 1.0 } else { 0.0 }),
+                            Value::F32(v) => Some(f64::from(v)),
+                            Value::F64(v) => Some(v),
+                            ref v => value_as_f64(v),
+                        };
+                        raw.and_then(|raw| method.convert(raw))
+                            .map(|physical| (physical, method.unit.clone()))
+                    });
+                    let arg = match converted {
+                        Some((PhysicalValue::Number(physical), unit)) => Argument {
+                            type_info: TypeInfo {
+                                kind: TypeInfoKind::Float(FloatWidth::Width64),
+                                coding: StringCoding::UTF8,
+                                has_trace_info: false,
+                                // the unit is variable info of a verbose argument
+                                has_variable_info: unit.is_some(),
+                            },
+                            name: None,
+                            unit,
+                            fixed_point: None,
+                            value: Value::F64(physical),
+                        },
+                        Some((PhysicalValue::Text(text), _)) => Argument {
+                            type_info: TypeInfo {
+                                kind: TypeInfoKind::StringType,
+                                coding: StringCoding::UTF8,
+                                has_trace_info: false,
+                                has_variable_info: false,
+                            },
+                            name: None,
+                            unit: None,
+                            fixed_point: None,
+                            value: Value::StringVal(text),
+                        },
+                        None => Argument {
+                            type_info: signal_type.clone(),
+                            name: None,
+                            unit: None,
+                            fixed_point,
+                            value,
+                        },
+                    };
+                    arguments.push(arg);
+                }
+            }
+        }
+        Some(arguments)
+    }
 }
 
 impl From<&LogLevel> for u8 {
@@ -1805,24 +2503,72 @@ impl fmt::Display for Value {
                     .join(&DLT_NEWLINE_SENTINAL_STR)
             ),
             Value::Raw(value) => write!(f, "{:02X?}", value),
+            Value::Array { dimensions, values } => {
+                fmt_array(f, dimensions, values, &|v, f| v.fmt(f))
+            }
+            Value::Struct(entries) => {
+                write!(f, "{{")?;
+                for (i, entry) in entries.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    entry.fmt(f)?;
+                }
+                write!(f, "}}")
+            }
         }
     }
 }
+/// writes the values of a multi dimensional array as nested lists
+fn fmt_array(
+    f: &mut Formatter,
+    dimensions: &[u16],
+    values: &[Value],
+    fmt_value: &dyn Fn(&Value, &mut Formatter) -> Result<(), fmt::Error>,
+) -> Result<(), fmt::Error> {
+    write!(f, "[")?;
+    if dimensions.len() > 1 {
+        // an empty array can have huge inner dimensions
+        let inner_len = dimensions[1..]
+            .iter()
+            .fold(1usize, |len, d| len.saturating_mul(*d as usize));
+        for (i, inner_values) in values.chunks(std::cmp::max(inner_len, 1)).enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            fmt_array(f, &dimensions[1..], inner_values, fmt_value)?;
+        }
+    } else {
+        for (i, v) in values.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            fmt_value(v, f)?;
+        }
+    }
+    write!(f, "]")
+}
 
 impl fmt::Display for Argument {
     fn fmt(&self, f: &mut Formatter) -> Result<(), fmt::Error> {
         if let Some(n) = &self.name {
             write!(f, "{}: ", n)?;
         }
-        if let Some(u) = &self.unit {
-            u.fmt(f)?;
-        }
         if let Some(v) = self.to_real_value() {
             write!(f, "{}", v)?;
+        } else if let (Value::Array { dimensions, values }, Some(fp)) =
+            (&self.value, &self.fixed_point)
+        {
+            fmt_array(f, dimensions, values, &|v, f| match log_v(fp, v) {
+                Some(real_value) => write!(f, "{}", real_value),
+                None => v.fmt(f),
+            })?;
         } else {
             self.value.fmt(f)?;
         }
-
+        if let Some(u) = &self.unit {
+            write!(f, " {}", u)?;
+        }
         Ok(())
     }
 }
@@ -1848,6 +2594,20 @@ pub const WITH_SESSION_ID_FLAG: u8 = 1 << 3;
 pub const WITH_TIMESTAMP_FLAG: u8 = 1 << 4;
 pub const HEADER_MIN_LENGTH: u16 = 4;
 
+// Base header of version 2, first byte of the header type
+pub const V2_CONTENT_INFO_MASK: u8 = 0b11;
+pub const V2_WITH_ECU_ID_FLAG: u8 = 1 << 2;
+pub const V2_WITH_APP_AND_CONTEXT_ID_FLAG: u8 = 1 << 3;
+pub const V2_WITH_SESSION_ID_FLAG: u8 = 1 << 4;
+// second byte of the header type
+pub const V2_WITH_SOURCE_LOCATION_FLAG: u8 = 1;
+pub const V2_WITH_TAGS_FLAG: u8 = 1 << 1;
+pub const V2_WITH_PRIVACY_LEVEL_FLAG: u8 = 1 << 2;
+pub const V2_WITH_SEGMENTATION_FLAG: u8 = 1 << 3;
+/// header type (4 bytes), message counter and length
+pub const V2_HEADER_MIN_LENGTH: u16 = 7;
+pub const V2_TIMESTAMP_LENGTH: u16 = 9;
+
 // Verbose Mode
 
 // Extended header
@@ -1860,13 +2620,12 @@ pub const TYPE_INFO_BOOL_FLAG: u32 = 1 << 4;
 pub const TYPE_INFO_SINT_FLAG: u32 = 1 << 5;
 pub const TYPE_INFO_UINT_FLAG: u32 = 1 << 6;
 pub const TYPE_INFO_FLOAT_FLAG: u32 = 1 << 7;
-pub const _TYPE_INFO_ARRAY_FLAG: u32 = 1 << 8;
+pub const TYPE_INFO_ARRAY_FLAG: u32 = 1 << 8;
 pub const TYPE_INFO_STRING_FLAG: u32 = 1 << 9;
 pub const TYPE_INFO_RAW_FLAG: u32 = 1 << 10;
 pub const TYPE_INFO_VARIABLE_INFO: u32 = 1 << 11;
 pub const TYPE_INFO_FIXED_POINT_FLAG: u32 = 1 << 12;
 pub const TYPE_INFO_TRACE_INFO_FLAG: u32 = 1 << 13;
-#[allow(dead_code)]
 pub const TYPE_INFO_STRUCT_FLAG: u32 = 1 << 14;
 
 // TODO use header struct not u8
@@ -1893,6 +2652,44 @@ pub fn calculate_all_headers_length(header_type: u8) -> u16 {
     length
 }
 
+/// length of the base header of a version 2 message without extension header
+pub fn calculate_v2_base_header_length(header_type: u8) -> u16 {
+    V2_HEADER_MIN_LENGTH
+        + match ContentInfo::from_value(header_type & V2_CONTENT_INFO_MASK) {
+            // message info and number of arguments
+            Some(ContentInfo::Verbose) => 2 + V2_TIMESTAMP_LENGTH,
+            // message id
+            Some(ContentInfo::NonVerbose) => V2_TIMESTAMP_LENGTH + 4,
+            Some(ContentInfo::Control) => 2,
+            None => 0,
+        }
+}
+
+/// number of bytes of a standard header needed by `peek_message_length`
+pub const MESSAGE_LENGTH_PEEK_LENGTH: usize = V2_HEADER_MIN_LENGTH as usize;
+
+/// overall length of the message that starts with these bytes of its standard header
+/// None if the version is not supported or the length is shorter than the headers
+pub fn peek_message_length(header: &[u8]) -> Option<u16> {
+    let header_type = header[0];
+    let (overall_length, headers_length) = match header_type >> 5 & 0b111 {
+        1 => (
+            BigEndian::read_u16(&header[2..]),
+            calculate_all_headers_length(header_type),
+        ),
+        2 => (
+            BigEndian::read_u16(&header[5..]),
+            calculate_v2_base_header_length(header_type),
+        ),
+        _ => return None,
+    };
+    if overall_length < headers_length {
+        None
+    } else {
+        Some(overall_length)
+    }
+}
+
 pub fn zero_terminated_string(raw: &[u8]) -> Result<String, Error> {
     let nul_range_end = raw
         .iter()
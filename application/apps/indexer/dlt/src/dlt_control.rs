@@ -0,0 +1,681 @@
+// Copyright (c) 2019 E.S.R.Labs. All rights reserved.
+//
+// NOTICE:  All information contained herein is, and remains
+// the property of E.S.R.Labs and its suppliers, if any.
+// The intellectual and technical concepts contained herein are
+// proprietary to E.S.R.Labs and its suppliers and may be covered
+// by German and Foreign Patents, patents in process, and are protected
+// by trade secret or copyright law.
+// Dissemination of this information or reproduction of this material
+// is strictly forbidden unless prior written permission is obtained
+// from E.S.R.Labs.
+use crate::dlt::{u8_to_log_level, ControlType, Endianness};
+use crate::dlt_parse::{dlt_zero_terminated_string, NomByteOrder};
+use crate::service_id::SERVICE_ID_MAPPING;
+use byteorder::{BigEndian, LittleEndian};
+use nom::bytes::streaming::take;
+use nom::{combinator::map, multi::count, number::streaming, sequence::tuple, IResult};
+use serde::Serialize;
+use std::fmt;
+
+const SET_LOG_LEVEL: u32 = 0x01;
+const SET_TRACE_STATUS: u32 = 0x02;
+const GET_LOG_INFO: u32 = 0x03;
+const GET_DEFAULT_LOG_LEVEL: u32 = 0x04;
+const SET_MESSAGE_FILTERING: u32 = 0x0A;
+const SET_DEFAULT_LOG_LEVEL: u32 = 0x11;
+const SET_DEFAULT_TRACE_STATUS: u32 = 0x12;
+const GET_SOFTWARE_VERSION: u32 = 0x13;
+const MESSAGE_BUFFER_OVERFLOW: u32 = 0x14;
+const GET_DEFAULT_TRACE_STATUS: u32 = 0x15;
+const GET_LOG_CHANNEL_NAMES: u32 = 0x17;
+const GET_TRACE_STATUS: u32 = 0x1F;
+const SET_LOG_CHANNEL_ASSIGNMENT: u32 = 0x20;
+const SET_LOG_CHANNEL_THRESHOLD: u32 = 0x21;
+const GET_LOG_CHANNEL_THRESHOLD: u32 = 0x22;
+const BUFFER_OVERFLOW_NOTIFICATION: u32 = 0x23;
+
+/// get_log_info options (request) and status values (response) that
+/// define which parts of the log info are transmitted
+const LOG_INFO_IDS_ONLY: u8 = 3;
+const LOG_INFO_WITH_LOG_LEVEL: u8 = 4;
+const LOG_INFO_WITH_TRACE_STATUS: u8 = 5;
+const LOG_INFO_WITH_LEVEL_AND_STATUS: u8 = 6;
+const LOG_INFO_WITH_DESCRIPTIONS: u8 = 7;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub enum ControlStatus {
+    Ok,
+    NotSupported,
+    Error,
+    Other(u8),
+}
+impl From<u8> for ControlStatus {
+    fn from(v: u8) -> Self {
+        match v {
+            0 => ControlStatus::Ok,
+            1 => ControlStatus::NotSupported,
+            2 => ControlStatus::Error,
+            n => ControlStatus::Other(n),
+        }
+    }
+}
+impl fmt::Display for ControlStatus {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ControlStatus::Ok => f.write_str("ok"),
+            ControlStatus::NotSupported => f.write_str("not_supported"),
+            ControlStatus::Error => f.write_str("error"),
+            ControlStatus::Other(n) => write!(f, "{}", n),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct LogInfoContext {
+    pub context_id: String,
+    pub log_level: Option<i8>,
+    pub trace_status: Option<i8>,
+    pub description: Option<String>,
+}
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct LogInfoApplication {
+    pub application_id: String,
+    pub contexts: Vec<LogInfoContext>,
+    pub description: Option<String>,
+}
+
+/// the parameters of a control request or response
+/// log levels and trace states use -1 for "use the default"
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub enum ControlContent {
+    SetLogLevel {
+        application_id: String,
+        context_id: String,
+        log_level: i8,
+        com_interface: String,
+    },
+    SetTraceStatus {
+        application_id: String,
+        context_id: String,
+        trace_status: i8,
+        com_interface: String,
+    },
+    GetLogInfo {
+        options: u8,
+        application_id: String,
+        context_id: String,
+        com_interface: String,
+    },
+    SetMessageFiltering {
+        enabled: bool,
+    },
+    SetDefaultLogLevel {
+        log_level: i8,
+        com_interface: String,
+    },
+    SetDefaultTraceStatus {
+        trace_status: i8,
+        com_interface: String,
+    },
+    GetTraceStatus {
+        application_id: String,
+        context_id: String,
+    },
+    SetLogChannelAssignment {
+        log_channel: String,
+        application_id: String,
+        context_id: String,
+        add: bool,
+    },
+    SetLogChannelThreshold {
+        log_channel: String,
+        log_level: i8,
+        trace_status: i8,
+    },
+    GetLogChannelThreshold {
+        log_channel: String,
+    },
+    /// request without parameters
+    Request,
+    /// response that only contains the status
+    Status(ControlStatus),
+    /// `status` is the option of the request or one of 8 (no matching context)
+    /// 9 (response data overflow) or 2 (error)
+    LogInfo {
+        status: u8,
+        applications: Vec<LogInfoApplication>,
+        com_interface: String,
+    },
+    DefaultLogLevel {
+        status: ControlStatus,
+        log_level: i8,
+    },
+    SoftwareVersion {
+        status: ControlStatus,
+        version: String,
+    },
+    MessageBufferOverflow {
+        status: ControlStatus,
+        overflow: bool,
+    },
+    TraceStatus {
+        status: ControlStatus,
+        trace_status: i8,
+    },
+    LogChannelNames {
+        status: ControlStatus,
+        names: Vec<String>,
+    },
+    LogChannelThreshold {
+        status: ControlStatus,
+        log_level: i8,
+        trace_status: i8,
+    },
+    BufferOverflowNotification {
+        status: ControlStatus,
+        overflow_counter: u32,
+    },
+    /// payload of unknown services or payload that does not match the layout
+    Raw(Vec<u8>),
+}
+
+/// decoded payload of a control message
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ControlMessage {
+    pub service_id: u32,
+    pub content: ControlContent,
+}
+impl ControlMessage {
+    pub fn service_name(&self) -> Option<&'static str> {
+        service_mapping(self.service_id).map(|(name, _desc)| *name)
+    }
+}
+fn service_mapping(service_id: u32) -> Option<&'static (&'static str, &'static str)> {
+    if service_id > u32::from(u8::MAX) {
+        return None;
+    }
+    SERVICE_ID_MAPPING.get(&(service_id as u8))
+}
+
+fn fmt_log_level(level: i8) -> String {
+    match level {
+        -1 => "default".to_string(),
+        0 => "off".to_string(),
+        n if n > 0 => match u8_to_log_level(n as u8) {
+            Some(level) => level.to_string(),
+            None => format!("invalid({})", n),
+        },
+        n => format!("invalid({})", n),
+    }
+}
+fn fmt_trace_status(status: i8) -> String {
+    match status {
+        -1 => "default".to_string(),
+        0 => "off".to_string(),
+        1 => "on".to_string(),
+        n => format!("invalid({})", n),
+    }
+}
+impl fmt::Display for LogInfoContext {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.context_id)?;
+        if let Some(level) = self.log_level {
+            write!(f, " level={}", fmt_log_level(level))?;
+        }
+        if let Some(status) = self.trace_status {
+            write!(f, " trace={}", fmt_trace_status(status))?;
+        }
+        if let Some(description) = self.description.as_ref().filter(|d| !d.is_empty()) {
+            write!(f, " \"{}\"", description)?;
+        }
+        Ok(())
+    }
+}
+impl fmt::Display for LogInfoApplication {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.application_id)?;
+        if let Some(description) = self.description.as_ref().filter(|d| !d.is_empty()) {
+            write!(f, " \"{}\"", description)?;
+        }
+        f.write_str(" [")?;
+        for (i, context) in self.contexts.iter().enumerate() {
+            if i > 0 {
+                f.write_str(", ")?;
+            }
+            write!(f, "{}", context)?;
+        }
+        f.write_str("]")
+    }
+}
+impl fmt::Display for ControlContent {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ControlContent::SetLogLevel {
+                application_id,
+                context_id,
+                log_level,
+                ..
+            } => write!(
+                f,
+                "{} {} level={}",
+                application_id,
+                context_id,
+                fmt_log_level(*log_level)
+            ),
+            ControlContent::SetTraceStatus {
+                application_id,
+                context_id,
+                trace_status,
+                ..
+            } => write!(
+                f,
+                "{} {} trace={}",
+                application_id,
+                context_id,
+                fmt_trace_status(*trace_status)
+            ),
+            ControlContent::GetLogInfo {
+                options,
+                application_id,
+                context_id,
+                ..
+            } => write!(f, "options={} {} {}", options, application_id, context_id),
+            ControlContent::SetMessageFiltering { enabled } => write!(f, "enabled={}", enabled),
+            ControlContent::SetDefaultLogLevel { log_level, .. } => {
+                write!(f, "level={}", fmt_log_level(*log_level))
+            }
+            ControlContent::SetDefaultTraceStatus { trace_status, .. } => {
+                write!(f, "trace={}", fmt_trace_status(*trace_status))
+            }
+            ControlContent::GetTraceStatus {
+                application_id,
+                context_id,
+            } => write!(f, "{} {}", application_id, context_id),
+            ControlContent::SetLogChannelAssignment {
+                log_channel,
+                application_id,
+                context_id,
+                add,
+            } => write!(
+                f,
+                "{} {} {} {}",
+                if *add { "add" } else { "remove" },
+                log_channel,
+                application_id,
+                context_id
+            ),
+            ControlContent::SetLogChannelThreshold {
+                log_channel,
+                log_level,
+                trace_status,
+            } => write!(
+                f,
+                "{} level={} trace={}",
+                log_channel,
+                fmt_log_level(*log_level),
+                fmt_trace_status(*trace_status)
+            ),
+            ControlContent::GetLogChannelThreshold { log_channel } => f.write_str(log_channel),
+            ControlContent::Request => Ok(()),
+            ControlContent::Status(status) => write!(f, "status={}", status),
+            ControlContent::LogInfo {
+                status,
+                applications,
+                ..
+            } => {
+                write!(f, "status={}", status)?;
+                applications
+                    .iter()
+                    .try_for_each(|app| write!(f, " {}", app))
+            }
+            ControlContent::DefaultLogLevel { status, log_level } => {
+                write!(f, "status={} level={}", status, fmt_log_level(*log_level))
+            }
+            ControlContent::SoftwareVersion { status, version } => {
+                write!(f, "status={} {}", status, version)
+            }
+            ControlContent::MessageBufferOverflow { status, overflow } => {
+                write!(f, "status={} overflow={}", status, overflow)
+            }
+            ControlContent::TraceStatus {
+                status,
+                trace_status,
+            } => write!(
+                f,
+                "status={} trace={}",
+                status,
+                fmt_trace_status(*trace_status)
+            ),
+            ControlContent::LogChannelNames { status, names } => {
+                write!(f, "status={} {}", status, names.join(", "))
+            }
+            ControlContent::LogChannelThreshold {
+                status,
+                log_level,
+                trace_status,
+            } => write!(
+                f,
+                "status={} level={} trace={}",
+                status,
+                fmt_log_level(*log_level),
+                fmt_trace_status(*trace_status)
+            ),
+            ControlContent::BufferOverflowNotification {
+                status,
+                overflow_counter,
+            } => write!(f, "status={} overflow_counter={}", status, overflow_counter),
+            ControlContent::Raw(bytes) => write!(f, "{:02X?}", bytes),
+        }
+    }
+}
+impl fmt::Display for ControlMessage {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.service_name() {
+            Some(name) => write!(f, "[{}]", name)?,
+            None => write!(f, "[Unknown CtrlCommand]")?,
+        }
+        match &self.content {
+            ControlContent::Request => Ok(()),
+            ControlContent::Raw(bytes) if bytes.is_empty() => Ok(()),
+            content => write!(f, " {}", content),
+        }
+    }
+}
+
+/// application, context, log channel and com interface ids are
+/// 4 characters, unused characters are filled with zeros
+fn dlt_control_id(input: &[u8]) -> IResult<&[u8], String> {
+    map(|i| dlt_zero_terminated_string(i, 4), String::from)(input)
+}
+fn dlt_description<T: NomByteOrder>(input: &[u8]) -> IResult<&[u8], String> {
+    let (i, len) = T::parse_u16(input)?;
+    let (rest, description) = take(len)(i)?;
+    Ok((rest, text_without_null(description)))
+}
+fn text_without_null(bytes: &[u8]) -> String {
+    String::from_utf8_lossy(bytes)
+        .trim_end_matches('\0')
+        .to_string()
+}
+fn dlt_level(input: &[u8]) -> IResult<&[u8], i8> {
+    streaming::be_i8(input)
+}
+fn dlt_status(input: &[u8]) -> IResult<&[u8], ControlStatus> {
+    map(streaming::be_u8, ControlStatus::from)(input)
+}
+
+fn dlt_log_info_application<T: NomByteOrder>(
+    status: u8,
+) -> impl Fn(&[u8]) -> IResult<&[u8], LogInfoApplication> {
+    let with_level = status == LOG_INFO_WITH_LOG_LEVEL
+        || status == LOG_INFO_WITH_LEVEL_AND_STATUS
+        || status == LOG_INFO_WITH_DESCRIPTIONS;
+    let with_trace_status = status == LOG_INFO_WITH_TRACE_STATUS
+        || status == LOG_INFO_WITH_LEVEL_AND_STATUS
+        || status == LOG_INFO_WITH_DESCRIPTIONS;
+    let with_description = status == LOG_INFO_WITH_DESCRIPTIONS;
+    move |input| {
+        let (i, (application_id, context_cnt)) = tuple((dlt_control_id, T::parse_u16))(input)?;
+        let (i, contexts) = count(
+            |i| {
+                let (i, context_id) = dlt_control_id(i)?;
+                let (i, log_level) = optional(with_level, dlt_level)(i)?;
+                let (i, trace_status) = optional(with_trace_status, dlt_level)(i)?;
+                let (i, description) = optional(with_description, dlt_description::<T>)(i)?;
+                Ok((
+                    i,
+                    LogInfoContext {
+                        context_id,
+                        log_level,
+                        trace_status,
+                        description,
+                    },
+                ))
+            },
+            context_cnt as usize,
+        )(i)?;
+        let (rest, description) = optional(with_description, dlt_description::<T>)(i)?;
+        Ok((
+            rest,
+            LogInfoApplication {
+                application_id,
+                contexts,
+                description,
+            },
+        ))
+    }
+}
+fn optional<'a, O, F>(present: bool, f: F) -> impl Fn(&'a [u8]) -> IResult<&'a [u8], Option<O>>
+where
+    F: Fn(&'a [u8]) -> IResult<&'a [u8], O>,
+{
+    move |input| {
+        if present {
+            map(&f, Some)(input)
+        } else {
+            Ok((input, None))
+        }
+    }
+}
+
+fn dlt_control_request(service_id: u32, input: &[u8]) -> IResult<&[u8], ControlContent> {
+    match service_id {
+        SET_LOG_LEVEL => map(
+            tuple((dlt_control_id, dlt_control_id, dlt_level, dlt_control_id)),
+            |(application_id, context_id, log_level, com_interface)| ControlContent::SetLogLevel {
+                application_id,
+                context_id,
+                log_level,
+                com_interface,
+            },
+        )(input),
+        SET_TRACE_STATUS => map(
+            tuple((dlt_control_id, dlt_control_id, dlt_level, dlt_control_id)),
+            |(application_id, context_id, trace_status, com_interface)| {
+                ControlContent::SetTraceStatus {
+                    application_id,
+                    context_id,
+                    trace_status,
+                    com_interface,
+                }
+            },
+        )(input),
+        GET_LOG_INFO => map(
+            tuple((
+                streaming::be_u8,
+                dlt_control_id,
+                dlt_control_id,
+                dlt_control_id,
+            )),
+            |(options, application_id, context_id, com_interface)| ControlContent::GetLogInfo {
+                options,
+                application_id,
+                context_id,
+                com_interface,
+            },
+        )(input),
+        SET_MESSAGE_FILTERING => map(streaming::be_u8, |v| ControlContent::SetMessageFiltering {
+            enabled: v != 0,
+        })(input),
+        SET_DEFAULT_LOG_LEVEL => map(
+            tuple((dlt_level, dlt_control_id)),
+            |(log_level, com_interface)| ControlContent::SetDefaultLogLevel {
+                log_level,
+                com_interface,
+            },
+        )(input),
+        SET_DEFAULT_TRACE_STATUS => map(
+            tuple((dlt_level, dlt_control_id)),
+            |(trace_status, com_interface)| ControlContent::SetDefaultTraceStatus {
+                trace_status,
+                com_interface,
+            },
+        )(input),
+        GET_TRACE_STATUS => map(
+            tuple((dlt_control_id, dlt_control_id)),
+            |(application_id, context_id)| ControlContent::GetTraceStatus {
+                application_id,
+                context_id,
+            },
+        )(input),
+        SET_LOG_CHANNEL_ASSIGNMENT => map(
+            tuple((
+                dlt_control_id,
+                dlt_control_id,
+                dlt_control_id,
+                streaming::be_u8,
+            )),
+            |(log_channel, application_id, context_id, operation)| {
+                ControlContent::SetLogChannelAssignment {
+                    log_channel,
+                    application_id,
+                    context_id,
+                    add: operation == 1,
+                }
+            },
+        )(input),
+        SET_LOG_CHANNEL_THRESHOLD => map(
+            tuple((dlt_control_id, dlt_level, dlt_level)),
+            |(log_channel, log_level, trace_status)| ControlContent::SetLogChannelThreshold {
+                log_channel,
+                log_level,
+                trace_status,
+            },
+        )(input),
+        GET_LOG_CHANNEL_THRESHOLD => map(dlt_control_id, |log_channel| {
+            ControlContent::GetLogChannelThreshold { log_channel }
+        })(input),
+        _ if service_mapping(service_id).is_some() && input.is_empty() => {
+            Ok((input, ControlContent::Request))
+        }
+        _ => Ok((&[], ControlContent::Raw(input.to_vec()))),
+    }
+}
+fn dlt_control_response<T: NomByteOrder>(
+    service_id: u32,
+    input: &[u8],
+) -> IResult<&[u8], ControlContent> {
+    match service_id {
+        GET_LOG_INFO => {
+            let (i, status) = streaming::be_u8(input)?;
+            let has_applications =
+                (LOG_INFO_IDS_ONLY..=LOG_INFO_WITH_DESCRIPTIONS).contains(&status);
+            if !has_applications {
+                return Ok((
+                    i,
+                    ControlContent::LogInfo {
+                        status,
+                        applications: vec![],
+                        com_interface: String::new(),
+                    },
+                ));
+            }
+            let (i, application_cnt) = T::parse_u16(i)?;
+            let (i, applications) = count(
+                dlt_log_info_application::<T>(status),
+                application_cnt as usize,
+            )(i)?;
+            let (rest, com_interface) = dlt_control_id(i)?;
+            Ok((
+                rest,
+                ControlContent::LogInfo {
+                    status,
+                    applications,
+                    com_interface,
+                },
+            ))
+        }
+        GET_DEFAULT_LOG_LEVEL => map(tuple((dlt_status, dlt_level)), |(status, log_level)| {
+            ControlContent::DefaultLogLevel { status, log_level }
+        })(input),
+        GET_SOFTWARE_VERSION => {
+            let (i, (status, len)) = tuple((dlt_status, T::parse_u32))(input)?;
+            let (rest, version) = take(len)(i)?;
+            Ok((
+                rest,
+                ControlContent::SoftwareVersion {
+                    status,
+                    version: text_without_null(version),
+                },
+            ))
+        }
+        MESSAGE_BUFFER_OVERFLOW => map(tuple((dlt_status, streaming::be_u8)), |(status, v)| {
+            ControlContent::MessageBufferOverflow {
+                status,
+                overflow: v != 0,
+            }
+        })(input),
+        GET_DEFAULT_TRACE_STATUS | GET_TRACE_STATUS => {
+            map(tuple((dlt_status, dlt_level)), |(status, trace_status)| {
+                ControlContent::TraceStatus {
+                    status,
+                    trace_status,
+                }
+            })(input)
+        }
+        GET_LOG_CHANNEL_NAMES => {
+            let (i, (status, cnt)) = tuple((dlt_status, streaming::be_u8))(input)?;
+            let (rest, names) = count(dlt_control_id, cnt as usize)(i)?;
+            Ok((rest, ControlContent::LogChannelNames { status, names }))
+        }
+        GET_LOG_CHANNEL_THRESHOLD => map(
+            tuple((dlt_status, dlt_level, dlt_level)),
+            |(status, log_level, trace_status)| ControlContent::LogChannelThreshold {
+                status,
+                log_level,
+                trace_status,
+            },
+        )(input),
+        BUFFER_OVERFLOW_NOTIFICATION => map(
+            tuple((dlt_status, T::parse_u32)),
+            |(status, overflow_counter)| ControlContent::BufferOverflowNotification {
+                status,
+                overflow_counter,
+            },
+        )(input),
+        _ if service_mapping(service_id).is_some() && input.len() == 1 => {
+            map(dlt_status, ControlContent::Status)(input)
+        }
+        _ => Ok((&[], ControlContent::Raw(input.to_vec()))),
+    }
+}
+fn dlt_control_message<'a, T: NomByteOrder>(
+    input: &'a [u8],
+    control_type: &ControlType,
+) -> IResult<&'a [u8], ControlMessage> {
+    let (i, service_id) = T::parse_u32(input)?;
+    let parsed = match control_type {
+        ControlType::Request => dlt_control_request(service_id, i),
+        ControlType::Response => dlt_control_response::<T>(service_id, i),
+        ControlType::Unknown(_) => Ok((&[][..], ControlContent::Raw(i.to_vec()))),
+    };
+    let content = match parsed {
+        Ok((_, content)) => content,
+        Err(_) => ControlContent::Raw(i.to_vec()),
+    };
+    Ok((
+        &[],
+        ControlMessage {
+            service_id,
+            content,
+        },
+    ))
+}
+
+/// decode the payload of a control message (starting with the service id)
+/// `control_type` is the message type info of the extended header and
+/// decides if the payload has the layout of a request or a response.
+/// payloads that do not match the layout of the service are kept as raw bytes,
+/// payloads that are too short for a service id are not decoded at all
+pub fn decode_control_message(
+    payload: &[u8],
+    endianness: Endianness,
+    control_type: &ControlType,
+) -> Option<ControlMessage> {
+    let res = match endianness {
+        Endianness::Big => dlt_control_message::<BigEndian>(payload, control_type),
+        Endianness::Little => dlt_control_message::<LittleEndian>(payload, control_type),
+    };
+    res.ok().map(|(_, msg)| msg)
+}
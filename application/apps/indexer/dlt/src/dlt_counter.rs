@@ -0,0 +1,214 @@
+// Copyright (c) 2020 E.S.R.Labs. All rights reserved.
+//
+// NOTICE:  All information contained herein is, and remains
+// the property of E.S.R.Labs and its suppliers, if any.
+// The intellectual and technical concepts contained herein are
+// proprietary to E.S.R.Labs and its suppliers and may be covered
+// by German and Foreign Patents, patents in process, and are protected
+// by trade secret or copyright law.
+// Dissemination of this information or reproduction of this material
+// is strictly forbidden unless prior written permission is obtained
+// from E.S.R.Labs.
+//! Lost messages
+//!
+//! Every sender (a context of an application in one session on an ECU)
+//! increases the message counter of the standard header with each message,
+//! wrapping from 255 to 0. When the counter of the next message of the same
+//! sender is not the expected one, messages were lost between the sender and
+//! the logger. A message with the same counter as the one before is seen as a
+//! repeated message, not as a gap.
+//! Messages without session id and without extended header can come from
+//! any application, their counters are not checked.
+use crate::dlt::Message;
+use rustc_hash::FxHashMap;
+use serde::Serialize;
+use std::fmt;
+
+/// the sender of a message, ids that are not part of the message are `None`
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Sender<'a> {
+    pub ecu_id: Option<&'a str>,
+    pub session_id: Option<u32>,
+    pub app_id: Option<&'a str>,
+    pub context_id: Option<&'a str>,
+}
+
+/// gives the sender and the counter of a message
+pub trait MessageCounter {
+    fn message_counter(&self) -> (Sender<'_>, u8);
+}
+
+impl MessageCounter for Message {
+    fn message_counter(&self) -> (Sender<'_>, u8) {
+        let ext = self.extended_header.as_ref();
+        let sender = Sender {
+            ecu_id: self.header.ecu_id.as_deref(),
+            session_id: self.header.session_id,
+            app_id: ext.map(|h| h.application_id.as_str()),
+            context_id: ext.map(|h| h.context_id.as_str()),
+        };
+        (sender, self.header.message_counter)
+    }
+}
+
+/// messages that are missing between two messages of the same sender
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CounterGap {
+    pub ecu_id: Option<String>,
+    pub session_id: Option<u32>,
+    pub app_id: Option<String>,
+    pub context_id: Option<String>,
+    pub expected: u8,
+    pub found: u8,
+    pub lost: usize,
+}
+impl fmt::Display for CounterGap {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} messages lost from ECU {}",
+            self.lost,
+            self.ecu_id.as_deref().unwrap_or("NONE")
+        )?;
+        if let (Some(app_id), Some(context_id)) = (&self.app_id, &self.context_id) {
+            write!(f, " {}/{}", app_id, context_id)?;
+        }
+        if let Some(session_id) = self.session_id {
+            write!(f, " (session {})", session_id)?;
+        }
+        write!(
+            f,
+            ": message counter {} expected but found {}",
+            self.expected, self.found
+        )
+    }
+}
+
+/// all lost messages of one sender
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct LostMessages {
+    pub ecu_id: Option<String>,
+    pub session_id: Option<u32>,
+    pub app_id: Option<String>,
+    pub context_id: Option<String>,
+    pub gaps: usize,
+    pub lost: usize,
+}
+
+/// ids in dlt headers have at most 4 bytes, so senders can be looked up
+/// without allocating
+type Id = Option<[u8; 4]>;
+type SenderKey = (Id, Option<u32>, Id, Id);
+
+fn id_key(id: Option<&str>) -> Id {
+    id.map(|id| {
+        let mut key = [0u8; 4];
+        for (k, b) in key.iter_mut().zip(id.bytes()) {
+            *k = b;
+        }
+        key
+    })
+}
+
+#[derive(Debug)]
+struct SenderCounter {
+    ecu_id: Option<String>,
+    session_id: Option<u32>,
+    app_id: Option<String>,
+    context_id: Option<String>,
+    last: u8,
+    gaps: usize,
+    lost: usize,
+}
+
+/// tracks the message counter of every sender
+#[derive(Debug, Default)]
+pub struct MessageCounterCheck {
+    senders: FxHashMap<SenderKey, SenderCounter>,
+}
+
+impl MessageCounterCheck {
+    pub fn new() -> MessageCounterCheck {
+        Default::default()
+    }
+
+    /// check the counter of the next message of a sender
+    pub fn check(&mut self, sender: Sender, counter: u8) -> Option<CounterGap> {
+        if sender.session_id.is_none() && sender.app_id.is_none() {
+            return None;
+        }
+        let key = (
+            id_key(sender.ecu_id),
+            sender.session_id,
+            id_key(sender.app_id),
+            id_key(sender.context_id),
+        );
+        let state = match self.senders.get_mut(&key) {
+            Some(state) => state,
+            None => {
+                self.senders.insert(
+                    key,
+                    SenderCounter {
+                        ecu_id: sender.ecu_id.map(str::to_string),
+                        session_id: sender.session_id,
+                        app_id: sender.app_id.map(str::to_string),
+                        context_id: sender.context_id.map(str::to_string),
+                        last: counter,
+                        gaps: 0,
+                        lost: 0,
+                    },
+                );
+                return None;
+            }
+        };
+        let expected = state.last.wrapping_add(1);
+        let last = state.last;
+        state.last = counter;
+        if counter == expected || counter == last {
+            return None;
+        }
+        let lost = usize::from(counter.wrapping_sub(expected));
+        state.gaps += 1;
+        state.lost += lost;
+        Some(CounterGap {
+            ecu_id: state.ecu_id.clone(),
+            session_id: state.session_id,
+            app_id: state.app_id.clone(),
+            context_id: state.context_id.clone(),
+            expected,
+            found: counter,
+            lost,
+        })
+    }
+
+    pub fn check_message<M: MessageCounter>(&mut self, msg: &M) -> Option<CounterGap> {
+        let (sender, counter) = msg.message_counter();
+        self.check(sender, counter)
+    }
+
+    /// the senders that lost messages, ordered by ECU, session and ids
+    pub fn summary(&self) -> Vec<LostMessages> {
+        let mut summary: Vec<LostMessages> = self
+            .senders
+            .values()
+            .filter(|state| state.gaps > 0)
+            .map(|state| LostMessages {
+                ecu_id: state.ecu_id.clone(),
+                session_id: state.session_id,
+                app_id: state.app_id.clone(),
+                context_id: state.context_id.clone(),
+                gaps: state.gaps,
+                lost: state.lost,
+            })
+            .collect();
+        summary.sort_by(|a, b| {
+            (&a.ecu_id, a.session_id, &a.app_id, &a.context_id).cmp(&(
+                &b.ecu_id,
+                b.session_id,
+                &b.app_id,
+                &b.context_id,
+            ))
+        });
+        summary
+    }
+}
@@ -0,0 +1,379 @@
+// Copyright (c) 2019 E.S.R.Labs. All rights reserved.
+//
+// NOTICE:  All information contained herein is, and remains
+// the property of E.S.R.Labs and its suppliers, if any.
+// The intellectual and technical concepts contained herein are
+// proprietary to E.S.R.Labs and its suppliers and may be covered
+// by German and Foreign Patents, patents in process, and are protected
+// by trade secret or copyright law.
+// Dissemination of this information or reproduction of this material
+// is strictly forbidden unless prior written permission is obtained
+// from E.S.R.Labs.
+use crate::dlt::*;
+use crate::dlt_parse::{DltParseError, FileMessageProducer, MessageProducer};
+use crate::dlt_time::{time_model_for_file, AbsoluteTimes};
+use crate::fibex::FibexMetadata;
+use crate::filtering;
+use crossbeam_channel as cc;
+use failure::{err_msg, Error};
+use indexer_base::chunks::ChunkResults;
+use indexer_base::input::{input_size, is_random_access};
+use indexer_base::progress::*;
+use std::fs;
+use std::io::{BufWriter, Write};
+use std::rc::Rc;
+
+/// columns that can be used for the csv export
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CsvColumn {
+    /// line of the message (counted over all messages that pass the filter)
+    Index,
+    /// time from the storage header
+    Time,
+    Ecu,
+    Counter,
+    SessionId,
+    /// timestamp of the standard header (0.1 ms since ECU start)
+    Timestamp,
+    ApplicationId,
+    ContextId,
+    MessageType,
+    /// arguments of verbose and non verbose messages or the decoded control message
+    Payload,
+    /// send time of the message in us since epoch, the header timestamp
+    /// anchored to the start of the ECU (see `dlt_time`)
+    AbsoluteTime,
+}
+pub const DEFAULT_CSV_COLUMNS: [CsvColumn; 10] = [
+    CsvColumn::Index,
+    CsvColumn::Time,
+    CsvColumn::Ecu,
+    CsvColumn::Counter,
+    CsvColumn::SessionId,
+    CsvColumn::Timestamp,
+    CsvColumn::ApplicationId,
+    CsvColumn::ContextId,
+    CsvColumn::MessageType,
+    CsvColumn::Payload,
+];
+/// columns that are only exported when they are requested
+pub const OPTIONAL_CSV_COLUMNS: [CsvColumn; 1] = [CsvColumn::AbsoluteTime];
+impl CsvColumn {
+    pub fn name(self) -> &'static str {
+        match self {
+            CsvColumn::Index => "index",
+            CsvColumn::Time => "time",
+            CsvColumn::Ecu => "ecu",
+            CsvColumn::Counter => "counter",
+            CsvColumn::SessionId => "session",
+            CsvColumn::Timestamp => "timestamp",
+            CsvColumn::ApplicationId => "app",
+            CsvColumn::ContextId => "context",
+            CsvColumn::MessageType => "type",
+            CsvColumn::Payload => "payload",
+            CsvColumn::AbsoluteTime => "abstime",
+        }
+    }
+    pub fn from_name(name: &str) -> Option<CsvColumn> {
+        DEFAULT_CSV_COLUMNS
+            .iter()
+            .chain(OPTIONAL_CSV_COLUMNS.iter())
+            .find(|column| column.name() == name)
+            .cloned()
+    }
+    fn value(self, index: usize, msg: &Message, absolute_time: Option<&DltTimeStamp>) -> String {
+        match self {
+            CsvColumn::Index => index.to_string(),
+            CsvColumn::Time => msg
+                .storage_header
+                .as_ref()
+                .map(|h| h.timestamp.to_string())
+                .unwrap_or_default(),
+            CsvColumn::Ecu => msg
+                .storage_header
+                .as_ref()
+                .map(|h| h.ecu_id.clone())
+                .or_else(|| msg.header.ecu_id.clone())
+                .unwrap_or_default(),
+            CsvColumn::Counter => msg.header.message_counter.to_string(),
+            CsvColumn::SessionId => msg
+                .header
+                .session_id
+                .map(|id| id.to_string())
+                .unwrap_or_default(),
+            CsvColumn::Timestamp => msg
+                .header
+                .timestamp
+                .map(|t| t.to_string())
+                .unwrap_or_default(),
+            CsvColumn::ApplicationId => msg
+                .extended_header
+                .as_ref()
+                .map(|h| h.application_id.clone())
+                .unwrap_or_default(),
+            CsvColumn::ContextId => msg
+                .extended_header
+                .as_ref()
+                .map(|h| h.context_id.clone())
+                .unwrap_or_default(),
+            CsvColumn::MessageType => msg
+                .extended_header
+                .as_ref()
+                .map(|h| h.message_type.to_string())
+                .unwrap_or_default(),
+            CsvColumn::Payload => payload_text(msg),
+            CsvColumn::AbsoluteTime => absolute_time
+                .map(|t| t.as_micros().to_string())
+                .unwrap_or_default(),
+        }
+    }
+}
+
+/// format of the exported messages
+/// `Dlt`: binary dlt messages with storage header
+/// `JsonLines`: one json object per message (see `Message::serialize`)
+/// `Csv`: one line per message with the configured columns and a header line
+#[derive(Debug, Clone, PartialEq)]
+pub enum ExportFormat {
+    Dlt,
+    JsonLines,
+    Csv(Vec<CsvColumn>),
+}
+
+/// restricts which of the messages that pass the filter are exported
+/// `line_range`: first and last line (inclusive) to export. lines are counted
+/// from 0 over all messages that pass the filter, which is the same numbering
+/// used for the lines of the indexed output
+/// `time_range`: first and last timestamp (inclusive, in ms since epoch) taken
+/// from the storage header of the message
+/// `format`: how the messages are written
+#[derive(Debug)]
+pub struct DltExportConfig {
+    pub line_range: Option<(usize, usize)>,
+    pub time_range: Option<(u64, u64)>,
+    pub format: ExportFormat,
+}
+impl Default for DltExportConfig {
+    fn default() -> Self {
+        DltExportConfig {
+            line_range: None,
+            time_range: None,
+            format: ExportFormat::Dlt,
+        }
+    }
+}
+
+/// the arguments as text without the sentinals that are used for the ui
+fn payload_text(msg: &Message) -> String {
+    let arguments = match &msg.payload.payload_content {
+        PayloadContent::Verbose(arguments) => Some(arguments.clone()),
+        PayloadContent::NonVerbose(..) => msg.nonverbose_arguments(),
+        PayloadContent::ControlMsg(..) => None,
+    };
+    let text = match (arguments, &msg.payload.payload_content) {
+        (Some(arguments), _) => arguments
+            .iter()
+            .map(|arg| arg.to_string())
+            .collect::<Vec<String>>()
+            .join(" "),
+        (None, PayloadContent::NonVerbose(id, data)) => format!("[{}] {:02X?}", id, data),
+        (None, _) => msg
+            .control_message()
+            .map(|control_message| control_message.to_string())
+            .unwrap_or_default(),
+    };
+    text.replace(char::from(DLT_NEWLINE_SENTINAL_SLICE[0]), "\n")
+}
+fn csv_field(value: &str) -> String {
+    if value.contains(&[',', '"', '\n', '\r'][..]) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+fn write_csv_line<W: Write>(writer: &mut W, fields: Vec<String>) -> Result<(), Error> {
+    let line = fields
+        .iter()
+        .map(|field| csv_field(field))
+        .collect::<Vec<String>>()
+        .join(",");
+    writer.write_all(line.as_bytes())?;
+    writer.write_all(b"\n")?;
+    Ok(())
+}
+fn write_message<W: Write>(
+    writer: &mut W,
+    format: &ExportFormat,
+    index: usize,
+    msg: &Message,
+    absolute_time: Option<&DltTimeStamp>,
+) -> Result<(), Error> {
+    match format {
+        ExportFormat::Dlt => writer.write_all(&msg.as_bytes())?,
+        ExportFormat::JsonLines => {
+            serde_json::to_writer(&mut *writer, msg)?;
+            writer.write_all(b"\n")?;
+        }
+        ExportFormat::Csv(columns) => write_csv_line(
+            writer,
+            columns
+                .iter()
+                .map(|c| c.value(index, msg, absolute_time))
+                .collect(),
+        )?,
+    }
+    Ok(())
+}
+
+fn storage_time_in_ms(msg: &Message) -> Option<u64> {
+    msg.storage_header
+        .as_ref()
+        .map(|h| u64::from(h.timestamp.seconds) * 1000 + u64::from(h.timestamp.microseconds) / 1000)
+}
+
+/// write all messages of a dlt file that pass the filter and are inside the
+/// configured ranges to a new dlt file (including storage headers)
+/// the result can be read again with `dlt_message`
+/// for json lines and csv the `fibex_metadata` is used to resolve the arguments
+/// of non verbose messages
+/// for the absolute time of csv exports a file is read twice, so every message
+/// is corrected with the final estimate for the start of its ECU. a stream is
+/// read once and filtered after the time model has seen every message
+/// returns the number of exported messages
+pub fn export_dlt_messages(
+    in_file: &std::path::PathBuf,
+    out_path: &std::path::PathBuf,
+    filter_config: Option<filtering::ProcessedDltFilterConfig>,
+    export_config: DltExportConfig,
+    fibex_metadata: Option<Rc<FibexMetadata>>,
+    update_channel: &cc::Sender<ChunkResults>,
+    shutdown_receiver: Option<cc::Receiver<()>>,
+) -> Result<usize, Error> {
+    trace!("export_dlt_messages from {:?} to {:?}", in_file, out_path);
+    let source_file_size = input_size(in_file);
+    let absolute_time_needed = match &export_config.format {
+        ExportFormat::Csv(columns) => columns.contains(&CsvColumn::AbsoluteTime),
+        _ => false,
+    };
+    // a stream can only be read once, the time model learns while exporting
+    let calibrated = absolute_time_needed && is_random_access(in_file);
+    let (filter_config, stream_filter) = if absolute_time_needed && !calibrated {
+        (None, filter_config)
+    } else {
+        (filter_config, None)
+    };
+    let mut message_producer =
+        FileMessageProducer::new(in_file, filter_config, 0, update_channel.clone(), true)?;
+    let out_file = fs::File::create(out_path)?;
+    let mut buf_writer = BufWriter::with_capacity(10 * 1024 * 1024, out_file);
+    // a calibrated export reads the file twice
+    let total = source_file_size.map(|size| if calibrated { 2 * size } else { size });
+    let mut progress_reporter = ProgressReporter::new(total, update_channel.clone());
+    if let Some(position) = message_producer.source_position() {
+        progress_reporter.track_source(position);
+    }
+    if let ExportFormat::Csv(columns) = &export_config.format {
+        if columns.is_empty() {
+            return Err(err_msg("no columns for csv export"));
+        }
+        write_csv_line(
+            &mut buf_writer,
+            columns.iter().map(|c| c.name().to_string()).collect(),
+        )?;
+    }
+
+    let mut absolute_times = if calibrated {
+        match time_model_for_file(
+            in_file,
+            update_channel,
+            &mut progress_reporter,
+            shutdown_receiver.as_ref(),
+        )? {
+            Some(model) => Some(AbsoluteTimes::calibrated(model)),
+            None => {
+                buf_writer.flush()?;
+                update_channel.send(Ok(IndexingProgress::Stopped))?;
+                return Ok(0);
+            }
+        }
+    } else if absolute_time_needed {
+        Some(AbsoluteTimes::observed(stream_filter))
+    } else {
+        None
+    };
+
+    let mut line_nr = 0usize;
+    let mut exported = 0usize;
+    loop {
+        if let Some(rx) = shutdown_receiver.as_ref() {
+            if let Ok(()) = rx.try_recv() {
+                info!("shutdown received in dlt export");
+                buf_writer.flush()?;
+                update_channel.send(Ok(IndexingProgress::Stopped))?;
+                return Ok(exported);
+            }
+        }
+        let (consumed, next) = message_producer.produce_next_message(fibex_metadata.clone());
+        if consumed == 0 {
+            break;
+        } else {
+            progress_reporter.make_progress(consumed);
+        }
+        match next {
+            Ok(Some(msg)) => {
+                let absolute_time = absolute_times.as_mut().and_then(|t| t.absolute_time(&msg));
+                if absolute_times.as_ref().is_some_and(|t| t.excludes(&msg)) {
+                    trace!("message was filtered");
+                    continue;
+                }
+                let current_line = line_nr;
+                line_nr += 1;
+                if let Some((start, end)) = export_config.line_range {
+                    if current_line < start {
+                        continue;
+                    }
+                    if current_line > end {
+                        break;
+                    }
+                }
+                if let Some((start, end)) = export_config.time_range {
+                    match storage_time_in_ms(&msg) {
+                        Some(t) if t >= start && t <= end => (),
+                        _ => continue,
+                    }
+                }
+                write_message(
+                    &mut buf_writer,
+                    &export_config.format,
+                    current_line,
+                    &msg,
+                    absolute_time.as_ref(),
+                )?;
+                exported += 1;
+            }
+            Ok(None) => {
+                trace!("message was filtered");
+            }
+            Err(DltParseError::ParsingHickup { reason }) => {
+                trace!(
+                    "error parsing 1 dlt message, try to continue parsing: {}",
+                    reason
+                );
+            }
+            Err(DltParseError::Unrecoverable { cause }) => {
+                warn!("cannot continue parsing: {}", cause);
+                update_channel.send(Err(Notification {
+                    severity: Severity::ERROR,
+                    content: format!("error parsing dlt file: {}", cause),
+                    line: None,
+                }))?;
+                break;
+            }
+            Err(DltParseError::IncompleteParse { .. }) => break,
+        }
+    }
+    buf_writer.flush()?;
+    trace!("exported {} messages", exported);
+    update_channel.send(Ok(IndexingProgress::Finished))?;
+    Ok(exported)
+}
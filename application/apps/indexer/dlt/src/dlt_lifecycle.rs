@@ -0,0 +1,331 @@
+// Copyright (c) 2020 E.S.R.Labs. All rights reserved.
+//
+// NOTICE:  All information contained herein is, and remains
+// the property of E.S.R.Labs and its suppliers, if any.
+// The intellectual and technical concepts contained herein are
+// proprietary to E.S.R.Labs and its suppliers and may be covered
+// by German and Foreign Patents, patents in process, and are protected
+// by trade secret or copyright law.
+// Dissemination of this information or reproduction of this material
+// is strictly forbidden unless prior written permission is obtained
+// from E.S.R.Labs.
+//! Lifecycles of the ECUs in a dlt file
+//!
+//! A lifecycle is one run of an ECU from its start until it is restarted.
+//!
+//! The header timestamp counts 0.1 ms ticks since the ECU started and the
+//! storage header holds the time the logger received the message. The
+//! receive time is the send time plus a latency that is never negative, so
+//! every message gives an upper bound for the start of the ECU:
+//! `storage time - ticks`. The smallest of these bounds is the estimated
+//! start of the lifecycle.
+//!
+//! A restart is detected when the ticks jump back and the ECU must have
+//! started after the last message that was already seen (a message that
+//! was buffered only arrives late). Without storage time the ticks have to
+//! jump back by more than a tolerance. Messages without header timestamp
+//! can only show a restart when an application logs with a new session id
+//! and its message counter starts again from 0 at the same time.
+use crate::dlt::Message;
+use crate::dlt_offsets;
+use crate::dlt_parse::{DltParseError, FileMessageProducer, MessageProducer};
+use crossbeam_channel as cc;
+use failure::{err_msg, Error};
+use indexer_base::chunks::ChunkResults;
+use indexer_base::input::input_size;
+use indexer_base::progress::*;
+use rustc_hash::FxHashMap;
+use serde::Serialize;
+
+/// without storage time the header timestamp has to jump back more than
+/// this (in 0.1 ms) to be seen as a restart, buffered messages can be late
+const TIMESTAMP_RESET_TOLERANCE: u32 = 10_000;
+/// microseconds per tick of the standard header timestamp
+pub(crate) const MICROS_PER_TICK: i64 = 100;
+const STOP_CHECK_MESSAGE_THRESHOLD: usize = 250_000;
+
+/// what showed that a new lifecycle started
+#[derive(Serialize, Debug, Clone, Copy, PartialEq)]
+pub enum LifecycleStart {
+    /// first message of the ECU in the file
+    FirstMessage,
+    TimestampReset,
+    /// new session id together with a restarted message counter
+    SessionRestart,
+}
+
+/// one lifecycle of an ECU
+/// lines are the lines of the indexed output of the whole file (without
+/// filter), offsets are byte positions in the dlt file and times are the
+/// storage times in ms since epoch, all ranges are inclusive except
+/// `end_offset` which is the position after the last message
+/// messages of other ECUs can be inside of these ranges
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct EcuLifecycle {
+    pub ecu_id: String,
+    pub start: LifecycleStart,
+    pub start_line: usize,
+    pub end_line: usize,
+    pub start_offset: usize,
+    pub end_offset: usize,
+    pub start_time: Option<u64>,
+    pub end_time: Option<u64>,
+    pub message_count: usize,
+}
+
+pub type LifecycleResults = std::result::Result<IndexingProgress<Vec<EcuLifecycle>>, Notification>;
+
+#[derive(Default)]
+struct EcuState {
+    /// index of the current lifecycle of the ECU
+    current: usize,
+    max_timestamp: Option<u32>,
+    /// last session id and message counter of each application and context
+    sessions: FxHashMap<(String, String), (Option<u32>, u8)>,
+}
+
+impl EcuState {
+    /// update with the next message and tell if it starts a new lifecycle
+    /// `start_bound` is the latest start of the ECU the message allows and
+    /// `current_start` the estimated start of the current lifecycle
+    fn restart(
+        &mut self,
+        msg: &Message,
+        start_bound: Option<i64>,
+        current_start: Option<i64>,
+    ) -> Option<LifecycleStart> {
+        let mut restart = None;
+        if let (Some(timestamp), Some(max)) = (msg.header.timestamp, self.max_timestamp) {
+            let reset = match (start_bound, current_start) {
+                (Some(bound), Some(start)) => {
+                    timestamp < max && bound > start + i64::from(max) * MICROS_PER_TICK
+                }
+                _ => timestamp.saturating_add(TIMESTAMP_RESET_TOLERANCE) < max,
+            };
+            if reset {
+                restart = Some(LifecycleStart::TimestampReset);
+            }
+        }
+        if let Some(ext) = &msg.extended_header {
+            let key = (ext.application_id.clone(), ext.context_id.clone());
+            let counter = msg.header.message_counter;
+            if let Some((session_id, last_counter)) =
+                self.sessions.insert(key, (msg.header.session_id, counter))
+            {
+                if restart.is_none()
+                    && msg.header.timestamp.is_none()
+                    && session_id != msg.header.session_id
+                    && counter == 0
+                    && last_counter != 255
+                {
+                    restart = Some(LifecycleStart::SessionRestart);
+                }
+            }
+        }
+        if restart.is_some() {
+            self.max_timestamp = msg.header.timestamp;
+            let msg_key = msg
+                .extended_header
+                .as_ref()
+                .map(|ext| (ext.application_id.clone(), ext.context_id.clone()));
+            // only the message that started the lifecycle is known so far
+            self.sessions.retain(|key, _| Some(key) == msg_key.as_ref());
+        } else {
+            self.max_timestamp = self.max_timestamp.max(msg.header.timestamp);
+        }
+        restart
+    }
+}
+
+/// detects the lifecycles of all ECUs in the messages they are given
+#[derive(Default)]
+pub struct LifecycleDetector {
+    ecus: FxHashMap<String, EcuState>,
+    lifecycles: Vec<EcuLifecycle>,
+    /// estimated start of each lifecycle in microseconds since epoch
+    starts: Vec<Option<i64>>,
+}
+
+impl LifecycleDetector {
+    pub fn new() -> LifecycleDetector {
+        Default::default()
+    }
+
+    /// add the message of line `line_nr` that starts at `offset` in the file
+    /// and return the index of its lifecycle
+    pub fn add(&mut self, msg: &Message, line_nr: usize, offset: usize) -> usize {
+        let ecu_id = ecu_id(msg).to_string();
+        let start_bound = start_bound(msg);
+        let end_offset = offset + dlt_offsets::source_length(msg);
+        let time = msg.storage_header.as_ref().map(|h| {
+            u64::from(h.timestamp.seconds) * 1000 + u64::from(h.timestamp.microseconds) / 1000
+        });
+        let new_lifecycle = |start| EcuLifecycle {
+            ecu_id: ecu_id.clone(),
+            start,
+            start_line: line_nr,
+            end_line: line_nr,
+            start_offset: offset,
+            end_offset,
+            start_time: time,
+            end_time: time,
+            message_count: 1,
+        };
+        let lifecycles = &mut self.lifecycles;
+        let starts = &mut self.starts;
+        match self.ecus.get_mut(&ecu_id) {
+            None => {
+                let mut state = EcuState::default();
+                state.restart(msg, start_bound, None);
+                let current = lifecycles.len();
+                state.current = current;
+                lifecycles.push(new_lifecycle(LifecycleStart::FirstMessage));
+                starts.push(start_bound);
+                self.ecus.insert(ecu_id.clone(), state);
+                current
+            }
+            Some(state) => {
+                match state.restart(msg, start_bound, starts[state.current]) {
+                    Some(start) => {
+                        state.current = lifecycles.len();
+                        lifecycles.push(new_lifecycle(start));
+                        starts.push(start_bound);
+                    }
+                    None => {
+                        let lifecycle = &mut lifecycles[state.current];
+                        lifecycle.end_line = line_nr;
+                        lifecycle.end_offset = end_offset;
+                        lifecycle.end_time = time.or(lifecycle.end_time);
+                        lifecycle.start_time = lifecycle.start_time.or(time);
+                        lifecycle.message_count += 1;
+                        let start = &mut starts[state.current];
+                        *start = match (*start, start_bound) {
+                            (Some(s), Some(bound)) => Some(s.min(bound)),
+                            (s, bound) => s.or(bound),
+                        };
+                    }
+                }
+                state.current
+            }
+        }
+    }
+
+    /// estimated start of a lifecycle in microseconds since epoch
+    pub(crate) fn start_estimate(&self, lifecycle: usize) -> Option<i64> {
+        self.starts.get(lifecycle).cloned().flatten()
+    }
+
+    /// estimated start of the last lifecycle of the ECU that started no
+    /// later than `start_bound`
+    pub(crate) fn start_estimate_before(&self, ecu_id: &str, start_bound: i64) -> Option<i64> {
+        self.lifecycles
+            .iter()
+            .zip(&self.starts)
+            .rev()
+            .filter(|(l, _)| l.ecu_id == ecu_id)
+            .find_map(|(_, start)| start.filter(|s| *s <= start_bound))
+    }
+
+    /// all lifecycles in the order they started
+    pub fn lifecycles(self) -> Vec<EcuLifecycle> {
+        self.lifecycles
+    }
+}
+
+/// ECU of a message, from the header or else from the storage header
+pub(crate) fn ecu_id(msg: &Message) -> &str {
+    msg.header
+        .ecu_id
+        .as_ref()
+        .or_else(|| msg.storage_header.as_ref().map(|h| &h.ecu_id))
+        .map_or("NONE", |id| id.as_str())
+}
+
+/// latest time the ECU can have started to send the message
+/// (`storage time - ticks` in microseconds since epoch)
+pub(crate) fn start_bound(msg: &Message) -> Option<i64> {
+    let storage_header = msg.storage_header.as_ref()?;
+    let ticks = msg.header.timestamp?;
+    let storage_time = i64::from(storage_header.timestamp.seconds) * 1_000_000
+        + i64::from(storage_header.timestamp.microseconds);
+    Some(storage_time - i64::from(ticks) * MICROS_PER_TICK)
+}
+
+/// find the lifecycles of all ECUs in a dlt file
+/// the result is sent as one item when the whole file was read
+pub fn get_dlt_lifecycles(
+    in_file: &std::path::PathBuf,
+    update_channel: &cc::Sender<LifecycleResults>,
+    shutdown_receiver: Option<cc::Receiver<()>>,
+) -> Result<(), Error> {
+    let source_file_size = input_size(in_file);
+    // the notifications of the producer are passed on to the update channel
+    let (producer_tx, producer_rx): (cc::Sender<ChunkResults>, cc::Receiver<ChunkResults>) =
+        cc::unbounded();
+    let mut producer = FileMessageProducer::new(in_file, None, 0, producer_tx, true)?;
+    let source_position = producer.source_position();
+    let mut detector = LifecycleDetector::new();
+    let mut line_nr = 0usize;
+    let mut offset = 0usize;
+    let mut index = 0usize;
+    loop {
+        let (consumed, next) = producer.produce_next_message(None);
+        if consumed == 0 {
+            break;
+        }
+        offset += consumed;
+        match next {
+            Ok(Some(msg)) => {
+                // consumed bytes might include skipped garbage before the message
+                let length = dlt_offsets::source_length(&msg).min(consumed);
+                detector.add(&msg, line_nr, offset - length);
+                line_nr += 1;
+            }
+            Ok(None) | Err(DltParseError::ParsingHickup { .. }) => (),
+            Err(DltParseError::IncompleteParse { .. }) => break,
+            Err(DltParseError::Unrecoverable { cause }) => {
+                return Err(err_msg(format!("error parsing dlt file: {}", cause)));
+            }
+        }
+        forward_notifications(&producer_rx, update_channel)?;
+        index += 1;
+        if index.is_multiple_of(STOP_CHECK_MESSAGE_THRESHOLD) {
+            if let Some(rx) = shutdown_receiver.as_ref() {
+                match rx.try_recv() {
+                    Ok(_) | Err(cc::TryRecvError::Disconnected) => {
+                        info!("shutdown received in dlt lifecycles, sending stopped");
+                        update_channel.send(Ok(IndexingProgress::Stopped))?;
+                        return Ok(());
+                    }
+                    Err(cc::TryRecvError::Empty) => (),
+                }
+            };
+            let processed = source_position.as_ref().map_or(offset, |p| p.get());
+            update_channel.send(Ok(match source_file_size {
+                Some(size) => IndexingProgress::Progress {
+                    ticks: (processed, size),
+                },
+                None => IndexingProgress::Processed { bytes: offset },
+            }))?;
+        }
+    }
+    drop(producer);
+    forward_notifications(&producer_rx, update_channel)?;
+    update_channel.send(Ok(IndexingProgress::GotItem {
+        item: detector.lifecycles(),
+    }))?;
+    update_channel.send(Ok(IndexingProgress::Finished))?;
+    Ok(())
+}
+
+fn forward_notifications(
+    rx: &cc::Receiver<ChunkResults>,
+    update_channel: &cc::Sender<LifecycleResults>,
+) -> Result<(), Error> {
+    for notification in rx.try_iter() {
+        if let Err(n) = notification {
+            update_channel.send(Err(n))?;
+        }
+    }
+    Ok(())
+}
@@ -4,13 +4,10 @@ use crate::dlt_parse::*;
 use crate::dlt::*;
 use std::rc::Rc;
 use futures::FutureExt;
-use futures::stream::StreamExt;
 use indexer_base::progress::*;
-use std::io::{BufWriter, Write};
-use indexer_base::utils;
-use indexer_base::config::SocketConfig;
-use indexer_base::chunks::{ChunkFactory, ChunkResults};
-use async_std::net::{Ipv4Addr, UdpSocket};
+use indexer_base::config::{SocketConfig, TcpConfig};
+use indexer_base::chunks::ChunkResults;
+use async_std::net::{Ipv4Addr, TcpStream, UdpSocket};
 use std::net::SocketAddr;
 use async_std::task;
 use crate::dlt_parse::dlt_message;
@@ -61,13 +58,11 @@ pub fn index_from_socket(
     shutdown_receiver: async_std::sync::Receiver<()>,
 ) -> Result<(), ConnectionError> {
     trace!("index_from_socket for socket conf: {:?}", socket_config);
-    let (out_file, current_out_file_size) = utils::get_out_file_and_size(true, out_path)?;
-    let mut chunk_factory = ChunkFactory::new(0, current_out_file_size);
-    let mut line_nr = initial_line_nr;
-    let mut buf_writer = BufWriter::with_capacity(10 * 1024 * 1024, out_file);
-    let mut chunk_count = 0usize;
-    let mut last_byte_index = 0usize;
-    task::block_on(async {
+    let mut sink = IndexSink::for_stream(tag, out_path, initial_line_nr, &update_channel)?;
+    if filter_config.is_none() {
+        sink.check_counters();
+    }
+    let res: Result<(), ConnectionError> = task::block_on(async {
         let s = format!("{}:{}", socket_config.bind_addr, socket_config.port);
         let bind_addr_and_port: SocketAddr = s.parse()?;
         let socket = UdpSocket::bind(bind_addr_and_port).await?;
@@ -91,135 +86,336 @@ pub fn index_from_socket(
                 b: (0, 0),
             },
         }));
-        let udp_msg_producer = UdpMessageProducer {
+        let mut udp_msg_producer = UdpMessageProducer {
             socket,
             update_channel: update_channel.clone(),
             ecu_id,
-            fibex_metadata,
             filter_config,
+            index: initial_line_nr,
         };
-        // listen for both a shutdown request and incomming messages
-        // to do this we need to select over streams of the same type
-        // the type we use to unify is this Event enum
-        enum Event {
-            Shutdown,
-            Msg(Result<Option<Message>, DltParseError>),
+        if index_messages(
+            &mut udp_msg_producer,
+            &mut sink,
+            fibex_metadata,
+            Some(&shutdown_receiver),
+        )
+        .await?
+        {
+            let _ = update_channel.send(Ok(IndexingProgress::Stopped));
         }
-        let shutdown_stream = shutdown_receiver.map(|_| {
-            debug!("shutdown_receiver event");
-            Event::Shutdown
-        });
-        let message_stream = udp_msg_producer.map(Event::Msg);
-        let mut event_stream = futures::stream::select(message_stream, shutdown_stream);
-        while let Some(event) = event_stream.next().await {
-            let maybe_msg = match event {
-                Event::Shutdown => {
-                    debug!("received shutdown through future channel");
+        Ok(())
+    });
+    res?;
+    sink.finish()?;
+    Ok(())
+}
+
+/// Receives dlt messages as udp datagrams, every datagram contains one
+/// message without storage header.
+struct UdpMessageProducer {
+    socket: UdpSocket,
+    update_channel: cc::Sender<ChunkResults>,
+    ecu_id: String,
+    filter_config: Option<filtering::ProcessedDltFilterConfig>,
+    index: usize,
+}
+impl AsyncMessageProducer for UdpMessageProducer {
+    fn poll_next_message(
+        mut self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context,
+        fibex_metadata: Option<Rc<FibexMetadata>>,
+    ) -> futures::task::Poll<(usize, Result<Option<Message>, DltParseError>)> {
+        let mut buf = [0u8; 65535];
+        let amt = loop {
+            let mut f = self.socket.recv_from(&mut buf).boxed();
+            match f.as_mut().poll(cx) {
+                futures::task::Poll::Pending => return futures::task::Poll::Pending,
+                futures::task::Poll::Ready(Err(e)) => {
+                    return futures::task::Poll::Ready((0, Err(e.into())));
+                }
+                // an empty datagram does not contain anything we could parse
+                futures::task::Poll::Ready(Ok((0, _src))) => continue,
+                futures::task::Poll::Ready(Ok((amt, _src))) => break amt,
+            }
+        };
+        let res = match dlt_message(
+            &buf[..amt],
+            self.filter_config.as_ref(),
+            self.index,
+            Some(&self.update_channel),
+            fibex_metadata,
+            false,
+        ) {
+            Ok((_, None)) => Ok(None),
+            Ok((_, Some(m))) => {
+                self.index += 1;
+                Ok(Some(with_storage_header(m, &self.ecu_id)))
+            }
+            Err(nom::Err::Incomplete(_)) => Err(DltParseError::ParsingHickup {
+                reason: "datagram does not contain a complete message".to_string(),
+            }),
+            Err(nom::Err::Error(e)) | Err(nom::Err::Failure(e)) => {
+                Err(DltParseError::ParsingHickup {
+                    reason: format!("{:?}", e),
+                })
+            }
+        };
+        if let Err(DltParseError::ParsingHickup { reason }) = &res {
+            let _ = self.update_channel.send(Err(Notification {
+                severity: Severity::WARNING,
+                content: format!("parsing faild for one message: {}", reason),
+                line: None,
+            }));
+        }
+        futures::task::Poll::Ready((amt, res))
+    }
+}
+
+/// how long to wait before trying to reconnect to a dlt server
+/// after the tcp connection was lost or could not be established
+const TCP_RECONNECT_INTERVAL: std::time::Duration = std::time::Duration::from_secs(2);
+const TCP_READ_BUFFER_SIZE: usize = 64 * 1024;
+
+#[allow(clippy::too_many_arguments)]
+pub fn index_from_tcp_stream(
+    tcp_config: TcpConfig,
+    filter_config: Option<filtering::ProcessedDltFilterConfig>,
+    update_channel: cc::Sender<ChunkResults>,
+    fibex_metadata: Option<Rc<FibexMetadata>>,
+    tag: &str,
+    ecu_id: String,
+    out_path: &std::path::PathBuf,
+    initial_line_nr: usize,
+    shutdown_receiver: async_std::sync::Receiver<()>,
+) -> Result<(), ConnectionError> {
+    trace!("index_from_tcp_stream for tcp conf: {:?}", tcp_config);
+    let mut sink = IndexSink::for_stream(tag, out_path, initial_line_nr, &update_channel)?;
+    if filter_config.is_none() {
+        sink.check_counters();
+    }
+    let server_addr = format!("{}:{}", tcp_config.address, tcp_config.port);
+    let res: Result<(), ConnectionError> = task::block_on(async {
+        // the filter config is moved into each producer, keep it in an option
+        // so we can hand it over again after a reconnect
+        let mut filter_config = filter_config;
+        loop {
+            enum Connect {
+                Shutdown,
+                Connected(std::io::Result<TcpStream>),
+            }
+            let connect = TcpStream::connect(server_addr.clone()).map(Connect::Connected);
+            let shutdown = shutdown_receiver.recv().map(|_| Connect::Shutdown);
+            let stream = match futures::future::select(Box::pin(connect), Box::pin(shutdown))
+                .await
+                .factor_first()
+                .0
+            {
+                Connect::Shutdown => {
+                    debug!("received shutdown while connecting");
                     let _ = update_channel.send(Ok(IndexingProgress::Stopped));
-                    break;
+                    return Ok(());
                 }
-                Event::Msg(Ok(maybe_msg)) => maybe_msg,
-                Event::Msg(Err(DltParseError::ParsingHickup { reason })) => {
+                Connect::Connected(Ok(stream)) => stream,
+                Connect::Connected(Err(e)) => {
                     let _ = update_channel.send(Err(Notification {
                         severity: Severity::WARNING,
-                        content: format!("parsing faild for one message: {}", reason),
+                        content: format!(
+                            "could not connect to {} ({}), will retry in {}s",
+                            server_addr,
+                            e,
+                            TCP_RECONNECT_INTERVAL.as_secs()
+                        ),
                         line: None,
                     }));
-                    None
-                }
-                Event::Msg(Err(DltParseError::Unrecoverable { .. })) => break,
-            };
-            match maybe_msg {
-                Some(msg) => {
-                    trace!("got msg ...({} bytes)", msg.as_bytes().len());
-                    let written_bytes_len =
-                        utils::create_tagged_line_d(tag, &mut buf_writer, &msg, line_nr, true)?;
-                    line_nr += 1;
-                    if let Some(chunk) =
-                        chunk_factory.create_chunk_if_needed(line_nr, written_bytes_len)
-                    {
-                        chunk_count += 1;
-                        last_byte_index = chunk.b.1;
-                        buf_writer.flush()?;
-                        let _ = update_channel.send(Ok(IndexingProgress::GotItem { item: chunk }));
+                    if wait_for_reconnect(&shutdown_receiver).await {
+                        let _ = update_channel.send(Ok(IndexingProgress::Stopped));
+                        return Ok(());
                     }
+                    continue;
                 }
-                None => {
-                    trace!("msg was filtered");
-                }
+            };
+            trace!("connected to {}", server_addr);
+            // send (0,0),(0,0) to indicate connection established
+            let _ = update_channel.send(Ok(IndexingProgress::GotItem {
+                item: Chunk {
+                    r: (0, 0),
+                    b: (0, 0),
+                },
+            }));
+            let mut tcp_msg_producer = TcpMessageProducer {
+                stream,
+                server_addr: server_addr.clone(),
+                buffer: Vec::with_capacity(TCP_READ_BUFFER_SIZE),
+                update_channel: update_channel.clone(),
+                ecu_id: ecu_id.clone(),
+                filter_config: filter_config.take(),
+                index: sink.line_nr(),
+            };
+            if index_messages(
+                &mut tcp_msg_producer,
+                &mut sink,
+                fibex_metadata.clone(),
+                Some(&shutdown_receiver),
+            )
+            .await?
+            {
+                let _ = update_channel.send(Ok(IndexingProgress::Stopped));
+                return Ok(());
+            }
+            filter_config = tcp_msg_producer.filter_config.take();
+            let _ = update_channel.send(Err(Notification {
+                severity: Severity::WARNING,
+                content: format!(
+                    "lost connection to {}, will reconnect in {}s",
+                    server_addr,
+                    TCP_RECONNECT_INTERVAL.as_secs()
+                ),
+                line: None,
+            }));
+            if wait_for_reconnect(&shutdown_receiver).await {
+                let _ = update_channel.send(Ok(IndexingProgress::Stopped));
+                return Ok(());
             }
         }
-        Ok(())
-    })
+    });
+    res?;
+    sink.finish()?;
+    Ok(())
 }
-struct UdpMessageProducer {
-    socket: UdpSocket,
+
+/// waits `TCP_RECONNECT_INTERVAL` before the next connection attempt
+/// returns true if a shutdown was requested in the meantime
+async fn wait_for_reconnect(shutdown_receiver: &async_std::sync::Receiver<()>) -> bool {
+    let sleep = task::sleep(TCP_RECONNECT_INTERVAL).map(|_| false);
+    let shutdown = shutdown_receiver.recv().map(|_| true);
+    futures::future::select(Box::pin(sleep), Box::pin(shutdown))
+        .await
+        .factor_first()
+        .0
+}
+
+/// Reads dlt messages from a tcp stream. Since tcp delivers a plain byte stream
+/// without storage headers, the messages have to be re-framed using the length
+/// information of the standard header. Incoming data is collected in `buffer`
+/// until at least one complete message is available.
+/// A lost connection ends the production of messages.
+struct TcpMessageProducer {
+    stream: TcpStream,
+    server_addr: String,
+    buffer: Vec<u8>,
     update_channel: cc::Sender<ChunkResults>,
     ecu_id: String,
-    fibex_metadata: Option<Rc<FibexMetadata>>,
     filter_config: Option<filtering::ProcessedDltFilterConfig>,
+    index: usize,
 }
-impl futures::Stream for UdpMessageProducer {
-    type Item = Result<Option<Message>, DltParseError>;
-    fn poll_next(
-        self: std::pin::Pin<&mut Self>,
-        cx: &mut std::task::Context,
-    ) -> futures::task::Poll<Option<Self::Item>> {
-        let mut buf = [0u8; 65535];
-        let pending = {
-            let mut f = self.socket.recv_from(&mut buf).boxed();
-            match f.as_mut().poll(cx) {
-                futures::task::Poll::Pending => true,
-                futures::task::Poll::Ready(Err(e)) => {
-                    return futures::task::Poll::Ready(Some(Err(e.into())));
-                }
-                futures::task::Poll::Ready(Ok((_amt, _src))) => false,
+impl TcpMessageProducer {
+    /// try to parse the next message from the data we already received
+    /// returns None if we need more data
+    fn next_from_buffer(
+        &mut self,
+        fibex_metadata: Option<Rc<FibexMetadata>>,
+    ) -> Option<(usize, Result<Option<Message>, DltParseError>)> {
+        if self.buffer.is_empty() {
+            return None;
+        }
+        let (consumed, res) = match dlt_message(
+            &self.buffer,
+            self.filter_config.as_ref(),
+            self.index,
+            Some(&self.update_channel),
+            fibex_metadata,
+            false,
+        ) {
+            Ok((rest, maybe_msg)) => (
+                self.buffer.len() - rest.len(),
+                Ok(maybe_msg.map(|m| with_storage_header(m, &self.ecu_id))),
+            ),
+            Err(nom::Err::Incomplete(_)) => return None,
+            Err(nom::Err::Error(e)) | Err(nom::Err::Failure(e)) => {
+                // without storage headers there is no pattern we could use to find
+                // the next message, so we skip the broken message using its length
+                // information or drop everything we have if even that is not available
+                let to_skip = match dlt_standard_header(&self.buffer) {
+                    Ok((_, header)) => {
+                        std::cmp::min(header.overall_length() as usize, self.buffer.len())
+                    }
+                    Err(_) => self.buffer.len(),
+                };
+                let reason = format!("{:?}", e);
+                let _ = self.update_channel.send(Err(Notification {
+                    severity: Severity::WARNING,
+                    content: format!("parsing faild for one message: {}", reason),
+                    line: Some(self.index),
+                }));
+                (
+                    std::cmp::max(to_skip, 1),
+                    Err(DltParseError::ParsingHickup { reason }),
+                )
             }
         };
-        if pending {
-            futures::task::Poll::Pending
-        } else {
-            match dlt_message(
-                &buf,
-                self.filter_config.as_ref(),
-                0,
-                Some(&self.update_channel),
-                self.fibex_metadata.clone(),
-                false,
+        self.buffer.drain(..consumed);
+        if let Ok(Some(_)) = res {
+            self.index += 1;
+        }
+        Some((consumed, res))
+    }
+}
+impl AsyncMessageProducer for TcpMessageProducer {
+    fn poll_next_message(
+        mut self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context,
+        fibex_metadata: Option<Rc<FibexMetadata>>,
+    ) -> futures::task::Poll<(usize, Result<Option<Message>, DltParseError>)> {
+        let mut read_buf = [0u8; TCP_READ_BUFFER_SIZE];
+        loop {
+            if let Some(res) = self.next_from_buffer(fibex_metadata.clone()) {
+                return futures::task::Poll::Ready(res);
+            }
+            match futures::AsyncRead::poll_read(
+                std::pin::Pin::new(&mut self.stream),
+                cx,
+                &mut read_buf,
             ) {
-                Ok((_, None)) => futures::task::Poll::Ready(None),
-                Ok((_, Some(m))) => {
-                    let msg_with_storage_header = match m.storage_header {
-                        Some(_) => m,
-                        None => {
-                            let now = SystemTime::now();
-                            let since_the_epoch = now
-                                .duration_since(UNIX_EPOCH)
-                                .unwrap_or(std::time::Duration::from_secs(0));
-                            let in_ms = since_the_epoch.as_millis();
-                            Message {
-                                storage_header: Some(StorageHeader {
-                                    timestamp: DltTimeStamp::from_ms(in_ms as u64),
-                                    ecu_id: self.ecu_id.clone(),
-                                }),
-                                ..m
-                            }
-                        }
-                    };
-                    futures::task::Poll::Ready(Some(Ok(Some(msg_with_storage_header))))
+                futures::task::Poll::Pending => return futures::task::Poll::Pending,
+                futures::task::Poll::Ready(Ok(0)) => {
+                    debug!("tcp connection closed by peer");
+                    return futures::task::Poll::Ready((0, Ok(None)));
                 }
-                Err(nom::Err::Incomplete(_n)) => futures::task::Poll::Pending,
-                Err(nom::Err::Error(_e)) => {
-                    futures::task::Poll::Ready(Some(Err(DltParseError::ParsingHickup {
-                        reason: format!("{:?}", _e),
-                    })))
+                futures::task::Poll::Ready(Ok(n)) => {
+                    trace!("received {} bytes over tcp", n);
+                    self.buffer.extend_from_slice(&read_buf[..n]);
                 }
-                Err(nom::Err::Failure(_e)) => {
-                    futures::task::Poll::Ready(Some(Err(DltParseError::Unrecoverable {
-                        cause: format!("{:?}", _e),
-                    })))
+                futures::task::Poll::Ready(Err(e)) => {
+                    let _ = self.update_channel.send(Err(Notification {
+                        severity: Severity::WARNING,
+                        content: format!("connection to {} broken: {}", self.server_addr, e),
+                        line: None,
+                    }));
+                    return futures::task::Poll::Ready((0, Ok(None)));
                 }
             }
         }
     }
 }
+
+/// messages received over the network do not carry a storage header,
+/// add one with the current time as receive time
+fn with_storage_header(m: Message, ecu_id: &str) -> Message {
+    match m.storage_header {
+        Some(_) => m,
+        None => {
+            let now = SystemTime::now();
+            let since_the_epoch = now
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or(std::time::Duration::from_secs(0));
+            let in_ms = since_the_epoch.as_millis();
+            Message {
+                storage_header: Some(StorageHeader {
+                    timestamp: DltTimeStamp::from_ms(in_ms as u64),
+                    ecu_id: ecu_id.to_string(),
+                }),
+                ..m
+            }
+        }
+    }
+}
@@ -0,0 +1,147 @@
+// Copyright (c) 2019 E.S.R.Labs. All rights reserved.
+//
+// NOTICE:  All information contained herein is, and remains
+// the property of E.S.R.Labs and its suppliers, if any.
+// The intellectual and technical concepts contained herein are
+// proprietary to E.S.R.Labs and its suppliers and may be covered
+// by German and Foreign Patents, patents in process, and are protected
+// by trade secret or copyright law.
+// Dissemination of this information or reproduction of this material
+// is strictly forbidden unless prior written permission is obtained
+// from E.S.R.Labs.
+use crate::dlt::*;
+use crate::dlt_parse::dlt_message;
+use crate::fibex::FibexMetadata;
+use byteorder::{ByteOrder, LittleEndian};
+use failure::{err_msg, Error};
+use std::fs;
+use std::io::{BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+/// every line of the indexed output is described by one fixed size record:
+/// line number (u64), byte offset in the source file (u64) and length of
+/// the message including the storage header (u32), all little endian
+pub const OFFSET_RECORD_LENGTH: usize = 20;
+const OFFSET_INDEX_EXTENSION: &str = "offsets";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OffsetRecord {
+    pub line_nr: u64,
+    pub offset: u64,
+    pub length: u32,
+}
+impl OffsetRecord {
+    fn to_bytes(self) -> [u8; OFFSET_RECORD_LENGTH] {
+        let mut buf = [0u8; OFFSET_RECORD_LENGTH];
+        LittleEndian::write_u64(&mut buf[0..8], self.line_nr);
+        LittleEndian::write_u64(&mut buf[8..16], self.offset);
+        LittleEndian::write_u32(&mut buf[16..20], self.length);
+        buf
+    }
+    fn from_bytes(buf: &[u8; OFFSET_RECORD_LENGTH]) -> OffsetRecord {
+        OffsetRecord {
+            line_nr: LittleEndian::read_u64(&buf[0..8]),
+            offset: LittleEndian::read_u64(&buf[8..16]),
+            length: LittleEndian::read_u32(&buf[16..20]),
+        }
+    }
+}
+
+/// the sidecar index is written next to the indexed output file
+/// e.g. `trace.dlt.out` -> `trace.dlt.out.offsets`
+pub fn offset_index_path(out_path: &Path) -> PathBuf {
+    let mut file_name = out_path
+        .file_name()
+        .map(|n| n.to_os_string())
+        .unwrap_or_default();
+    file_name.push(".");
+    file_name.push(OFFSET_INDEX_EXTENSION);
+    out_path.with_file_name(file_name)
+}
+
+pub struct OffsetIndexWriter {
+    writer: BufWriter<fs::File>,
+}
+impl OffsetIndexWriter {
+    pub fn new(path: &Path) -> Result<OffsetIndexWriter, Error> {
+        Ok(OffsetIndexWriter {
+            writer: BufWriter::new(fs::File::create(path)?),
+        })
+    }
+    pub fn write_record(&mut self, record: OffsetRecord) -> Result<(), Error> {
+        self.writer.write_all(&record.to_bytes())?;
+        Ok(())
+    }
+    pub fn flush(&mut self) -> Result<(), Error> {
+        self.writer.flush()?;
+        Ok(())
+    }
+}
+
+/// length of a message in the source file (storage header + headers + payload)
+pub fn source_length(msg: &Message) -> usize {
+    let storage_header_length = if msg.storage_header.is_some() {
+        STORAGE_HEADER_LENGTH
+    } else {
+        0
+    };
+    storage_header_length + msg.header.overall_length() as usize
+}
+
+/// find the record for a line number
+/// line numbers are increasing, so we can do a binary search on the records
+pub fn lookup_offset(index_path: &Path, line_nr: u64) -> Result<Option<OffsetRecord>, Error> {
+    let mut file = fs::File::open(index_path)?;
+    let file_size = file.metadata()?.len();
+    if file_size % OFFSET_RECORD_LENGTH as u64 != 0 {
+        return Err(err_msg(format!(
+            "offset index {:?} is corrupted (size {} is no multiple of {})",
+            index_path, file_size, OFFSET_RECORD_LENGTH
+        )));
+    }
+    let mut buf = [0u8; OFFSET_RECORD_LENGTH];
+    let mut low = 0u64;
+    let mut high = file_size / OFFSET_RECORD_LENGTH as u64;
+    while low < high {
+        let mid = low + (high - low) / 2;
+        file.seek(SeekFrom::Start(mid * OFFSET_RECORD_LENGTH as u64))?;
+        file.read_exact(&mut buf)?;
+        let record = OffsetRecord::from_bytes(&buf);
+        if record.line_nr == line_nr {
+            return Ok(Some(record));
+        } else if record.line_nr < line_nr {
+            low = mid + 1;
+        } else {
+            high = mid;
+        }
+    }
+    Ok(None)
+}
+
+/// re-read the message that was written to a line of the indexed output
+/// directly from the original dlt file
+pub fn read_message_at_line(
+    dlt_file: &Path,
+    index_path: &Path,
+    line_nr: u64,
+    fibex_metadata: Option<Rc<FibexMetadata>>,
+) -> Result<Message, Error> {
+    let record = lookup_offset(index_path, line_nr)?
+        .ok_or_else(|| err_msg(format!("no offset recorded for line {}", line_nr)))?;
+    let mut file = fs::File::open(dlt_file)?;
+    file.seek(SeekFrom::Start(record.offset))?;
+    let mut buf = vec![0u8; record.length as usize];
+    file.read_exact(&mut buf)?;
+    match dlt_message(&buf, None, line_nr as usize, None, fibex_metadata, true) {
+        Ok((_, Some(msg))) => Ok(msg),
+        Ok((_, None)) => Err(err_msg(format!(
+            "message for line {} could not be read at offset {}",
+            line_nr, record.offset
+        ))),
+        Err(e) => Err(err_msg(format!(
+            "error parsing message for line {} at offset {}: {:?}",
+            line_nr, record.offset, e
+        ))),
+    }
+}
@@ -0,0 +1,471 @@
+// Copyright (c) 2020 E.S.R.Labs. All rights reserved.
+//
+// NOTICE:  All information contained herein is, and remains
+// the property of E.S.R.Labs and its suppliers, if any.
+// The intellectual and technical concepts contained herein are
+// proprietary to E.S.R.Labs and its suppliers and may be covered
+// by German and Foreign Patents, patents in process, and are protected
+// by trade secret or copyright law.
+// Dissemination of this information or reproduction of this material
+// is strictly forbidden unless prior written permission is obtained
+// from E.S.R.Labs.
+use crate::dlt::*;
+use crate::dlt_counter::{MessageCounter, Sender};
+use crate::dlt_offsets;
+use crate::dlt_parse::{
+    create_index_and_mapping_dlt, find_storage_header_pattern, parse_next_message, DltParseError,
+    FileMessageProducer, IndexSink, MessageProducer,
+};
+use crate::fibex::{read_fibexes, FibexMetadata};
+use crate::filtering;
+use crossbeam_channel as cc;
+use failure::{err_msg, Error};
+use indexer_base::chunks::ChunkResults;
+use indexer_base::config::IndexingConfig;
+use indexer_base::input::{input_size, is_random_access};
+use indexer_base::progress::*;
+use indexer_base::utils;
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::PathBuf;
+use std::rc::Rc;
+use std::thread;
+
+/// default number of bytes of the source file that are handed to a worker at once
+pub const DEFAULT_SEGMENT_SIZE: usize = 8 * 1024 * 1024;
+/// how far after the nominal start of a segment we look for a storage header
+const BOUNDARY_SEARCH_WINDOW: usize = 64 * 1024;
+/// number of consecutive messages that have to line up before we accept
+/// a storage header as segment boundary
+const BOUNDARY_VERIFY_COUNT: usize = 3;
+const DLT_PATTERN: &[u8] = &[0x44, 0x4C, 0x54, 0x01];
+
+#[derive(Debug, Clone)]
+pub struct ParallelConfig {
+    /// number of worker threads
+    pub threads: usize,
+    /// size of the segments the input is split into
+    pub segment_size: usize,
+    /// read the file memory mapped when it is indexed sequentially
+    pub memory_map: bool,
+    /// add the absolute time as last column, the file is then indexed
+    /// sequentially (see `dlt_time`)
+    pub absolute_time: bool,
+}
+
+#[derive(Debug, Clone)]
+struct SegmentJob {
+    index: usize,
+    start: usize,
+    end: usize,
+    is_last: bool,
+}
+
+/// result of parsing one message in a worker
+/// notifications that were issued while parsing are kept so they can be
+/// forwarded in the right order
+struct ParsedItem {
+    consumed: usize,
+    notifications: Vec<ChunkResults>,
+    result: Result<Option<(RenderedMessage, usize)>, DltParseError>,
+}
+
+/// a message formatted by a worker together with its sender and counter
+struct RenderedMessage {
+    text: String,
+    ecu_id: Option<String>,
+    session_id: Option<u32>,
+    app_id: Option<String>,
+    context_id: Option<String>,
+    counter: u8,
+}
+impl RenderedMessage {
+    fn new(msg: &Message) -> RenderedMessage {
+        RenderedMessage {
+            text: msg.to_string(),
+            ecu_id: msg.header.ecu_id.clone(),
+            session_id: msg.header.session_id,
+            app_id: msg
+                .extended_header
+                .as_ref()
+                .map(|h| h.application_id.clone()),
+            context_id: msg.extended_header.as_ref().map(|h| h.context_id.clone()),
+            counter: msg.header.message_counter,
+        }
+    }
+}
+impl fmt::Display for RenderedMessage {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.text)
+    }
+}
+impl MessageCounter for RenderedMessage {
+    fn message_counter(&self) -> (Sender<'_>, u8) {
+        let sender = Sender {
+            ecu_id: self.ecu_id.as_deref(),
+            session_id: self.session_id,
+            app_id: self.app_id.as_deref(),
+            context_id: self.context_id.as_deref(),
+        };
+        (sender, self.counter)
+    }
+}
+
+struct ParsedSegment {
+    index: usize,
+    items: Vec<ParsedItem>,
+    /// offset in the source file where parsing of this segment stopped
+    end: usize,
+    /// false if the segment ended in the middle of a message
+    complete: bool,
+}
+
+/// index a dlt file using multiple threads
+///
+/// the file is split at verified storage header boundaries into segments that
+/// are parsed and formatted by a pool of workers. The results are written in
+/// order, so the output, the chunks, the offset index and the progress reports
+/// are the same as for `create_index_and_mapping_dlt`.
+/// Since the fibex metadata cannot be shared between threads, every worker
+/// reads the fibex files itself.
+pub fn create_index_and_mapping_dlt_parallel(
+    config: IndexingConfig,
+    source_file_size: Option<usize>,
+    dlt_filter: Option<filtering::DltFilterConfig>,
+    update_channel: &cc::Sender<ChunkResults>,
+    shutdown_receiver: Option<cc::Receiver<()>>,
+    fibex_paths: Vec<PathBuf>,
+    parallel_config: ParallelConfig,
+) -> Result<(), Error> {
+    trace!("create_index_and_mapping_dlt_parallel");
+    let fibex_metadata = load_fibex(&fibex_paths)?;
+    // stdin, named pipes and compressed files can only be read sequentially
+    let file_size = input_size(&config.in_file).unwrap_or(0);
+    let segments = if file_size > 0 && is_random_access(&config.in_file) {
+        segment_boundaries(&config.in_file, file_size, parallel_config.segment_size)?
+    } else {
+        vec![]
+    };
+    if parallel_config.threads <= 1 || segments.len() <= 1 || parallel_config.absolute_time {
+        return create_index_and_mapping_dlt(
+            config,
+            source_file_size,
+            dlt_filter,
+            update_channel,
+            shutdown_receiver,
+            fibex_metadata,
+            parallel_config.memory_map,
+            parallel_config.absolute_time,
+        );
+    }
+    let initial_line_nr = match utils::next_line_nr(config.out_path) {
+        Ok(nr) => nr,
+        Err(e) => {
+            let content = format!(
+                "could not determine last line number of {:?} ({})",
+                config.out_path, e
+            );
+            let _ = update_channel.send(Err(Notification {
+                severity: Severity::ERROR,
+                content: content.clone(),
+                line: None,
+            }));
+            return Err(err_msg(content));
+        }
+    };
+    let filter_config: Option<filtering::ProcessedDltFilterConfig> =
+        dlt_filter.map(filtering::process_filter_config);
+    // used when a segment did not end on a message boundary
+    let mut message_producer = FileMessageProducer::new(
+        &config.in_file,
+        filter_config.clone(),
+        initial_line_nr,
+        update_channel.clone(),
+        true,
+    )?;
+
+    let (job_tx, job_rx): (cc::Sender<SegmentJob>, cc::Receiver<SegmentJob>) = cc::unbounded();
+    #[allow(clippy::type_complexity)]
+    let (result_tx, result_rx): (
+        cc::Sender<Result<ParsedSegment, Error>>,
+        cc::Receiver<Result<ParsedSegment, Error>>,
+    ) = cc::unbounded();
+    for _ in 0..parallel_config.threads {
+        spawn_worker(
+            config.in_file.clone(),
+            filter_config.clone(),
+            fibex_paths.clone(),
+            initial_line_nr,
+            job_rx.clone(),
+            result_tx.clone(),
+        );
+    }
+    drop(result_tx);
+
+    let mut sink = IndexSink::new(
+        &config,
+        initial_line_nr,
+        source_file_size,
+        update_channel,
+        shutdown_receiver,
+    )?;
+    if filter_config.is_none() {
+        sink.check_counters();
+    }
+    let max_in_flight = 2 * parallel_config.threads;
+    let mut pending: HashMap<usize, ParsedSegment> = HashMap::new();
+    let mut dispatched = 0usize;
+    let mut current = 0usize;
+    'segments: while current < segments.len() {
+        while dispatched < segments.len() && dispatched < current + max_in_flight {
+            job_tx.send(SegmentJob {
+                index: dispatched,
+                start: segments[dispatched],
+                end: segments.get(dispatched + 1).cloned().unwrap_or(file_size),
+                is_last: dispatched + 1 == segments.len(),
+            })?;
+            dispatched += 1;
+        }
+        let segment = loop {
+            if let Some(segment) = pending.remove(&current) {
+                break segment;
+            }
+            let segment = result_rx.recv()??;
+            // results of segments we already skipped are not needed anymore
+            if segment.index >= current {
+                pending.insert(segment.index, segment);
+            }
+        };
+        for item in segment.items {
+            if sink.stopped {
+                info!("we were stopped in dlt-indexer",);
+                break 'segments;
+            }
+            for notification in item.notifications {
+                update_channel.send(notification)?;
+            }
+            if !sink.handle(item.consumed, item.result)? {
+                break 'segments;
+            }
+        }
+        if segment.complete {
+            current += 1;
+            continue;
+        }
+        // the segment ended in the middle of a message: continue sequentially
+        // until we are in sync with one of the following segments again
+        debug!(
+            "segment {} ended at {}, continue sequentially",
+            current, segment.end
+        );
+        message_producer.seek(segment.end as u64)?;
+        let mut position = segment.end;
+        loop {
+            if sink.stopped {
+                info!("we were stopped in dlt-indexer",);
+                break 'segments;
+            }
+            let (consumed, next) = message_producer.produce_next_message(fibex_metadata.clone());
+            let next = next.map(|m| {
+                m.map(|msg| {
+                    let length = dlt_offsets::source_length(&msg);
+                    (msg, length)
+                })
+            });
+            if !sink.handle(consumed, next)? {
+                break 'segments;
+            }
+            position += consumed;
+            if let Some(i) = segments[current + 1..].iter().position(|s| *s == position) {
+                current += 1 + i;
+                pending.retain(|index, _| *index >= current);
+                continue 'segments;
+            }
+        }
+    }
+    // workers stop as soon as there are no more jobs
+    drop(job_tx);
+    sink.finish()
+}
+
+fn load_fibex(fibex_paths: &[PathBuf]) -> Result<Option<Rc<FibexMetadata>>, Error> {
+    if fibex_paths.is_empty() {
+        return Ok(None);
+    }
+    let metadata = read_fibexes(fibex_paths.to_vec())
+        .map_err(|e| err_msg(format!("could not read fibex {:?}: {}", fibex_paths, e)))?;
+    Ok(Some(Rc::new(metadata)))
+}
+
+fn spawn_worker(
+    in_file: PathBuf,
+    filter_config: Option<filtering::ProcessedDltFilterConfig>,
+    fibex_paths: Vec<PathBuf>,
+    index: usize,
+    jobs: cc::Receiver<SegmentJob>,
+    results: cc::Sender<Result<ParsedSegment, Error>>,
+) {
+    thread::spawn(move || {
+        let fibex_metadata = match load_fibex(&fibex_paths) {
+            Ok(metadata) => metadata,
+            Err(e) => {
+                let _ = results.send(Err(e));
+                return;
+            }
+        };
+        let mut file = match fs::File::open(&in_file) {
+            Ok(file) => file,
+            Err(e) => {
+                let _ = results.send(Err(e.into()));
+                return;
+            }
+        };
+        for job in jobs.iter() {
+            let res = parse_segment(
+                &mut file,
+                &job,
+                filter_config.as_ref(),
+                index,
+                fibex_metadata.clone(),
+            );
+            if results.send(res).is_err() {
+                // nobody is interested in the results anymore
+                break;
+            }
+        }
+    });
+}
+
+fn parse_segment(
+    file: &mut fs::File,
+    job: &SegmentJob,
+    filter_config: Option<&filtering::ProcessedDltFilterConfig>,
+    index: usize,
+    fibex_metadata: Option<Rc<FibexMetadata>>,
+) -> Result<ParsedSegment, Error> {
+    let mut content = vec![0u8; job.end - job.start];
+    file.seek(SeekFrom::Start(job.start as u64))?;
+    file.read_exact(&mut content)?;
+    let (tx, rx): (cc::Sender<ChunkResults>, cc::Receiver<ChunkResults>) = cc::unbounded();
+    let mut items = vec![];
+    let mut offset = 0usize;
+    let mut complete = true;
+    while offset < content.len() {
+        let (consumed, next) = parse_next_message(
+            &content[offset..],
+            job.start + offset,
+            job.is_last,
+            filter_config,
+            index,
+            &tx,
+            fibex_metadata.clone(),
+            true,
+        );
+        let notifications: Vec<ChunkResults> = rx.try_iter().collect();
+        let result = next.map(|m| {
+            m.map(|msg| {
+                let length = dlt_offsets::source_length(&msg);
+                (RenderedMessage::new(&msg), length)
+            })
+        });
+        if consumed == 0 {
+            // only at the end of the file this is the real end of parsing,
+            // otherwise the message continues in the next segment
+            complete = job.is_last;
+            if job.is_last {
+                items.push(ParsedItem {
+                    consumed,
+                    notifications,
+                    result,
+                });
+            }
+            break;
+        }
+        items.push(ParsedItem {
+            consumed,
+            notifications,
+            result,
+        });
+        offset += consumed;
+    }
+    Ok(ParsedSegment {
+        index: job.index,
+        items,
+        end: job.start + offset,
+        complete,
+    })
+}
+
+/// split the file into segments of roughly `segment_size` bytes
+/// returns the start offsets of the segments
+fn segment_boundaries(
+    in_file: &PathBuf,
+    file_size: usize,
+    segment_size: usize,
+) -> Result<Vec<usize>, Error> {
+    let mut file = fs::File::open(in_file)?;
+    let mut boundaries = vec![0usize];
+    let mut nominal = segment_size.max(1);
+    while nominal < file_size {
+        match find_boundary(&mut file, nominal, file_size)? {
+            Some(boundary) => {
+                boundaries.push(boundary);
+                nominal = boundary + segment_size.max(1);
+            }
+            None => nominal += segment_size.max(1),
+        }
+    }
+    Ok(boundaries)
+}
+
+/// find the first storage header after `from` that starts a chain of
+/// messages that line up
+fn find_boundary(
+    file: &mut fs::File,
+    from: usize,
+    file_size: usize,
+) -> Result<Option<usize>, Error> {
+    let window_len = (BOUNDARY_SEARCH_WINDOW + DLT_PATTERN.len() - 1).min(file_size - from);
+    let mut window = vec![0u8; window_len];
+    file.seek(SeekFrom::Start(from as u64))?;
+    file.read_exact(&mut window)?;
+    let mut offset = 0usize;
+    while let Some(i) = find_storage_header_pattern(&window[offset..]) {
+        let candidate = from + offset + i;
+        if is_verified_boundary(file, candidate, file_size)? {
+            return Ok(Some(candidate));
+        }
+        offset += i + 1;
+    }
+    Ok(None)
+}
+
+fn is_verified_boundary(
+    file: &mut fs::File,
+    candidate: usize,
+    file_size: usize,
+) -> Result<bool, Error> {
+    let mut position = candidate;
+    // storage header and the start of the standard header up to the length
+    let mut header = [0u8; STORAGE_HEADER_LENGTH + MESSAGE_LENGTH_PEEK_LENGTH];
+    for _ in 0..BOUNDARY_VERIFY_COUNT {
+        if position == file_size {
+            return Ok(true);
+        }
+        if position + header.len() > file_size {
+            return Ok(false);
+        }
+        file.seek(SeekFrom::Start(position as u64))?;
+        file.read_exact(&mut header)?;
+        if &header[..DLT_PATTERN.len()] != DLT_PATTERN {
+            return Ok(false);
+        }
+        match peek_message_length(&header[STORAGE_HEADER_LENGTH..]) {
+            Some(overall_length) => position += STORAGE_HEADER_LENGTH + overall_length as usize,
+            None => return Ok(false),
+        }
+    }
+    Ok(position <= file_size)
+}
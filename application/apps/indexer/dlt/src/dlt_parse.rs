@@ -10,26 +10,40 @@
 // is strictly forbidden unless prior written permission is obtained
 // from E.S.R.Labs.
 use crate::dlt::*;
+use crate::dlt_counter::{LostMessages, MessageCounter, MessageCounterCheck, Sender};
 use crate::dlt_net::*;
+use crate::dlt_offsets::{self, OffsetIndexWriter, OffsetRecord};
+use crate::dlt_reader::DltFileReader;
+use crate::dlt_recovery::{next_plausible_storage_header, report_skipped, Resync};
+use crate::dlt_time::{time_model_for_file, AbsoluteTimes, TimedMessage};
 use crate::filtering;
 use crossbeam_channel as cc;
 use indexer_base::chunks::{ChunkFactory, ChunkResults};
 use indexer_base::config::*;
 use indexer_base::error_reporter::*;
+use indexer_base::input::{input_size, is_random_access, open_input, SourcePosition};
 use indexer_base::progress::*;
 use indexer_base::utils;
 use serde::Serialize;
 
-use buf_redux::policy::MinBuffered;
-use buf_redux::BufReader as ReduxReader;
 use byteorder::{BigEndian, LittleEndian};
 use failure::{err_msg, Error};
 use nom::bytes::streaming::{tag, take, take_while_m_n};
-use nom::{combinator::map, multi::count, number::streaming, sequence::tuple, IResult};
+use nom::{
+    combinator::{cond, map},
+    multi::{count, length_data},
+    number::streaming,
+    sequence::tuple,
+    IResult,
+};
 use rustc_hash::FxHashMap;
+use std::collections::VecDeque;
 use std::fs;
-use std::io::{BufRead, BufWriter, Read, Write};
+use futures::stream::StreamExt;
+use std::io::{BufWriter, Write};
+use std::pin::Pin;
 use std::rc::Rc;
+use std::task::{Context, Poll};
 
 use crate::fibex::FibexMetadata;
 use std::str;
@@ -41,35 +55,40 @@ const DLT_PATTERN: &[u8] = &[0x44, 0x4C, 0x54, 0x01];
 pub(crate) fn parse_ecu_id(input: &[u8]) -> IResult<&[u8], &str> {
     dlt_zero_terminated_string(input, 4)
 }
+/// position of the next `DLT\x01` pattern that starts a storage header
+pub fn find_storage_header_pattern(input: &[u8]) -> Option<usize> {
+    let mut offset = 0usize;
+    while let Some(i) = memchr::memchr(DLT_PATTERN[0], &input[offset..]) {
+        let position = offset + i;
+        if input[position..].starts_with(DLT_PATTERN) {
+            return Some(position);
+        }
+        offset = position + 1;
+    }
+    None
+}
 fn skip_to_next_storage_header<'a, T>(
     input: &'a [u8],
     index: Option<usize>,
     update_channel: Option<&cc::Sender<IndexingResults<T>>>,
 ) -> Option<&'a [u8]> {
-    let mut found = false;
-    let mut to_drop = 0usize;
-    for v in input.windows(4) {
-        if v == DLT_PATTERN {
-            found = true;
-            break;
-        }
-        to_drop += 1;
-    }
-
-    if !found {
-        debug!(
-            "did not find another storage header (input left {})",
-            input.len()
-        );
-        if let Some(tx) = update_channel {
-            let _ = tx.send(Err(Notification {
-                severity: Severity::ERROR,
-                content: "did not find another storage header".to_string(),
-                line: index,
-            }));
+    let to_drop = match find_storage_header_pattern(input) {
+        Some(position) => position,
+        None => {
+            debug!(
+                "did not find another storage header (input left {})",
+                input.len()
+            );
+            if let Some(tx) = update_channel {
+                let _ = tx.send(Err(Notification {
+                    severity: Severity::ERROR,
+                    content: "did not find another storage header".to_string(),
+                    line: index,
+                }));
+            }
+            return None;
         }
-        return None;
-    }
+    };
     if to_drop > 0 {
         if let Some(tx) = update_channel {
             let _ = tx.send(Err(Notification {
@@ -82,17 +101,28 @@ fn skip_to_next_storage_header<'a, T>(
     }
     Some(&input[to_drop..])
 }
-fn dlt_skip_storage_header<'a, T>(
+/// the time of the storage header, the ecu id is skipped
+fn dlt_storage_header_time<'a, T>(
     input: &'a [u8],
     index: Option<usize>,
     update_channel: Option<&cc::Sender<IndexingResults<T>>>,
-) -> IResult<&'a [u8], ()> {
-    // println!("dlt_skip_storage_header");
+) -> IResult<&'a [u8], DltTimeStamp> {
     match skip_to_next_storage_header(input, index, update_channel) {
         Some(rest) => {
-            let (i, (_, _, _)): (&'a [u8], _) =
-                tuple((tag("DLT"), tag(&[0x01]), take(12usize)))(rest)?;
-            Ok((i, ()))
+            let (i, (_, _, seconds, microseconds, _)) = tuple((
+                tag("DLT"),
+                tag(&[0x01]),
+                streaming::le_u32,
+                streaming::le_u32,
+                take(4usize),
+            ))(rest)?;
+            Ok((
+                i,
+                DltTimeStamp {
+                    seconds,
+                    microseconds,
+                },
+            ))
         }
         None => Err(nom::Err::Error((&[], nom::error::ErrorKind::Verify))),
     }
@@ -156,9 +186,16 @@ fn maybe_parse_u32(a: bool) -> impl Fn(&[u8]) -> IResult<&[u8], Option<u32>> {
     }
 }
 
-/// The standard header is part of every DLT message
-/// all big endian format [PRS_Dlt_00091]
-pub(crate) fn dlt_standard_header(input: &[u8]) -> IResult<&[u8], StandardHeader> {
+/// fields of the standard header, the ecu id is borrowed from the input
+pub(crate) struct StandardHeaderRef<'a> {
+    pub(crate) header_type_byte: u8,
+    pub(crate) message_counter: u8,
+    pub(crate) overall_length: u16,
+    pub(crate) ecu_id: Option<&'a str>,
+    pub(crate) session_id: Option<u32>,
+    pub(crate) timestamp: Option<u32>,
+}
+pub(crate) fn dlt_standard_header_ref(input: &[u8]) -> IResult<&[u8], StandardHeaderRef<'_>> {
     let (rest, header_type_byte) = streaming::be_u8(input)?;
     let has_ecu_id = (header_type_byte & WITH_ECU_ID_FLAG) != 0;
     let has_session_id = (header_type_byte & WITH_SESSION_ID_FLAG) != 0;
@@ -170,8 +207,38 @@ pub(crate) fn dlt_standard_header(input: &[u8]) -> IResult<&[u8], StandardHeader
         maybe_parse_u32(has_session_id),
         maybe_parse_u32(has_timestamp),
     ))(rest)?;
+    Ok((
+        i,
+        StandardHeaderRef {
+            header_type_byte,
+            message_counter,
+            overall_length,
+            ecu_id,
+            session_id,
+            timestamp,
+        },
+    ))
+}
+/// The standard header is part of every DLT message
+/// all big endian format [PRS_Dlt_00091]
+/// for version 2 messages this includes the extension header
+pub(crate) fn dlt_standard_header(input: &[u8]) -> IResult<&[u8], StandardHeader> {
+    if header_version(input) == Some(2) {
+        return map(dlt_headers_v2_ref, HeadersV2Ref::into_standard_header)(input);
+    }
+    let (i, header) = dlt_standard_header_ref(input)?;
+    let StandardHeaderRef {
+        header_type_byte,
+        message_counter,
+        overall_length,
+        ecu_id,
+        session_id,
+        timestamp,
+    } = header;
     let has_extended_header = (header_type_byte & WITH_EXTENDED_HEADER_FLAG) != 0;
-    let payload_length = overall_length - calculate_all_headers_length(header_type_byte);
+    // a corrupted length must not make us panic
+    let payload_length =
+        overall_length.saturating_sub(calculate_all_headers_length(header_type_byte));
 
     Ok((
         i,
@@ -192,11 +259,242 @@ pub(crate) fn dlt_standard_header(input: &[u8]) -> IResult<&[u8], StandardHeader
     ))
 }
 
-pub(crate) fn dlt_extended_header<'a, T>(
+/// version of the message that starts with this standard header
+pub(crate) fn header_version(input: &[u8]) -> Option<u8> {
+    input.first().map(|header_type| header_type >> 5 & 0b111)
+}
+
+/// fields of the base and extension header of a version 2 message,
+/// the strings are borrowed from the input
+pub(crate) struct HeadersV2Ref<'a> {
+    pub(crate) content_info: ContentInfo,
+    pub(crate) message_counter: u8,
+    pub(crate) overall_length: u16,
+    /// length of base and extension header
+    pub(crate) headers_length: u16,
+    pub(crate) message_type: Option<MessageType>,
+    pub(crate) argument_count: u8,
+    pub(crate) timestamp: Option<DltTimeStampV2>,
+    pub(crate) message_id: Option<u32>,
+    pub(crate) ecu_id: Option<&'a str>,
+    pub(crate) app_id_context_id: Option<(&'a str, &'a str)>,
+    pub(crate) session_id: Option<u32>,
+    pub(crate) source_location: Option<(&'a str, u32)>,
+    pub(crate) tags: Option<Vec<&'a str>>,
+    pub(crate) privacy_level: Option<u8>,
+}
+impl<'a> HeadersV2Ref<'a> {
+    pub(crate) fn into_standard_header(self) -> StandardHeader {
+        StandardHeader {
+            version: 2,
+            endianness: Endianness::Big,
+            has_extended_header: self.app_id_context_id.is_some(),
+            message_counter: self.message_counter,
+            ecu_id: self.ecu_id.map(|id| id.to_string()),
+            session_id: self.session_id,
+            timestamp: None,
+            // a corrupted length must not make us panic
+            payload_length: self.overall_length.saturating_sub(self.headers_length),
+            v2: Some(Box::new(HeaderV2 {
+                content_info: self.content_info,
+                message_type: self.message_type,
+                timestamp: self.timestamp,
+                app_id_context_id: self
+                    .app_id_context_id
+                    .map(|(app_id, context_id)| (app_id.to_string(), context_id.to_string())),
+                source_location: self.source_location.map(|(file_name, line_number)| {
+                    SourceLocation {
+                        file_name: file_name.to_string(),
+                        line_number,
+                    }
+                }),
+                tags: self
+                    .tags
+                    .map(|tags| tags.iter().map(|tag| tag.to_string()).collect()),
+                privacy_level: self.privacy_level,
+            })),
+        }
+    }
+}
+/// strings of version 2 headers start with their length, some ECUs include
+/// the terminating zero
+fn dlt_length_prefixed_string(input: &[u8]) -> IResult<&[u8], &str> {
+    let (rest, raw) = length_data(streaming::be_u8)(input)?;
+    let end = raw.iter().position(|&c| c == 0).unwrap_or(raw.len());
+    match str::from_utf8(&raw[..end]) {
+        Ok(s) => Ok((rest, s)),
+        Err(_) => Err(nom::Err::Error((input, nom::error::ErrorKind::Verify))),
+    }
+}
+/// number of tags followed by the tags
+fn dlt_tags(input: &[u8]) -> IResult<&[u8], Vec<&str>> {
+    let (rest, tag_count) = streaming::be_u8(input)?;
+    count(dlt_length_prefixed_string, tag_count as usize)(rest)
+}
+/// nanoseconds followed by 40 bits of seconds
+fn dlt_timestamp_v2(input: &[u8]) -> IResult<&[u8], DltTimeStampV2> {
+    map(
+        tuple((streaming::be_u32, streaming::be_u8, streaming::be_u32)),
+        |(nanoseconds, seconds_high, seconds_low)| DltTimeStampV2 {
+            seconds: u64::from(seconds_high) << 32 | u64::from(seconds_low),
+            nanoseconds,
+        },
+    )(input)
+}
+/// base and extension header of a version 2 message (AUTOSAR R19-11)
+/// all big endian format, segmented messages are not supported
+pub(crate) fn dlt_headers_v2_ref(input: &[u8]) -> IResult<&[u8], HeadersV2Ref<'_>> {
+    let (rest, (header_type, extension_type, _reserved, message_counter, overall_length)) =
+        tuple((
+            streaming::be_u8,
+            streaming::be_u8,
+            streaming::be_u16,
+            streaming::be_u8,
+            streaming::be_u16,
+        ))(input)?;
+    let content_info = match ContentInfo::from_value(header_type & V2_CONTENT_INFO_MASK) {
+        Some(content_info) => content_info,
+        None => return Err(nom::Err::Error((input, nom::error::ErrorKind::Verify))),
+    };
+    if (extension_type & V2_WITH_SEGMENTATION_FLAG) != 0 {
+        return Err(nom::Err::Error((input, nom::error::ErrorKind::Verify)));
+    }
+    let (rest, message_info) = cond(
+        content_info != ContentInfo::NonVerbose,
+        tuple((streaming::be_u8, streaming::be_u8)),
+    )(rest)?;
+    let (rest, timestamp) = cond(content_info != ContentInfo::Control, dlt_timestamp_v2)(rest)?;
+    let (rest, message_id) =
+        cond(content_info == ContentInfo::NonVerbose, streaming::be_u32)(rest)?;
+    let (rest, ecu_id) = cond(
+        (header_type & V2_WITH_ECU_ID_FLAG) != 0,
+        dlt_length_prefixed_string,
+    )(rest)?;
+    let (rest, app_id_context_id) = cond(
+        (header_type & V2_WITH_APP_AND_CONTEXT_ID_FLAG) != 0,
+        tuple((dlt_length_prefixed_string, dlt_length_prefixed_string)),
+    )(rest)?;
+    let (rest, session_id) = cond(
+        (header_type & V2_WITH_SESSION_ID_FLAG) != 0,
+        streaming::be_u32,
+    )(rest)?;
+    let (rest, source_location) = cond(
+        (extension_type & V2_WITH_SOURCE_LOCATION_FLAG) != 0,
+        tuple((dlt_length_prefixed_string, streaming::be_u32)),
+    )(rest)?;
+    let (rest, tags) = cond((extension_type & V2_WITH_TAGS_FLAG) != 0, dlt_tags)(rest)?;
+    let (rest, privacy_level) = cond(
+        (extension_type & V2_WITH_PRIVACY_LEVEL_FLAG) != 0,
+        streaming::be_u8,
+    )(rest)?;
+    let message_type = match message_info {
+        Some((message_info, _)) => Some(
+            MessageType::try_from(message_info)
+                .map_err(|_| nom::Err::Error((input, nom::error::ErrorKind::Verify)))?,
+        ),
+        None => None,
+    };
+    Ok((
+        rest,
+        HeadersV2Ref {
+            content_info,
+            message_counter,
+            overall_length,
+            headers_length: (input.len() - rest.len()) as u16,
+            message_type,
+            argument_count: message_info.map(|(_, count)| count).unwrap_or(0),
+            timestamp,
+            message_id,
+            ecu_id,
+            app_id_context_id,
+            session_id,
+            source_location,
+            tags,
+            privacy_level,
+        },
+    ))
+}
+
+/// fields of the extended header, application and context id are borrowed
+/// from the input
+pub(crate) struct ExtendedHeaderRef<'a> {
+    pub(crate) verbose: bool,
+    pub(crate) argument_count: u8,
+    pub(crate) message_type: MessageType,
+    pub(crate) application_id: &'a str,
+    pub(crate) context_id: &'a str,
+}
+impl<'a> ExtendedHeaderRef<'a> {
+    pub(crate) fn into_extended_header(self) -> ExtendedHeader {
+        ExtendedHeader {
+            verbose: self.verbose,
+            argument_count: self.argument_count,
+            message_type: self.message_type,
+            application_id: self.application_id.to_string(),
+            context_id: self.context_id.to_string(),
+        }
+    }
+}
+pub fn dlt_extended_header<'a, T>(
     input: &'a [u8],
     index: Option<usize>,
     update_channel: Option<&cc::Sender<IndexingResults<T>>>,
 ) -> IResult<&'a [u8], ExtendedHeader> {
+    let (i, header) = dlt_extended_header_ref(input, index, update_channel)?;
+    Ok((i, header.into_extended_header()))
+}
+/// unknown or invalid message types are reported as warnings
+fn notify_unusual_message_type<T>(
+    message_type: &MessageType,
+    index: Option<usize>,
+    update_channel: Option<&cc::Sender<IndexingResults<T>>>,
+) {
+    if let Some(tx) = update_channel {
+        match message_type {
+            MessageType::Unknown(n) => {
+                let _ = tx.send(Err(Notification {
+                    severity: Severity::WARNING,
+                    content: format!("unknown message type {:?}", n),
+                    line: index,
+                }));
+            }
+            MessageType::Log(LogLevel::Invalid(n)) => {
+                let _ = tx.send(Err(Notification {
+                    severity: Severity::WARNING,
+                    content: format!("unknown log level {}", n),
+                    line: index,
+                }));
+            }
+            MessageType::Control(ControlType::Unknown(n)) => {
+                let _ = tx.send(Err(Notification {
+                    severity: Severity::WARNING,
+                    content: format!("unknown control type {}", n),
+                    line: index,
+                }));
+            }
+            MessageType::ApplicationTrace(ApplicationTraceType::Invalid(n)) => {
+                let _ = tx.send(Err(Notification {
+                    severity: Severity::WARNING,
+                    content: format!("invalid application-trace type {}", n),
+                    line: index,
+                }));
+            }
+            MessageType::NetworkTrace(NetworkTraceType::Invalid) => {
+                let _ = tx.send(Err(Notification {
+                    severity: Severity::WARNING,
+                    content: "invalid application-trace type 0".to_string(),
+                    line: index,
+                }));
+            }
+            _ => (),
+        };
+    }
+}
+pub(crate) fn dlt_extended_header_ref<'a, T>(
+    input: &'a [u8],
+    index: Option<usize>,
+    update_channel: Option<&cc::Sender<IndexingResults<T>>>,
+) -> IResult<&'a [u8], ExtendedHeaderRef<'a>> {
     let (i, (message_info, argument_count, app_id, context_id)) = tuple((
         streaming::be_u8,
         streaming::be_u8,
@@ -206,54 +504,15 @@ pub(crate) fn dlt_extended_header<'a, T>(
     let verbose = (message_info & VERBOSE_FLAG) != 0;
     match MessageType::try_from(message_info) {
         Ok(message_type) => {
-            if let Some(tx) = update_channel {
-                match message_type {
-                    MessageType::Unknown(n) => {
-                        let _ = tx.send(Err(Notification {
-                            severity: Severity::WARNING,
-                            content: format!("unknown message type {:?}", n),
-                            line: index,
-                        }));
-                    }
-                    MessageType::Log(LogLevel::Invalid(n)) => {
-                        let _ = tx.send(Err(Notification {
-                            severity: Severity::WARNING,
-                            content: format!("unknown log level {}", n),
-                            line: index,
-                        }));
-                    }
-                    MessageType::Control(ControlType::Unknown(n)) => {
-                        let _ = tx.send(Err(Notification {
-                            severity: Severity::WARNING,
-                            content: format!("unknown control type {}", n),
-                            line: index,
-                        }));
-                    }
-                    MessageType::ApplicationTrace(ApplicationTraceType::Invalid(n)) => {
-                        let _ = tx.send(Err(Notification {
-                            severity: Severity::WARNING,
-                            content: format!("invalid application-trace type {}", n),
-                            line: index,
-                        }));
-                    }
-                    MessageType::NetworkTrace(NetworkTraceType::Invalid) => {
-                        let _ = tx.send(Err(Notification {
-                            severity: Severity::WARNING,
-                            content: "invalid application-trace type 0".to_string(),
-                            line: index,
-                        }));
-                    }
-                    _ => (),
-                };
-            };
+            notify_unusual_message_type(&message_type, index, update_channel);
             Ok((
                 i,
-                ExtendedHeader {
+                ExtendedHeaderRef {
                     verbose,
                     argument_count,
                     message_type,
-                    application_id: app_id.to_string(),
-                    context_id: context_id.to_string(),
+                    application_id: app_id,
+                    context_id,
                 },
             ))
         }
@@ -437,6 +696,7 @@ pub(crate) fn dlt_sint<T: NomByteOrder>(width: TypeLength) -> fn(&[u8]) -> IResu
 }
 pub(crate) fn dlt_fint<T: NomByteOrder>(width: FloatWidth) -> fn(&[u8]) -> IResult<&[u8], Value> {
     match width {
+        FloatWidth::Width16 => |i| map(T::parse_u16, |bits| Value::F32(f16_to_f32(bits)))(i),
         FloatWidth::Width32 => |i| map(T::parse_f32, Value::F32)(i),
         FloatWidth::Width64 => |i| map(T::parse_f64, Value::F64)(i),
     }
@@ -453,36 +713,49 @@ pub(crate) fn dlt_type_info<T: NomByteOrder>(input: &[u8]) -> IResult<&[u8], Typ
 }
 pub(crate) fn dlt_fixed_point<T: NomByteOrder>(
     input: &[u8],
-    width: FloatWidth,
+    width: TypeLength,
 ) -> IResult<&[u8], FixedPoint> {
     // println!("width {:?} dlt_fixedpoint,input: \t{:02X?}", width, input);
     let (i, quantization) = T::parse_f32(input)?;
     // println!("parsed quantization: {:?}", quantization);
-    if width == FloatWidth::Width32 {
-        let (rest, offset) = T::parse_i32(i)?;
-        // println!("parsed offset: {:?}", offset);
-        Ok((
-            rest,
-            FixedPoint {
-                quantization,
-                offset: FixedPointValue::I32(offset),
-            },
-        ))
-    } else if width == FloatWidth::Width64 {
-        let (rest, offset) = T::parse_i64(i)?;
-        Ok((
-            rest,
-            FixedPoint {
-                quantization,
-                offset: FixedPointValue::I64(offset),
-            },
-        ))
-    } else {
-        report_error("error in dlt_fixed_point");
-        Err(nom::Err::Error((&[], nom::error::ErrorKind::Verify)))
+    let (rest, offset) = match width {
+        TypeLength::BitLength64 => map(T::parse_i64, FixedPointValue::I64)(i)?,
+        TypeLength::BitLength128 => map(T::parse_i128, FixedPointValue::I128)(i)?,
+        _ => map(T::parse_i32, FixedPointValue::I32)(i)?,
+    };
+    Ok((
+        rest,
+        FixedPoint {
+            quantization,
+            offset,
+        },
+    ))
+}
+/// structs can contain structs, limit the nesting so corrupted data
+/// cannot exhaust the stack
+const MAX_STRUCT_NESTING: usize = 8;
+/// parser for the values of an array with elements of type `kind`
+#[allow(clippy::type_complexity)]
+fn dlt_array_element<T: NomByteOrder>(
+    kind: &TypeInfoKind,
+) -> Option<fn(&[u8]) -> IResult<&[u8], Value>> {
+    match *kind {
+        TypeInfoKind::Bool => Some(|i| map(streaming::be_u8, |v| Value::Bool(v != 0))(i)),
+        TypeInfoKind::Signed(width) => Some(dlt_sint::<T>(width)),
+        TypeInfoKind::SignedFixedPoint(width) => Some(dlt_sint::<T>(width)),
+        TypeInfoKind::Unsigned(width) => Some(dlt_uint::<T>(width)),
+        TypeInfoKind::UnsignedFixedPoint(width) => Some(dlt_uint::<T>(width)),
+        TypeInfoKind::Float(width) => Some(dlt_fint::<T>(width)),
+        _ => None,
     }
 }
 pub(crate) fn dlt_argument<T: NomByteOrder>(input: &[u8]) -> IResult<&[u8], Argument> {
+    dlt_nested_argument::<T>(input, 0)
+}
+fn dlt_nested_argument<T: NomByteOrder>(
+    input: &[u8],
+    nesting_level: usize,
+) -> IResult<&[u8], Argument> {
     let (i, type_info) = dlt_type_info::<T>(input)?;
     dbg_parsed("type info", input, i);
     // println!("type info: {:?}", type_info);
@@ -511,8 +784,7 @@ pub(crate) fn dlt_argument<T: NomByteOrder>(input: &[u8]) -> IResult<&[u8], Argu
             let (r, fp) = dlt_fixed_point::<T>(before_val, width)?;
             let (after_fixed_point, fixed_point) = (r, Some(fp));
             dbg_parsed("fixed_point", before_val, after_fixed_point);
-            let (rest, value) =
-                dlt_sint::<T>(float_width_to_type_length(width))(after_fixed_point)?;
+            let (rest, value) = dlt_sint::<T>(width)(after_fixed_point)?;
             Ok((
                 rest,
                 Argument {
@@ -549,8 +821,7 @@ pub(crate) fn dlt_argument<T: NomByteOrder>(input: &[u8]) -> IResult<&[u8], Argu
             //     "UnsignedFixedPoint: calling dlt_uint for {:02X?}",
             //     before_val
             // );
-            let (rest, value) =
-                dlt_uint::<T>(float_width_to_type_length(width))(after_fixed_point)?;
+            let (rest, value) = dlt_uint::<T>(width)(after_fixed_point)?;
             Ok((
                 rest,
                 Argument {
@@ -640,6 +911,81 @@ pub(crate) fn dlt_argument<T: NomByteOrder>(input: &[u8]) -> IResult<&[u8], Argu
                 },
             ))
         }
+        TypeInfoKind::Array(ref element_kind) => {
+            let element_parser = match dlt_array_element::<T>(element_kind) {
+                Some(parser) => parser,
+                None => {
+                    report_error(format!("unsupported array element type {:?}", element_kind));
+                    return Err(nom::Err::Error((&[], nom::error::ErrorKind::Verify)));
+                }
+            };
+            let fixed_point_width = match **element_kind {
+                TypeInfoKind::SignedFixedPoint(width) => Some(width),
+                TypeInfoKind::UnsignedFixedPoint(width) => Some(width),
+                _ => None,
+            };
+            let (i2, dimension_cnt) = T::parse_u16(i)?;
+            let (i3, dimensions) = count(T::parse_u16, dimension_cnt as usize)(i2)?;
+            let element_cnt: Option<usize> = if dimensions.is_empty() {
+                Some(0)
+            } else {
+                dimensions
+                    .iter()
+                    .try_fold(1usize, |cnt, d| cnt.checked_mul(*d as usize))
+            };
+            // a dlt message cannot be bigger then 64k
+            let element_cnt = match element_cnt {
+                Some(cnt) if cnt <= u16::MAX as usize => cnt,
+                _ => {
+                    report_error(format!("invalid array dimensions {:?}", dimensions));
+                    return Err(nom::Err::Error((&[], nom::error::ErrorKind::Verify)));
+                }
+            };
+            let (i4, (name, unit)) = dlt_variable_name_and_unit::<T>(&type_info)(i3)?;
+            let (i5, fixed_point) = match fixed_point_width {
+                Some(width) => map(|i| dlt_fixed_point::<T>(i, width), Some)(i4)?,
+                None => (i4, None),
+            };
+            let (rest, values) = count(element_parser, element_cnt)(i5)?;
+            Ok((
+                rest,
+                Argument {
+                    name,
+                    unit,
+                    fixed_point,
+                    value: Value::Array { dimensions, values },
+                    type_info,
+                },
+            ))
+        }
+        TypeInfoKind::Struct => {
+            if nesting_level >= MAX_STRUCT_NESTING {
+                report_error("dlt struct nested too deep");
+                return Err(nom::Err::Error((&[], nom::error::ErrorKind::TooLarge)));
+            }
+            let (i2, entry_cnt) = T::parse_u16(i)?;
+            let (mut rest, name) = if type_info.has_variable_info {
+                map(dlt_variable_name::<T>, Some)(i2)?
+            } else {
+                (i2, None)
+            };
+            let mut entries = Vec::with_capacity(std::cmp::min(entry_cnt as usize, rest.len()));
+            for _ in 0..entry_cnt {
+                let (r, entry) = dlt_nested_argument::<T>(rest, nesting_level + 1)?;
+                entries.push(entry);
+                rest = r;
+            }
+            Ok((
+                rest,
+                Argument {
+                    name,
+                    unit: None,
+                    fixed_point: None,
+                    value: Value::Struct(entries),
+                    type_info,
+                },
+            ))
+        }
     }
 }
 
@@ -783,6 +1129,16 @@ pub fn dlt_message<'a>(
         (input, None)
     };
     dbg_parsed("storage header", &input, &after_storage_header);
+    if header_version(after_storage_header) == Some(2) {
+        return dlt_message_v2(
+            after_storage_header,
+            storage_header,
+            filter_config_opt,
+            index,
+            update_channel,
+            fibex_metadata,
+        );
+    }
     // trace!("dlt_msg 2");
     let (after_storage_and_normal_header, header) = dlt_standard_header(after_storage_header)?;
     // trace!(
@@ -815,7 +1171,7 @@ pub fn dlt_message<'a>(
     let (after_headers, extended_header) = if header.has_extended_header {
         // trace!("try to parse extended header");
         let (rest, ext_header) =
-            dlt_extended_header(after_storage_and_normal_header, Some(index), update_channel)?;
+            dlt_extended_header_ref(after_storage_and_normal_header, Some(index), update_channel)?;
         verbose = ext_header.verbose;
         arg_count = ext_header.argument_count;
         // trace!(
@@ -847,39 +1203,16 @@ pub fn dlt_message<'a>(
     // );
     // trace!("dlt_msg 5");
     if let Some(filter_config) = filter_config_opt {
-        // trace!("dlt_msg 6");
-        if let Some(h) = &extended_header {
-            // trace!("dlt_msg 7");
-            if let Some(min_filter_level) = filter_config.min_log_level {
-                if h.skip_with_level(min_filter_level) {
-                    // trace!("no need to parse further, skip payload (skipped level)");
-                    let (after_message, _) = take(payload_length)(after_headers)?;
-                    return Ok((after_message, None));
-                }
-            }
-            if let Some(only_these_components) = &filter_config.app_ids {
-                if !only_these_components.contains(&h.application_id) {
-                    // trace!("no need to parse further, skip payload (skipped app id)");
-                    let (after_message, _) = take(payload_length)(after_headers)?;
-                    return Ok((after_message, None));
-                }
-            }
-            if let Some(only_these_context_ids) = &filter_config.context_ids {
-                if !only_these_context_ids.contains(&h.context_id) {
-                    // trace!("no need to parse further, skip payload (skipped context id)");
-                    let (after_message, _) = take(payload_length)(after_headers)?;
-                    return Ok((after_message, None));
-                }
-            }
-            if let Some(only_these_ecu_ids) = &filter_config.ecu_ids {
-                if let Some(ecu_id) = &header.ecu_id {
-                    if !only_these_ecu_ids.contains(ecu_id) {
-                        // trace!("no need to parse further, skip payload (skipped ecu id)");
-                        let (after_message, _) = take(payload_length)(after_headers)?;
-                        return Ok((after_message, None));
-                    }
-                }
-            }
+        if filter_config.excludes(&filtering::FilteredHeaders {
+            storage_header: storage_header.as_ref(),
+            ecu_id: header.ecu_id.as_deref(),
+            extended: extended_header
+                .as_ref()
+                .map(|h| (&h.message_type, h.application_id, h.context_id, h.verbose)),
+        }) {
+            // trace!("no need to parse further, skip payload");
+            let (after_message, _) = take(payload_length)(after_headers)?;
+            return Ok((after_message, None));
         }
     }
     // trace!("about to parse payload, left: {}", after_headers.len());
@@ -913,6 +1246,84 @@ pub fn dlt_message<'a>(
     };
     dbg_parsed("payload", &after_headers, &i);
     // trace!("after payload: {} bytes left", i.len());
+    Ok((
+        i,
+        Some(Message {
+            storage_header,
+            header,
+            extended_header: extended_header.map(ExtendedHeaderRef::into_extended_header),
+            payload,
+            fibex_metadata,
+        }),
+    ))
+}
+/// a version 2 message: [BASE-HEADER][EXTENSION-HEADER][PAYLOAD]
+/// the payload is big endian, the message id of non verbose messages is
+/// part of the base header
+fn dlt_message_v2<'a>(
+    input: &'a [u8],
+    storage_header: Option<StorageHeader>,
+    filter_config_opt: Option<&filtering::ProcessedDltFilterConfig>,
+    index: usize,
+    update_channel: Option<&cc::Sender<ChunkResults>>,
+    fibex_metadata: Option<Rc<FibexMetadata>>,
+) -> IResult<&'a [u8], Option<Message>> {
+    let (after_headers, headers) = dlt_headers_v2_ref(input)?;
+    dbg_parsed("version 2 headers", input, after_headers);
+    let payload_length = match validated_length(
+        headers.headers_length,
+        headers.overall_length,
+        Some(index),
+        update_channel,
+    ) {
+        Some(length) => length,
+        None => {
+            return Ok((after_headers, None));
+        }
+    };
+    if let Some(message_type) = &headers.message_type {
+        notify_unusual_message_type(message_type, Some(index), update_channel);
+    }
+    if let Some(filter_config) = filter_config_opt {
+        let message_type = headers
+            .message_type
+            .clone()
+            .unwrap_or(MessageType::Unknown((0, 0)));
+        let verbose = headers.content_info == ContentInfo::Verbose;
+        if filter_config.excludes(&filtering::FilteredHeaders {
+            storage_header: storage_header.as_ref(),
+            ecu_id: headers.ecu_id,
+            extended: headers
+                .app_id_context_id
+                .map(|(app_id, context_id)| (&message_type, app_id, context_id, verbose)),
+        }) {
+            let (after_message, _) = take(payload_length)(after_headers)?;
+            return Ok((after_message, None));
+        }
+    }
+    let argument_count = headers.argument_count;
+    let message_id = headers.message_id;
+    let header = headers.into_standard_header();
+    let (i, payload) = match message_id {
+        Some(message_id) => map(take(payload_length), |data: &[u8]| Payload2 {
+            payload_content: PayloadContent::NonVerbose(message_id, data.to_vec()),
+        })(after_headers)?,
+        None => {
+            let content_info = header.v2.as_ref().map(|v2| v2.content_info);
+            dlt_payload::<BigEndian>(
+                after_headers,
+                content_info == Some(ContentInfo::Verbose),
+                payload_length,
+                argument_count,
+                content_info == Some(ContentInfo::Control),
+            )?
+        }
+    };
+    dbg_parsed("payload", after_headers, i);
+    let extended_header = header
+        .v2
+        .as_ref()
+        .and_then(|v2| v2.extended_header(argument_count));
     Ok((
         i,
         Some(Message {
@@ -929,8 +1340,19 @@ fn validated_payload_length<T>(
     index: Option<usize>,
     update_channel: Option<&cc::Sender<IndexingResults<T>>>,
 ) -> Option<u16> {
-    let message_length = header.overall_length();
-    let headers_length = calculate_all_headers_length(header.header_type_byte());
+    validated_length(
+        calculate_all_headers_length(header.header_type_byte()),
+        header.overall_length(),
+        index,
+        update_channel,
+    )
+}
+fn validated_length<T>(
+    headers_length: u16,
+    message_length: u16,
+    index: Option<usize>,
+    update_channel: Option<&cc::Sender<IndexingResults<T>>>,
+) -> Option<u16> {
     if message_length < headers_length {
         if let Some(tx) = update_channel {
             let _ = tx.send(Err(Notification {
@@ -950,12 +1372,26 @@ pub fn dlt_statistic_row_info<'a, T>(
     input: &'a [u8],
     index: Option<usize>,
     update_channel: Option<&cc::Sender<IndexingResults<T>>>,
-) -> IResult<&'a [u8], StatisticRowInfo> {
+) -> IResult<&'a [u8], StatisticRowInfo<'a>> {
     let update_channel_ref = update_channel;
-    let (after_storage_header, _) = dlt_skip_storage_header(input, index, update_channel_ref)?;
-    let (after_storage_and_normal_header, header) = dlt_standard_header(after_storage_header)?;
+    let (after_storage_header, storage_time) =
+        dlt_storage_header_time(input, index, update_channel_ref)?;
+    if header_version(after_storage_header) == Some(2) {
+        return dlt_statistic_row_info_v2(
+            after_storage_header,
+            storage_time,
+            index,
+            update_channel,
+        );
+    }
+    let (after_storage_and_normal_header, header) = dlt_standard_header_ref(after_storage_header)?;
 
-    let payload_length = match validated_payload_length(&header, index, update_channel_ref) {
+    let payload_length = match validated_length(
+        calculate_all_headers_length(header.header_type_byte),
+        header.overall_length,
+        index,
+        update_channel_ref,
+    ) {
         Some(length) => length,
         None => {
             return Ok((
@@ -963,51 +1399,117 @@ pub fn dlt_statistic_row_info<'a, T>(
                 StatisticRowInfo {
                     app_id_context_id: None,
                     ecu_id: header.ecu_id,
+                    storage_time,
+                    session_id: header.session_id,
+                    message_counter: header.message_counter,
                     level: None,
                     verbose: false,
+                    nonverbose_id: None,
                 },
             ));
         }
     };
-    if !header.has_extended_header {
+    if (header.header_type_byte & WITH_EXTENDED_HEADER_FLAG) == 0 {
         // no app id, skip rest
-        let (after_message, _) = take(payload_length)(after_storage_and_normal_header)?;
+        let (after_message, payload) = take(payload_length)(after_storage_and_normal_header)?;
         return Ok((
             after_message,
             StatisticRowInfo {
                 app_id_context_id: None,
                 ecu_id: header.ecu_id,
+                storage_time,
+                session_id: header.session_id,
+                message_counter: header.message_counter,
                 level: None,
                 verbose: false,
+                nonverbose_id: nonverbose_message_id(payload, header.header_type_byte),
             },
         ));
     }
 
     let (after_headers, extended_header) =
-        dlt_extended_header(after_storage_and_normal_header, index, update_channel)?;
+        dlt_extended_header_ref(after_storage_and_normal_header, index, update_channel)?;
     // skip payload
-    let (after_message, _) = take(payload_length)(after_headers)?;
+    let (after_message, payload) = take(payload_length)(after_headers)?;
     let level = match extended_header.message_type {
         MessageType::Log(level) => Some(level),
         _ => None,
     };
+    let nonverbose_id = match extended_header.message_type {
+        MessageType::Control(_) => None,
+        _ if extended_header.verbose => None,
+        _ => nonverbose_message_id(payload, header.header_type_byte),
+    };
     Ok((
         after_message,
         StatisticRowInfo {
             app_id_context_id: Some((extended_header.application_id, extended_header.context_id)),
             ecu_id: header.ecu_id,
+            storage_time,
+            session_id: header.session_id,
+            message_counter: header.message_counter,
             level,
             verbose: extended_header.verbose,
+            nonverbose_id,
+        },
+    ))
+}
+
+fn dlt_statistic_row_info_v2<'a, T>(
+    input: &'a [u8],
+    storage_time: DltTimeStamp,
+    index: Option<usize>,
+    update_channel: Option<&cc::Sender<IndexingResults<T>>>,
+) -> IResult<&'a [u8], StatisticRowInfo<'a>> {
+    let (after_headers, headers) = dlt_headers_v2_ref(input)?;
+    let payload_length = validated_length(
+        headers.headers_length,
+        headers.overall_length,
+        index,
+        update_channel,
+    );
+    // skip payload
+    let (after_message, _) = take(payload_length.unwrap_or(0))(after_headers)?;
+    let level = match headers.message_type {
+        Some(MessageType::Log(level)) => Some(level),
+        _ => None,
+    };
+    Ok((
+        after_message,
+        StatisticRowInfo {
+            app_id_context_id: headers.app_id_context_id,
+            ecu_id: headers.ecu_id,
+            storage_time,
+            session_id: headers.session_id,
+            message_counter: headers.message_counter,
+            level,
+            verbose: headers.content_info == ContentInfo::Verbose,
+            nonverbose_id: headers.message_id,
         },
     ))
 }
 
+/// the id at the start of the payload of a non verbose message
+fn nonverbose_message_id(payload: &[u8], header_type_byte: u8) -> Option<u32> {
+    if payload.len() < 4 {
+        return None;
+    }
+    let bytes = [payload[0], payload[1], payload[2], payload[3]];
+    Some(if (header_type_byte & BIG_ENDIAN_FLAG) != 0 {
+        u32::from_be_bytes(bytes)
+    } else {
+        u32::from_le_bytes(bytes)
+    })
+}
+
 #[derive(Debug, Fail)]
 pub enum DltParseError {
     #[fail(display = "parsing stopped, cannot continue: {}", cause)]
     Unrecoverable { cause: String },
     #[fail(display = "parsing error, try to continue: {}", reason)]
     ParsingHickup { reason: String },
+    #[fail(display = "parsing incomplete, more data needed: {:?}", needed)]
+    IncompleteParse { needed: Option<usize> },
 }
 impl From<std::io::Error> for DltParseError {
     fn from(err: std::io::Error) -> DltParseError {
@@ -1017,24 +1519,104 @@ impl From<std::io::Error> for DltParseError {
     }
 }
 
-pub struct FileMessageProducer {
-    reader: ReduxReader<fs::File, MinBuffered>,
-    filter_config: Option<filtering::ProcessedDltFilterConfig>,
-    index: usize,
-    update_channel: cc::Sender<ChunkResults>,
-    with_storage_header: bool,
-}
+/// a source of dlt messages
+/// returns the number of bytes consumed from the source together with the
+/// parse result, nothing consumed means there is nothing more to produce
+pub trait MessageProducer {
+    fn produce_next_message(
+        &mut self,
+        fibex_metadata: Option<Rc<FibexMetadata>>,
+    ) -> (usize, Result<Option<Message>, DltParseError>);
+
+    /// the position in the compressed source if the messages are decompressed
+    fn source_position(&self) -> Option<SourcePosition> {
+        None
+    }
+
+    /// true if no message of the source is filtered out
+    fn produces_all_messages(&self) -> bool {
+        false
+    }
+}
+
+/// a source of dlt messages that has to wait for them to arrive (e.g. a socket)
+/// every `MessageProducer` can be used as an `AsyncMessageProducer` that is
+/// always ready
+pub trait AsyncMessageProducer {
+    fn poll_next_message(
+        self: Pin<&mut Self>,
+        cx: &mut Context,
+        fibex_metadata: Option<Rc<FibexMetadata>>,
+    ) -> Poll<(usize, Result<Option<Message>, DltParseError>)>;
+}
+impl<T: MessageProducer + Unpin> AsyncMessageProducer for T {
+    fn poll_next_message(
+        self: Pin<&mut Self>,
+        _cx: &mut Context,
+        fibex_metadata: Option<Rc<FibexMetadata>>,
+    ) -> Poll<(usize, Result<Option<Message>, DltParseError>)> {
+        Poll::Ready(self.get_mut().produce_next_message(fibex_metadata))
+    }
+}
+
+pub struct FileMessageProducer {
+    reader: DltFileReader,
+    /// position in the file
+    offset: usize,
+    source_position: Option<SourcePosition>,
+    filter_config: Option<filtering::ProcessedDltFilterConfig>,
+    index: usize,
+    update_channel: cc::Sender<ChunkResults>,
+    with_storage_header: bool,
+}
 
 impl FileMessageProducer {
-    fn new(
+    pub fn new(
         in_file: &std::path::PathBuf,
         filter_config: Option<filtering::ProcessedDltFilterConfig>,
         index: usize,
         update_channel: cc::Sender<ChunkResults>,
         with_storage_header: bool,
     ) -> Result<FileMessageProducer, Error> {
-        let f = match fs::File::open(&in_file) {
-            Ok(file) => file,
+        FileMessageProducer::open(
+            in_file,
+            filter_config,
+            index,
+            update_channel,
+            with_storage_header,
+            false,
+        )
+    }
+
+    /// reads the file memory mapped, which is faster but only safe if the
+    /// file is not truncated or replaced (e.g. by log rotation) while it is read
+    pub fn memory_mapped(
+        in_file: &std::path::PathBuf,
+        filter_config: Option<filtering::ProcessedDltFilterConfig>,
+        index: usize,
+        update_channel: cc::Sender<ChunkResults>,
+        with_storage_header: bool,
+    ) -> Result<FileMessageProducer, Error> {
+        FileMessageProducer::open(
+            in_file,
+            filter_config,
+            index,
+            update_channel,
+            with_storage_header,
+            true,
+        )
+    }
+
+    fn open(
+        in_file: &std::path::PathBuf,
+        filter_config: Option<filtering::ProcessedDltFilterConfig>,
+        index: usize,
+        update_channel: cc::Sender<ChunkResults>,
+        with_storage_header: bool,
+        memory_map: bool,
+    ) -> Result<FileMessageProducer, Error> {
+        let f = match open_input(in_file) {
+            Ok(input) => input,
             Err(e) => {
                 eprint!("could not open {:?}", in_file);
                 let _ = update_channel.try_send(Err(Notification {
@@ -1045,10 +1627,16 @@ impl FileMessageProducer {
                 return Err(err_msg(format!("could not open file ({})", e)));
             }
         };
-        let reader =
-            ReduxReader::with_capacity(10 * 1024 * 1024, f).set_policy(MinBuffered(10 * 1024));
+        let source_position = f.source_position();
+        let reader = if memory_map {
+            DltFileReader::memory_mapped(f)
+        } else {
+            DltFileReader::new(f)
+        };
         Ok(FileMessageProducer {
             reader,
+            offset: 0,
+            source_position,
             filter_config,
             index,
             update_channel,
@@ -1056,96 +1644,234 @@ impl FileMessageProducer {
         })
     }
 }
-impl FileMessageProducer {
+impl MessageProducer for FileMessageProducer {
     fn produce_next_message(
         &mut self,
         fibex_metadata: Option<Rc<FibexMetadata>>,
     ) -> (usize, Result<Option<Message>, DltParseError>) {
-        #[allow(clippy::never_loop)]
+        let mut at_end = self.reader.is_complete();
         let res = loop {
-            match self.reader.fill_buf() {
+            let res = match self.reader.fill_buf() {
                 Ok(content) => {
                     trace!("got content: {} bytes", content.len());
                     if content.is_empty() {
                         return (0, Ok(None));
                     }
-                    let available = content.len();
-
-                    let res: nom::IResult<&[u8], Option<Message>> = dlt_message(
+                    parse_next_message(
                         content,
+                        self.offset,
+                        at_end,
                         self.filter_config.as_ref(),
                         self.index,
-                        Some(&self.update_channel),
-                        fibex_metadata,
+                        &self.update_channel,
+                        fibex_metadata.clone(),
                         self.with_storage_header,
-                    );
-                    match res {
-                        Ok(r) => {
-                            let consumed = available - r.0.len();
-                            trace!("parse ok, consumed: {}", consumed);
-                            break (consumed, Ok(r.1));
-                        }
-                        Err(nom::Err::Incomplete(n)) => {
-                            trace!("parse incomplete");
-                            let needed = match n {
-                                nom::Needed::Size(s) => format!("{}", s),
-                                nom::Needed::Unknown => "unknown".to_string(),
-                            };
-                            break (0, Err(DltParseError::Unrecoverable {
-                            cause: format!(
-                            "read_one_dlt_message: imcomplete parsing error for dlt messages: (bytes left: {}, but needed: {})",
-                            content.len(),
-                            needed
-                        ),
-                        }));
-                        }
-                        Err(nom::Err::Error(_e)) => {
-                            trace!("parse error");
-                            break (
-                                DLT_PATTERN_SIZE,
-                                Err(DltParseError::ParsingHickup {
-                                    reason: format!(
-                                    "read_one_dlt_message: parsing error for dlt messages: {:?}",
-                                    _e
-                                ),
-                                }),
-                            );
-                        }
-                        Err(nom::Err::Failure(_e)) => {
-                            trace!("parse failure");
-                            break (
-                                0,
-                                Err(DltParseError::Unrecoverable {
-                                    cause: format!(
-                                    "read_one_dlt_message: parsing failure for dlt messages: {:?}",
-                                    _e
-                                ),
-                                }),
-                            );
-                        }
-                    }
+                    )
                 }
                 Err(e) => {
                     trace!("no more content");
-                    break (
+                    (
                         0,
                         Err(DltParseError::Unrecoverable {
                             cause: format!("error for filling buffer with dlt messages: {:?}", e),
                         }),
-                    );
+                    )
+                }
+            };
+            if let (_, Err(DltParseError::IncompleteParse { .. })) = res {
+                // try again with more content, if there is nothing more to read
+                // we have to work with what we have
+                match self.reader.fill_more() {
+                    Ok(true) => continue,
+                    Ok(false) if !at_end => {
+                        at_end = true;
+                        continue;
+                    }
+                    _ => (),
                 }
             }
+            break res;
         };
         self.reader.consume(res.0);
+        self.offset += res.0;
         res
     }
+
+    fn source_position(&self) -> Option<SourcePosition> {
+        self.source_position.clone()
+    }
+
+    fn produces_all_messages(&self) -> bool {
+        self.filter_config.is_none()
+    }
+}
+impl FileMessageProducer {
+    /// continue reading at a byte offset of the file
+    pub(crate) fn seek(&mut self, offset: u64) -> Result<(), Error> {
+        self.reader.seek(offset)?;
+        self.offset = offset as usize;
+        Ok(())
+    }
+}
+
+/// produces messages from content that is already in memory
+pub struct MemoryMessageProducer<'a> {
+    content: &'a [u8],
+    /// position in the content
+    offset: usize,
+    filter_config: Option<filtering::ProcessedDltFilterConfig>,
+    index: usize,
+    update_channel: cc::Sender<ChunkResults>,
+    with_storage_header: bool,
+}
+impl<'a> MemoryMessageProducer<'a> {
+    pub fn new(
+        content: &'a [u8],
+        filter_config: Option<filtering::ProcessedDltFilterConfig>,
+        index: usize,
+        update_channel: cc::Sender<ChunkResults>,
+        with_storage_header: bool,
+    ) -> MemoryMessageProducer<'a> {
+        MemoryMessageProducer {
+            content,
+            offset: 0,
+            filter_config,
+            index,
+            update_channel,
+            with_storage_header,
+        }
+    }
+}
+impl<'a> MessageProducer for MemoryMessageProducer<'a> {
+    fn produce_next_message(
+        &mut self,
+        fibex_metadata: Option<Rc<FibexMetadata>>,
+    ) -> (usize, Result<Option<Message>, DltParseError>) {
+        if self.offset >= self.content.len() {
+            return (0, Ok(None));
+        }
+        let res = parse_next_message(
+            &self.content[self.offset..],
+            self.offset,
+            true,
+            self.filter_config.as_ref(),
+            self.index,
+            &self.update_channel,
+            fibex_metadata,
+            self.with_storage_header,
+        );
+        self.offset += res.0;
+        res
+    }
+
+    fn produces_all_messages(&self) -> bool {
+        self.filter_config.is_none()
+    }
+}
+/// parse the next message from the available content
+/// returns the number of bytes that were consumed together with the parse result
+///
+/// `offset` is the position of the content in the input and `at_end` tells if
+/// the content reaches to the end of the input. Corrupted parts and a truncated
+/// last message are skipped and reported.
+/// If more content is needed to decide, `DltParseError::IncompleteParse` is
+/// returned and nothing is consumed.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn parse_next_message(
+    content: &[u8],
+    offset: usize,
+    at_end: bool,
+    filter_config: Option<&filtering::ProcessedDltFilterConfig>,
+    index: usize,
+    update_channel: &cc::Sender<ChunkResults>,
+    fibex_metadata: Option<Rc<FibexMetadata>>,
+    with_storage_header: bool,
+) -> (usize, Result<Option<Message>, DltParseError>) {
+    let available = content.len();
+    if with_storage_header {
+        match next_plausible_storage_header(content, at_end) {
+            Resync::Found(0) => (),
+            Resync::Found(start) => {
+                report_skipped(
+                    update_channel,
+                    "no valid message found",
+                    offset,
+                    offset + start,
+                );
+                return (start, Ok(None));
+            }
+            Resync::NotFound => {
+                report_skipped(
+                    update_channel,
+                    "no more valid messages found",
+                    offset,
+                    offset + available,
+                );
+                return (available, Ok(None));
+            }
+            Resync::NeedMore => {
+                return (0, Err(DltParseError::IncompleteParse { needed: None }));
+            }
+        }
+    }
+    let res: nom::IResult<&[u8], Option<Message>> = dlt_message(
+        content,
+        filter_config,
+        index,
+        Some(update_channel),
+        fibex_metadata,
+        with_storage_header,
+    );
+    match res {
+        Ok(r) => {
+            let consumed = available - r.0.len();
+            trace!("parse ok, consumed: {}", consumed);
+            (consumed, Ok(r.1))
+        }
+        Err(nom::Err::Incomplete(n)) => {
+            trace!("parse incomplete");
+            if at_end {
+                // e.g. the recording was cut off by a power loss
+                report_skipped(
+                    update_channel,
+                    "truncated message at end of input",
+                    offset,
+                    offset + available,
+                );
+                return (available, Ok(None));
+            }
+            let needed = match n {
+                nom::Needed::Size(s) => Some(s),
+                nom::Needed::Unknown => None,
+            };
+            (0, Err(DltParseError::IncompleteParse { needed }))
+        }
+        Err(nom::Err::Error(_e)) => {
+            trace!("parse error");
+            (
+                DLT_PATTERN_SIZE.min(available),
+                Err(DltParseError::ParsingHickup {
+                    reason: format!(
+                        "read_one_dlt_message: parsing error for dlt messages: {:?}",
+                        _e
+                    ),
+                }),
+            )
+        }
+        Err(nom::Err::Failure(_e)) => {
+            trace!("parse failure");
+            (
+                0,
+                Err(DltParseError::Unrecoverable {
+                    cause: format!(
+                        "read_one_dlt_message: parsing failure for dlt messages: {:?}",
+                        _e
+                    ),
+                }),
+            )
+        }
+    }
 }
-// pub trait MessageProducer {
-//     fn produce_next_message(
-//         &mut self,
-//         fibex_metadata: Option<Rc<FibexMetadata>>,
-//     ) -> (usize, Result<Option<Message>, DltParseError>);
-// }
 #[allow(clippy::too_many_arguments)]
 pub fn create_index_and_mapping_dlt_from_socket(
     socket_config: SocketConfig,
@@ -1162,7 +1888,7 @@ pub fn create_index_and_mapping_dlt_from_socket(
         Ok(initial_line_nr) => {
             let filter_config: Option<filtering::ProcessedDltFilterConfig> =
                 dlt_filter.map(filtering::process_filter_config);
-            match index_from_socket(
+            let res = index_from_socket(
                 socket_config,
                 filter_config,
                 update_channel.clone(),
@@ -1172,33 +1898,8 @@ pub fn create_index_and_mapping_dlt_from_socket(
                 out_path,
                 initial_line_nr,
                 shutdown_receiver,
-            ) {
-                Err(ConnectionError::WrongConfiguration { cause }) => {
-                    let _ = update_channel.send(Err(Notification {
-                        severity: Severity::ERROR,
-                        content: cause.clone(),
-                        line: None,
-                    }));
-                    Err(err_msg(cause))
-                }
-                Err(ConnectionError::UnableToConnect { reason }) => {
-                    let _ = update_channel.send(Err(Notification {
-                        severity: Severity::ERROR,
-                        content: reason.clone(),
-                        line: None,
-                    }));
-                    Err(err_msg(reason))
-                }
-                Err(ConnectionError::Other { info }) => {
-                    let _ = update_channel.send(Err(Notification {
-                        severity: Severity::ERROR,
-                        content: info.clone(),
-                        line: None,
-                    }));
-                    Err(err_msg(info))
-                }
-                Ok(_) => Ok(()),
-            }
+            );
+            report_connection_result(res, update_channel)
         }
         Err(e) => {
             let content = format!(
@@ -1213,9 +1914,81 @@ pub fn create_index_and_mapping_dlt_from_socket(
             Err(err_msg(content))
         }
     };
-    let _ = update_channel.send(Ok(IndexingProgress::Finished));
+    // when indexing was successful the sink already reported that we are finished
+    if res.is_err() {
+        let _ = update_channel.send(Ok(IndexingProgress::Finished));
+    }
     res
 }
+/// forwards connection errors to the update channel
+fn report_connection_result(
+    res: Result<(), ConnectionError>,
+    update_channel: &cc::Sender<ChunkResults>,
+) -> Result<(), Error> {
+    let content = match res {
+        Ok(()) => return Ok(()),
+        Err(ConnectionError::WrongConfiguration { cause }) => cause,
+        Err(ConnectionError::UnableToConnect { reason }) => reason,
+        Err(ConnectionError::Other { info }) => info,
+    };
+    let _ = update_channel.send(Err(Notification {
+        severity: Severity::ERROR,
+        content: content.clone(),
+        line: None,
+    }));
+    Err(err_msg(content))
+}
+#[allow(clippy::too_many_arguments)]
+pub fn create_index_and_mapping_dlt_from_tcp(
+    tcp_config: TcpConfig,
+    tag: &str,
+    ecu_id: String,
+    out_path: &std::path::PathBuf,
+    dlt_filter: Option<filtering::DltFilterConfig>,
+    update_channel: &cc::Sender<ChunkResults>,
+    shutdown_receiver: async_std::sync::Receiver<()>,
+    fibex_metadata: Option<Rc<FibexMetadata>>,
+) -> Result<(), Error> {
+    trace!("create_index_and_mapping_dlt_from_tcp");
+    let res = match utils::next_line_nr(out_path) {
+        Ok(initial_line_nr) => {
+            let filter_config: Option<filtering::ProcessedDltFilterConfig> =
+                dlt_filter.map(filtering::process_filter_config);
+            let res = index_from_tcp_stream(
+                tcp_config,
+                filter_config,
+                update_channel.clone(),
+                fibex_metadata,
+                tag,
+                ecu_id,
+                out_path,
+                initial_line_nr,
+                shutdown_receiver,
+            );
+            report_connection_result(res, update_channel)
+        }
+        Err(e) => {
+            let content = format!(
+                "could not determine last line number of {:?} ({})",
+                out_path, e
+            );
+            let _ = update_channel.send(Err(Notification {
+                severity: Severity::ERROR,
+                content: content.clone(),
+                line: None,
+            }));
+            Err(err_msg(content))
+        }
+    };
+    // when indexing was successful the sink already reported that we are finished
+    if res.is_err() {
+        let _ = update_channel.send(Ok(IndexingProgress::Finished));
+    }
+    res
+}
+/// `memory_map` reads the file memory mapped (see `FileMessageProducer::memory_mapped`)
+/// `absolute_time` adds the send time as last column of every line (see `dlt_time`)
+#[allow(clippy::too_many_arguments)]
 pub fn create_index_and_mapping_dlt(
     config: IndexingConfig,
     source_file_size: Option<usize>,
@@ -1223,20 +1996,30 @@ pub fn create_index_and_mapping_dlt(
     update_channel: &cc::Sender<ChunkResults>,
     shutdown_receiver: Option<cc::Receiver<()>>,
     fibex_metadata: Option<Rc<FibexMetadata>>,
+    memory_map: bool,
+    absolute_time: bool,
 ) -> Result<(), Error> {
     trace!("create_index_and_mapping_dlt");
     match utils::next_line_nr(config.out_path) {
         Ok(initial_line_nr) => {
             let filter_config: Option<filtering::ProcessedDltFilterConfig> =
                 dlt_filter.map(filtering::process_filter_config);
-            let mut message_producer = FileMessageProducer::new(
+            // the time model of a stream has to see the filtered messages too
+            let streamed_absolute_time = absolute_time && !is_random_access(&config.in_file);
+            let (filter_config, stream_filter) = if streamed_absolute_time {
+                (None, filter_config)
+            } else {
+                (filter_config, None)
+            };
+            let mut message_producer = FileMessageProducer::open(
                 &config.in_file,
                 filter_config,
                 initial_line_nr,
                 update_channel.clone(),
                 true,
+                memory_map,
             )?;
-            index_dlt_content(
+            index_messages_of_producer(
                 config,
                 initial_line_nr,
                 source_file_size,
@@ -1244,6 +2027,8 @@ pub fn create_index_and_mapping_dlt(
                 shutdown_receiver,
                 fibex_metadata,
                 &mut message_producer,
+                absolute_time,
+                stream_filter,
             )
         }
         Err(e) => {
@@ -1261,67 +2046,245 @@ pub fn create_index_and_mapping_dlt(
     }
 }
 
-/// create index for a dlt file
-/// source_file_size: if progress updates should be made, add this value
-pub fn index_dlt_content(
-    config: IndexingConfig,
-    initial_line_nr: usize,
-    source_file_size: Option<usize>,
-    update_channel: &cc::Sender<ChunkResults>,
+/// writes the tagged lines, the chunks and the offset index for parsed messages
+/// the sequential and the parallel indexer both feed their results in here so
+/// that they produce exactly the same output
+pub(crate) struct IndexSink<'a> {
+    tag: &'a str,
+    out_path: &'a std::path::PathBuf,
+    update_channel: &'a cc::Sender<ChunkResults>,
     shutdown_receiver: Option<cc::Receiver<()>>,
-    fibex_metadata: Option<Rc<FibexMetadata>>,
-    message_producer: &mut FileMessageProducer,
-) -> Result<(), Error> {
-    trace!("index_dlt_file {:?}", config);
-    let (out_file, current_out_file_size) =
-        utils::get_out_file_and_size(config.append, &config.out_path)?;
+    buf_writer: BufWriter<fs::File>,
+    /// only written when the messages come from a file
+    offset_writer: Option<OffsetIndexWriter>,
+    chunk_factory: ChunkFactory,
+    progress_reporter: ProgressReporter,
+    chunk_count: usize,
+    last_byte_index: usize,
+    line_nr: usize,
+    source_offset: usize,
+    /// only used when all messages of the source are indexed
+    counter_check: Option<MessageCounterCheck>,
+    /// only used when the absolute time is added to the lines
+    absolute_times: Option<AbsoluteTimes>,
+    pub(crate) stopped: bool,
+}
+impl<'a> IndexSink<'a> {
+    pub(crate) fn new(
+        config: &IndexingConfig<'a>,
+        initial_line_nr: usize,
+        source_file_size: Option<usize>,
+        update_channel: &'a cc::Sender<ChunkResults>,
+        shutdown_receiver: Option<cc::Receiver<()>>,
+    ) -> Result<IndexSink<'a>, Error> {
+        // messages can only be read again by offset from an uncompressed file.
+        // appended lines can come from another source file, so the offsets
+        // would be ambiguous and an existing index is removed instead
+        let index_path = dlt_offsets::offset_index_path(config.out_path);
+        let offset_writer = if config.append {
+            if index_path.exists() {
+                fs::remove_file(&index_path)?;
+            }
+            None
+        } else if is_random_access(&config.in_file) {
+            Some(OffsetIndexWriter::new(&index_path)?)
+        } else {
+            None
+        };
+        IndexSink::create(
+            config.tag,
+            config.out_path,
+            config.chunk_size,
+            config.append,
+            initial_line_nr,
+            source_file_size,
+            update_channel,
+            shutdown_receiver,
+            offset_writer,
+        )
+    }
 
-    let mut chunk_count = 0usize;
-    let mut last_byte_index = 0usize;
-    let mut chunk_factory = ChunkFactory::new(config.chunk_size, current_out_file_size);
-    let mut line_nr = initial_line_nr;
-    let mut buf_writer = BufWriter::with_capacity(10 * 1024 * 1024, out_file);
+    /// a sink for messages received over the network
+    /// the output is always appended and every line is reported as a chunk
+    pub(crate) fn for_stream(
+        tag: &'a str,
+        out_path: &'a std::path::PathBuf,
+        initial_line_nr: usize,
+        update_channel: &'a cc::Sender<ChunkResults>,
+    ) -> Result<IndexSink<'a>, Error> {
+        IndexSink::create(
+            tag,
+            out_path,
+            0,
+            true,
+            initial_line_nr,
+            None,
+            update_channel,
+            None,
+            None,
+        )
+    }
 
-    let mut progress_reporter = ProgressReporter::new(source_file_size, update_channel.clone());
+    #[allow(clippy::too_many_arguments)]
+    fn create(
+        tag: &'a str,
+        out_path: &'a std::path::PathBuf,
+        chunk_size: usize,
+        append: bool,
+        initial_line_nr: usize,
+        source_file_size: Option<usize>,
+        update_channel: &'a cc::Sender<ChunkResults>,
+        shutdown_receiver: Option<cc::Receiver<()>>,
+        offset_writer: Option<OffsetIndexWriter>,
+    ) -> Result<IndexSink<'a>, Error> {
+        let (out_file, current_out_file_size) = utils::get_out_file_and_size(append, out_path)?;
+        Ok(IndexSink {
+            tag,
+            out_path,
+            update_channel,
+            shutdown_receiver,
+            buf_writer: BufWriter::with_capacity(10 * 1024 * 1024, out_file),
+            offset_writer,
+            chunk_factory: ChunkFactory::new(chunk_size, current_out_file_size),
+            progress_reporter: ProgressReporter::new(source_file_size, update_channel.clone()),
+            chunk_count: 0,
+            last_byte_index: 0,
+            line_nr: initial_line_nr,
+            source_offset: 0,
+            counter_check: None,
+            absolute_times: None,
+            stopped: false,
+        })
+    }
 
-    let mut stopped = false;
-    loop {
-        if stopped {
-            info!("we were stopped in dlt-indexer",);
-            break;
-        };
-        let (consumed, next) = message_producer.produce_next_message(fibex_metadata.clone());
+    /// report progress against the compressed source
+    pub(crate) fn track_source(&mut self, position: SourcePosition) {
+        self.progress_reporter.track_source(position);
+    }
+
+    /// report gaps in the message counters as lost messages
+    /// filtered messages would look like lost ones, so this must only be
+    /// enabled if every message of the source is handled
+    pub(crate) fn check_counters(&mut self) {
+        self.counter_check = Some(MessageCounterCheck::new());
+    }
+
+    /// read the whole file once to estimate the start of its ECUs, the
+    /// absolute time is then added to every line
+    /// the sink is stopped if a shutdown is received meanwhile
+    pub(crate) fn calibrate(&mut self, in_file: &std::path::PathBuf) -> Result<(), Error> {
+        match time_model_for_file(
+            in_file,
+            self.update_channel,
+            &mut self.progress_reporter,
+            self.shutdown_receiver.as_ref(),
+        )? {
+            Some(model) => self.absolute_times = Some(AbsoluteTimes::calibrated(model)),
+            None => self.stopped = true,
+        }
+        Ok(())
+    }
+
+    /// add the absolute time to every line, the time model learns from the
+    /// messages while they are indexed. messages excluded by `filter` are
+    /// only observed
+    pub(crate) fn observe_absolute_times(
+        &mut self,
+        filter: Option<filtering::ProcessedDltFilterConfig>,
+    ) {
+        self.absolute_times = Some(AbsoluteTimes::observed(filter));
+    }
+
+    /// handle the result of parsing one message, with the absolute time
+    /// as last column if it was requested
+    pub(crate) fn handle_message(
+        &mut self,
+        consumed: usize,
+        next: Result<Option<(Message, usize)>, DltParseError>,
+    ) -> Result<bool, Error> {
+        match self.absolute_times.as_mut() {
+            Some(times) => {
+                let next = next.map(|m| {
+                    m.and_then(|(msg, length)| {
+                        let absolute_time = times.absolute_time(&msg);
+                        if times.excludes(&msg) {
+                            return None;
+                        }
+                        Some((TimedMessage { msg, absolute_time }, length))
+                    })
+                });
+                self.handle(consumed, next)
+            }
+            None => self.handle(consumed, next),
+        }
+    }
+
+    /// handle the result of parsing one message
+    /// a message is passed in with its length in the source file
+    /// returns false if no more messages should be processed
+    pub(crate) fn handle<T: std::fmt::Display + MessageCounter>(
+        &mut self,
+        consumed: usize,
+        next: Result<Option<(T, usize)>, DltParseError>,
+    ) -> Result<bool, Error> {
         if consumed == 0 {
-            break;
+            return Ok(false);
         } else {
-            progress_reporter.make_progress(consumed);
+            self.progress_reporter.make_progress(consumed);
+            self.source_offset += consumed;
         }
         match next {
-            Ok(Some(msg)) => {
-                trace!("next was Ok(msg){} bytes", msg.as_bytes().len());
-                let written_bytes_len =
-                    utils::create_tagged_line_d(config.tag, &mut buf_writer, &msg, line_nr, true)?;
-                line_nr += 1;
-                if let Some(chunk) =
-                    chunk_factory.create_chunk_if_needed(line_nr, written_bytes_len)
+            Ok(Some((msg, source_length))) => {
+                // consumed bytes might include skipped garbage before the message
+                let length = source_length.min(consumed);
+                if let Some(gap) = self
+                    .counter_check
+                    .as_mut()
+                    .and_then(|check| check.check_message(&msg))
+                {
+                    self.update_channel.send(Err(Notification {
+                        severity: Severity::WARNING,
+                        content: gap.to_string(),
+                        line: Some(self.line_nr),
+                    }))?;
+                }
+                if let Some(offset_writer) = self.offset_writer.as_mut() {
+                    offset_writer.write_record(OffsetRecord {
+                        line_nr: self.line_nr as u64,
+                        offset: (self.source_offset - length) as u64,
+                        length: length as u32,
+                    })?;
+                }
+                let written_bytes_len = utils::create_tagged_line_d(
+                    self.tag,
+                    &mut self.buf_writer,
+                    msg,
+                    self.line_nr,
+                    true,
+                )?;
+                self.line_nr += 1;
+                if let Some(chunk) = self
+                    .chunk_factory
+                    .create_chunk_if_needed(self.line_nr, written_bytes_len)
                 {
                     // check if stop was requested
-                    if let Some(rx) = shutdown_receiver.as_ref() {
+                    if let Some(rx) = self.shutdown_receiver.as_ref() {
                         match rx.try_recv() {
                             // Shutdown if we have received a command or if there is
                             // nothing to send it.
                             Ok(_) | Err(cc::TryRecvError::Disconnected) => {
                                 info!("shutdown received in indexer",);
-                                stopped = true // stop
+                                self.stopped = true // stop
                             }
                             // No shutdown command, continue
                             Err(cc::TryRecvError::Empty) => (),
                         }
                     };
-                    chunk_count += 1;
-                    last_byte_index = chunk.b.1;
-                    update_channel.send(Ok(IndexingProgress::GotItem { item: chunk }))?;
-                    buf_writer.flush()?;
+                    self.chunk_count += 1;
+                    self.last_byte_index = chunk.b.1;
+                    self.update_channel
+                        .send(Ok(IndexingProgress::GotItem { item: chunk }))?;
+                    self.flush()?;
                 }
             }
             Ok(None) => {
@@ -1336,41 +2299,188 @@ pub fn index_dlt_content(
                 }
                 DltParseError::Unrecoverable { cause } => {
                     warn!("cannot continue parsing: {}", cause);
-                    update_channel.send(Err(Notification {
+                    self.update_channel.send(Err(Notification {
                         severity: Severity::ERROR,
                         content: format!("error parsing dlt file: {}", cause),
                         line: None,
                     }))?;
-                    break;
+                    return Ok(false);
                 }
+                DltParseError::IncompleteParse { .. } => return Ok(false),
             },
         }
+        Ok(true)
     }
 
-    buf_writer.flush()?;
-    if let Some(chunk) = chunk_factory.create_last_chunk(line_nr, chunk_count == 0) {
-        update_channel.send(Ok(IndexingProgress::GotItem {
-            item: chunk.clone(),
-        }))?;
-        chunk_count += 1;
-        last_byte_index = chunk.b.1;
+    /// the line number of the next message
+    pub(crate) fn line_nr(&self) -> usize {
+        self.line_nr
     }
-    if chunk_count > 0 {
-        let last_expected_byte_index = fs::metadata(config.out_path).map(|md| md.len() as usize)?;
-        if last_expected_byte_index != last_byte_index {
-            update_channel.send(Err(Notification {
-                severity: Severity::ERROR,
-                content: format!(
-                    "error in computation! last byte in chunks is {} but should be {}",
-                    last_byte_index, last_expected_byte_index
-                ),
-                line: Some(line_nr),
+
+    fn flush(&mut self) -> Result<(), Error> {
+        self.buf_writer.flush()?;
+        if let Some(offset_writer) = self.offset_writer.as_mut() {
+            offset_writer.flush()?;
+        }
+        Ok(())
+    }
+
+    pub(crate) fn finish(mut self) -> Result<(), Error> {
+        self.flush()?;
+        if let Some(chunk) = self
+            .chunk_factory
+            .create_last_chunk(self.line_nr, self.chunk_count == 0)
+        {
+            self.update_channel.send(Ok(IndexingProgress::GotItem {
+                item: chunk.clone(),
             }))?;
+            self.chunk_count += 1;
+            self.last_byte_index = chunk.b.1;
+        }
+        if self.chunk_count > 0 {
+            let last_expected_byte_index =
+                fs::metadata(self.out_path).map(|md| md.len() as usize)?;
+            if last_expected_byte_index != self.last_byte_index {
+                self.update_channel.send(Err(Notification {
+                    severity: Severity::ERROR,
+                    content: format!(
+                        "error in computation! last byte in chunks is {} but should be {}",
+                        self.last_byte_index, last_expected_byte_index
+                    ),
+                    line: Some(self.line_nr),
+                }))?;
+            }
         }
+        trace!("sending IndexingProgress::Finished");
+        self.update_channel.send(Ok(IndexingProgress::Finished))?;
+        Ok(())
     }
-    trace!("sending IndexingProgress::Finished");
-    update_channel.send(Ok(IndexingProgress::Finished))?;
-    Ok(())
+}
+
+/// feeds the messages of a producer into the sink until the producer is
+/// exhausted, parsing cannot continue or a shutdown was requested
+/// returns true if the shutdown was requested through `shutdown_receiver`
+pub(crate) async fn index_messages<P: AsyncMessageProducer + Unpin>(
+    message_producer: &mut P,
+    sink: &mut IndexSink<'_>,
+    fibex_metadata: Option<Rc<FibexMetadata>>,
+    shutdown_receiver: Option<&async_std::sync::Receiver<()>>,
+) -> Result<bool, Error> {
+    // listen for both a shutdown request and incoming messages
+    // to do this we need to select over streams of the same type
+    // the type we use to unify is this Event enum
+    enum Event {
+        Shutdown,
+        Msg((usize, Result<Option<Message>, DltParseError>)),
+    }
+    let message_stream = futures::stream::poll_fn(|cx| {
+        Pin::new(&mut *message_producer)
+            .poll_next_message(cx, fibex_metadata.clone())
+            .map(Some)
+    })
+    .map(Event::Msg);
+    let shutdown_stream = futures::stream::iter(shutdown_receiver.cloned())
+        .flatten()
+        .map(|_| {
+            debug!("shutdown_receiver event");
+            Event::Shutdown
+        });
+    let mut event_stream = futures::stream::select(message_stream, shutdown_stream);
+    while let Some(event) = event_stream.next().await {
+        if sink.stopped {
+            info!("we were stopped in dlt-indexer",);
+            break;
+        };
+        let (consumed, next) = match event {
+            Event::Shutdown => {
+                debug!("received shutdown through future channel");
+                return Ok(true);
+            }
+            Event::Msg(msg) => msg,
+        };
+        let next = next.map(|m| {
+            m.map(|msg| {
+                trace!("next was Ok(msg){} bytes", msg.as_bytes().len());
+                let length = dlt_offsets::source_length(&msg);
+                (msg, length)
+            })
+        });
+        if !sink.handle_message(consumed, next)? {
+            break;
+        }
+    }
+    Ok(false)
+}
+
+/// create index for a dlt file
+/// source_file_size: if progress updates should be made, add this value
+pub fn index_dlt_content<P: MessageProducer + Unpin>(
+    config: IndexingConfig,
+    initial_line_nr: usize,
+    source_file_size: Option<usize>,
+    update_channel: &cc::Sender<ChunkResults>,
+    shutdown_receiver: Option<cc::Receiver<()>>,
+    fibex_metadata: Option<Rc<FibexMetadata>>,
+    message_producer: &mut P,
+) -> Result<(), Error> {
+    index_messages_of_producer(
+        config,
+        initial_line_nr,
+        source_file_size,
+        update_channel,
+        shutdown_receiver,
+        fibex_metadata,
+        message_producer,
+        false,
+        None,
+    )
+}
+
+/// index the messages of the producer, with the absolute time as last
+/// column if `absolute_time` is set
+/// `stream_filter` is applied after the time model has seen a message, it is
+/// only used for streams that cannot be read twice
+#[allow(clippy::too_many_arguments)]
+fn index_messages_of_producer<P: MessageProducer + Unpin>(
+    config: IndexingConfig,
+    initial_line_nr: usize,
+    source_file_size: Option<usize>,
+    update_channel: &cc::Sender<ChunkResults>,
+    shutdown_receiver: Option<cc::Receiver<()>>,
+    fibex_metadata: Option<Rc<FibexMetadata>>,
+    message_producer: &mut P,
+    absolute_time: bool,
+    stream_filter: Option<filtering::ProcessedDltFilterConfig>,
+) -> Result<(), Error> {
+    trace!("index_dlt_file {:?}", config);
+    let calibrated = absolute_time && is_random_access(&config.in_file);
+    // a calibrated file is read twice
+    let total = source_file_size.map(|size| if calibrated { 2 * size } else { size });
+    let mut sink = IndexSink::new(
+        &config,
+        initial_line_nr,
+        total,
+        update_channel,
+        shutdown_receiver,
+    )?;
+    if let Some(position) = message_producer.source_position() {
+        sink.track_source(position);
+    }
+    if message_producer.produces_all_messages() && stream_filter.is_none() {
+        sink.check_counters();
+    }
+    if calibrated {
+        sink.calibrate(&config.in_file)?;
+    } else if absolute_time {
+        sink.observe_absolute_times(stream_filter);
+    }
+    async_std::task::block_on(index_messages(
+        message_producer,
+        &mut sink,
+        fibex_metadata,
+        None,
+    ))?;
+    sink.finish()
 }
 
 #[derive(Serialize, Debug, Default)]
@@ -1385,6 +2495,18 @@ struct LevelDistribution {
     log_invalid: usize,
 }
 impl LevelDistribution {
+    fn add(&mut self, level: Option<LogLevel>) {
+        match level {
+            None => self.non_log += 1,
+            Some(LogLevel::Fatal) => self.log_fatal += 1,
+            Some(LogLevel::Error) => self.log_error += 1,
+            Some(LogLevel::Warn) => self.log_warning += 1,
+            Some(LogLevel::Info) => self.log_info += 1,
+            Some(LogLevel::Debug) => self.log_debug += 1,
+            Some(LogLevel::Verbose) => self.log_verbose += 1,
+            Some(LogLevel::Invalid(_)) => self.log_invalid += 1,
+        }
+    }
     pub fn new(level: Option<LogLevel>) -> LevelDistribution {
         let all_zero = Default::default();
         match level {
@@ -1425,60 +2547,143 @@ impl LevelDistribution {
 }
 type IdMap = FxHashMap<String, LevelDistribution>;
 
-fn add_for_level(level: Option<LogLevel>, ids: &mut IdMap, id: String) {
-    if let Some(n) = ids.get_mut(&id) {
-        match level {
-            Some(LogLevel::Fatal) => {
-                *n = LevelDistribution {
-                    log_fatal: n.log_fatal + 1,
-                    ..*n
-                }
-            }
-            Some(LogLevel::Error) => {
-                *n = LevelDistribution {
-                    log_error: n.log_error + 1,
-                    ..*n
-                }
-            }
-            Some(LogLevel::Warn) => {
-                *n = LevelDistribution {
-                    log_warning: n.log_warning + 1,
-                    ..*n
-                }
-            }
-            Some(LogLevel::Info) => {
-                *n = LevelDistribution {
-                    log_info: n.log_info + 1,
-                    ..*n
-                }
-            }
-            Some(LogLevel::Debug) => {
-                *n = LevelDistribution {
-                    log_debug: n.log_debug + 1,
-                    ..*n
-                };
-            }
-            Some(LogLevel::Verbose) => {
-                *n = LevelDistribution {
-                    log_verbose: n.log_verbose + 1,
-                    ..*n
-                };
-            }
-            Some(LogLevel::Invalid(_)) => {
-                *n = LevelDistribution {
-                    log_invalid: n.log_invalid + 1,
-                    ..*n
-                };
+fn add_for_level(level: Option<LogLevel>, ids: &mut IdMap, id: &str) {
+    if let Some(n) = ids.get_mut(id) {
+        n.add(level);
+    } else {
+        ids.insert(id.to_string(), LevelDistribution::new(level));
+    }
+}
+/// the timeline never has more buckets, their size grows with the time
+/// range of the file instead
+const MAX_TIME_BUCKETS: usize = 100;
+/// size of the time buckets in ms for files that cover less than
+/// `MAX_TIME_BUCKETS` seconds
+const INITIAL_BUCKET_SIZE: u64 = 1000;
+/// storage times before 2000-01-01 (in ms since epoch) come from loggers
+/// without a real time clock and would stretch the time range of the file
+const MIN_PLAUSIBLE_STORAGE_TIME: u64 = 946_684_800_000;
+
+/// messages with their storage time in one bucket of the timeline
+#[derive(Serialize, Debug)]
+struct TimeBucket {
+    /// ms since epoch
+    start: u64,
+    levels: LevelDistribution,
+    app_ids: Vec<(String, usize)>,
+}
+
+#[derive(Serialize, Debug)]
+struct Timeline {
+    /// length of a bucket in ms
+    bucket_size: u64,
+    buckets: Vec<TimeBucket>,
+}
+
+#[derive(Default)]
+struct BucketCounts {
+    levels: LevelDistribution,
+    app_ids: FxHashMap<String, usize>,
+}
+impl BucketCounts {
+    fn merge(&mut self, other: BucketCounts) {
+        let levels = &mut self.levels;
+        levels.non_log += other.levels.non_log;
+        levels.log_fatal += other.levels.log_fatal;
+        levels.log_error += other.levels.log_error;
+        levels.log_warning += other.levels.log_warning;
+        levels.log_info += other.levels.log_info;
+        levels.log_debug += other.levels.log_debug;
+        levels.log_verbose += other.levels.log_verbose;
+        levels.log_invalid += other.levels.log_invalid;
+        for (app_id, count) in other.app_ids {
+            *self.app_ids.entry(app_id).or_insert(0) += count;
+        }
+    }
+}
+
+/// counts messages in buckets that start at multiples of the bucket size
+/// when the buckets would not fit into `MAX_TIME_BUCKETS` anymore, the size
+/// is doubled and neighbouring buckets are merged
+struct TimelineBuilder {
+    bucket_size: u64,
+    /// start of the first bucket divided by the bucket size
+    first: u64,
+    buckets: VecDeque<BucketCounts>,
+}
+impl TimelineBuilder {
+    fn new() -> TimelineBuilder {
+        TimelineBuilder {
+            bucket_size: INITIAL_BUCKET_SIZE,
+            first: 0,
+            buckets: VecDeque::new(),
+        }
+    }
+
+    fn add(&mut self, time: u64, level: Option<LogLevel>, app_id: &str) {
+        if self.buckets.is_empty() {
+            self.first = time / self.bucket_size;
+        }
+        let mut index = time / self.bucket_size;
+        loop {
+            let last = (self.first + self.buckets.len() as u64).saturating_sub(1);
+            if last.max(index) - self.first.min(index) < MAX_TIME_BUCKETS as u64 {
+                break;
             }
-            None => {
-                *n = LevelDistribution {
-                    non_log: n.non_log + 1,
-                    ..*n
-                };
+            self.double_bucket_size();
+            index = time / self.bucket_size;
+        }
+        while index < self.first {
+            self.buckets.push_front(BucketCounts::default());
+            self.first -= 1;
+        }
+        while index >= self.first + self.buckets.len() as u64 {
+            self.buckets.push_back(BucketCounts::default());
+        }
+        let bucket = &mut self.buckets[(index - self.first) as usize];
+        bucket.levels.add(level);
+        if let Some(n) = bucket.app_ids.get_mut(app_id) {
+            *n += 1;
+        } else {
+            bucket.app_ids.insert(app_id.to_string(), 1);
+        }
+    }
+
+    fn double_bucket_size(&mut self) {
+        let first = self.first / 2;
+        let mut buckets: VecDeque<BucketCounts> = VecDeque::new();
+        for (i, bucket) in self.buckets.drain(..).enumerate() {
+            let index = ((self.first + i as u64) / 2 - first) as usize;
+            match buckets.get_mut(index) {
+                Some(merged) => merged.merge(bucket),
+                None => buckets.push_back(bucket),
             }
         }
-    } else {
-        ids.insert(id, LevelDistribution::new(level));
+        self.first = first;
+        self.buckets = buckets;
+        self.bucket_size *= 2;
+    }
+
+    fn build(self) -> Timeline {
+        let bucket_size = self.bucket_size;
+        let first = self.first;
+        Timeline {
+            bucket_size,
+            buckets: self
+                .buckets
+                .into_iter()
+                .enumerate()
+                .map(|(i, counts)| {
+                    let mut app_ids: Vec<(String, usize)> = counts.app_ids.into_iter().collect();
+                    app_ids.sort();
+                    TimeBucket {
+                        start: (first + i as u64) * bucket_size,
+                        levels: counts.levels,
+                        app_ids,
+                    }
+                })
+                .collect(),
+        }
     }
 }
 #[derive(Serialize, Debug)]
@@ -1487,24 +2692,43 @@ pub struct StatisticInfo {
     context_ids: Vec<(String, LevelDistribution)>,
     ecu_ids: Vec<(String, LevelDistribution)>,
     contained_non_verbose: bool,
+    /// senders with gaps in their message counters
+    lost_messages: Vec<LostMessages>,
+    /// first and last storage time in ms since epoch
+    time_range: Option<(u64, u64)>,
+    timeline: Timeline,
+    /// messages that are left out of the time range and the timeline
+    /// since their storage time is implausible
+    implausible_storage_times: usize,
+    /// size of all messages of an app id in the file
+    app_id_bytes: Vec<(String, usize)>,
 }
 pub type StatisticsResults = std::result::Result<IndexingProgress<StatisticInfo>, Notification>;
+/// the fibex metadata gives application id, context id and log level of non
+/// verbose messages if it describes them
+/// `memory_map` reads the file memory mapped (see `FileMessageProducer::memory_mapped`)
 pub fn get_dlt_file_info(
     in_file: &std::path::PathBuf,
     update_channel: &cc::Sender<StatisticsResults>,
     shutdown_receiver: Option<cc::Receiver<()>>,
+    fibex_metadata: Option<Rc<FibexMetadata>>,
+    memory_map: bool,
 ) -> Result<(), Error> {
-    let f = match fs::File::open(in_file) {
-        Ok(file) => file,
+    let f = match open_input(in_file) {
+        Ok(input) => input,
         Err(e) => {
             error!("could not open {:?}", in_file);
             return Err(err_msg(format!("could not open {:?} ({})", in_file, e)));
         }
     };
 
-    let source_file_size: usize = fs::metadata(&in_file)?.len() as usize;
-    let mut reader =
-        ReduxReader::with_capacity(10 * 1024 * 1024, f).set_policy(MinBuffered(10 * 1024));
+    let source_file_size = input_size(in_file);
+    let source_position = f.source_position();
+    let mut reader = if memory_map {
+        DltFileReader::memory_mapped(f)
+    } else {
+        DltFileReader::new(f)
+    };
 
     let mut app_ids: IdMap = FxHashMap::default();
     let mut context_ids: IdMap = FxHashMap::default();
@@ -1512,44 +2736,73 @@ pub fn get_dlt_file_info(
     let mut index = 0usize;
     let mut processed_bytes = 0usize;
     let mut contained_non_verbose = false;
+    let mut counter_check = MessageCounterCheck::new();
+    let mut time_range: Option<(u64, u64)> = None;
+    let mut timeline = TimelineBuilder::new();
+    let mut implausible_storage_times = 0usize;
+    let mut app_id_bytes: FxHashMap<String, usize> = FxHashMap::default();
+    let mut at_end = reader.is_complete();
     loop {
         match read_one_dlt_message_info(&mut reader, Some(index), Some(update_channel)) {
-            Ok(Some((
-                consumed,
-                StatisticRowInfo {
-                    app_id_context_id: Some((app_id, context_id)),
-                    ecu_id: ecu,
-                    level,
-                    verbose,
-                },
-            ))) => {
-                contained_non_verbose = contained_non_verbose || !verbose;
-                reader.consume(consumed);
+            Ok(Some((consumed, info))) => {
+                let frame = match (&fibex_metadata, info.nonverbose_id) {
+                    (Some(fibex), Some(id)) => fibex.frame(info.app_id_context_id, id),
+                    _ => None,
+                };
+                let (app_id, context_id) = info.app_id_context_id.unwrap_or(("NONE", "NONE"));
+                let (app_id, context_id, level) = match frame {
+                    Some(frame) => (
+                        frame
+                            .application_id
+                            .as_ref()
+                            .map_or(app_id, |id| id.0.as_str()),
+                        frame
+                            .context_id
+                            .as_ref()
+                            .map_or(context_id, |id| id.0.as_str()),
+                        frame
+                            .message_info
+                            .as_ref()
+                            .and_then(|message_info| {
+                                LogLevel::from_fibex_message_info(message_info)
+                            })
+                            .or(info.level),
+                    ),
+                    None => (app_id, context_id, info.level),
+                };
+                contained_non_verbose = contained_non_verbose || !info.verbose;
                 add_for_level(level, &mut app_ids, app_id);
                 add_for_level(level, &mut context_ids, context_id);
-                match ecu {
-                    Some(id) => add_for_level(level, &mut ecu_ids, id),
-                    None => add_for_level(level, &mut ecu_ids, "NONE".to_string()),
+                add_for_level(level, &mut ecu_ids, info.ecu_id.unwrap_or("NONE"));
+                let time = info.storage_time.as_ms();
+                if time < MIN_PLAUSIBLE_STORAGE_TIME {
+                    implausible_storage_times += 1;
+                } else {
+                    time_range = Some(match time_range {
+                        Some((start, end)) => (start.min(time), end.max(time)),
+                        None => (time, time),
+                    });
+                    timeline.add(time, level, app_id);
+                }
+                if let Some(n) = app_id_bytes.get_mut(app_id) {
+                    *n += consumed;
+                } else {
+                    app_id_bytes.insert(app_id.to_string(), consumed);
+                }
+                let sender = Sender {
+                    ecu_id: info.ecu_id,
+                    session_id: info.session_id,
+                    app_id: info.app_id_context_id.map(|ids| ids.0),
+                    context_id: info.app_id_context_id.map(|ids| ids.1),
                 };
-                processed_bytes += consumed;
-            }
-            Ok(Some((
-                consumed,
-                StatisticRowInfo {
-                    app_id_context_id: None,
-                    ecu_id: ecu,
-                    level,
-                    verbose,
-                },
-            ))) => {
-                contained_non_verbose = contained_non_verbose || !verbose;
+                if let Some(gap) = counter_check.check(sender, info.message_counter) {
+                    update_channel.send(Err(Notification {
+                        severity: Severity::WARNING,
+                        content: gap.to_string(),
+                        line: Some(index),
+                    }))?;
+                }
                 reader.consume(consumed);
-                add_for_level(level, &mut app_ids, "NONE".to_string());
-                add_for_level(level, &mut context_ids, "NONE".to_string());
-                match ecu {
-                    Some(id) => add_for_level(level, &mut ecu_ids, id),
-                    None => add_for_level(level, &mut ecu_ids, "NONE".to_string()),
-                };
                 processed_bytes += consumed;
             }
             Ok(None) => {
@@ -1567,6 +2820,7 @@ pub fn get_dlt_file_info(
                     DltParseError::ParsingHickup { reason } => {
                         // we couldn't parse the message. try to skip it and find the next.
                         reader.consume(4); // at least skip the magic DLT pattern
+                        processed_bytes += 4;
                         trace!(
                             "error parsing 1 dlt message, try to continue parsing: {}",
                             reason
@@ -1581,6 +2835,29 @@ pub fn get_dlt_file_info(
                         }))?;
                         break;
                     }
+                    DltParseError::IncompleteParse { .. } => {
+                        // try again with more content, if there is nothing
+                        // more to read the message was cut off
+                        match reader.fill_more() {
+                            Ok(true) => continue,
+                            Ok(false) if !at_end => {
+                                at_end = true;
+                                continue;
+                            }
+                            _ => (),
+                        }
+                        let available = reader.fill_buf().map_or(0, |content| content.len());
+                        // e.g. the recording was cut off by a power loss
+                        report_skipped(
+                            update_channel,
+                            "truncated message at end of input",
+                            processed_bytes,
+                            processed_bytes + available,
+                        );
+                        reader.consume(available);
+                        processed_bytes += available;
+                        continue;
+                    }
                 }
             }
         }
@@ -1600,8 +2877,18 @@ pub fn get_dlt_file_info(
                     Err(cc::TryRecvError::Empty) => (),
                 }
             };
-            update_channel.send(Ok(IndexingProgress::Progress {
-                ticks: (processed_bytes, source_file_size),
+            update_channel.send(Ok(match source_file_size {
+                Some(size) => IndexingProgress::Progress {
+                    ticks: (
+                        source_position
+                            .as_ref()
+                            .map_or(processed_bytes, SourcePosition::get),
+                        size,
+                    ),
+                },
+                None => IndexingProgress::Processed {
+                    bytes: processed_bytes,
+                },
             }))?;
         }
     }
@@ -1616,6 +2903,15 @@ pub fn get_dlt_file_info(
             .into_iter()
             .collect::<Vec<(String, LevelDistribution)>>(),
         contained_non_verbose,
+        lost_messages: counter_check.summary(),
+        time_range,
+        timeline: timeline.build(),
+        implausible_storage_times,
+        app_id_bytes: {
+            let mut app_id_bytes: Vec<(String, usize)> = app_id_bytes.into_iter().collect();
+            app_id_bytes.sort();
+            app_id_bytes
+        },
     };
 
     update_channel.send(Ok(IndexingProgress::GotItem { item: res }))?;
@@ -1623,18 +2919,23 @@ pub fn get_dlt_file_info(
     Ok(())
 }
 
+/// the ids are borrowed from the parsed content
 #[derive(Serialize, Debug)]
-pub struct StatisticRowInfo {
-    app_id_context_id: Option<(String, String)>,
-    ecu_id: Option<String>,
+pub struct StatisticRowInfo<'a> {
+    app_id_context_id: Option<(&'a str, &'a str)>,
+    ecu_id: Option<&'a str>,
+    storage_time: DltTimeStamp,
+    session_id: Option<u32>,
+    message_counter: u8,
     level: Option<LogLevel>,
     verbose: bool,
+    nonverbose_id: Option<u32>,
 }
-fn read_one_dlt_message_info<T: Read>(
-    reader: &mut ReduxReader<T, MinBuffered>,
+fn read_one_dlt_message_info<'a>(
+    reader: &'a mut DltFileReader,
     index: Option<usize>,
     update_channel: Option<&cc::Sender<StatisticsResults>>,
-) -> Result<Option<(usize, StatisticRowInfo)>, DltParseError> {
+) -> Result<Option<(usize, StatisticRowInfo<'a>)>, DltParseError> {
     match reader.fill_buf() {
         Ok(content) => {
             if content.is_empty() {
@@ -1653,16 +2954,10 @@ fn read_one_dlt_message_info<T: Read>(
                     Err(nom::Err::Incomplete(n)) => {
                         trace!("parse incomplete");
                         let needed = match n {
-                            nom::Needed::Size(s) => format!("{}", s),
-                            nom::Needed::Unknown => "unknown".to_string(),
+                            nom::Needed::Size(s) => Some(s),
+                            nom::Needed::Unknown => None,
                         };
-                        Err(DltParseError::Unrecoverable {
-                            cause: format!(
-                            "read_one_dlt_message: imcomplete parsing error for dlt messages: (bytes left: {}, but needed: {})",
-                            content.len(),
-                            needed
-                        ),
-                        })
+                        Err(DltParseError::IncompleteParse { needed })
                     }
                     Err(nom::Err::Error(_e)) => Err(DltParseError::ParsingHickup {
                         reason: format!("parsing error for dlt message info: {:?}", _e),
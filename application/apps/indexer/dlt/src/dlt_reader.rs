@@ -0,0 +1,104 @@
+// Copyright (c) 2020 E.S.R.Labs. All rights reserved.
+//
+// NOTICE:  All information contained herein is, and remains
+// the property of E.S.R.Labs and its suppliers, if any.
+// The intellectual and technical concepts contained herein are
+// proprietary to E.S.R.Labs and its suppliers and may be covered
+// by German and Foreign Patents, patents in process, and are protected
+// by trade secret or copyright law.
+// Dissemination of this information or reproduction of this material
+// is strictly forbidden unless prior written permission is obtained
+// from E.S.R.Labs.
+use buf_redux::policy::MinBuffered;
+use buf_redux::BufReader as ReduxReader;
+use indexer_base::input::Input;
+use memmap::Mmap;
+use std::io::{self, BufRead, Seek, SeekFrom};
+
+/// gives access to the content of a dlt file
+///
+/// by default the input is read through a buffer. Memory mapped files let the
+/// parser work directly on the file content without copying it, but a file
+/// that is truncated or rotated while it is mapped crashes the process
+/// (SIGBUS), so mapping has to be requested explicitly.
+pub(crate) enum DltFileReader {
+    Mapped { map: Mmap, position: usize },
+    Buffered(ReduxReader<Input, MinBuffered>),
+}
+
+impl DltFileReader {
+    pub(crate) fn new(input: Input) -> DltFileReader {
+        DltFileReader::Buffered(
+            ReduxReader::with_capacity(10 * 1024 * 1024, input).set_policy(MinBuffered(10 * 1024)),
+        )
+    }
+
+    /// map regular files that do not change while they are read
+    /// if the input cannot be mapped (e.g. it is empty, a named pipe or stdin)
+    /// we fall back to a buffered reader
+    pub(crate) fn memory_mapped(input: Input) -> DltFileReader {
+        if let Input::File(file) = &input {
+            if let Ok(metadata) = file.metadata() {
+                if metadata.is_file() && metadata.len() > 0 {
+                    // the file must not be truncated while it is mapped
+                    match unsafe { Mmap::map(file) } {
+                        Ok(map) => return DltFileReader::Mapped { map, position: 0 },
+                        Err(e) => debug!("could not map file, use buffered reader ({})", e),
+                    }
+                }
+            }
+        }
+        DltFileReader::new(input)
+    }
+
+    /// the content that was not consumed yet
+    /// for a buffered reader this is only the currently buffered part
+    pub(crate) fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        match self {
+            DltFileReader::Mapped { map, position } => Ok(&map[*position..]),
+            DltFileReader::Buffered(reader) => reader.fill_buf(),
+        }
+    }
+
+    /// true if the content reaches up to the end of the file
+    pub(crate) fn is_complete(&self) -> bool {
+        match self {
+            DltFileReader::Mapped { .. } => true,
+            DltFileReader::Buffered(_) => false,
+        }
+    }
+
+    /// try to make more content available
+    /// returns false if nothing more could be read (end of file or full buffer)
+    pub(crate) fn fill_more(&mut self) -> io::Result<bool> {
+        match self {
+            DltFileReader::Mapped { .. } => Ok(false),
+            DltFileReader::Buffered(reader) => {
+                reader.make_room();
+                Ok(reader.read_into_buf()? > 0)
+            }
+        }
+    }
+
+    pub(crate) fn consume(&mut self, amount: usize) {
+        match self {
+            DltFileReader::Mapped { map, position } => {
+                *position = (*position + amount).min(map.len())
+            }
+            DltFileReader::Buffered(reader) => reader.consume(amount),
+        }
+    }
+
+    /// continue reading at a byte offset of the file
+    pub(crate) fn seek(&mut self, offset: u64) -> io::Result<()> {
+        match self {
+            DltFileReader::Mapped { map, position } => {
+                *position = (offset as usize).min(map.len());
+            }
+            DltFileReader::Buffered(reader) => {
+                reader.seek(SeekFrom::Start(offset))?;
+            }
+        }
+        Ok(())
+    }
+}
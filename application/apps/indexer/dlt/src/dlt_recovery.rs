@@ -0,0 +1,181 @@
+// Copyright (c) 2020 E.S.R.Labs. All rights reserved.
+//
+// NOTICE:  All information contained herein is, and remains
+// the property of E.S.R.Labs and its suppliers, if any.
+// The intellectual and technical concepts contained herein are
+// proprietary to E.S.R.Labs and its suppliers and may be covered
+// by German and Foreign Patents, patents in process, and are protected
+// by trade secret or copyright law.
+// Dissemination of this information or reproduction of this material
+// is strictly forbidden unless prior written permission is obtained
+// from E.S.R.Labs.
+//! Recovery from corrupted dlt files
+//!
+//! Instead of trusting the first `DLT\x01` pattern, a storage header is only
+//! accepted as start of the next message if the headers are sane and the
+//! message either ends where the next storage header starts or does not
+//! swallow another plausible message.
+use crate::dlt::{peek_message_length, MESSAGE_LENGTH_PEEK_LENGTH, STORAGE_HEADER_LENGTH};
+use crate::dlt_parse::find_storage_header_pattern;
+use crossbeam_channel as cc;
+use indexer_base::progress::{IndexingResults, Notification, Severity};
+
+const DLT_PATTERN: &[u8] = &[0x44, 0x4C, 0x54, 0x01];
+/// storage header and the start of the standard header up to the length
+const HEADER_PEEK_LENGTH: usize = STORAGE_HEADER_LENGTH + MESSAGE_LENGTH_PEEK_LENGTH;
+
+/// where the next message starts
+#[derive(Debug, PartialEq)]
+pub(crate) enum Resync {
+    /// a plausible storage header was found at this position
+    Found(usize),
+    /// there is no plausible storage header in the rest of the content
+    NotFound,
+    /// we cannot decide without seeing more content
+    NeedMore,
+}
+
+#[derive(Debug, PartialEq)]
+enum HeaderCheck {
+    /// headers look fine, the message would end at this position
+    Sane(usize),
+    Insane,
+    /// the headers are cut off at the end of the file
+    Truncated,
+    NeedMore,
+}
+
+#[derive(Debug, PartialEq)]
+enum Chained {
+    Yes,
+    No,
+    NeedMore,
+}
+
+/// find the next storage header that plausibly starts a message
+/// `at_end` is true if the content reaches to the end of the input
+pub(crate) fn next_plausible_storage_header(content: &[u8], at_end: bool) -> Resync {
+    let mut offset = 0usize;
+    while let Some(i) = find_storage_header_pattern(&content[offset..]) {
+        let candidate = offset + i;
+        match plausible(content, candidate, at_end) {
+            Chained::Yes => return Resync::Found(candidate),
+            Chained::NeedMore => return Resync::NeedMore,
+            Chained::No => offset = candidate + 1,
+        }
+    }
+    if at_end {
+        Resync::NotFound
+    } else {
+        Resync::NeedMore
+    }
+}
+
+/// report bytes of the input that could not be used
+pub(crate) fn report_skipped<T>(
+    update_channel: &cc::Sender<IndexingResults<T>>,
+    reason: &str,
+    from: usize,
+    to: usize,
+) {
+    warn!("{}: skipped bytes {}..{}", reason, from, to);
+    let _ = update_channel.send(Err(Notification {
+        severity: Severity::WARNING,
+        content: format!(
+            "{}: skipped {} bytes (offset {}..{})",
+            reason,
+            to - from,
+            from,
+            to
+        ),
+        line: None,
+    }));
+}
+
+fn check_header(content: &[u8], position: usize, at_end: bool) -> HeaderCheck {
+    if position + HEADER_PEEK_LENGTH > content.len() {
+        return if at_end {
+            HeaderCheck::Truncated
+        } else {
+            HeaderCheck::NeedMore
+        };
+    }
+    if &content[position..position + DLT_PATTERN.len()] != DLT_PATTERN {
+        return HeaderCheck::Insane;
+    }
+    match peek_message_length(&content[position + STORAGE_HEADER_LENGTH..]) {
+        Some(overall_length) => {
+            HeaderCheck::Sane(position + STORAGE_HEADER_LENGTH + overall_length as usize)
+        }
+        None => HeaderCheck::Insane,
+    }
+}
+
+/// true if the message at `position` is directly followed by the next storage header
+fn chained(content: &[u8], position: usize, at_end: bool) -> Chained {
+    match check_header(content, position, at_end) {
+        HeaderCheck::Sane(end) => {
+            if end == content.len() {
+                Chained::Yes
+            } else if end > content.len() {
+                if at_end {
+                    Chained::No
+                } else {
+                    Chained::NeedMore
+                }
+            } else if end + DLT_PATTERN.len() <= content.len() {
+                if content[end..].starts_with(DLT_PATTERN) {
+                    Chained::Yes
+                } else {
+                    Chained::No
+                }
+            } else if at_end {
+                // the next message is cut off right in the pattern
+                if DLT_PATTERN.starts_with(&content[end..]) {
+                    Chained::Yes
+                } else {
+                    Chained::No
+                }
+            } else {
+                Chained::NeedMore
+            }
+        }
+        HeaderCheck::NeedMore => Chained::NeedMore,
+        HeaderCheck::Insane | HeaderCheck::Truncated => Chained::No,
+    }
+}
+
+/// decide if a message plausibly starts at `position`
+fn plausible(content: &[u8], position: usize, at_end: bool) -> Chained {
+    let end = match check_header(content, position, at_end) {
+        HeaderCheck::Sane(end) => end,
+        // the last message of the file, nothing to verify
+        HeaderCheck::Truncated => return Chained::Yes,
+        HeaderCheck::Insane => return Chained::No,
+        HeaderCheck::NeedMore => return Chained::NeedMore,
+    };
+    match chained(content, position, at_end) {
+        Chained::No => (),
+        other => return other,
+    }
+    // the length might be broken or the message is followed by garbage:
+    // it is only accepted if it does not contain the start of another message
+    let search_end = end + DLT_PATTERN.len() - 1;
+    if search_end > content.len() && !at_end {
+        return Chained::NeedMore;
+    }
+    let inner = &content[..search_end.min(content.len())];
+    let mut offset = position + 1;
+    while let Some(i) = find_storage_header_pattern(&inner[offset..]) {
+        let candidate = offset + i;
+        if candidate >= end {
+            break;
+        }
+        match chained(content, candidate, at_end) {
+            Chained::Yes => return Chained::No,
+            Chained::NeedMore => return Chained::NeedMore,
+            Chained::No => offset = candidate + 1,
+        }
+    }
+    Chained::Yes
+}
@@ -0,0 +1,181 @@
+// Copyright (c) 2020 E.S.R.Labs. All rights reserved.
+//
+// NOTICE:  All information contained herein is, and remains
+// the property of E.S.R.Labs and its suppliers, if any.
+// The intellectual and technical concepts contained herein are
+// proprietary to E.S.R.Labs and its suppliers and may be covered
+// by German and Foreign Patents, patents in process, and are protected
+// by trade secret or copyright law.
+// Dissemination of this information or reproduction of this material
+// is strictly forbidden unless prior written permission is obtained
+// from E.S.R.Labs.
+//! Absolute time of dlt messages
+//!
+//! The timestamp of the standard header counts 0.1 ms ticks since the ECU
+//! started. The lifecycles of the ECUs and their estimated starts come from
+//! the `LifecycleDetector` (see `dlt_lifecycle`), the absolute time of a
+//! message is the start of its lifecycle plus its ticks.
+//!
+//! The absolute time is used for the `abstime` column of the csv export and,
+//! if it is requested, added as last column of indexed lines. Merged files
+//! keep the columns of the indexed lines.
+//!
+//! A file is read twice, so every message is corrected with the final
+//! estimate for the start of its ECU. A stream can only be read once, the
+//! time model learns from all messages before they are filtered.
+use crate::dlt::{DltTimeStamp, Message, DLT_COLUMN_SENTINAL};
+use crate::dlt_counter::{MessageCounter, Sender};
+use crate::dlt_lifecycle::{ecu_id, start_bound, LifecycleDetector, MICROS_PER_TICK};
+use crate::dlt_offsets::source_length;
+use crate::dlt_parse::{DltParseError, FileMessageProducer, MessageProducer};
+use crate::filtering::ProcessedDltFilterConfig;
+use crossbeam_channel as cc;
+use failure::{err_msg, Error};
+use indexer_base::chunks::ChunkResults;
+use indexer_base::progress::ProgressReporter;
+use std::fmt;
+
+/// estimates the start time of every ECU from the messages it has seen
+///
+/// `observe` gives the absolute time based on the messages seen so far and
+/// can be used while reading a stream. `absolute_time` uses everything that
+/// was observed and is more precise, e.g. after a first pass over a file.
+#[derive(Default)]
+pub struct TimeModel {
+    detector: LifecycleDetector,
+    line_nr: usize,
+    offset: usize,
+}
+
+impl TimeModel {
+    pub fn new() -> TimeModel {
+        Default::default()
+    }
+
+    pub fn observe(&mut self, msg: &Message) -> Option<DltTimeStamp> {
+        let lifecycle = self.detector.add(msg, self.line_nr, self.offset);
+        self.line_nr += 1;
+        self.offset += source_length(msg);
+        let ticks = msg.header.timestamp?;
+        let start = self.detector.start_estimate(lifecycle)?;
+        to_timestamp(start + i64::from(ticks) * MICROS_PER_TICK)
+    }
+
+    pub fn absolute_time(&self, msg: &Message) -> Option<DltTimeStamp> {
+        let ticks = msg.header.timestamp?;
+        let start = self
+            .detector
+            .start_estimate_before(ecu_id(msg), start_bound(msg)?)?;
+        to_timestamp(start + i64::from(ticks) * MICROS_PER_TICK)
+    }
+}
+
+/// the absolute times of the messages that are indexed or exported
+pub(crate) struct AbsoluteTimes {
+    model: TimeModel,
+    /// the model knows the whole file (see `time_model_for_file`), otherwise
+    /// it learns while a stream is read
+    calibrated: bool,
+    /// only applied after a message was observed
+    stream_filter: Option<ProcessedDltFilterConfig>,
+}
+impl AbsoluteTimes {
+    pub(crate) fn calibrated(model: TimeModel) -> AbsoluteTimes {
+        AbsoluteTimes {
+            model,
+            calibrated: true,
+            stream_filter: None,
+        }
+    }
+
+    pub(crate) fn observed(stream_filter: Option<ProcessedDltFilterConfig>) -> AbsoluteTimes {
+        AbsoluteTimes {
+            model: TimeModel::new(),
+            calibrated: false,
+            stream_filter,
+        }
+    }
+
+    pub(crate) fn absolute_time(&mut self, msg: &Message) -> Option<DltTimeStamp> {
+        if self.calibrated {
+            self.model.absolute_time(msg)
+        } else {
+            self.model.observe(msg)
+        }
+    }
+
+    /// true if the message is left out by the filter of a stream
+    pub(crate) fn excludes(&self, msg: &Message) -> bool {
+        self.stream_filter
+            .as_ref()
+            .is_some_and(|filter| filter.excludes_message(msg))
+    }
+}
+
+/// a message with its absolute time (us since epoch) as additional last
+/// column of the indexed line, the column is empty if the time is unknown
+pub(crate) struct TimedMessage {
+    pub(crate) msg: Message,
+    pub(crate) absolute_time: Option<DltTimeStamp>,
+}
+impl fmt::Display for TimedMessage {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}{}", self.msg, DLT_COLUMN_SENTINAL)?;
+        match &self.absolute_time {
+            Some(time) => write!(f, "{}", time.as_micros()),
+            None => Ok(()),
+        }
+    }
+}
+impl MessageCounter for TimedMessage {
+    fn message_counter(&self) -> (Sender<'_>, u8) {
+        self.msg.message_counter()
+    }
+}
+
+fn to_timestamp(micros: i64) -> Option<DltTimeStamp> {
+    if micros < 0 || micros / 1_000_000 > i64::from(u32::MAX) {
+        return None;
+    }
+    Some(DltTimeStamp {
+        seconds: (micros / 1_000_000) as u32,
+        microseconds: (micros % 1_000_000) as u32,
+    })
+}
+
+/// observe all messages of a dlt file
+/// the consumed bytes are reported to `progress_reporter`, returns `None`
+/// when a shutdown was received
+pub fn time_model_for_file(
+    in_file: &std::path::PathBuf,
+    update_channel: &cc::Sender<ChunkResults>,
+    progress_reporter: &mut ProgressReporter,
+    shutdown_receiver: Option<&cc::Receiver<()>>,
+) -> Result<Option<TimeModel>, Error> {
+    let mut producer = FileMessageProducer::new(in_file, None, 0, update_channel.clone(), true)?;
+    let mut model = TimeModel::new();
+    loop {
+        if let Some(rx) = shutdown_receiver {
+            if let Ok(()) = rx.try_recv() {
+                info!("shutdown received while reading the time model");
+                return Ok(None);
+            }
+        }
+        let (consumed, next) = producer.produce_next_message(None);
+        if consumed == 0 {
+            break;
+        }
+        progress_reporter.make_progress(consumed);
+        match next {
+            Ok(Some(msg)) => {
+                model.observe(&msg);
+            }
+            Ok(None) | Err(DltParseError::ParsingHickup { .. }) => (),
+            Err(DltParseError::IncompleteParse { .. }) => break,
+            Err(DltParseError::Unrecoverable { cause }) => {
+                return Err(err_msg(format!("error parsing dlt file: {}", cause)))
+            }
+        }
+    }
+    Ok(Some(model))
+}
@@ -3,6 +3,7 @@ use std::{
     io::{BufRead, BufReader},
     path::{PathBuf, Path},
     mem,
+    fmt,
     fs::File,
     rc::Rc,
 };
@@ -12,8 +13,12 @@ use quick_xml::{
 };
 use std::collections::HashMap;
 use std::collections::hash_map::Entry;
+use std::hash::Hash;
 use derive_more::{Deref, Display};
 use crate::dlt::{TypeInfo, TypeInfoKind, TypeLength, StringCoding, FloatWidth};
+use crate::someip::{is_service_mapping, read_service_mapping, ServiceMetadata};
+use crate::can::{is_can_database, read_can_database, CanMessageMetadata};
+use crate::arxml::{is_arxml, read_arxmls_with_report};
 
 type Result<T = ()> = std::result::Result<T, Error>;
 
@@ -21,6 +26,36 @@ type Result<T = ()> = std::result::Result<T, Error>;
 pub struct FibexMetadata {
     pub(crate) frame_map_with_key: HashMap<(ContextId, ApplicationId, FrameId), Rc<FrameMetadata>>, // TODO: avoid cloning on .get
     pub(crate) frame_map: HashMap<FrameId, Rc<FrameMetadata>>,
+    pub(crate) services: HashMap<u16, Rc<ServiceMetadata>>,
+    pub(crate) can_messages: HashMap<u32, Rc<CanMessageMetadata>>,
+}
+impl FibexMetadata {
+    /// the frame of a non verbose message, messages with extended header are
+    /// looked up with their application and context id
+    pub fn frame(
+        &self,
+        app_id_context_id: Option<(&str, &str)>,
+        id: u32,
+    ) -> Option<&Rc<FrameMetadata>> {
+        let id = FrameId(format!("ID_{}", id));
+        match app_id_context_id {
+            Some((app_id, context_id)) => self.frame_map_with_key.get(&(
+                ContextId(context_id.to_string()),
+                ApplicationId(app_id.to_string()),
+                id,
+            )), // TODO: avoid cloning here (Cow or Borrow)
+            None => self.frame_map.get(&id),
+        }
+    }
+    /// the SOME/IP service with the given id
+    pub fn service(&self, id: u16) -> Option<&Rc<ServiceMetadata>> {
+        self.services.get(&id)
+    }
+    /// the CAN message of a DBC file, extended ids have
+    /// `CAN_EXTENDED_ID_FLAG` set
+    pub fn can_message(&self, id: u32) -> Option<&Rc<CanMessageMetadata>> {
+        self.can_messages.get(&id)
+    }
 }
 #[derive(Debug, PartialEq, Clone)]
 pub struct FrameMetadata {
@@ -34,7 +69,98 @@ pub struct FrameMetadata {
 #[derive(Debug, PartialEq, Clone)]
 pub struct PduMetadata {
     pub description: Option<String>,
-    pub signal_types: Vec<TypeInfo>,
+    pub signals: Vec<SignalMetadata>,
+}
+#[derive(Debug, PartialEq, Clone)]
+pub struct SignalMetadata {
+    pub type_info: TypeInfo,
+    /// converts the raw value into its physical value or a name
+    pub compu_method: Option<Rc<CompuMethod>>,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum CompuCategory {
+    Identical,
+    TextTable,
+    Linear,
+    ScaleLinear,
+    ScaleLinearAndTextTable,
+}
+impl CompuCategory {
+    pub(crate) fn from_name(name: &str) -> Option<CompuCategory> {
+        match name {
+            "IDENTICAL" => Some(CompuCategory::Identical),
+            "TEXTTABLE" => Some(CompuCategory::TextTable),
+            "LINEAR" => Some(CompuCategory::Linear),
+            "SCALE-LINEAR" => Some(CompuCategory::ScaleLinear),
+            "SCALE-LINEAR-AND-TEXTTABLE" => Some(CompuCategory::ScaleLinearAndTextTable),
+            _ => None,
+        }
+    }
+}
+/// a range of raw values that is converted to a text (`COMPU-CONST`) or with
+/// rational coefficients: `(n0 + n1 * x + ...) / (d0 + d1 * x + ...)`
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct CompuScale {
+    pub lower_limit: Option<f64>,
+    pub upper_limit: Option<f64>,
+    pub text: Option<String>,
+    pub numerator: Vec<f64>,
+    pub denominator: Vec<f64>,
+}
+impl CompuScale {
+    fn contains(&self, raw: f64) -> bool {
+        !matches!(self.lower_limit, Some(l) if raw < l)
+            && !matches!(self.upper_limit, Some(u) if raw > u)
+    }
+    fn convert(&self, raw: f64) -> Option<f64> {
+        if self.numerator.is_empty() {
+            return None;
+        }
+        let polynomial = |coeffs: &[f64]| coeffs.iter().rev().fold(0.0, |acc, c| acc * raw + c);
+        let denominator = if self.denominator.is_empty() {
+            1.0
+        } else {
+            polynomial(&self.denominator)
+        };
+        if denominator == 0.0 {
+            return None;
+        }
+        Some(polynomial(&self.numerator) / denominator)
+    }
+}
+#[derive(Debug, PartialEq, Clone)]
+pub struct CompuMethod {
+    pub category: CompuCategory,
+    pub scales: Vec<CompuScale>,
+    /// display name of the unit of the physical value
+    pub unit: Option<String>,
+}
+/// a raw value converted by a `CompuMethod`
+#[derive(Debug, PartialEq, Clone)]
+pub enum PhysicalValue {
+    /// in the unit of the method
+    Number(f64),
+    /// the name of the raw value from a text table
+    Text(String),
+}
+impl CompuMethod {
+    /// the physical value or the name of a raw value
+    /// `None` if the method does not define a value for `raw`
+    pub fn convert(&self, raw: f64) -> Option<PhysicalValue> {
+        let physical = match self.category {
+            CompuCategory::Identical => raw,
+            _ => {
+                let scale = self.scales.iter().find(|s| s.contains(raw))?;
+                if let Some(text) = &scale.text {
+                    return Some(PhysicalValue::Text(text.clone()));
+                }
+                scale.convert(raw)?
+            }
+        };
+        // avoid printing rounding errors like 23.500000000000004
+        Some(PhysicalValue::Number((physical * 1e9).round() / 1e9))
+    }
 }
 
 #[derive(Hash, PartialEq, Eq, Clone, Debug, Deref, Display)]
@@ -46,8 +172,49 @@ pub struct ContextId(pub String);
 #[derive(Hash, PartialEq, Eq, Clone, Debug, Deref, Display)]
 pub struct ApplicationId(pub String);
 
-fn type_info_for_signal_ref(signal_ref: String) -> TypeInfo {
-    match signal_ref.as_ref() {
+/// an element of a FIBEX file that can't be used (completely)
+#[derive(Debug, PartialEq, Clone)]
+pub struct FibexWarning {
+    pub file: PathBuf,
+    /// name of the xml element, e.g. `PDU`
+    pub element: String,
+    /// the ID of the element if it has one
+    pub id: Option<String>,
+    pub line_and_column: Option<(usize, usize)>,
+    pub reason: String,
+}
+impl fmt::Display for FibexWarning {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.file.display())?;
+        if let Some((line, column)) = self.line_and_column {
+            write!(f, ":{}:{}", line, column)?;
+        }
+        write!(f, ": {}", self.element)?;
+        if let Some(id) = &self.id {
+            write!(f, " {}", id)?;
+        }
+        write!(f, ": {}", self.reason)
+    }
+}
+
+/// the usable metadata of FIBEX files and the warnings about the elements
+/// that were left out
+#[derive(Debug)]
+pub struct FibexReport {
+    pub metadata: FibexMetadata,
+    /// number of frames that can be decoded
+    pub frames: usize,
+    /// number of PDUs that can be decoded
+    pub pdus: usize,
+    /// number of SOME/IP services of service mappings
+    pub services: usize,
+    /// number of CAN messages of DBC files
+    pub can_messages: usize,
+    pub warnings: Vec<FibexWarning>,
+}
+
+fn type_info_for_signal_ref(signal_ref: &str) -> Option<TypeInfo> {
+    Some(match signal_ref {
         "S_BOOL" => TypeInfo {
             kind: TypeInfoKind::Bool,
             coding: StringCoding::ASCII,
@@ -102,7 +269,24 @@ fn type_info_for_signal_ref(signal_ref: String) -> TypeInfo {
             has_variable_info: false,
             has_trace_info: false,
         },
-        "S_FLOA16" => unimplemented!("16-bit float not supported"),
+        "S_SINT128" => TypeInfo {
+            kind: TypeInfoKind::Signed(TypeLength::BitLength128),
+            coding: StringCoding::ASCII,
+            has_variable_info: false,
+            has_trace_info: false,
+        },
+        "S_UINT128" => TypeInfo {
+            kind: TypeInfoKind::Unsigned(TypeLength::BitLength128),
+            coding: StringCoding::ASCII,
+            has_variable_info: false,
+            has_trace_info: false,
+        },
+        "S_FLOA16" => TypeInfo {
+            kind: TypeInfoKind::Float(FloatWidth::Width16),
+            coding: StringCoding::ASCII,
+            has_variable_info: false,
+            has_trace_info: false,
+        },
         "S_FLOA32" => TypeInfo {
             kind: TypeInfoKind::Float(FloatWidth::Width32),
             coding: StringCoding::ASCII,
@@ -133,44 +317,188 @@ fn type_info_for_signal_ref(signal_ref: String) -> TypeInfo {
             has_variable_info: false,
             has_trace_info: false,
         },
-        s => unimplemented!("{}", s),
+        _ => return None,
+    })
+}
+
+/// type of a signal from the `BASE-DATA-TYPE` of its coding
+fn type_info_for_base_data_type(base_data_type: &str) -> Option<TypeInfo> {
+    let kind = match base_data_type {
+        "A_BOOLEAN" => TypeInfoKind::Bool,
+        "A_INT8" => TypeInfoKind::Signed(TypeLength::BitLength8),
+        "A_UINT8" => TypeInfoKind::Unsigned(TypeLength::BitLength8),
+        "A_INT16" => TypeInfoKind::Signed(TypeLength::BitLength16),
+        "A_UINT16" => TypeInfoKind::Unsigned(TypeLength::BitLength16),
+        "A_INT32" => TypeInfoKind::Signed(TypeLength::BitLength32),
+        "A_UINT32" => TypeInfoKind::Unsigned(TypeLength::BitLength32),
+        "A_INT64" => TypeInfoKind::Signed(TypeLength::BitLength64),
+        "A_UINT64" => TypeInfoKind::Unsigned(TypeLength::BitLength64),
+        "A_FLOAT32" => TypeInfoKind::Float(FloatWidth::Width32),
+        "A_FLOAT64" => TypeInfoKind::Float(FloatWidth::Width64),
+        "A_ASCIISTRING" | "A_UNICODE2STRING" => TypeInfoKind::StringType,
+        "A_BYTEFIELD" => TypeInfoKind::Raw,
+        _ => return None,
+    };
+    Some(TypeInfo {
+        kind,
+        coding: if base_data_type == "A_UNICODE2STRING" {
+            StringCoding::UTF8
+        } else {
+            StringCoding::ASCII
+        },
+        has_variable_info: false,
+        has_trace_info: false,
+    })
+}
+
+#[derive(Default, PartialEq)]
+struct CodingReadData {
+    base_data_type: Option<String>,
+    compu_method: Option<(CompuCategory, Option<String>, Vec<CompuScale>)>,
+    /// category of a COMPU-METHOD that can't be used
+    unsupported_category: Option<String>,
+}
+
+/// signals that are declared with a coding, all others are referenced
+/// by the name of their type (e.g. `S_UINT32`)
+#[derive(Default)]
+struct SignalCodings {
+    signals: HashMap<String, String>,
+    codings: HashMap<String, CodingReadData>,
+    units: HashMap<String, String>,
+}
+impl SignalCodings {
+    fn signal(&self, signal_ref: String) -> Result<SignalMetadata> {
+        let coding = match self.signals.get(&signal_ref) {
+            Some(coding_ref) => self.codings.get(coding_ref).ok_or_else(|| {
+                format_err!("coding {} of signal {} not found", coding_ref, signal_ref)
+            })?,
+            None => {
+                return Ok(SignalMetadata {
+                    type_info: type_info_for_signal_ref(&signal_ref)
+                        .ok_or_else(|| format_err!("unsupported signal type {}", signal_ref))?,
+                    compu_method: None,
+                })
+            }
+        };
+        let type_info = match &coding.base_data_type {
+            Some(base_data_type) => {
+                type_info_for_base_data_type(base_data_type).ok_or_else(|| {
+                    format_err!(
+                        "unsupported base data type {} for signal {}",
+                        base_data_type,
+                        signal_ref
+                    )
+                })?
+            }
+            None => bail!("coding of signal {} has no base data type", signal_ref),
+        };
+        let compu_method = match &coding.compu_method {
+            Some((CompuCategory::Identical, None, _)) | None => None,
+            Some((category, unit_ref, scales)) => Some(Rc::new(CompuMethod {
+                category: category.clone(),
+                scales: scales.clone(),
+                unit: unit_ref
+                    .as_ref()
+                    .and_then(|unit_ref| self.units.get(unit_ref).cloned()),
+            })),
+        };
+        Ok(SignalMetadata {
+            type_info,
+            compu_method,
+        })
     }
 }
 
+/// reads the metadata of FIBEX files, the elements that can't be used are
+/// left out with a warning in the log
+/// only files that can't be opened are an error
 pub fn read_fibexes(files: Vec<PathBuf>) -> Result<FibexMetadata> {
-    let mut frames = vec![];
-    let mut frame_map_with_key = HashMap::new();
-    let mut frame_map = HashMap::new();
-    let mut pdu_by_id = HashMap::new();
+    let report = read_fibexes_with_report(files)?;
+    for warning in &report.warnings {
+        warn!("{}", warning);
+    }
+    Ok(report.metadata)
+}
+
+/// reads the metadata of FIBEX files and reports the elements that can't be
+/// used: unsupported or incomplete elements are skipped (together with the
+/// PDUs and frames that need them), errors in the xml syntax stop the
+/// reading of the file
+/// files with the extension `json` are read as SOME/IP service mapping, those
+/// with the extension `dbc` as CAN database and those with the extension
+/// `arxml` as ARXML (see `read_arxmls_with_report`), frames of ARXML files are
+/// only used when no FIBEX file has a frame with the same ids
+pub fn read_fibexes_with_report(files: Vec<PathBuf>) -> Result<FibexReport> {
+    let mut data = FibexReadData::default();
+    let mut warnings = vec![];
+    let mut services = HashMap::new();
+    let mut can_messages = HashMap::new();
+    let mut located_warnings = vec![];
+    let mut arxml_files = vec![];
     for f in files {
+        if is_arxml(&f) {
+            arxml_files.push(f);
+            continue;
+        }
+        if is_service_mapping(&f) {
+            read_service_mapping(&f, &mut services, &mut located_warnings)?;
+            continue;
+        }
+        if is_can_database(&f) {
+            read_can_database(&f, &mut can_messages, &mut located_warnings)?;
+            continue;
+        }
         let mut reader = Reader::from_file(f)?;
-        loop {
-            match reader.read_event()? {
-                Event::PduStart { id } => {
-                    let (description, signal_refs) = read_pdu(&mut reader)?;
-                    match pdu_by_id.entry(id.clone()) {
-                        Entry::Occupied(_) => warn!("duplicate PDU ID {} found in fibexes", id),
-                        Entry::Vacant(v) => {
-                            v.insert(Rc::new(PduMetadata {
-                                description,
-                                signal_types: signal_refs
-                                    .into_iter()
-                                    .map(type_info_for_signal_ref)
-                                    .collect(),
-                            }));
-                        }
-                    }
-                }
-                Event::FrameStart { id } => {
-                    frames.push((FrameId(id), read_frame(&mut reader)?));
-                }
-                Event::Eof => break,
-                _ => {}
+        if let Err(e) = read_fibex(&mut reader, &mut data, &mut warnings) {
+            warnings.push(reader.warning(format!("reading of the file stopped: {}", e)));
+        }
+    }
+    let FibexReadData {
+        pdus,
+        frames,
+        signal_codings,
+    } = data;
+    // files can define the same element again, the first definition is used
+    let pdus = first_definitions(pdus, B_PDU, |id, _| id.clone(), &mut warnings);
+    let frames = first_definitions(
+        frames,
+        B_FRAME,
+        |id, frame| {
+            (
+                id.clone(),
+                frame.context_id.clone(),
+                frame.application_id.clone(),
+            )
+        },
+        &mut warnings,
+    );
+    // signals, codings and units can be defined after the PDUs that use them
+    let mut pdu_by_id = HashMap::new();
+    for (id, origin, (description, signal_refs)) in pdus {
+        match signal_refs
+            .into_iter()
+            .map(|r| signal_codings.signal(r))
+            .collect::<Result<Vec<_>>>()
+        {
+            Ok(signals) => {
+                pdu_by_id.insert(
+                    id,
+                    Rc::new(PduMetadata {
+                        description,
+                        signals,
+                    }),
+                );
             }
+            Err(e) => warnings.push(origin.warning(B_PDU, &id, format!("skipped: {}", e))),
         }
     }
+    let mut frame_map_with_key = HashMap::new();
+    let mut frame_map = HashMap::new();
+    let mut frame_count = 0;
     for (
         id,
+        origin,
         FrameReadData {
             short_name,
             context_id,
@@ -181,70 +509,412 @@ pub fn read_fibexes(files: Vec<PathBuf>) -> Result<FibexMetadata> {
         },
     ) in frames
     {
+        let pdus = match pdu_refs
+            .iter()
+            .map(|r| {
+                pdu_by_id
+                    .get(r)
+                    .cloned()
+                    .ok_or_else(|| format_err!("PDU {} not found", r))
+            })
+            .collect::<Result<Vec<_>>>()
+        {
+            Ok(pdus) => pdus,
+            Err(e) => {
+                warnings.push(origin.warning(B_FRAME, &id, format!("skipped: {}", e)));
+                continue;
+            }
+        };
         let frame = Rc::new(FrameMetadata {
             short_name,
-            pdus: pdu_refs
-                .into_iter()
-                .map(|r| {
-                    pdu_by_id
-                        .get(&r)
-                        .cloned()
-                        .ok_or_else(|| format_err!("pdu {} not found", &r))
-                })
-                .collect::<Result<Vec<_>>>()?,
+            pdus,
             application_id,
             context_id,
             message_type,
             message_info,
         });
-        if let (Some(context_id), Some(application_id)) =
+        let id = FrameId(id);
+        // frames are unique per context and application id after
+        // `first_definitions`, only frames without those ids can collide
+        let with_key = if let (Some(context_id), Some(application_id)) =
             (frame.context_id.clone(), frame.application_id.clone())
         {
-            let key = (context_id, application_id, id.clone());
-
-            match frame_map_with_key.entry(key.clone()) {
-                Entry::Occupied(_) => warn!(
-                    "duplicate Frame context_id={} application_id={} id={}",
-                    key.0, key.1, key.2
-                ),
-                Entry::Vacant(entry) => {
-                    entry.insert(frame.clone());
+            frame_map_with_key.insert((context_id, application_id, id.clone()), frame.clone());
+            true
+        } else {
+            false
+        };
+        match frame_map.entry(id) {
+            Entry::Occupied(entry) => {
+                if !with_key {
+                    warnings.push(origin.warning(
+                        B_FRAME,
+                        entry.key(),
+                        "skipped: ID is used by another frame".to_string(),
+                    ));
+                    continue;
                 }
             }
-        } // else error?
-        match frame_map.entry(id.clone()) {
-            Entry::Occupied(_) => warn!("duplicate Frame id={}", id),
             Entry::Vacant(entry) => {
                 entry.insert(frame);
             }
         }
+        frame_count += 1;
     }
-    Ok(FibexMetadata {
-        frame_map_with_key,
-        frame_map,
+    let mut pdu_count = pdu_by_id.len();
+    if !arxml_files.is_empty() {
+        let mut arxml = read_arxmls_with_report(arxml_files)?;
+        for (key, frame) in arxml.metadata.frame_map_with_key {
+            frame_map_with_key.entry(key).or_insert(frame);
+        }
+        for (id, frame) in arxml.metadata.frame_map {
+            frame_map.entry(id).or_insert(frame);
+        }
+        frame_count += arxml.frames;
+        pdu_count += arxml.pdus;
+        located_warnings.append(&mut arxml.warnings);
+    }
+    let mut warnings = locate_warnings(warnings);
+    warnings.append(&mut located_warnings);
+    let service_count = services.len();
+    let can_message_count = can_messages.len();
+    Ok(FibexReport {
+        metadata: FibexMetadata {
+            frame_map_with_key,
+            frame_map,
+            services,
+            can_messages,
+        },
+        frames: frame_count,
+        pdus: pdu_count,
+        services: service_count,
+        can_messages: can_message_count,
+        warnings,
     })
 }
 
-fn read_pdu(reader: &mut Reader<BufReader<File>>) -> Result<(Option<String>, Vec<String>)> {
-    let mut signal_refs = vec![];
+/// leaves out repeated definitions of elements with the same key, those that
+/// differ from the first definition are reported as conflict
+pub(crate) fn first_definitions<K: Eq + Hash, T: PartialEq>(
+    elements: Vec<(String, Origin, T)>,
+    element: &[u8],
+    key: impl Fn(&String, &T) -> K,
+    warnings: &mut Vec<PositionedWarning>,
+) -> Vec<(String, Origin, T)> {
+    let mut first_by_key: HashMap<K, usize> = HashMap::new();
+    let mut firsts: Vec<(String, Origin, T)> = vec![];
+    for (id, origin, content) in elements {
+        match first_by_key.entry(key(&id, &content)) {
+            Entry::Occupied(entry) => {
+                let (_, first_origin, first_content) = &firsts[*entry.get()];
+                if *first_content != content {
+                    warnings.push(origin.warning(
+                        element,
+                        &id,
+                        format!(
+                            "skipped: conflicts with the definition in {}",
+                            first_origin.file.display()
+                        ),
+                    ));
+                }
+            }
+            Entry::Vacant(entry) => {
+                entry.insert(firsts.len());
+                firsts.push((id, origin, content));
+            }
+        }
+    }
+    firsts
+}
+
+/// adds a definition of an element unless there is one already, gives back
+/// the ID if the existing definition is a different one
+pub(crate) fn insert_first<T: PartialEq>(
+    map: &mut HashMap<String, T>,
+    id: String,
+    value: T,
+) -> Option<String> {
+    match map.entry(id) {
+        Entry::Occupied(entry) if *entry.get() != value => Some(entry.key().clone()),
+        Entry::Occupied(_) => None,
+        Entry::Vacant(entry) => {
+            entry.insert(value);
+            None
+        }
+    }
+}
+
+/// where an element starts, to point to it in warnings
+pub(crate) struct Origin {
+    pub(crate) file: Rc<PathBuf>,
+    pub(crate) position: usize,
+}
+impl Origin {
+    pub(crate) fn warning(&self, element: &[u8], id: &str, reason: String) -> PositionedWarning {
+        PositionedWarning {
+            file: self.file.as_ref().clone(),
+            position: self.position,
+            element: String::from_utf8_lossy(element).to_string(),
+            id: Some(id.to_string()),
+            reason,
+        }
+    }
+}
+
+/// a warning with a byte position in the file, line and column are only
+/// looked up at the end
+pub(crate) struct PositionedWarning {
+    pub(crate) file: PathBuf,
+    pub(crate) position: usize,
+    pub(crate) element: String,
+    pub(crate) id: Option<String>,
+    pub(crate) reason: String,
+}
+
+pub(crate) fn locate_warnings(warnings: Vec<PositionedWarning>) -> Vec<FibexWarning> {
+    let mut line_indexes: HashMap<PathBuf, Option<LineIndex>> = HashMap::new();
+    warnings
+        .into_iter()
+        .map(|w| {
+            let line_index = line_indexes
+                .entry(w.file.clone())
+                .or_insert_with(|| LineIndex::from_file(&w.file).ok());
+            FibexWarning {
+                line_and_column: line_index
+                    .as_ref()
+                    .map(|index| index.line_and_column(w.position)),
+                file: w.file,
+                element: w.element,
+                id: w.id,
+                reason: w.reason,
+            }
+        })
+        .collect()
+}
+
+/// finds line and column (in characters) of byte positions in a file
+struct LineIndex {
+    content: Vec<u8>,
+    line_ends: Vec<usize>,
+}
+impl LineIndex {
+    fn from_file(path: &Path) -> Result<LineIndex> {
+        let content = std::fs::read(path)?;
+        let line_ends = content
+            .iter()
+            .enumerate()
+            .filter(|(_, b)| **b == b'\n')
+            .map(|(i, _)| i)
+            .collect();
+        Ok(LineIndex { content, line_ends })
+    }
+    fn line_and_column(&self, position: usize) -> (usize, usize) {
+        let position = position.min(self.content.len());
+        let line = match self.line_ends.binary_search(&position) {
+            Ok(i) | Err(i) => i,
+        };
+        let line_start = if line == 0 {
+            0
+        } else {
+            self.line_ends[line - 1] + 1
+        };
+        let column = String::from_utf8_lossy(&self.content[line_start..position])
+            .chars()
+            .count();
+        (line + 1, column)
+    }
+}
+
+/// elements of all files, they can refer to elements of other files
+#[derive(Default)]
+struct FibexReadData {
+    pdus: Vec<(String, Origin, PduReadData)>,
+    frames: Vec<(String, Origin, FrameReadData)>,
+    signal_codings: SignalCodings,
+}
+
+/// only errors in the xml syntax are returned, the reading of the file can't
+/// go on after them
+fn read_fibex(
+    reader: &mut Reader<BufReader<File>>,
+    data: &mut FibexReadData,
+    warnings: &mut Vec<PositionedWarning>,
+) -> Result {
+    let file = Rc::new(reader.xml_reader.file_path.clone());
+    let conflict = || "skipped: conflicts with an earlier definition".to_string();
     loop {
-        match reader.read_event()? {
-            Event::SignalInstance {
-                signal_ref,
-                sequence_number,
-                ..
-            } => {
-                signal_refs.push((sequence_number, signal_ref));
+        let event = match reader.read_event() {
+            Ok(event) => event,
+            Err(e) if is_xml_error(&e) => return Err(e),
+            Err(e) => {
+                warnings.push(reader.warning(format!("skipped: {}", e)));
+                continue;
+            }
+        };
+        let origin = || Origin {
+            file: file.clone(),
+            position: reader.xml_reader.buffer_position(),
+        };
+        match event {
+            Event::PduStart { id } => {
+                let origin = origin();
+                if let Some(pdu) = read_pdu(reader, &id, warnings)? {
+                    data.pdus.push((id, origin, pdu));
+                }
+            }
+            Event::FrameStart { id } => {
+                let origin = origin();
+                if let Some(frame) = read_frame(reader, &id, warnings)? {
+                    data.frames.push((id, origin, frame));
+                }
             }
-            Event::PduEnd { description, .. } => {
-                signal_refs.sort_by_key(|s| s.0);
-                return Ok((description, signal_refs.into_iter().map(|v| v.1).collect()));
+            Event::Signal { id, coding_ref } => {
+                if let Some(id) = insert_first(&mut data.signal_codings.signals, id, coding_ref) {
+                    warnings.push(reader.warning_for(B_SIGNAL, Some(&id), conflict()));
+                }
+            }
+            Event::CodingStart { id } => {
+                if let Some(coding) = read_coding(reader, &id, warnings)? {
+                    if let Some(category) = &coding.unsupported_category {
+                        warnings.push(reader.warning_for(
+                            B_CODING,
+                            Some(&id),
+                            format!(
+                                "unsupported COMPU-METHOD category {}, values are not converted",
+                                category
+                            ),
+                        ));
+                    }
+                    if let Some(id) = insert_first(&mut data.signal_codings.codings, id, coding) {
+                        warnings.push(reader.warning_for(B_CODING, Some(&id), conflict()));
+                    }
+                }
+            }
+            Event::Unit { id, display_name } => {
+                if let Some(id) = insert_first(&mut data.signal_codings.units, id, display_name) {
+                    warnings.push(reader.warning_for(B_UNIT, Some(&id), conflict()));
+                }
             }
+            Event::Eof => return Ok(()),
             _ => {}
         }
     }
 }
 
+/// errors of quick-xml mean that the file is no valid xml
+fn is_xml_error(e: &Error) -> bool {
+    e.downcast_ref::<quick_xml::Error>().is_some()
+}
+
+/// reads the content of an element, `handle` gives the result when the
+/// element ends
+/// an element with errors in its content is skipped with a warning, errors in
+/// the xml syntax are returned
+fn read_content<T>(
+    reader: &mut Reader<BufReader<File>>,
+    element: &[u8],
+    id: &str,
+    warnings: &mut Vec<PositionedWarning>,
+    mut handle: impl FnMut(Event) -> Option<Result<T>>,
+) -> Result<Option<T>> {
+    let mut skipped = false;
+    loop {
+        match reader.read_event() {
+            Ok(Event::Eof) => bail!(
+                "unexpected end of file in {} {}",
+                String::from_utf8_lossy(element),
+                id
+            ),
+            Ok(event) => {
+                if let Some(result) = handle(event) {
+                    return Ok(match result {
+                        Ok(content) if !skipped => Some(content),
+                        Ok(_) => None,
+                        Err(e) => {
+                            if !skipped {
+                                warnings.push(reader.warning_for(
+                                    element,
+                                    Some(id),
+                                    format!("skipped: {}", e),
+                                ));
+                            }
+                            None
+                        }
+                    });
+                }
+            }
+            Err(e) if is_xml_error(&e) => return Err(e),
+            Err(e) => {
+                // only the first error of an element is reported
+                if !skipped {
+                    warnings.push(reader.warning_for(element, Some(id), format!("skipped: {}", e)));
+                    skipped = true;
+                }
+            }
+        }
+    }
+}
+
+type PduReadData = (Option<String>, Vec<String>);
+
+fn read_pdu(
+    reader: &mut Reader<BufReader<File>>,
+    id: &str,
+    warnings: &mut Vec<PositionedWarning>,
+) -> Result<Option<PduReadData>> {
+    let mut signal_refs = vec![];
+    read_content(reader, B_PDU, id, warnings, |event| match event {
+        Event::SignalInstance {
+            signal_ref,
+            sequence_number,
+            ..
+        } => {
+            signal_refs.push((sequence_number, signal_ref));
+            None
+        }
+        Event::PduEnd { description, .. } => {
+            signal_refs.sort_by_key(|s| s.0);
+            Some(Ok((
+                description,
+                mem::take(&mut signal_refs)
+                    .into_iter()
+                    .map(|v| v.1)
+                    .collect(),
+            )))
+        }
+        _ => None,
+    })
+}
+
+fn read_coding(
+    reader: &mut Reader<BufReader<File>>,
+    id: &str,
+    warnings: &mut Vec<PositionedWarning>,
+) -> Result<Option<CodingReadData>> {
+    let mut coding = CodingReadData::default();
+    let mut scales = vec![];
+    read_content(reader, B_CODING, id, warnings, |event| {
+        match event {
+            Event::CodedType { base_data_type } => coding.base_data_type = base_data_type,
+            Event::CompuScale(scale) => scales.push(scale),
+            Event::CompuMethodEnd { category, unit_ref } => {
+                let scales = mem::take(&mut scales);
+                // only the first method of a coding is used
+                if coding.compu_method.is_none() && coding.unsupported_category.is_none() {
+                    match CompuCategory::from_name(&category) {
+                        Some(category) => coding.compu_method = Some((category, unit_ref, scales)),
+                        None => coding.unsupported_category = Some(category),
+                    }
+                }
+            }
+            Event::CodingEnd => return Some(Ok(mem::take(&mut coding))),
+            _ => {}
+        }
+        None
+    })
+}
+
+#[derive(PartialEq)]
 struct FrameReadData {
     short_name: String,
     context_id: Option<ContextId>,
@@ -254,14 +924,18 @@ struct FrameReadData {
     pdu_refs: Vec<String>,
 }
 
-fn read_frame(reader: &mut Reader<BufReader<File>>) -> Result<FrameReadData> {
+fn read_frame(
+    reader: &mut Reader<BufReader<File>>,
+    id: &str,
+    warnings: &mut Vec<PositionedWarning>,
+) -> Result<Option<FrameReadData>> {
     let mut pdus = vec![];
     let mut frame_context_id = None;
     let mut frame_application_id = None;
     let mut frame_message_type = None;
     let mut frame_message_info = None;
-    loop {
-        match reader.read_event()? {
+    read_content(reader, B_FRAME, id, warnings, |event| {
+        match event {
             Event::PduInstance {
                 pdu_ref,
                 sequence_number,
@@ -283,18 +957,23 @@ fn read_frame(reader: &mut Reader<BufReader<File>>) -> Result<FrameReadData> {
             }
             Event::FrameEnd { short_name, .. } => {
                 pdus.sort_by_key(|p| p.0);
-                return Ok(FrameReadData {
-                    short_name,
-                    context_id: frame_context_id,
-                    application_id: frame_application_id,
-                    message_type: frame_message_type,
-                    message_info: frame_message_info,
-                    pdu_refs: pdus.into_iter().map(|p| p.1).collect(),
-                });
+                return Some(
+                    short_name
+                        .ok_or_else(|| format_err!("required SHORT-NAME tag is missing"))
+                        .map(|short_name| FrameReadData {
+                            short_name,
+                            context_id: frame_context_id.take(),
+                            application_id: frame_application_id.take(),
+                            message_type: frame_message_type.take(),
+                            message_info: frame_message_info.take(),
+                            pdu_refs: mem::take(&mut pdus).into_iter().map(|p| p.1).collect(),
+                        }),
+                );
             }
             _ => {}
         }
-    }
+        None
+    })
 }
 
 const B_SHORT_NAME: &[u8] = b"SHORT-NAME";
@@ -317,6 +996,24 @@ const B_MESSAGE_TYPE: &[u8] = b"MESSAGE_TYPE";
 const B_MESSAGE_INFO: &[u8] = b"MESSAGE_INFO";
 const B_APPLICATION_ID: &[u8] = b"APPLICATION_ID";
 const B_CONTEXT_ID: &[u8] = b"CONTEXT_ID";
+const B_SIGNAL: &[u8] = b"SIGNAL";
+const B_CODING_REF: &[u8] = b"CODING-REF";
+const B_CODING: &[u8] = b"CODING";
+const B_CODED_TYPE: &[u8] = b"CODED-TYPE";
+const B_BASE_DATA_TYPE: &[u8] = b"BASE-DATA-TYPE";
+const B_COMPU_METHOD: &[u8] = b"COMPU-METHOD";
+const B_CATEGORY: &[u8] = b"CATEGORY";
+const B_UNIT_REF: &[u8] = b"UNIT-REF";
+const B_COMPU_SCALE: &[u8] = b"COMPU-SCALE";
+const B_COMPU_PHYS_TO_INTERNAL: &[u8] = b"COMPU-PHYS-TO-INTERNAL";
+const B_LOWER_LIMIT: &[u8] = b"LOWER-LIMIT";
+const B_UPPER_LIMIT: &[u8] = b"UPPER-LIMIT";
+const B_VT: &[u8] = b"VT";
+const B_COMPU_NUMERATOR: &[u8] = b"COMPU-NUMERATOR";
+const B_COMPU_DENOMINATOR: &[u8] = b"COMPU-DENOMINATOR";
+const B_V: &[u8] = b"V";
+const B_UNIT: &[u8] = b"UNIT";
+const B_DISPLAY_NAME: &[u8] = b"DISPLAY-NAME";
 
 #[derive(Debug)]
 pub enum Event {
@@ -326,7 +1023,7 @@ pub enum Event {
     PduEnd {
         short_name: Option<String>,
         description: Option<String>,
-        byte_length: usize,
+        byte_length: Option<usize>,
     },
     SignalInstance {
         id: String,
@@ -337,8 +1034,8 @@ pub enum Event {
         id: String,
     },
     FrameEnd {
-        short_name: String,
-        byte_length: usize,
+        short_name: Option<String>,
+        byte_length: Option<usize>,
     },
     ManufacturerExtension {
         message_type: Option<String>,
@@ -351,6 +1048,26 @@ pub enum Event {
         pdu_ref: String,
         sequence_number: usize,
     },
+    Signal {
+        id: String,
+        coding_ref: String,
+    },
+    CodingStart {
+        id: String,
+    },
+    CodedType {
+        base_data_type: Option<String>,
+    },
+    CompuScale(CompuScale),
+    CompuMethodEnd {
+        category: String,
+        unit_ref: Option<String>,
+    },
+    CodingEnd,
+    Unit {
+        id: String,
+        display_name: String,
+    },
     Eof,
 }
 pub struct XmlReaderWithContext<B: BufRead> {
@@ -368,18 +1085,7 @@ impl<B: BufRead> XmlReaderWithContext<B> {
         Ok(self.xml_reader.read_text(tag, buf)?)
     }
     pub fn line_and_column(&self) -> Result<(usize, usize)> {
-        let s = std::fs::read_to_string(self.file_path.clone())?;
-        let mut line = 1;
-        let mut column = 0;
-        for c in s.chars().take(self.buffer_position()) {
-            if c == '\n' {
-                line += 1;
-                column = 0;
-            } else {
-                column += 1;
-            }
-        }
-        Ok((line, column))
+        Ok(LineIndex::from_file(&self.file_path)?.line_and_column(self.buffer_position()))
     }
     pub fn read_usize(&mut self, e: &BytesStart<'_>) -> Result<usize> {
         Ok(self.read_text_buf(e)?.parse::<usize>().map_err(|e| {
@@ -395,6 +1101,12 @@ impl<B: BufRead> XmlReaderWithContext<B> {
             .attr_opt(e.attributes(), B_ID_REF)?
             .ok_or_else(|| missing_attr_err(B_ID_REF, tag, self.line_and_column()))?)
     }
+    pub fn read_f64(&mut self, e: &BytesStart<'_>) -> Result<f64> {
+        self.read_text_buf(e)?.trim().parse::<f64>().map_err(|e| {
+            let (line, column) = self.line_and_column().unwrap_or((0, 0));
+            format_err!("can't parse number at {}:{}: {}", line, column, e)
+        })
+    }
     pub fn read_bool(&mut self, e: &BytesStart<'_>) -> Result<bool> {
         match self.read_text_buf(e)?.as_ref() {
             "true" => Ok(true),
@@ -454,6 +1166,15 @@ pub struct Reader<B: BufRead> {
     context_id: Option<String>,
     message_type: Option<String>,
     message_info: Option<String>,
+    category: Option<String>,
+    compu_scale: CompuScale,
+    /// values of a COMPU-NUMERATOR or COMPU-DENOMINATOR that is read
+    coefficients: Option<Vec<f64>>,
+    /// only the conversion from internal to physical values is used
+    in_phys_to_internal: bool,
+    display_name: Option<String>,
+    /// local name of the last xml element, for warnings
+    element: Vec<u8>,
 }
 impl Reader<BufReader<File>> {
     pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
@@ -475,14 +1196,49 @@ impl Reader<BufReader<File>> {
             context_id: None,
             message_type: None,
             message_info: None,
+            category: None,
+            compu_scale: CompuScale::default(),
+            coefficients: None,
+            in_phys_to_internal: false,
+            display_name: None,
+            element: vec![],
         })
     }
 }
 impl<B: BufRead> Reader<B> {
+    /// a warning for the last element
+    fn warning(&self, reason: String) -> PositionedWarning {
+        self.warning_for(&self.element, None, reason)
+    }
+    /// a warning for an element that contains the current position
+    fn warning_for(&self, element: &[u8], id: Option<&str>, reason: String) -> PositionedWarning {
+        PositionedWarning {
+            file: self.xml_reader.file_path.clone(),
+            position: self.xml_reader.buffer_position(),
+            element: String::from_utf8_lossy(element).to_string(),
+            id: id.map(str::to_string),
+            reason,
+        }
+    }
     #[allow(clippy::cognitive_complexity)]
     pub fn read_event(&mut self) -> Result<Event> {
         loop {
-            match self.xml_reader.read_event(&mut self.buf)? {
+            // an error can leave the buffers filled
+            self.buf.clear();
+            self.buf2.clear();
+            let event = self.xml_reader.read_event(&mut self.buf)?;
+            match &event {
+                XmlEvent::Start(e) | XmlEvent::Empty(e) => {
+                    self.element.clear();
+                    self.element.extend_from_slice(e.local_name());
+                }
+                XmlEvent::End(e) => {
+                    self.element.clear();
+                    self.element.extend_from_slice(e.local_name());
+                }
+                _ => {}
+            }
+            match event {
                 XmlEvent::Start(ref e) => match e.local_name() {
                     B_PDU => {
                         self.short_name = None;
@@ -502,7 +1258,7 @@ impl<B: BufRead> Reader<B> {
                         self.byte_length = Some(self.xml_reader.read_usize(e)?);
                     }
                     B_SIGNAL_INSTANCE => {
-                        self.id = Some(self.xml_reader.id_attr(e, B_SIGNAL_INSTANCE)?);
+                        self.id = self.xml_reader.attr_opt(e.attributes(), B_ID)?;
                         self.r#ref = None;
                         self.sequence_number = None;
                     }
@@ -525,11 +1281,11 @@ impl<B: BufRead> Reader<B> {
                         self.byte_length = None;
                         self.r#type = None;
                         return Ok(Event::FrameStart {
-                            id: self.xml_reader.id_attr(e, B_PDU)?,
+                            id: self.xml_reader.id_attr(e, B_FRAME)?,
                         });
                     }
                     B_PDU_INSTANCE => {
-                        self.id = Some(self.xml_reader.id_attr(e, B_PDU_INSTANCE)?);
+                        self.id = self.xml_reader.attr_opt(e.attributes(), B_ID)?;
                         self.r#ref = None;
                         self.sequence_number = None;
                     }
@@ -560,6 +1316,61 @@ impl<B: BufRead> Reader<B> {
                         self.description =
                             Some(self.xml_reader.read_text(e.name(), &mut self.buf2)?);
                     }
+                    B_SIGNAL => {
+                        self.id = self.xml_reader.attr_opt(e.attributes(), B_ID)?;
+                        self.r#ref = None;
+                    }
+                    B_CODING_REF => {
+                        self.r#ref = Some(self.xml_reader.id_ref_attr(e, B_CODING_REF)?)
+                    }
+                    B_CODING => {
+                        return Ok(Event::CodingStart {
+                            id: self.xml_reader.id_attr(e, B_CODING)?,
+                        });
+                    }
+                    B_CODED_TYPE => {
+                        return Ok(Event::CodedType {
+                            base_data_type: self
+                                .xml_reader
+                                .attr_opt(e.attributes(), B_BASE_DATA_TYPE)?,
+                        });
+                    }
+                    B_COMPU_METHOD => {
+                        self.category = None;
+                        self.r#ref = None;
+                    }
+                    B_CATEGORY => {
+                        self.category = Some(self.xml_reader.read_text(e.name(), &mut self.buf2)?);
+                    }
+                    B_UNIT_REF => self.r#ref = Some(self.xml_reader.id_ref_attr(e, B_UNIT_REF)?),
+                    B_COMPU_SCALE => self.compu_scale = CompuScale::default(),
+                    B_COMPU_PHYS_TO_INTERNAL => self.in_phys_to_internal = true,
+                    B_LOWER_LIMIT => {
+                        self.compu_scale.lower_limit = Some(self.xml_reader.read_f64(e)?)
+                    }
+                    B_UPPER_LIMIT => {
+                        self.compu_scale.upper_limit = Some(self.xml_reader.read_f64(e)?)
+                    }
+                    B_VT => {
+                        self.compu_scale.text =
+                            Some(self.xml_reader.read_text(e.name(), &mut self.buf2)?);
+                    }
+                    B_COMPU_NUMERATOR | B_COMPU_DENOMINATOR => self.coefficients = Some(vec![]),
+                    B_V => {
+                        let v = self.xml_reader.read_f64(e)?;
+                        if let Some(coefficients) = self.coefficients.as_mut() {
+                            coefficients.push(v);
+                        }
+                    }
+                    B_UNIT => {
+                        self.id = self.xml_reader.attr_opt(e.attributes(), B_ID)?;
+                        self.short_name = None;
+                        self.display_name = None;
+                    }
+                    B_DISPLAY_NAME => {
+                        self.display_name =
+                            Some(self.xml_reader.read_text(e.name(), &mut self.buf2)?);
+                    }
                     _ => {}
                 },
                 XmlEvent::Empty(ref e) => match e.local_name() {
@@ -567,6 +1378,17 @@ impl<B: BufRead> Reader<B> {
                         self.r#ref = Some(self.xml_reader.id_ref_attr(e, B_SIGNAL_REF)?)
                     }
                     B_PDU_REF => self.r#ref = Some(self.xml_reader.id_ref_attr(e, B_PDU_REF)?),
+                    B_CODING_REF => {
+                        self.r#ref = Some(self.xml_reader.id_ref_attr(e, B_CODING_REF)?)
+                    }
+                    B_UNIT_REF => self.r#ref = Some(self.xml_reader.id_ref_attr(e, B_UNIT_REF)?),
+                    B_CODED_TYPE => {
+                        return Ok(Event::CodedType {
+                            base_data_type: self
+                                .xml_reader
+                                .attr_opt(e.attributes(), B_BASE_DATA_TYPE)?,
+                        });
+                    }
                     _ => {}
                 },
                 XmlEvent::End(ref e) => match e.local_name() {
@@ -574,15 +1396,7 @@ impl<B: BufRead> Reader<B> {
                         return Ok(Event::PduEnd {
                             short_name: mem::replace(&mut self.short_name, None),
                             description: mem::replace(&mut self.description, None),
-                            byte_length: mem::replace(&mut self.byte_length, None).ok_or_else(
-                                || {
-                                    missing_tag_err(
-                                        B_BYTE_LENGTH,
-                                        B_PDU,
-                                        self.xml_reader.line_and_column(),
-                                    )
-                                },
-                            )?,
+                            byte_length: self.byte_length.take(),
                         })
                     }
                     B_SIGNAL_INSTANCE => {
@@ -613,24 +1427,8 @@ impl<B: BufRead> Reader<B> {
                     }
                     B_FRAME => {
                         return Ok(Event::FrameEnd {
-                            short_name: mem::replace(&mut self.short_name, None).ok_or_else(
-                                || {
-                                    missing_tag_err(
-                                        B_SHORT_NAME,
-                                        B_FRAME,
-                                        self.xml_reader.line_and_column(),
-                                    )
-                                },
-                            )?,
-                            byte_length: mem::replace(&mut self.byte_length, None).ok_or_else(
-                                || {
-                                    missing_tag_err(
-                                        B_BYTE_LENGTH,
-                                        B_FRAME,
-                                        self.xml_reader.line_and_column(),
-                                    )
-                                },
-                            )?,
+                            short_name: self.short_name.take(),
+                            byte_length: self.byte_length.take(),
                         })
                     }
                     B_PDU_INSTANCE => {
@@ -659,6 +1457,56 @@ impl<B: BufRead> Reader<B> {
                             })?,
                         })
                     }
+                    B_SIGNAL => {
+                        let id = self.id.take().ok_or_else(|| {
+                            missing_attr_err(B_ID, B_SIGNAL, self.xml_reader.line_and_column())
+                        })?;
+                        // signals without coding are referenced by their type name
+                        if let Some(coding_ref) = self.r#ref.take() {
+                            return Ok(Event::Signal { id, coding_ref });
+                        }
+                    }
+                    B_COMPU_NUMERATOR => {
+                        self.compu_scale.numerator = self.coefficients.take().unwrap_or_default()
+                    }
+                    B_COMPU_DENOMINATOR => {
+                        self.compu_scale.denominator = self.coefficients.take().unwrap_or_default()
+                    }
+                    B_COMPU_SCALE if !self.in_phys_to_internal => {
+                        return Ok(Event::CompuScale(mem::take(&mut self.compu_scale)))
+                    }
+                    B_COMPU_PHYS_TO_INTERNAL => self.in_phys_to_internal = false,
+                    B_COMPU_METHOD => {
+                        return Ok(Event::CompuMethodEnd {
+                            category: self.category.take().ok_or_else(|| {
+                                missing_tag_err(
+                                    B_CATEGORY,
+                                    B_COMPU_METHOD,
+                                    self.xml_reader.line_and_column(),
+                                )
+                            })?,
+                            unit_ref: self.r#ref.take(),
+                        })
+                    }
+                    B_CODING => return Ok(Event::CodingEnd),
+                    B_UNIT => {
+                        return Ok(Event::Unit {
+                            id: self.id.take().ok_or_else(|| {
+                                missing_attr_err(B_ID, B_UNIT, self.xml_reader.line_and_column())
+                            })?,
+                            display_name: self
+                                .display_name
+                                .take()
+                                .or_else(|| self.short_name.take())
+                                .ok_or_else(|| {
+                                    missing_tag_err(
+                                        B_DISPLAY_NAME,
+                                        B_UNIT,
+                                        self.xml_reader.line_and_column(),
+                                    )
+                                })?,
+                        })
+                    }
                     B_MANUFACTURER_EXTENSION => {
                         return Ok(Event::ManufacturerExtension {
                             application_id: mem::replace(&mut self.application_id, None),
@@ -672,8 +1520,6 @@ impl<B: BufRead> Reader<B> {
                 XmlEvent::Eof => return Ok(Event::Eof),
                 _ => {}
             }
-            self.buf.clear();
-            self.buf2.clear();
         }
     }
 }
@@ -12,7 +12,7 @@
 use serde::{Deserialize, Serialize};
 use std::io::{Read};
 use std::fs;
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use crate::dlt;
 use std::iter::FromIterator;
 
@@ -23,26 +23,188 @@ use std::iter::FromIterator;
 ///  4 => INFO
 ///  5 => DEBUG
 ///  6 => VERBOSE
-#[derive(Serialize, Deserialize, Debug)]
+/// the levels of `app_log_levels` and `context_log_levels` (keyed by app or
+/// context id) take precedence over `min_log_level`, context levels over app
+/// levels
+/// app and context ids, message types and verbosity are only known for
+/// messages with extended header, other messages are only left out with
+/// `skip_without_extended_header`
+/// timestamps are ms since epoch and compared with the storage header, the
+/// range includes both ends
+/// messages without ECU id (or storage header) are left out if ECU ids (or a
+/// time range) are selected
+#[derive(Serialize, Deserialize, Debug, Default)]
 pub struct DltFilterConfig {
     pub min_log_level: Option<u8>,
     pub app_ids: Option<Vec<String>>,
     pub ecu_ids: Option<Vec<String>>,
     pub context_ids: Option<Vec<String>>,
+    pub exclude_app_ids: Option<Vec<String>>,
+    pub exclude_ecu_ids: Option<Vec<String>>,
+    pub exclude_context_ids: Option<Vec<String>>,
+    pub app_log_levels: Option<HashMap<String, u8>>,
+    pub context_log_levels: Option<HashMap<String, u8>>,
+    pub message_types: Option<Vec<MessageTypeFilter>>,
+    pub exclude_message_types: Option<Vec<MessageTypeFilter>>,
+    /// only verbose (true) or only non verbose (false) messages
+    pub verbose: Option<bool>,
+    pub from_timestamp: Option<i64>,
+    pub to_timestamp: Option<i64>,
+    pub skip_without_extended_header: Option<bool>,
 }
+/// the kinds of `dlt::MessageType`
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum MessageTypeFilter {
+    Log,
+    AppTrace,
+    NetworkTrace,
+    Control,
+    Unknown,
+}
+impl From<&dlt::MessageType> for MessageTypeFilter {
+    fn from(message_type: &dlt::MessageType) -> Self {
+        match message_type {
+            dlt::MessageType::Log(_) => MessageTypeFilter::Log,
+            dlt::MessageType::ApplicationTrace(_) => MessageTypeFilter::AppTrace,
+            dlt::MessageType::NetworkTrace(_) => MessageTypeFilter::NetworkTrace,
+            dlt::MessageType::Control(_) => MessageTypeFilter::Control,
+            dlt::MessageType::Unknown(_) => MessageTypeFilter::Unknown,
+        }
+    }
+}
+#[derive(Clone, Default)]
 pub struct ProcessedDltFilterConfig {
     pub min_log_level: Option<dlt::LogLevel>,
     pub app_ids: Option<HashSet<String>>,
     pub ecu_ids: Option<HashSet<String>>,
     pub context_ids: Option<HashSet<String>>,
+    pub exclude_app_ids: HashSet<String>,
+    pub exclude_ecu_ids: HashSet<String>,
+    pub exclude_context_ids: HashSet<String>,
+    pub app_log_levels: HashMap<String, dlt::LogLevel>,
+    pub context_log_levels: HashMap<String, dlt::LogLevel>,
+    pub message_types: Option<HashSet<MessageTypeFilter>>,
+    pub exclude_message_types: HashSet<MessageTypeFilter>,
+    pub verbose: Option<bool>,
+    pub from_timestamp: Option<i64>,
+    pub to_timestamp: Option<i64>,
+    pub skip_without_extended_header: bool,
+}
+
+/// the headers of a message that are known before the payload is parsed
+pub struct FilteredHeaders<'a> {
+    pub storage_header: Option<&'a dlt::StorageHeader>,
+    pub ecu_id: Option<&'a str>,
+    /// message type, app id, context id and verbosity of the extended header
+    pub extended: Option<(&'a dlt::MessageType, &'a str, &'a str, bool)>,
+}
+
+impl ProcessedDltFilterConfig {
+    /// true if the filter leaves out a message with these headers
+    pub fn excludes(&self, headers: &FilteredHeaders) -> bool {
+        if self.from_timestamp.is_some() || self.to_timestamp.is_some() {
+            let time = match headers.storage_header {
+                Some(storage_header) => storage_header.timestamp.as_ms() as i64,
+                None => return true,
+            };
+            if self.from_timestamp.is_some_and(|from| time < from)
+                || self.to_timestamp.is_some_and(|to| time > to)
+            {
+                return true;
+            }
+        }
+        let ecu_id = headers
+            .ecu_id
+            .or_else(|| headers.storage_header.map(|h| h.ecu_id.as_str()));
+        if let Some(ecu_ids) = &self.ecu_ids {
+            if !ecu_id.is_some_and(|id| ecu_ids.contains(id)) {
+                return true;
+            }
+        }
+        if ecu_id.is_some_and(|id| self.exclude_ecu_ids.contains(id)) {
+            return true;
+        }
+        let (message_type, app_id, context_id, verbose) = match headers.extended {
+            Some(extended) => extended,
+            None => return self.skip_without_extended_header,
+        };
+        let level = self
+            .context_log_levels
+            .get(context_id)
+            .or_else(|| self.app_log_levels.get(app_id))
+            .or(self.min_log_level.as_ref());
+        if let Some(level) = level {
+            if message_type.skip_with_level(*level) {
+                return true;
+            }
+        }
+        let type_filter = MessageTypeFilter::from(message_type);
+        if let Some(message_types) = &self.message_types {
+            if !message_types.contains(&type_filter) {
+                return true;
+            }
+        }
+        if self.exclude_message_types.contains(&type_filter) {
+            return true;
+        }
+        if self.verbose.is_some_and(|v| v != verbose) {
+            return true;
+        }
+        if let Some(app_ids) = &self.app_ids {
+            if !app_ids.contains(app_id) {
+                return true;
+            }
+        }
+        if let Some(context_ids) = &self.context_ids {
+            if !context_ids.contains(context_id) {
+                return true;
+            }
+        }
+        self.exclude_app_ids.contains(app_id) || self.exclude_context_ids.contains(context_id)
+    }
+
+    /// true if the filter leaves out a message that was parsed without it
+    pub fn excludes_message(&self, msg: &dlt::Message) -> bool {
+        self.excludes(&FilteredHeaders {
+            storage_header: msg.storage_header.as_ref(),
+            ecu_id: msg.header.ecu_id.as_deref(),
+            extended: msg.extended_header.as_ref().map(|h| {
+                (
+                    &h.message_type,
+                    h.application_id.as_str(),
+                    h.context_id.as_str(),
+                    h.verbose,
+                )
+            }),
+        })
+    }
 }
 
 pub fn process_filter_config(cfg: DltFilterConfig) -> ProcessedDltFilterConfig {
+    let log_levels = |levels: Option<HashMap<String, u8>>| -> HashMap<String, dlt::LogLevel> {
+        levels
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|(id, level)| dlt::u8_to_log_level(level).map(|level| (id, level)))
+            .collect()
+    };
     ProcessedDltFilterConfig {
         min_log_level: cfg.min_log_level.and_then(dlt::u8_to_log_level),
         app_ids: cfg.app_ids.map(HashSet::from_iter),
         ecu_ids: cfg.ecu_ids.map(HashSet::from_iter),
         context_ids: cfg.context_ids.map(HashSet::from_iter),
+        exclude_app_ids: cfg.exclude_app_ids.into_iter().flatten().collect(),
+        exclude_ecu_ids: cfg.exclude_ecu_ids.into_iter().flatten().collect(),
+        exclude_context_ids: cfg.exclude_context_ids.into_iter().flatten().collect(),
+        app_log_levels: log_levels(cfg.app_log_levels),
+        context_log_levels: log_levels(cfg.context_log_levels),
+        message_types: cfg.message_types.map(HashSet::from_iter),
+        exclude_message_types: cfg.exclude_message_types.into_iter().flatten().collect(),
+        verbose: cfg.verbose,
+        from_timestamp: cfg.from_timestamp,
+        to_timestamp: cfg.to_timestamp,
+        skip_without_extended_header: cfg.skip_without_extended_header.unwrap_or(false),
     }
 }
 
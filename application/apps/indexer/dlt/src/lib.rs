@@ -19,13 +19,25 @@ extern crate log;
 #[macro_use]
 extern crate failure;
 
+pub mod arxml;
+pub mod can;
 pub mod dlt;
+pub mod dlt_control;
+pub mod dlt_counter;
+pub mod dlt_export;
+pub mod dlt_lifecycle;
 pub mod dlt_net;
+pub mod dlt_offsets;
+pub mod dlt_parallel;
 pub mod dlt_parse;
+mod dlt_reader;
+mod dlt_recovery;
+pub mod dlt_time;
 pub mod fibex;
 pub mod filtering;
 pub mod proptest_strategies;
 pub mod service_id;
+pub mod someip;
 
 #[cfg(test)]
 mod tests;
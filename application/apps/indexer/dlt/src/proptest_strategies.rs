@@ -33,7 +33,8 @@ prop_compose! {
 }
 prop_compose! {
     pub fn header_strategy(payload_length: u16, endianness: Endianness)(
-        version in 0..8u8,
+        // version 2 has a different layout
+        version in prop_oneof![0..2u8, 3..8u8],
         message_counter in any::<u8>(),
         ecu_id in ecu_id_strategy(),
         session_id in any::<Option<u32>>(),
@@ -49,6 +50,7 @@ prop_compose! {
                 session_id,
                 timestamp,
                 payload_length,
+                v2: None,
         }
     }
 }
@@ -99,10 +101,77 @@ prop_compose! {
         }
     }
 }
+prop_compose! {
+    /// version 2 messages, the payload is always big endian
+    pub fn message_v2_strat()(
+        (ext_header, payload, _) in extheader_payload_endian_strategy(),
+        message_counter in any::<u8>(),
+        ecu_id in ecu_id_strategy(),
+        session_id in any::<Option<u32>>(),
+        timestamp in any::<DltTimeStampV2>(),
+        with_ids in any::<bool>(),
+        source_location in any::<Option<SourceLocation>>(),
+        tags in prop::option::of(prop::collection::vec("[a-zA-Z]{1,8}", 0..3)),
+        privacy_level in any::<Option<u8>>(),
+    ) -> Message {
+        let (content_info, message_type, payload_length) = match &payload.payload_content {
+            PayloadContent::Verbose(_) => (
+                ContentInfo::Verbose,
+                Some(ext_header.message_type.clone()),
+                payload.as_bytes::<BigEndian>().len(),
+            ),
+            PayloadContent::NonVerbose(_, data) => (ContentInfo::NonVerbose, None, data.len()),
+            PayloadContent::ControlMsg(control_type, _) => (
+                ContentInfo::Control,
+                Some(MessageType::Control(control_type.clone())),
+                payload.as_bytes::<BigEndian>().len(),
+            ),
+        };
+        let v2 = HeaderV2 {
+            content_info,
+            message_type,
+            timestamp: if content_info == ContentInfo::Control {
+                None
+            } else {
+                Some(timestamp)
+            },
+            app_id_context_id: if with_ids {
+                Some((ext_header.application_id, ext_header.context_id))
+            } else {
+                None
+            },
+            source_location,
+            tags,
+            privacy_level,
+        };
+        Message {
+            storage_header: None,
+            header: StandardHeader {
+                version: 2,
+                endianness: Endianness::Big,
+                has_extended_header: with_ids,
+                message_counter,
+                ecu_id,
+                session_id,
+                timestamp: None,
+                payload_length: payload_length as u16,
+                v2: Some(Box::new(v2.clone())),
+            },
+            extended_header: v2.extended_header(payload.arg_count()),
+            payload,
+            fibex_metadata: None,
+        }
+    }
+}
 fn value_strategy(info: &TypeInfo) -> impl Strategy<Value = Value> {
     // println!("value_strategy for {:?}", info);
     match &info.kind {
         TypeInfoKind::Bool => any::<bool>().prop_map(Value::Bool).boxed(),
+        // NaN is never equal to itself, so only use values that survive a round trip
+        TypeInfoKind::Float(FloatWidth::Width16) => any::<u16>()
+            .prop_filter("NaN", |bits| bits & 0x7c00 != 0x7c00 || bits & 0x3ff == 0)
+            .prop_map(|bits| Value::F32(f16_to_f32(bits)))
+            .boxed(),
         TypeInfoKind::Float(FloatWidth::Width32) => any::<f32>().prop_map(Value::F32).boxed(),
         TypeInfoKind::Float(FloatWidth::Width64) => any::<f64>().prop_map(Value::F64).boxed(),
         TypeInfoKind::Raw => prop::collection::vec(any::<u8>(), 0..5)
@@ -114,40 +183,66 @@ fn value_strategy(info: &TypeInfo) -> impl Strategy<Value = Value> {
                 Value::StringVal(v)
             })
             .boxed(),
-        // signed i8-i64
-        TypeInfoKind::Signed(TypeLength::BitLength8) => any::<i8>().prop_map(Value::I8).boxed(),
-        TypeInfoKind::Signed(TypeLength::BitLength16) => any::<i16>().prop_map(Value::I16).boxed(),
-        TypeInfoKind::Signed(TypeLength::BitLength32) => any::<i32>().prop_map(Value::I32).boxed(),
-        TypeInfoKind::Signed(TypeLength::BitLength64) => any::<i64>().prop_map(Value::I64).boxed(),
-        TypeInfoKind::Signed(TypeLength::BitLength128) => {
-            any::<i128>().prop_map(Value::I128).boxed()
+        // signed i8-i128
+        TypeInfoKind::Signed(TypeLength::BitLength8)
+        | TypeInfoKind::SignedFixedPoint(TypeLength::BitLength8) => {
+            any::<i8>().prop_map(Value::I8).boxed()
+        }
+        TypeInfoKind::Signed(TypeLength::BitLength16)
+        | TypeInfoKind::SignedFixedPoint(TypeLength::BitLength16) => {
+            any::<i16>().prop_map(Value::I16).boxed()
         }
-        TypeInfoKind::SignedFixedPoint(FloatWidth::Width32) => {
+        TypeInfoKind::Signed(TypeLength::BitLength32)
+        | TypeInfoKind::SignedFixedPoint(TypeLength::BitLength32) => {
             any::<i32>().prop_map(Value::I32).boxed()
         }
-        TypeInfoKind::SignedFixedPoint(FloatWidth::Width64) => {
+        TypeInfoKind::Signed(TypeLength::BitLength64)
+        | TypeInfoKind::SignedFixedPoint(TypeLength::BitLength64) => {
             any::<i64>().prop_map(Value::I64).boxed()
         }
-        // unsigned u8-u64
-        TypeInfoKind::Unsigned(TypeLength::BitLength8) => any::<u8>().prop_map(Value::U8).boxed(),
-        TypeInfoKind::Unsigned(TypeLength::BitLength16) => {
+        TypeInfoKind::Signed(TypeLength::BitLength128)
+        | TypeInfoKind::SignedFixedPoint(TypeLength::BitLength128) => {
+            any::<i128>().prop_map(Value::I128).boxed()
+        }
+        // unsigned u8-u128
+        TypeInfoKind::Unsigned(TypeLength::BitLength8)
+        | TypeInfoKind::UnsignedFixedPoint(TypeLength::BitLength8) => {
+            any::<u8>().prop_map(Value::U8).boxed()
+        }
+        TypeInfoKind::Unsigned(TypeLength::BitLength16)
+        | TypeInfoKind::UnsignedFixedPoint(TypeLength::BitLength16) => {
             any::<u16>().prop_map(Value::U16).boxed()
         }
-        TypeInfoKind::Unsigned(TypeLength::BitLength32) => {
+        TypeInfoKind::Unsigned(TypeLength::BitLength32)
+        | TypeInfoKind::UnsignedFixedPoint(TypeLength::BitLength32) => {
             any::<u32>().prop_map(Value::U32).boxed()
         }
-        TypeInfoKind::Unsigned(TypeLength::BitLength64) => {
+        TypeInfoKind::Unsigned(TypeLength::BitLength64)
+        | TypeInfoKind::UnsignedFixedPoint(TypeLength::BitLength64) => {
             any::<u64>().prop_map(Value::U64).boxed()
         }
-        TypeInfoKind::Unsigned(TypeLength::BitLength128) => {
+        TypeInfoKind::Unsigned(TypeLength::BitLength128)
+        | TypeInfoKind::UnsignedFixedPoint(TypeLength::BitLength128) => {
             any::<u128>().prop_map(Value::U128).boxed()
         }
-        TypeInfoKind::UnsignedFixedPoint(FloatWidth::Width32) => {
-            any::<u32>().prop_map(Value::U32).boxed()
-        }
-        TypeInfoKind::UnsignedFixedPoint(FloatWidth::Width64) => {
-            any::<u64>().prop_map(Value::U64).boxed()
+        TypeInfoKind::Array(element_kind) => {
+            let element_info = TypeInfo {
+                kind: *element_kind.clone(),
+                ..info.clone()
+            };
+            prop::collection::vec(1..3u16, 1..3)
+                .prop_flat_map(move |dimensions| {
+                    let element_cnt: usize = dimensions.iter().map(|d| *d as usize).product();
+                    (
+                        Just(dimensions),
+                        prop::collection::vec(value_strategy(&element_info), element_cnt),
+                    )
+                })
+                .prop_map(|(dimensions, values)| Value::Array { dimensions, values })
+                .boxed()
         }
+        // struct values are produced by `argument_strategy`
+        TypeInfoKind::Struct => Just(Value::Struct(vec![])).boxed(),
     }
 }
 #[allow(dead_code)]
@@ -173,11 +268,11 @@ prop_compose! {
 fn fp_none_strategy() -> impl Strategy<Value = Option<FixedPoint>> {
     Just(None)
 }
-fn fp_strategy(width: FloatWidth) -> impl Strategy<Value = FixedPoint> {
-    let fp_value_strat = if width == FloatWidth::Width32 {
-        any::<i32>().prop_map(FixedPointValue::I32).boxed()
-    } else {
-        any::<i64>().prop_map(FixedPointValue::I64).boxed()
+fn fp_strategy(width: TypeLength) -> impl Strategy<Value = FixedPoint> {
+    let fp_value_strat = match width {
+        TypeLength::BitLength64 => any::<i64>().prop_map(FixedPointValue::I64).boxed(),
+        TypeLength::BitLength128 => any::<i128>().prop_map(FixedPointValue::I128).boxed(),
+        _ => any::<i32>().prop_map(FixedPointValue::I32).boxed(),
     };
     (any::<f32>(), fp_value_strat).prop_map(|(quantization, offset)| FixedPoint {
         quantization,
@@ -194,10 +289,19 @@ fn type_info_and_fixed_point_strategy() -> impl Strategy<
     ),
 > {
     any::<TypeInfo>().prop_flat_map(move |ti| {
-        let fp_strat = match ti.kind {
-            TypeInfoKind::SignedFixedPoint(width) => fp_strategy(width).prop_map(Some).boxed(),
-            TypeInfoKind::UnsignedFixedPoint(width) => fp_strategy(width).prop_map(Some).boxed(),
-            _ => fp_none_strategy().boxed(),
+        let fp_width = match &ti.kind {
+            TypeInfoKind::Array(element_kind) => match **element_kind {
+                TypeInfoKind::SignedFixedPoint(width) => Some(width),
+                TypeInfoKind::UnsignedFixedPoint(width) => Some(width),
+                _ => None,
+            },
+            TypeInfoKind::SignedFixedPoint(width) => Some(*width),
+            TypeInfoKind::UnsignedFixedPoint(width) => Some(*width),
+            _ => None,
+        };
+        let fp_strat = match fp_width {
+            Some(width) => fp_strategy(width).prop_map(Some).boxed(),
+            None => fp_none_strategy().boxed(),
         };
         let name_unit_strat = name_and_unit_strategy(ti.has_variable_info, ti.kind.clone());
         let val_strat = value_strategy(&ti);
@@ -207,14 +311,34 @@ fn type_info_and_fixed_point_strategy() -> impl Strategy<
 
 pub fn argument_strategy() -> impl Strategy<Value = Argument> {
     let ti_and_fp_and_val = type_info_and_fixed_point_strategy();
-    ti_and_fp_and_val.prop_map(|(type_info, fixed_point, value, name_and_unit)| Argument {
-        type_info,
-        name: name_and_unit.0,
-        unit: name_and_unit.1,
-        fixed_point,
-        value,
+    let leaf =
+        ti_and_fp_and_val.prop_map(|(type_info, fixed_point, value, name_and_unit)| Argument {
+            type_info,
+            name: name_and_unit.0,
+            unit: name_and_unit.1,
+            fixed_point,
+            value,
+        });
+    // structs contain other arguments (including structs)
+    leaf.prop_recursive(2, 8, 3, |inner| {
+        (
+            any::<bool>(),
+            "[a-zA-Z]{2,5}", /*"*/
+            prop::collection::vec(inner, 0..3),
+        )
+            .prop_map(|(has_variable_info, name, entries)| Argument {
+                type_info: TypeInfo {
+                    kind: TypeInfoKind::Struct,
+                    coding: StringCoding::UTF8,
+                    has_variable_info,
+                    has_trace_info: false,
+                },
+                name: if has_variable_info { Some(name) } else { None },
+                unit: None,
+                fixed_point: None,
+                value: Value::Struct(entries),
+            })
     })
-    // any::<Argument>()
 }
 pub fn argument_vector_strategy() -> impl Strategy<Value = Vec<Argument>> {
     prop::collection::vec(argument_strategy(), 0..2)
@@ -249,18 +373,29 @@ fn non_verbose_payload_strategy() -> impl Strategy<Value = Payload2> {
             })
     ]
 }
-// strategy to produce signed TypeInfoKinds for only 32 and 64 bit width fixed point or
+// strategy to produce signed TypeInfoKinds for fixed point or
 // any other regular signed value
 pub fn signed_strategy() -> impl Strategy<Value = TypeInfoKind> {
     prop_oneof![
-        any::<FloatWidth>().prop_flat_map(|width| Just(TypeInfoKind::SignedFixedPoint(width))),
+        any::<TypeLength>().prop_flat_map(|width| Just(TypeInfoKind::SignedFixedPoint(width))),
         any::<TypeLength>().prop_flat_map(|width| Just(TypeInfoKind::Signed(width)))
     ]
 }
-// strategy to produce unsigned TypeInfoKinds for only 32 and 64 bit width
+// strategy to produce arrays of the scalar types that can be used as array elements
+pub fn array_strategy() -> impl Strategy<Value = TypeInfoKind> {
+    prop_oneof![
+        Just(TypeInfoKind::Bool),
+        signed_strategy(),
+        unsigned_strategy(),
+        any::<FloatWidth>().prop_map(TypeInfoKind::Float),
+    ]
+    .prop_map(|element_kind| TypeInfoKind::Array(Box::new(element_kind)))
+}
+// strategy to produce unsigned TypeInfoKinds for fixed point or
+// any other regular unsigned value
 pub fn unsigned_strategy() -> impl Strategy<Value = TypeInfoKind> {
     prop_oneof![
-        any::<FloatWidth>().prop_flat_map(|width| Just(TypeInfoKind::UnsignedFixedPoint(width))),
+        any::<TypeLength>().prop_flat_map(|width| Just(TypeInfoKind::UnsignedFixedPoint(width))),
         any::<TypeLength>().prop_flat_map(|width| Just(TypeInfoKind::Unsigned(width)))
     ]
 }
@@ -0,0 +1,465 @@
+// Copyright (c) 2020 E.S.R.Labs. All rights reserved.
+//
+// NOTICE:  All information contained herein is, and remains
+// the property of E.S.R.Labs and its suppliers, if any.
+// The intellectual and technical concepts contained herein are
+// proprietary to E.S.R.Labs and its suppliers and may be covered
+// by German and Foreign Patents, patents in process, and are protected
+// by trade secret or copyright law.
+// Dissemination of this information or reproduction of this material
+// is strictly forbidden unless prior written permission is obtained
+// from E.S.R.Labs.
+//! SOME/IP messages in network traces
+//!
+//! The header of SOME/IP messages is always decoded. Names of services and
+//! methods and the parameters are resolved with a service mapping in json
+//! that is read together with the FIBEX files:
+//!
+//! ```json
+//! { "services": [ { "id": 4660, "name": "Engine", "methods": [
+//!     { "id": 1, "name": "setSpeed",
+//!       "parameters": [ { "name": "rpm", "type": "uint16" } ],
+//!       "returns": [ { "name": "accepted", "type": "bool" } ] }
+//! ] } ] }
+//! ```
+//!
+//! Parameter types are `bool`, `uint8` to `uint64`, `int8` to `int64`,
+//! `float32`, `float64` and `string`. Requests and events use the
+//! `parameters`, responses and errors the `returns` of a method.
+use crate::dlt::{
+    Argument, FloatWidth, StringCoding, TypeInfo, TypeInfoKind, TypeLength, Value,
+    DLT_ARGUMENT_SENTINAL,
+};
+use crate::dlt_parse::{dlt_fint, dlt_sint, dlt_uint};
+use crate::fibex::{FibexMetadata, FibexWarning};
+use byteorder::{BigEndian, ByteOrder};
+use failure::Error;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{hash_map::Entry, HashMap},
+    fmt,
+    fs::File,
+    io::BufReader,
+    path::{Path, PathBuf},
+    rc::Rc,
+};
+
+pub const SOMEIP_HEADER_LENGTH: usize = 16;
+/// the length field counts the bytes from the request id on
+const LENGTH_COVERED_HEADER: u32 = 8;
+const TP_FLAG: u8 = 0x20;
+const UTF8_BOM: &[u8] = &[0xEF, 0xBB, 0xBF];
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub enum SomeipMessageType {
+    Request,
+    RequestNoReturn,
+    Notification,
+    Response,
+    Error,
+    Unknown(u8),
+}
+impl From<u8> for SomeipMessageType {
+    fn from(v: u8) -> Self {
+        match v {
+            0x00 => SomeipMessageType::Request,
+            0x01 => SomeipMessageType::RequestNoReturn,
+            0x02 => SomeipMessageType::Notification,
+            0x80 => SomeipMessageType::Response,
+            0x81 => SomeipMessageType::Error,
+            n => SomeipMessageType::Unknown(n),
+        }
+    }
+}
+impl fmt::Display for SomeipMessageType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SomeipMessageType::Request => f.write_str("REQUEST"),
+            SomeipMessageType::RequestNoReturn => f.write_str("REQUEST_NO_RETURN"),
+            SomeipMessageType::Notification => f.write_str("NOTIFICATION"),
+            SomeipMessageType::Response => f.write_str("RESPONSE"),
+            SomeipMessageType::Error => f.write_str("ERROR"),
+            SomeipMessageType::Unknown(n) => write!(f, "0x{:02X}", n),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub enum ReturnCode {
+    Ok,
+    NotOk,
+    UnknownService,
+    UnknownMethod,
+    NotReady,
+    NotReachable,
+    Timeout,
+    WrongProtocolVersion,
+    WrongInterfaceVersion,
+    MalformedMessage,
+    WrongMessageType,
+    Other(u8),
+}
+impl From<u8> for ReturnCode {
+    fn from(v: u8) -> Self {
+        match v {
+            0x00 => ReturnCode::Ok,
+            0x01 => ReturnCode::NotOk,
+            0x02 => ReturnCode::UnknownService,
+            0x03 => ReturnCode::UnknownMethod,
+            0x04 => ReturnCode::NotReady,
+            0x05 => ReturnCode::NotReachable,
+            0x06 => ReturnCode::Timeout,
+            0x07 => ReturnCode::WrongProtocolVersion,
+            0x08 => ReturnCode::WrongInterfaceVersion,
+            0x09 => ReturnCode::MalformedMessage,
+            0x0A => ReturnCode::WrongMessageType,
+            n => ReturnCode::Other(n),
+        }
+    }
+}
+impl fmt::Display for ReturnCode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ReturnCode::Ok => f.write_str("E_OK"),
+            ReturnCode::NotOk => f.write_str("E_NOT_OK"),
+            ReturnCode::UnknownService => f.write_str("E_UNKNOWN_SERVICE"),
+            ReturnCode::UnknownMethod => f.write_str("E_UNKNOWN_METHOD"),
+            ReturnCode::NotReady => f.write_str("E_NOT_READY"),
+            ReturnCode::NotReachable => f.write_str("E_NOT_REACHABLE"),
+            ReturnCode::Timeout => f.write_str("E_TIMEOUT"),
+            ReturnCode::WrongProtocolVersion => f.write_str("E_WRONG_PROTOCOL_VERSION"),
+            ReturnCode::WrongInterfaceVersion => f.write_str("E_WRONG_INTERFACE_VERSION"),
+            ReturnCode::MalformedMessage => f.write_str("E_MALFORMED_MESSAGE"),
+            ReturnCode::WrongMessageType => f.write_str("E_WRONG_MESSAGE_TYPE"),
+            ReturnCode::Other(n) => write!(f, "0x{:02X}", n),
+        }
+    }
+}
+
+/// the SOME/IP header, all big endian
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SomeipHeader {
+    pub service_id: u16,
+    /// events have ids from 0x8000
+    pub method_id: u16,
+    pub length: u32,
+    pub client_id: u16,
+    pub session_id: u16,
+    pub protocol_version: u8,
+    pub interface_version: u8,
+    pub message_type: SomeipMessageType,
+    /// the message is a segment of a SOME/IP-TP message
+    pub tp: bool,
+    pub return_code: ReturnCode,
+}
+impl SomeipHeader {
+    fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        if bytes.len() < SOMEIP_HEADER_LENGTH {
+            return None;
+        }
+        Some(SomeipHeader {
+            service_id: BigEndian::read_u16(&bytes[0..]),
+            method_id: BigEndian::read_u16(&bytes[2..]),
+            length: BigEndian::read_u32(&bytes[4..]),
+            client_id: BigEndian::read_u16(&bytes[8..]),
+            session_id: BigEndian::read_u16(&bytes[10..]),
+            protocol_version: bytes[12],
+            interface_version: bytes[13],
+            message_type: SomeipMessageType::from(bytes[14] & !TP_FLAG),
+            tp: (bytes[14] & TP_FLAG) != 0,
+            return_code: ReturnCode::from(bytes[15]),
+        })
+    }
+    fn is_response(&self) -> bool {
+        matches!(
+            self.message_type,
+            SomeipMessageType::Response | SomeipMessageType::Error
+        )
+    }
+}
+
+/// a decoded SOME/IP message, names and parameters are only known if the
+/// service mapping describes the method
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SomeipMessage {
+    pub header: SomeipHeader,
+    pub service_name: Option<String>,
+    pub method_name: Option<String>,
+    /// None if the payload does not match the parameters of the method
+    pub parameters: Option<Vec<Argument>>,
+    pub payload: Vec<u8>,
+}
+impl fmt::Display for SomeipMessage {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let header = &self.header;
+        f.write_str("SOME/IP ")?;
+        match &self.service_name {
+            Some(name) => f.write_str(name)?,
+            None => write!(f, "0x{:04X}", header.service_id)?,
+        }
+        match &self.method_name {
+            Some(name) => write!(f, ".{}", name)?,
+            None => write!(f, ".0x{:04X}", header.method_id)?,
+        }
+        write!(
+            f,
+            " {}{} client 0x{:04X} session 0x{:04X} {}",
+            header.message_type,
+            if header.tp { " (TP)" } else { "" },
+            header.client_id,
+            header.session_id,
+            header.return_code
+        )?;
+        match &self.parameters {
+            Some(parameters) => parameters
+                .iter()
+                .try_for_each(|p| write!(f, "{}{}", DLT_ARGUMENT_SENTINAL, p)),
+            None if self.payload.is_empty() => Ok(()),
+            None => write!(f, "{}{:02X?}", DLT_ARGUMENT_SENTINAL, self.payload),
+        }
+    }
+}
+
+/// a service of the service mapping
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct ServiceMetadata {
+    pub id: u16,
+    pub name: String,
+    #[serde(default)]
+    pub methods: Vec<MethodMetadata>,
+}
+/// a method or event of a service
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct MethodMetadata {
+    pub id: u16,
+    pub name: String,
+    /// parameters of requests and events
+    #[serde(default)]
+    pub parameters: Vec<ParameterMetadata>,
+    /// parameters of responses
+    #[serde(default)]
+    pub returns: Vec<ParameterMetadata>,
+}
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct ParameterMetadata {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub parameter_type: ParameterType,
+}
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ParameterType {
+    Bool,
+    Uint8,
+    Uint16,
+    Uint32,
+    Uint64,
+    Int8,
+    Int16,
+    Int32,
+    Int64,
+    Float32,
+    Float64,
+    String,
+}
+
+#[derive(Deserialize)]
+struct ServiceMapping {
+    services: Vec<ServiceMetadata>,
+}
+
+/// json files are read as service mapping instead of FIBEX
+pub(crate) fn is_service_mapping(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("json"))
+}
+
+/// adds the services of a service mapping, services are identified by their
+/// id and the first definition is used
+/// only a file that can't be opened is an error, invalid content is reported
+/// as warning
+pub(crate) fn read_service_mapping(
+    path: &Path,
+    services: &mut HashMap<u16, Rc<ServiceMetadata>>,
+    warnings: &mut Vec<FibexWarning>,
+) -> Result<(), Error> {
+    let warning = |id: Option<String>, line_and_column: Option<(usize, usize)>, reason: String| {
+        FibexWarning {
+            file: PathBuf::from(path),
+            element: "service".to_string(),
+            id,
+            line_and_column,
+            reason,
+        }
+    };
+    let file = File::open(path)?;
+    let mapping: ServiceMapping = match serde_json::from_reader(BufReader::new(file)) {
+        Ok(mapping) => mapping,
+        Err(e) => {
+            warnings.push(warning(
+                None,
+                Some((e.line(), e.column())),
+                format!("reading of the file stopped: {}", e),
+            ));
+            return Ok(());
+        }
+    };
+    for service in mapping.services {
+        let id = format!("0x{:04X}", service.id);
+        match services.entry(service.id) {
+            Entry::Occupied(entry) => {
+                if **entry.get() != service {
+                    warnings.push(warning(
+                        Some(id),
+                        None,
+                        "skipped: conflicts with an earlier definition".to_string(),
+                    ));
+                }
+            }
+            Entry::Vacant(entry) => {
+                entry.insert(Rc::new(service));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// decodes a SOME/IP message, None if it is too short for the header
+/// the payload is cut to the length given in the header, parameters of
+/// SOME/IP-TP segments are not decoded since their payload starts with
+/// the TP header and only contains a part of the message
+pub fn decode_someip_message(
+    bytes: &[u8],
+    metadata: Option<&FibexMetadata>,
+) -> Option<SomeipMessage> {
+    let header = SomeipHeader::from_bytes(bytes)?;
+    let payload_length = (header.length.saturating_sub(LENGTH_COVERED_HEADER) as usize)
+        .min(bytes.len() - SOMEIP_HEADER_LENGTH);
+    let payload = bytes[SOMEIP_HEADER_LENGTH..SOMEIP_HEADER_LENGTH + payload_length].to_vec();
+    let service = metadata.and_then(|m| m.service(header.service_id));
+    let method = service.and_then(|s| s.methods.iter().find(|m| m.id == header.method_id));
+    let parameters = method.filter(|_| !header.tp).and_then(|method| {
+        decode_parameters(
+            if header.is_response() {
+                &method.returns
+            } else {
+                &method.parameters
+            },
+            &payload,
+        )
+    });
+    Some(SomeipMessage {
+        service_name: service.map(|s| s.name.clone()),
+        method_name: method.map(|m| m.name.clone()),
+        parameters,
+        payload,
+        header,
+    })
+}
+
+fn decode_parameters(parameters: &[ParameterMetadata], payload: &[u8]) -> Option<Vec<Argument>> {
+    let mut rest = payload;
+    let mut arguments = vec![];
+    for parameter in parameters {
+        let (kind, coding, value, after) = decode_parameter(parameter.parameter_type, rest)?;
+        arguments.push(Argument {
+            type_info: TypeInfo {
+                kind,
+                coding,
+                has_variable_info: true,
+                has_trace_info: false,
+            },
+            name: Some(parameter.name.clone()),
+            unit: None,
+            fixed_point: None,
+            value,
+        });
+        rest = after;
+    }
+    Some(arguments)
+}
+fn decode_parameter(
+    parameter_type: ParameterType,
+    input: &[u8],
+) -> Option<(TypeInfoKind, StringCoding, Value, &[u8])> {
+    let number = |kind: TypeInfoKind, parser: fn(&[u8]) -> nom::IResult<&[u8], Value>| {
+        parser(input)
+            .ok()
+            .map(|(rest, value)| (kind, StringCoding::ASCII, value, rest))
+    };
+    match parameter_type {
+        ParameterType::Bool => {
+            let (&b, rest) = input.split_first()?;
+            Some((
+                TypeInfoKind::Bool,
+                StringCoding::ASCII,
+                Value::Bool(b != 0),
+                rest,
+            ))
+        }
+        ParameterType::Uint8 => number(
+            TypeInfoKind::Unsigned(TypeLength::BitLength8),
+            dlt_uint::<BigEndian>(TypeLength::BitLength8),
+        ),
+        ParameterType::Uint16 => number(
+            TypeInfoKind::Unsigned(TypeLength::BitLength16),
+            dlt_uint::<BigEndian>(TypeLength::BitLength16),
+        ),
+        ParameterType::Uint32 => number(
+            TypeInfoKind::Unsigned(TypeLength::BitLength32),
+            dlt_uint::<BigEndian>(TypeLength::BitLength32),
+        ),
+        ParameterType::Uint64 => number(
+            TypeInfoKind::Unsigned(TypeLength::BitLength64),
+            dlt_uint::<BigEndian>(TypeLength::BitLength64),
+        ),
+        ParameterType::Int8 => number(
+            TypeInfoKind::Signed(TypeLength::BitLength8),
+            dlt_sint::<BigEndian>(TypeLength::BitLength8),
+        ),
+        ParameterType::Int16 => number(
+            TypeInfoKind::Signed(TypeLength::BitLength16),
+            dlt_sint::<BigEndian>(TypeLength::BitLength16),
+        ),
+        ParameterType::Int32 => number(
+            TypeInfoKind::Signed(TypeLength::BitLength32),
+            dlt_sint::<BigEndian>(TypeLength::BitLength32),
+        ),
+        ParameterType::Int64 => number(
+            TypeInfoKind::Signed(TypeLength::BitLength64),
+            dlt_sint::<BigEndian>(TypeLength::BitLength64),
+        ),
+        ParameterType::Float32 => number(
+            TypeInfoKind::Float(FloatWidth::Width32),
+            dlt_fint::<BigEndian>(FloatWidth::Width32),
+        ),
+        ParameterType::Float64 => number(
+            TypeInfoKind::Float(FloatWidth::Width64),
+            dlt_fint::<BigEndian>(FloatWidth::Width64),
+        ),
+        ParameterType::String => {
+            // length, optional byte order mark, characters and terminating zero
+            if input.len() < 4 {
+                return None;
+            }
+            let length = BigEndian::read_u32(input) as usize;
+            let rest = &input[4..];
+            if rest.len() < length {
+                return None;
+            }
+            let mut text = &rest[..length];
+            if text.starts_with(UTF8_BOM) {
+                text = &text[UTF8_BOM.len()..];
+            }
+            while let Some((0, start)) = text.split_last() {
+                text = start;
+            }
+            Some((
+                TypeInfoKind::StringType,
+                StringCoding::UTF8,
+                Value::StringVal(String::from_utf8_lossy(text).to_string()),
+                &rest[length..],
+            ))
+        }
+    }
+}
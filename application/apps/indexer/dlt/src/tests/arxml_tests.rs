@@ -0,0 +1,167 @@
+#[cfg(test)]
+mod tests {
+    use crate::arxml::*;
+    use crate::dlt::*;
+    use crate::fibex::*;
+    use pretty_assertions::assert_eq;
+    use std::path::PathBuf;
+    use std::rc::Rc;
+
+    fn read_sample() -> Rc<FibexMetadata> {
+        Rc::new(
+            read_arxmls(vec![
+                PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/dlt-messages.arxml")
+            ])
+            .expect("can't read arxml"),
+        )
+    }
+
+    fn values(
+        metadata: Rc<FibexMetadata>,
+        app_id_context_id: Option<(&str, &str)>,
+        id: u32,
+        data: Vec<u8>,
+    ) -> Vec<String> {
+        Message::new(
+            MessageConfig {
+                version: 1,
+                endianness: Endianness::Big,
+                counter: 0,
+                ecu_id: Some("ECU1".to_string()),
+                session_id: None,
+                timestamp: None,
+                payload: Payload2 {
+                    payload_content: PayloadContent::NonVerbose(id, data),
+                },
+                extended_header_info: app_id_context_id.map(|(app_id, context_id)| {
+                    ExtendedHeaderConfig {
+                        message_type: MessageType::Log(LogLevel::Info),
+                        app_id: app_id.to_string(),
+                        context_id: context_id.to_string(),
+                    }
+                }),
+            },
+            Some(metadata),
+            None,
+        )
+        .nonverbose_arguments()
+        .expect("frame not decoded")
+        .iter()
+        .map(|arg| arg.to_string())
+        .collect()
+    }
+
+    #[test]
+    fn test_decode_arxml_messages() {
+        let metadata = read_sample();
+        assert_eq!(
+            vec!["STATE_RUNNING", "22.5 °C", "3000"],
+            values(
+                metadata.clone(),
+                Some(("ENG", "STAT")),
+                100,
+                vec![3, 0, 125, 0, 0, 0x0B, 0xB8]
+            )
+        );
+        // the same message id is used in another log channel
+        assert_eq!(
+            vec!["-2", "overheated"],
+            values(
+                metadata.clone(),
+                Some(("ENG", "ERR")),
+                100,
+                vec![0xFF, 0xFE, 0, 10, b'o', b'v', b'e', b'r', b'h', b'e', b'a', b't', b'e', b'd']
+            )
+        );
+        let frame = metadata.frame(Some(("ENG", "ERR")), 100).unwrap();
+        assert_eq!("EngineError", frame.short_name);
+        assert_eq!(Some("DLT_LOG_ERROR"), frame.message_info.as_deref());
+        assert_eq!(Some("DLT_TYPE_LOG"), frame.message_type.as_deref());
+    }
+
+    #[test]
+    fn test_read_arxml_with_fibexes() {
+        let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/dlt-messages.arxml");
+        let arxml = read_arxmls_with_report(vec![path.clone()]).expect("can't read arxml");
+        let report = read_fibexes_with_report(vec![path]).expect("can't read arxml");
+        assert_eq!((3, 6), (report.frames, report.pdus));
+        assert_eq!((arxml.frames, arxml.pdus), (report.frames, report.pdus));
+        assert!(report.metadata.frame(Some(("ENG", "ERR")), 100).is_some());
+    }
+
+    #[test]
+    fn test_arxml_message_without_log_channel() {
+        let metadata = read_sample();
+        let frame = metadata.frame(None, 200).expect("message not found");
+        assert_eq!(None, frame.application_id);
+        assert!(metadata.frame(Some(("ENG", "STAT")), 200).is_none());
+        assert_eq!(
+            vec!["86400"],
+            values(metadata.clone(), None, 200, vec![0, 1, 0x51, 0x80])
+        );
+        // messages of log channels are found without extended header as well
+        assert_eq!(
+            "EngineStatus",
+            metadata.frame(None, 100).unwrap().short_name
+        );
+    }
+
+    #[test]
+    fn test_skip_unusable_arxml_elements() {
+        let report = read_arxmls_with_report(vec![
+            PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/dlt-broken.arxml")
+        ])
+        .expect("can't read arxml");
+        assert_eq!((1, 1), (report.frames, report.pdus));
+        assert_eq!(
+            vec![
+                (
+                    "COMPU-METHOD",
+                    "/Types/Flags",
+                    "unsupported category BITFIELD_TEXTTABLE, values are not converted"
+                ),
+                (
+                    "DLT-MESSAGE",
+                    "/Dlt/Messages/BadId",
+                    "skipped: can't parse MESSAGE-ID \"five\": invalid digit found in string"
+                ),
+                (
+                    "DLT-LOG-CHANNEL",
+                    "/Dlt/DltConfig/NoContext",
+                    "skipped: APPLICATION-ID and CONTEXT-ID are required"
+                ),
+                (
+                    "DLT-MESSAGE",
+                    "/Dlt/Messages/MissingType",
+                    "skipped: SW-BASE-TYPE /Types/missing not found"
+                ),
+                (
+                    "DLT-MESSAGE",
+                    "/Dlt/Messages/OddSize",
+                    "skipped: unsupported SW-BASE-TYPE /Types/uint24: encoding NONE with 24 bits"
+                ),
+                (
+                    "DLT-MESSAGE",
+                    "/Dlt/Messages/Position",
+                    "skipped: structured argument position is not supported"
+                ),
+            ],
+            report
+                .warnings
+                .iter()
+                .map(|w| (
+                    w.element.as_str(),
+                    w.id.as_deref().unwrap_or_default(),
+                    w.reason.as_str()
+                ))
+                .collect::<Vec<_>>()
+        );
+        // warnings point to the start tag of the element
+        assert_eq!(Some((93, 37)), report.warnings[1].line_and_column);
+        // values of unsupported compu methods are shown as they are
+        assert_eq!(
+            vec!["5"],
+            values(Rc::new(report.metadata), None, 1, vec![5])
+        );
+    }
+}
@@ -0,0 +1,210 @@
+#[cfg(test)]
+mod tests {
+    use crate::can::*;
+    use crate::dlt::*;
+    use crate::fibex::*;
+    use crate::tests::payload_column;
+    use pretty_assertions::assert_eq;
+    use std::path::PathBuf;
+    use std::rc::Rc;
+    use tempdir::TempDir;
+
+    fn database_path() -> PathBuf {
+        PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/can-database.dbc")
+    }
+
+    fn read_database() -> FibexReport {
+        read_fibexes_with_report(vec![database_path()]).expect("can't read dbc")
+    }
+
+    fn can_bytes(id: u32, dlc: u8, data: &[u8]) -> Vec<u8> {
+        let mut bytes = id.to_be_bytes().to_vec();
+        bytes.push(dlc);
+        bytes.extend(data);
+        bytes
+    }
+
+    fn can_trace(bytes: &[u8], metadata: Option<Rc<FibexMetadata>>) -> Message {
+        Message::new(
+            MessageConfig {
+                version: 1,
+                endianness: Endianness::Big,
+                counter: 0,
+                ecu_id: Some("ECU1".to_string()),
+                session_id: None,
+                timestamp: Some(5),
+                payload: Payload2 {
+                    payload_content: PayloadContent::Verbose(vec![Argument {
+                        type_info: TypeInfo {
+                            kind: TypeInfoKind::Raw,
+                            coding: StringCoding::ASCII,
+                            has_variable_info: false,
+                            has_trace_info: false,
+                        },
+                        name: None,
+                        unit: None,
+                        fixed_point: None,
+                        value: Value::Raw(bytes.to_vec()),
+                    }]),
+                },
+                extended_header_info: Some(ExtendedHeaderConfig {
+                    message_type: MessageType::NetworkTrace(NetworkTraceType::Can),
+                    app_id: "NET".to_string(),
+                    context_id: "CAN".to_string(),
+                }),
+            },
+            metadata,
+            None,
+        )
+    }
+
+    #[test]
+    fn test_decode_can_frame() {
+        // the data is cut to the DLC
+        let bytes = can_bytes(CAN_EXTENDED_ID_FLAG | 0x18FE_F100, 3, &[1, 2, 3, 4, 5]);
+        let frame = decode_can_frame(&bytes, None).expect("not decoded");
+        assert_eq!(
+            CanFrame {
+                id: 0x18FE_F100,
+                extended: true,
+                dlc: 3,
+                data: vec![1, 2, 3],
+                name: None,
+                signals: None,
+            },
+            frame
+        );
+        assert_eq!(None, decode_can_frame(&bytes[..4], None));
+        assert_eq!(
+            "CAN 0x7DF DLC 2\u{5}[02, 01]",
+            payload_column(&can_trace(&can_bytes(0x7DF, 2, &[2, 1]), None))
+        );
+    }
+
+    #[test]
+    fn test_decode_signals() {
+        let metadata = Rc::new(read_database().metadata);
+        let engine = can_trace(
+            &can_bytes(291, 8, &[0xD2, 0x04, 0x01, 0xF6, 0x00, 0x00, 0x48, 0x41]),
+            Some(metadata.clone()),
+        );
+        assert_eq!(
+            "CAN 0x123 DLC 8 EngineData\u{5}Speed: 123.4 km/h\u{5}Gear: First\u{5}Temp: -50 °C\u{5}Torque: 12.5 Nm",
+            payload_column(&engine)
+        );
+        let json = serde_json::to_value(&engine).expect("can't serialize");
+        assert_eq!("EngineData", json["can_frame"]["name"]);
+        // physical values are numbers with a unit, only names of values are texts
+        let signals = decode_can_frame(
+            &can_bytes(291, 8, &[0xD2, 0x04, 0x01, 0xF6, 0x00, 0x00, 0x48, 0x41]),
+            Some(&metadata),
+        )
+        .and_then(|frame| frame.signals)
+        .expect("signals not decoded");
+        assert_eq!(
+            vec![
+                (Value::F64(123.4), Some("km/h")),
+                (Value::StringVal("First".to_string()), None),
+                (Value::F64(-50.0), Some("°C")),
+                (Value::F64(12.5), Some("Nm")),
+            ],
+            signals
+                .iter()
+                .map(|s| (s.value.clone(), s.unit.as_deref()))
+                .collect::<Vec<_>>()
+        );
+        // big endian signals across bytes
+        assert_eq!(
+            "CAN 0x200 DLC 2 Motor\u{5}Load: 2748\u{5}Level: 5",
+            payload_column(&can_trace(
+                &can_bytes(512, 2, &[0xAB, 0xC5]),
+                Some(metadata.clone())
+            ))
+        );
+        // the multiplexor selects the signals
+        let diagnostics = |data: &[u8]| {
+            payload_column(&can_trace(
+                &can_bytes(2_566_844_672, 8, data),
+                Some(metadata.clone()),
+            ))
+        };
+        assert_eq!(
+            "CAN 0x18FEF100 EXT DLC 8 Diagnostics\u{5}Mode: 1\u{5}Voltage: 10 V",
+            diagnostics(&[1, 0x10, 0x27, 0, 0, 0, 0, 0])
+        );
+        assert_eq!(
+            "CAN 0x18FEF100 EXT DLC 8 Diagnostics\u{5}Mode: 2\u{5}Counter: 16",
+            diagnostics(&[2, 0x10, 0x27, 0, 0, 0, 0, 0])
+        );
+        // data that is too short for the signals is shown as it is
+        assert_eq!(
+            "CAN 0x200 DLC 1 Motor\u{5}[AB]",
+            payload_column(&can_trace(&can_bytes(512, 1, &[0xAB]), Some(metadata)))
+        );
+    }
+
+    #[test]
+    fn test_can_database_warnings() {
+        let report = read_database();
+        assert_eq!(3, report.can_messages);
+        let warnings = |report: &FibexReport| {
+            report
+                .warnings
+                .iter()
+                .map(|w| {
+                    (
+                        w.element.clone(),
+                        w.id.clone().unwrap_or_default(),
+                        w.line_and_column.map(|(line, _)| line),
+                        w.reason.clone(),
+                    )
+                })
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            vec![
+                (
+                    "SG_".to_string(),
+                    "Odd".to_string(),
+                    Some(26),
+                    "skipped: unsupported size of 0 bits".to_string()
+                ),
+                (
+                    "BO_".to_string(),
+                    "five".to_string(),
+                    Some(31),
+                    "skipped: can't parse id \"five\": invalid digit found in string".to_string()
+                ),
+                (
+                    "VAL_".to_string(),
+                    "291.Missing".to_string(),
+                    Some(36),
+                    "skipped: signal not found".to_string()
+                ),
+            ],
+            warnings(&report)
+        );
+
+        let tmp_dir = TempDir::new("test_dir").expect("could not create temp dir");
+        let changed_path = tmp_dir.path().join("can_conflicts.dbc");
+        std::fs::write(
+            &changed_path,
+            std::fs::read_to_string(database_path())
+                .unwrap()
+                .replacen("km/h", "mph", 1),
+        )
+        .unwrap();
+        let report = read_fibexes_with_report(vec![database_path(), changed_path.clone()]);
+        let report = report.expect("can't read dbc");
+        assert_eq!(3, report.can_messages);
+        assert_eq!(
+            (
+                "BO_".to_string(),
+                "291".to_string(),
+                Some(12),
+                "skipped: conflicts with an earlier definition".to_string()
+            ),
+            warnings(&report)[6]
+        );
+    }
+}
@@ -0,0 +1,191 @@
+#[cfg(test)]
+mod tests {
+    use crate::dlt::*;
+    use crate::dlt_control::*;
+    use crate::dlt_parse::dlt_message;
+    use nom::IResult;
+    use pretty_assertions::assert_eq;
+
+    fn control_msg(payload: &[u8], endianness: Endianness, control_type: ControlType) -> Message {
+        let msg_conf = MessageConfig {
+            version: 1,
+            endianness,
+            counter: 0,
+            ecu_id: Some("ECU1".to_string()),
+            session_id: None,
+            timestamp: Some(5),
+            payload: Payload2 {
+                payload_content: PayloadContent::ControlMsg(
+                    ControlType::from_value(payload[0]),
+                    payload[1..].to_vec(),
+                ),
+            },
+            extended_header_info: Some(ExtendedHeaderConfig {
+                message_type: MessageType::Control(control_type),
+                app_id: "DA1".to_string(),
+                context_id: "DC1".to_string(),
+            }),
+        };
+        Message::new(msg_conf, None, None)
+    }
+
+    #[test]
+    fn test_decode_get_log_info_response() {
+        let mut payload = vec![0x03, 0x00, 0x00, 0x00, 0x07, 0x01, 0x00];
+        payload.extend(b"APP1");
+        payload.extend(&[0x02, 0x00]);
+        payload.extend(b"CTX1");
+        payload.extend(&[0x04, 0x00, 0x04, 0x00]);
+        payload.extend(b"ctx1");
+        payload.extend(b"CTX2");
+        payload.extend(&[0xFF, 0xFF, 0x00, 0x00]);
+        payload.extend(&[0x06, 0x00]);
+        payload.extend(b"my app");
+        payload.extend(b"remo");
+        let decoded = decode_control_message(&payload, Endianness::Little, &ControlType::Response)
+            .expect("not decoded");
+        let expected = ControlMessage {
+            service_id: 0x03,
+            content: ControlContent::LogInfo {
+                status: 7,
+                applications: vec![LogInfoApplication {
+                    application_id: "APP1".to_string(),
+                    contexts: vec![
+                        LogInfoContext {
+                            context_id: "CTX1".to_string(),
+                            log_level: Some(4),
+                            trace_status: Some(0),
+                            description: Some("ctx1".to_string()),
+                        },
+                        LogInfoContext {
+                            context_id: "CTX2".to_string(),
+                            log_level: Some(-1),
+                            trace_status: Some(-1),
+                            description: Some("".to_string()),
+                        },
+                    ],
+                    description: Some("my app".to_string()),
+                }],
+                com_interface: "remo".to_string(),
+            },
+        };
+        assert_eq!(expected, decoded);
+        assert_eq!(
+            "[get_log_info] status=7 APP1 \"my app\" [CTX1 level=INFO trace=off \"ctx1\", \
+             CTX2 level=default trace=default]",
+            decoded.to_string()
+        );
+    }
+
+    #[test]
+    fn test_decode_requests_and_responses() {
+        let mut set_log_level = vec![0x00, 0x00, 0x00, 0x01];
+        set_log_level.extend(b"APP1CTX1");
+        set_log_level.push(0x06);
+        set_log_level.extend(b"remo");
+        assert_eq!(
+            Some(ControlMessage {
+                service_id: 0x01,
+                content: ControlContent::SetLogLevel {
+                    application_id: "APP1".to_string(),
+                    context_id: "CTX1".to_string(),
+                    log_level: 6,
+                    com_interface: "remo".to_string(),
+                },
+            }),
+            decode_control_message(&set_log_level, Endianness::Big, &ControlType::Request)
+        );
+        assert_eq!(
+            Some(ControlMessage {
+                service_id: 0x01,
+                content: ControlContent::Status(ControlStatus::Ok),
+            }),
+            decode_control_message(
+                &[0x01, 0, 0, 0, 0],
+                Endianness::Little,
+                &ControlType::Response
+            )
+        );
+        let mut version = vec![0x13, 0x00, 0x00, 0x00, 0x00, 0x08, 0x00, 0x00, 0x00];
+        version.extend(b"v2.18.4\0");
+        assert_eq!(
+            "[get_software_version] status=ok v2.18.4",
+            decode_control_message(&version, Endianness::Little, &ControlType::Response)
+                .expect("not decoded")
+                .to_string()
+        );
+        assert_eq!(
+            Some(ControlMessage {
+                service_id: 0x23,
+                content: ControlContent::BufferOverflowNotification {
+                    status: ControlStatus::Ok,
+                    overflow_counter: 258,
+                },
+            }),
+            decode_control_message(
+                &[0x23, 0, 0, 0, 0x00, 0x02, 0x01, 0, 0],
+                Endianness::Little,
+                &ControlType::Response
+            )
+        );
+        assert_eq!(
+            "[get_default_log_level]",
+            decode_control_message(&[0x04, 0, 0, 0], Endianness::Little, &ControlType::Request)
+                .expect("not decoded")
+                .to_string()
+        );
+        // truncated payloads are kept as raw bytes
+        assert_eq!(
+            ControlContent::Raw(vec![0x00, 0x02]),
+            decode_control_message(
+                &[0x23, 0, 0, 0, 0x00, 0x02],
+                Endianness::Little,
+                &ControlType::Response
+            )
+            .expect("not decoded")
+            .content
+        );
+        // payloads that are too short for a service id are not decoded
+        assert_eq!(
+            None,
+            decode_control_message(&[0x23, 0, 0], Endianness::Little, &ControlType::Response)
+        );
+        let msg = control_msg(&[0x23, 0, 0], Endianness::Little, ControlType::Response);
+        assert_eq!(None, msg.control_message());
+        assert!(msg
+            .to_string()
+            .ends_with("[Unknown CtrlCommand] [23, 00, 00]"));
+    }
+
+    #[test]
+    fn test_control_message_is_decoded_after_parsing() {
+        let mut payload = vec![0x00, 0x00, 0x00, 0x04];
+        payload.extend(&[0x00, 0x05]);
+        let msg = control_msg(&payload, Endianness::Big, ControlType::Response);
+        let msg_bytes = msg.as_bytes();
+        let res: IResult<&[u8], Option<Message>> =
+            dlt_message(&msg_bytes, None, 0, None, None, false);
+        let parsed = match res {
+            Ok((_, Some(parsed))) => parsed,
+            _ => panic!("could not parse control message"),
+        };
+        assert!(parsed
+            .to_string()
+            .ends_with("[get_default_log_level] status=ok level=DEBUG"));
+        assert_eq!(
+            Some(ControlMessage {
+                service_id: 0x04,
+                content: ControlContent::DefaultLogLevel {
+                    status: ControlStatus::Ok,
+                    log_level: 5,
+                },
+            }),
+            parsed.control_message()
+        );
+        let json = serde_json::to_string(&parsed).expect("could not serialize");
+        assert!(json.contains(
+            "\"control_message\":{\"service_id\":4,\"content\":\
+             {\"DefaultLogLevel\":{\"status\":\"Ok\",\"log_level\":5}}}"
+        ));
+    }
+}
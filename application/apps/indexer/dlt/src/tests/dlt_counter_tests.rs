@@ -0,0 +1,186 @@
+#[cfg(test)]
+mod tests {
+    use crate::dlt::*;
+    use crate::dlt_counter::*;
+    use crate::dlt_parse::*;
+    use crate::filtering::ProcessedDltFilterConfig;
+    use crate::tests::TestMessage;
+    use crossbeam_channel as cc;
+    use indexer_base::chunks::ChunkResults;
+    use indexer_base::config::IndexingConfig;
+    use indexer_base::progress::{IndexingProgress, Notification, Severity};
+    use pretty_assertions::assert_eq;
+    use tempdir::TempDir;
+
+    fn sender(app_id: &'static str) -> Sender<'static> {
+        Sender {
+            ecu_id: Some("ECU1"),
+            session_id: Some(1),
+            app_id: Some(app_id),
+            context_id: Some("CTX"),
+        }
+    }
+
+    #[test]
+    fn test_counter_gaps() {
+        let mut check = MessageCounterCheck::new();
+        assert_eq!(None, check.check(sender("APP1"), 0));
+        assert_eq!(None, check.check(sender("APP1"), 1));
+        // other senders count on their own
+        assert_eq!(None, check.check(sender("APP2"), 250));
+        let gap = check.check(sender("APP1"), 4).expect("gap not found");
+        assert_eq!((2, 4, 2), (gap.expected, gap.found, gap.lost));
+        // a repeated message is no gap
+        assert_eq!(None, check.check(sender("APP1"), 4));
+        for counter in 5..=255 {
+            assert_eq!(None, check.check(sender("APP1"), counter));
+        }
+        assert_eq!(None, check.check(sender("APP1"), 0));
+        // the gap wraps around
+        let gap = check.check(sender("APP2"), 2).expect("gap not found");
+        assert_eq!((251, 2, 7), (gap.expected, gap.found, gap.lost));
+        assert_eq!(
+            vec![
+                (Some("APP1".to_string()), 1, 2),
+                (Some("APP2".to_string()), 1, 7)
+            ],
+            check
+                .summary()
+                .into_iter()
+                .map(|l| (l.app_id, l.gaps, l.lost))
+                .collect::<Vec<(Option<String>, usize, usize)>>()
+        );
+    }
+
+    #[test]
+    fn test_unknown_senders_are_not_checked() {
+        let mut check = MessageCounterCheck::new();
+        let anonymous = Sender {
+            ecu_id: Some("ECU1"),
+            ..Default::default()
+        };
+        assert_eq!(None, check.check(anonymous, 0));
+        assert_eq!(None, check.check(anonymous, 10));
+        assert!(check.summary().is_empty());
+    }
+
+    fn msg(app_id: &'static str, counter: u8) -> Message {
+        TestMessage {
+            counter,
+            session_id: Some(1),
+            timestamp: Some(u32::from(counter)),
+            storage_time: Some(DltTimeStamp::from_ms(1_000_000 + u64::from(counter))),
+            ..TestMessage::log(LogLevel::Info, app_id, "CTX")
+        }
+        .build()
+    }
+
+    /// messages of two applications, 3 messages of APP1 are missing
+    fn content_with_gap() -> Vec<u8> {
+        [
+            msg("APP1", 0),
+            msg("APP2", 0),
+            msg("APP1", 1),
+            msg("APP2", 1),
+            msg("APP1", 5),
+            msg("APP2", 2),
+        ]
+        .iter()
+        .flat_map(|m| m.as_bytes())
+        .collect()
+    }
+
+    fn warnings(notifications: impl Iterator<Item = Notification>) -> Vec<String> {
+        notifications
+            .filter(|n| n.severity == Severity::WARNING)
+            .map(|n| format!("{} {:?}", n.content, n.line))
+            .collect()
+    }
+
+    fn index_warnings(
+        content: &[u8],
+        filter_config: Option<ProcessedDltFilterConfig>,
+    ) -> Vec<String> {
+        let tmp_dir = TempDir::new("test_dir").expect("could not create temp dir");
+        let out_path = tmp_dir.path().join("dlt_counter_index.out");
+        let (tx, rx): (cc::Sender<ChunkResults>, cc::Receiver<ChunkResults>) = cc::unbounded();
+        let mut producer = MemoryMessageProducer::new(content, filter_config, 0, tx.clone(), true);
+        index_dlt_content(
+            IndexingConfig {
+                tag: "TAG",
+                chunk_size: 10,
+                in_file: tmp_dir.path().join("dlt_counter_in_memory.dlt"),
+                out_path: &out_path,
+                append: false,
+            },
+            0,
+            Some(content.len()),
+            &tx,
+            None,
+            None,
+            &mut producer,
+        )
+        .expect("indexing failed");
+        drop(producer);
+        drop(tx);
+        warnings(rx.iter().filter_map(|r| r.err()))
+    }
+
+    #[test]
+    fn test_index_reports_lost_messages() {
+        let content = content_with_gap();
+        assert_eq!(
+            vec!["3 messages lost from ECU ECU1 APP1/CTX (session 1): \
+                 message counter 2 expected but found 5 Some(4)"
+                .to_string()],
+            index_warnings(&content, None)
+        );
+        // filtered messages can not be told apart from lost ones
+        let filter_config = ProcessedDltFilterConfig {
+            min_log_level: None,
+            app_ids: Some(["APP1".to_string()].iter().cloned().collect()),
+            ecu_ids: None,
+            context_ids: None,
+            ..Default::default()
+        };
+        assert!(index_warnings(&content, Some(filter_config)).is_empty());
+    }
+
+    #[test]
+    fn test_statistics_summarize_lost_messages() {
+        let tmp_dir = TempDir::new("test_dir").expect("could not create temp dir");
+        let in_path = tmp_dir.path().join("dlt_counter_stats.dlt");
+        std::fs::write(&in_path, content_with_gap()).unwrap();
+        let (tx, rx): (
+            cc::Sender<StatisticsResults>,
+            cc::Receiver<StatisticsResults>,
+        ) = cc::unbounded();
+        get_dlt_file_info(&in_path, &tx, None, None, false).expect("could not get statistics");
+        drop(tx);
+        let results: Vec<StatisticsResults> = rx.iter().collect();
+        let summary = results
+            .iter()
+            .find_map(|r| match r {
+                Ok(IndexingProgress::GotItem { item }) => serde_json::to_value(item).ok(),
+                _ => None,
+            })
+            .expect("no statistics reported");
+        assert_eq!(
+            serde_json::json!([{
+                "ecu_id": "ECU1",
+                "session_id": 1,
+                "app_id": "APP1",
+                "context_id": "CTX",
+                "gaps": 1,
+                "lost": 3
+            }]),
+            summary["lost_messages"]
+        );
+        assert_eq!(
+            vec!["3 messages lost from ECU ECU1 APP1/CTX (session 1): \
+                 message counter 2 expected but found 5 Some(4)"
+                .to_string()],
+            warnings(results.into_iter().filter_map(|r| r.err()))
+        );
+    }
+}
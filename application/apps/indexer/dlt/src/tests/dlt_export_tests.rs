@@ -0,0 +1,159 @@
+#[cfg(test)]
+mod tests {
+    use crate::dlt::*;
+    use crate::dlt_export::*;
+    use crate::dlt_parse::dlt_message;
+    use crate::filtering;
+    use crate::tests::TestMessage;
+    use crossbeam_channel as cc;
+    use indexer_base::chunks::ChunkResults;
+    use nom::IResult;
+    use pretty_assertions::assert_eq;
+    use std::collections::HashSet;
+    use tempdir::TempDir;
+
+    fn test_msg(counter: u8, app_id: &'static str, seconds: u32) -> Message {
+        TestMessage {
+            endianness: Endianness::Little,
+            counter,
+            session_id: Some(7),
+            timestamp: Some(u32::from(counter) * 10),
+            payload: PayloadContent::Verbose(vec![Argument {
+                type_info: TypeInfo {
+                    kind: TypeInfoKind::Signed(TypeLength::BitLength16),
+                    coding: StringCoding::UTF8,
+                    has_variable_info: false,
+                    has_trace_info: false,
+                },
+                name: None,
+                unit: None,
+                fixed_point: None,
+                value: Value::I16(-i16::from(counter)),
+            }]),
+            storage_time: Some(DltTimeStamp {
+                seconds,
+                microseconds: 500,
+            }),
+            ..TestMessage::log(LogLevel::Warn, app_id, "CTX")
+        }
+        .build()
+    }
+
+    #[test]
+    fn test_export_round_trips() {
+        let messages = [
+            test_msg(0, "APP1", 100),
+            test_msg(1, "APP2", 101),
+            test_msg(2, "APP1", 102),
+            test_msg(3, "APP1", 103),
+            test_msg(4, "APP1", 104),
+        ];
+        let tmp_dir = TempDir::new("test_dir").expect("could not create temp dir");
+        let in_path = tmp_dir.path().join("dlt_export_test_in.dlt");
+        let out_path = tmp_dir.path().join("dlt_export_test_out.dlt");
+        let in_bytes: Vec<u8> = messages.iter().flat_map(|m| m.as_bytes()).collect();
+        std::fs::write(&in_path, &in_bytes).expect("could not write input");
+
+        let mut app_ids = HashSet::new();
+        app_ids.insert("APP1".to_string());
+        let filter_config = filtering::ProcessedDltFilterConfig {
+            min_log_level: None,
+            app_ids: Some(app_ids),
+            ecu_ids: None,
+            context_ids: None,
+            ..Default::default()
+        };
+        let (tx, _rx): (cc::Sender<ChunkResults>, cc::Receiver<ChunkResults>) = cc::unbounded();
+        // APP1 messages are lines 0..=3, skip the first one and
+        // everything after second 103
+        let exported = export_dlt_messages(
+            &in_path,
+            &out_path,
+            Some(filter_config),
+            DltExportConfig {
+                line_range: Some((1, 3)),
+                time_range: Some((0, 103_000)),
+                format: ExportFormat::Dlt,
+            },
+            None,
+            &tx,
+            None,
+        )
+        .expect("export failed");
+        assert_eq!(2, exported);
+
+        let out_bytes = std::fs::read(&out_path).expect("could not read output");
+        let mut rest: &[u8] = &out_bytes;
+        let mut parsed = vec![];
+        while !rest.is_empty() {
+            let res: IResult<&[u8], Option<Message>> = dlt_message(rest, None, 0, None, None, true);
+            let (r, msg) = res.expect("could not parse exported message");
+            parsed.push(msg.expect("message was filtered"));
+            rest = r;
+        }
+        assert_eq!(vec![messages[2].clone(), messages[3].clone()], parsed);
+    }
+
+    #[test]
+    fn test_export_json_lines_and_csv() {
+        let messages = [test_msg(0, "APP1", 100), test_msg(1, "APP2", 101)];
+        let tmp_dir = TempDir::new("test_dir").expect("could not create temp dir");
+        let in_path = tmp_dir.path().join("dlt_export_text_test_in.dlt");
+        let json_path = tmp_dir.path().join("dlt_export_text_test_out.json");
+        let csv_path = tmp_dir.path().join("dlt_export_text_test_out.csv");
+        let in_bytes: Vec<u8> = messages.iter().flat_map(|m| m.as_bytes()).collect();
+        std::fs::write(&in_path, &in_bytes).expect("could not write input");
+        let (tx, _rx): (cc::Sender<ChunkResults>, cc::Receiver<ChunkResults>) = cc::unbounded();
+
+        let exported = export_dlt_messages(
+            &in_path,
+            &json_path,
+            None,
+            DltExportConfig {
+                format: ExportFormat::JsonLines,
+                ..Default::default()
+            },
+            None,
+            &tx,
+            None,
+        )
+        .expect("json export failed");
+        assert_eq!(2, exported);
+        let json = std::fs::read_to_string(&json_path).expect("could not read json");
+        let lines: Vec<serde_json::Value> = json
+            .lines()
+            .map(|l| serde_json::from_str(l).expect("invalid json line"))
+            .collect();
+        assert_eq!(2, lines.len());
+        assert_eq!("APP2", lines[1]["extended_header"]["application_id"]);
+        assert_eq!(
+            serde_json::json!({"I16": -1}),
+            lines[1]["payload"]["payload_content"]["Verbose"][0]["value"]
+        );
+
+        export_dlt_messages(
+            &in_path,
+            &csv_path,
+            None,
+            DltExportConfig {
+                format: ExportFormat::Csv(vec![
+                    CsvColumn::Index,
+                    CsvColumn::Ecu,
+                    CsvColumn::ApplicationId,
+                    CsvColumn::MessageType,
+                    CsvColumn::Payload,
+                ]),
+                ..Default::default()
+            },
+            None,
+            &tx,
+            None,
+        )
+        .expect("csv export failed");
+        let csv = std::fs::read_to_string(&csv_path).expect("could not read csv");
+        assert_eq!(
+            "index,ecu,app,type,payload\n0,ECU1,APP1,WARN,0\n1,ECU1,APP2,WARN,-1\n",
+            csv
+        );
+    }
+}
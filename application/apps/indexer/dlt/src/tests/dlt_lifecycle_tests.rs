@@ -0,0 +1,174 @@
+#[cfg(test)]
+mod tests {
+    use crate::dlt::*;
+    use crate::dlt_lifecycle::*;
+    use crate::dlt_offsets::source_length;
+    use crate::tests::TestMessage;
+    use crossbeam_channel as cc;
+    use indexer_base::progress::IndexingProgress;
+    use pretty_assertions::assert_eq;
+    use std::path::PathBuf;
+    use tempdir::TempDir;
+
+    /// storage times of the test messages are ms after this
+    const START: u64 = 1_600_000_000_000;
+
+    fn msg(
+        ecu_id: &'static str,
+        app_id: &'static str,
+        counter: u8,
+        session: u32,
+        ticks: Option<u32>,
+        time: u64,
+    ) -> Message {
+        TestMessage {
+            counter,
+            ecu_id: Some(ecu_id),
+            session_id: Some(session),
+            timestamp: ticks,
+            storage_time: Some(DltTimeStamp::from_ms(START + time)),
+            ..TestMessage::log(LogLevel::Info, app_id, "CTX")
+        }
+        .build()
+    }
+
+    /// ECU, start, lines and message count of every lifecycle
+    fn detect(messages: &[Message]) -> Vec<(String, LifecycleStart, usize, usize, usize)> {
+        let mut detector = LifecycleDetector::new();
+        let mut offset = 0;
+        for (line_nr, m) in messages.iter().enumerate() {
+            detector.add(m, line_nr, offset);
+            offset += source_length(m);
+        }
+        detector
+            .lifecycles()
+            .into_iter()
+            .map(|l| (l.ecu_id, l.start, l.start_line, l.end_line, l.message_count))
+            .collect()
+    }
+
+    #[test]
+    fn test_timestamp_reset_without_storage_time() {
+        let mut messages = [
+            msg("ECU1", "APP1", 0, 1, Some(90_000), 0),
+            // within the tolerance for late messages
+            msg("ECU1", "APP1", 1, 1, Some(85_000), 0),
+            msg("ECU1", "APP1", 2, 1, Some(300), 0),
+        ];
+        for m in messages.iter_mut() {
+            m.storage_header = None;
+        }
+        assert_eq!(
+            vec![
+                ("ECU1".to_string(), LifecycleStart::FirstMessage, 0, 1, 2),
+                ("ECU1".to_string(), LifecycleStart::TimestampReset, 2, 2, 1),
+            ],
+            detect(&messages)
+        );
+    }
+
+    #[test]
+    fn test_timestamp_reset_starts_lifecycle() {
+        let messages = [
+            msg("ECU1", "APP1", 0, 1, Some(50_000), 5_010),
+            msg("ECU2", "APP1", 0, 1, Some(10), 5_020),
+            msg("ECU1", "APP1", 1, 1, Some(90_000), 9_010),
+            // a message that was buffered and arrives late is not a restart
+            msg("ECU1", "APP2", 0, 2, Some(85_000), 9_500),
+            msg("ECU1", "APP1", 0, 3, Some(300), 20_000),
+            msg("ECU2", "APP1", 1, 1, Some(20), 20_010),
+            msg("ECU1", "APP1", 1, 3, Some(400), 20_010),
+        ];
+        assert_eq!(
+            vec![
+                ("ECU1".to_string(), LifecycleStart::FirstMessage, 0, 3, 3),
+                ("ECU2".to_string(), LifecycleStart::FirstMessage, 1, 5, 2),
+                ("ECU1".to_string(), LifecycleStart::TimestampReset, 4, 6, 2),
+            ],
+            detect(&messages)
+        );
+    }
+
+    #[test]
+    fn test_session_restart_without_timestamps() {
+        let messages = [
+            msg("ECU1", "APP1", 0, 1, None, 0),
+            msg("ECU1", "APP1", 1, 1, None, 10),
+            // new session, but the counter continues: no restart
+            msg("ECU1", "APP1", 2, 2, None, 20),
+            // counter wraps in the same session: no restart
+            msg("ECU1", "APP2", 255, 5, None, 30),
+            msg("ECU1", "APP2", 0, 5, None, 40),
+            msg("ECU1", "APP1", 0, 3, None, 50),
+            msg("ECU1", "APP1", 1, 3, None, 60),
+        ];
+        assert_eq!(
+            vec![
+                ("ECU1".to_string(), LifecycleStart::FirstMessage, 0, 4, 5),
+                ("ECU1".to_string(), LifecycleStart::SessionRestart, 5, 6, 2),
+            ],
+            detect(&messages)
+        );
+    }
+
+    fn lifecycles_of(path: &PathBuf) -> Vec<EcuLifecycle> {
+        let (tx, rx): (cc::Sender<LifecycleResults>, cc::Receiver<LifecycleResults>) =
+            cc::unbounded();
+        get_dlt_lifecycles(path, &tx, None).expect("could not find lifecycles");
+        drop(tx);
+        rx.iter()
+            .filter_map(|r| match r {
+                Ok(IndexingProgress::GotItem { item }) => Some(item),
+                _ => None,
+            })
+            .next()
+            .expect("no lifecycles reported")
+    }
+
+    #[test]
+    fn test_lifecycles_of_concatenated_files() {
+        let test_file = PathBuf::from("..")
+            .join("../indexer-neon/tests")
+            .join("testfile.dlt");
+        let content = std::fs::read(&test_file).unwrap();
+        let single = lifecycles_of(&test_file);
+        assert_eq!(1, single.len());
+        assert_eq!((0, 104), (single[0].start_line, single[0].end_line));
+        assert_eq!(
+            (0, content.len()),
+            (single[0].start_offset, single[0].end_offset)
+        );
+        assert_eq!(105, single[0].message_count);
+
+        // the second recording was made two days later, the ECU was up for
+        // more than a day when the first one ended
+        let mut later = content.clone();
+        let mut pos = 0;
+        while pos < later.len() {
+            let seconds = u32::from_le_bytes([
+                later[pos + 4],
+                later[pos + 5],
+                later[pos + 6],
+                later[pos + 7],
+            ]);
+            later[pos + 4..pos + 8].copy_from_slice(&(seconds + 172_800).to_le_bytes());
+            let length = u16::from_be_bytes([later[pos + 18], later[pos + 19]]);
+            pos += 16 + usize::from(length);
+        }
+        let tmp_dir = TempDir::new("test_dir").expect("could not create temp dir");
+        let twice_path = tmp_dir.path().join("dlt_lifecycle_twice.dlt");
+        std::fs::write(&twice_path, [&content[..], &later[..]].concat()).unwrap();
+        let twice = lifecycles_of(&twice_path);
+        assert_eq!(2, twice.len());
+        assert_eq!(LifecycleStart::TimestampReset, twice[1].start);
+        assert_eq!((105, 209), (twice[1].start_line, twice[1].end_line));
+        assert_eq!(
+            (content.len(), 2 * content.len()),
+            (twice[1].start_offset, twice[1].end_offset)
+        );
+        assert_eq!(
+            single[0].start_time.map(|t| t + 172_800_000),
+            twice[1].start_time
+        );
+    }
+}
@@ -0,0 +1,195 @@
+#[cfg(test)]
+mod tests {
+    use crate::dlt::*;
+    use crate::dlt_net::*;
+    use crossbeam_channel as cc;
+    use indexer_base::chunks::ChunkResults;
+    use indexer_base::config::{SocketConfig, TcpConfig};
+    use indexer_base::progress::IndexingProgress;
+    use std::io::Write;
+    use std::net::TcpListener;
+    use std::thread;
+    use tempdir::TempDir;
+
+    fn test_msg_bytes(counter: u8) -> Vec<u8> {
+        let payload = Payload2 {
+            payload_content: PayloadContent::Verbose(vec![Argument {
+                type_info: TypeInfo {
+                    kind: TypeInfoKind::Unsigned(TypeLength::BitLength32),
+                    coding: StringCoding::UTF8,
+                    has_variable_info: false,
+                    has_trace_info: false,
+                },
+                name: None,
+                unit: None,
+                fixed_point: None,
+                value: Value::U32(u32::from(counter)),
+            }]),
+        };
+        let msg_conf = MessageConfig {
+            version: 1,
+            endianness: Endianness::Big,
+            counter,
+            ecu_id: Some("ECU1".to_string()),
+            session_id: None,
+            timestamp: Some(42),
+            payload,
+            extended_header_info: Some(ExtendedHeaderConfig {
+                message_type: MessageType::Log(LogLevel::Info),
+                app_id: "APP".to_string(),
+                context_id: "CTX".to_string(),
+            }),
+        };
+        Message::new(msg_conf, None, None).as_bytes()
+    }
+
+    #[test]
+    fn test_tcp_stream_is_reframed_and_reconnected() {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("could not bind listener");
+        let port = listener.local_addr().expect("no local addr").port();
+        let server = thread::spawn(move || {
+            // first connection: send 3 messages in small pieces to force reframing
+            let (mut stream, _) = listener.accept().expect("accept failed");
+            let bytes: Vec<u8> = (0..3).flat_map(test_msg_bytes).collect();
+            for piece in bytes.chunks(5) {
+                stream.write_all(piece).expect("write failed");
+                stream.flush().expect("flush failed");
+            }
+            drop(stream);
+            // second connection after the client reconnected
+            let (mut stream, _) = listener.accept().expect("accept failed");
+            stream.write_all(&test_msg_bytes(3)).expect("write failed");
+        });
+
+        let tmp_dir = TempDir::new("test_dir").expect("could not create temp dir");
+        let out_path = tmp_dir.path().join(format!("dlt_tcp_test_{}.out", port));
+        let (tx, rx): (cc::Sender<ChunkResults>, cc::Receiver<ChunkResults>) = cc::unbounded();
+        let (shutdown_tx, shutdown_rx) = async_std::sync::channel(1);
+        let indexer_out_path = out_path.clone();
+        let indexer = thread::spawn(move || {
+            index_from_tcp_stream(
+                TcpConfig {
+                    address: "127.0.0.1".to_string(),
+                    port: format!("{}", port),
+                },
+                None,
+                tx,
+                None,
+                "TAG",
+                "TCP".to_string(),
+                &indexer_out_path,
+                0,
+                shutdown_rx,
+            )
+        });
+
+        let mut connects = 0;
+        let mut lines = 0;
+        while lines < 4 {
+            match rx.recv_timeout(std::time::Duration::from_secs(10)) {
+                Ok(Ok(IndexingProgress::GotItem { item: chunk })) => {
+                    if chunk.r == (0, 0) && chunk.b == (0, 0) {
+                        connects += 1;
+                    } else {
+                        lines += 1;
+                    }
+                }
+                Ok(_) => (),
+                Err(e) => panic!("did not receive all messages: {}", e),
+            }
+        }
+        async_std::task::block_on(shutdown_tx.send(()));
+        assert!(indexer.join().expect("indexer thread panicked").is_ok());
+        server.join().expect("server thread panicked");
+        assert_eq!(2, connects);
+
+        let content = std::fs::read_to_string(&out_path).expect("could not read output");
+        let out_lines: Vec<&str> = content.lines().collect();
+        assert_eq!(4, out_lines.len());
+        for line in out_lines {
+            assert!(line.contains("ECU1"));
+            assert!(line.contains("TCP"));
+        }
+    }
+
+    #[test]
+    fn test_udp_datagrams_are_indexed_until_shutdown() {
+        // find a free port
+        let port = std::net::UdpSocket::bind("127.0.0.1:0")
+            .and_then(|s| s.local_addr())
+            .expect("no free port")
+            .port();
+        let tmp_dir = TempDir::new("test_dir").expect("could not create temp dir");
+        let out_path = tmp_dir.path().join(format!("dlt_udp_test_{}.out", port));
+        let (tx, rx): (cc::Sender<ChunkResults>, cc::Receiver<ChunkResults>) = cc::unbounded();
+        let (shutdown_tx, shutdown_rx) = async_std::sync::channel(1);
+        let indexer_out_path = out_path.clone();
+        let indexer = thread::spawn(move || {
+            index_from_socket(
+                SocketConfig {
+                    multicast_addr: None,
+                    bind_addr: "127.0.0.1".to_string(),
+                    port: format!("{}", port),
+                },
+                None,
+                tx,
+                None,
+                "TAG",
+                "UDP".to_string(),
+                &indexer_out_path,
+                0,
+                shutdown_rx,
+            )
+        });
+
+        let mut connected = false;
+        let mut lines = 0;
+        let mut warnings = 0;
+        let sender = std::net::UdpSocket::bind("127.0.0.1:0").expect("could not bind sender");
+        while lines < 3 {
+            match rx.recv_timeout(std::time::Duration::from_secs(10)) {
+                Ok(Ok(IndexingProgress::GotItem { item: chunk })) => {
+                    if chunk.r == (0, 0) && chunk.b == (0, 0) && !connected {
+                        connected = true;
+                        // the socket is bound now
+                        sender
+                            .send_to(b"no dlt message", ("127.0.0.1", port))
+                            .expect("send failed");
+                        for counter in 0..3 {
+                            sender
+                                .send_to(&test_msg_bytes(counter), ("127.0.0.1", port))
+                                .expect("send failed");
+                        }
+                    } else {
+                        lines += 1;
+                    }
+                }
+                Ok(Err(_)) => warnings += 1,
+                Ok(_) => (),
+                Err(e) => panic!("did not receive all messages: {}", e),
+            }
+        }
+        async_std::task::block_on(shutdown_tx.send(()));
+        assert!(indexer.join().expect("indexer thread panicked").is_ok());
+        let remaining: Vec<ChunkResults> = rx.try_iter().collect();
+        let stopped = remaining
+            .iter()
+            .filter(|r| matches!(r, Ok(IndexingProgress::Stopped)))
+            .count();
+        let finished = remaining
+            .iter()
+            .filter(|r| matches!(r, Ok(IndexingProgress::Finished)))
+            .count();
+        assert_eq!(1, warnings);
+        assert_eq!(1, stopped);
+        assert_eq!(1, finished);
+
+        let content = std::fs::read_to_string(&out_path).expect("could not read output");
+        let out_lines: Vec<&str> = content.lines().collect();
+        assert_eq!(3, out_lines.len());
+        for line in out_lines {
+            assert!(line.contains("ECU1"));
+            assert!(line.contains("UDP"));
+        }
+    }
+}
@@ -0,0 +1,87 @@
+#[cfg(test)]
+mod tests {
+    use crate::dlt_offsets::*;
+    use crate::dlt_parse::{create_index_and_mapping_dlt, dlt_message};
+    use crossbeam_channel as cc;
+    use indexer_base::chunks::ChunkResults;
+    use indexer_base::config::IndexingConfig;
+    use pretty_assertions::assert_eq;
+    use std::path::PathBuf;
+    use tempdir::TempDir;
+
+    #[test]
+    fn test_offset_index_path() {
+        assert_eq!(
+            PathBuf::from("/tmp/trace.dlt.out.offsets"),
+            offset_index_path(&PathBuf::from("/tmp/trace.dlt.out"))
+        );
+    }
+
+    #[test]
+    fn test_read_message_at_line() {
+        let in_path = PathBuf::from("..")
+            .join("../indexer-neon/tests")
+            .join("testfile.dlt");
+        let tmp_dir = TempDir::new("test_dir").expect("could not create temp dir");
+        let out_path = tmp_dir.path().join("dlt_offsets_test.out");
+        let (tx, _rx): (cc::Sender<ChunkResults>, cc::Receiver<ChunkResults>) = cc::unbounded();
+        create_index_and_mapping_dlt(
+            IndexingConfig {
+                tag: "TAG",
+                chunk_size: 20,
+                in_file: in_path.clone(),
+                out_path: &out_path,
+                append: false,
+            },
+            None,
+            None,
+            &tx,
+            None,
+            None,
+            false,
+            false,
+        )
+        .expect("indexing failed");
+
+        // parse all messages in sequence to compare with the random access
+        let content = std::fs::read(&in_path).expect("could not read test file");
+        let mut input: &[u8] = &content;
+        let mut expected = vec![];
+        while !input.is_empty() {
+            let (rest, msg) = dlt_message(input, None, 0, None, None, true).expect("parse error");
+            expected.extend(msg);
+            input = rest;
+        }
+        assert_eq!(105, expected.len());
+
+        let index_path = offset_index_path(&out_path);
+        let index_size = std::fs::metadata(&index_path).unwrap().len() as usize;
+        assert_eq!(expected.len() * OFFSET_RECORD_LENGTH, index_size);
+        for line_nr in &[0usize, 1, 42, 104] {
+            let msg = read_message_at_line(&in_path, &index_path, *line_nr as u64, None)
+                .expect("could not read message");
+            assert_eq!(expected[*line_nr], msg);
+        }
+        assert!(read_message_at_line(&in_path, &index_path, 105, None).is_err());
+
+        // appended lines could come from another file, so there is no index
+        create_index_and_mapping_dlt(
+            IndexingConfig {
+                tag: "TAG",
+                chunk_size: 20,
+                in_file: in_path,
+                out_path: &out_path,
+                append: true,
+            },
+            None,
+            None,
+            &tx,
+            None,
+            None,
+            false,
+            false,
+        )
+        .expect("indexing failed");
+        assert!(!index_path.exists());
+    }
+}
@@ -0,0 +1,106 @@
+#[cfg(test)]
+mod tests {
+    use crate::dlt_parallel::*;
+    use crate::dlt_parse::{create_index_and_mapping_dlt, dlt_message};
+    use crate::tests::index_into;
+    use indexer_base::config::IndexingConfig;
+    use pretty_assertions::assert_eq;
+    use std::path::{Path, PathBuf};
+    use tempdir::TempDir;
+
+    /// index the file and return the reported results, the output and the offset index
+    fn index(
+        in_path: &Path,
+        name: &str,
+        parallel: Option<ParallelConfig>,
+    ) -> (Vec<String>, Vec<u8>, Option<Vec<u8>>) {
+        let tmp_dir = TempDir::new("test_dir").expect("could not create temp dir");
+        let out_path = tmp_dir.path().join(name);
+        let source_file_size = Some(std::fs::metadata(in_path).unwrap().len() as usize);
+        index_into(&out_path, |tx| {
+            let config = IndexingConfig {
+                tag: "TAG",
+                chunk_size: 7,
+                in_file: in_path.to_path_buf(),
+                out_path: &out_path,
+                append: false,
+            };
+            match parallel {
+                Some(parallel_config) => create_index_and_mapping_dlt_parallel(
+                    config,
+                    source_file_size,
+                    None,
+                    tx,
+                    None,
+                    vec![],
+                    parallel_config,
+                ),
+                None => create_index_and_mapping_dlt(
+                    config,
+                    source_file_size,
+                    None,
+                    tx,
+                    None,
+                    None,
+                    false,
+                    false,
+                ),
+            }
+        })
+    }
+
+    fn assert_same_as_sequential(in_path: &Path, name: &str) {
+        let sequential = index(in_path, &format!("{}_seq.out", name), None);
+        assert!(sequential.2.is_some(), "no offset index");
+        for threads in &[2usize, 4] {
+            for segment_size in &[300usize, 1000, 4096] {
+                let parallel = index(
+                    in_path,
+                    &format!("{}_par.out", name),
+                    Some(ParallelConfig {
+                        threads: *threads,
+                        segment_size: *segment_size,
+                        memory_map: false,
+                        absolute_time: false,
+                    }),
+                );
+                assert_eq!(sequential.0, parallel.0);
+                assert!(sequential.1 == parallel.1, "output differs");
+                assert!(sequential.2 == parallel.2, "offset index differs");
+            }
+        }
+    }
+
+    fn test_file() -> PathBuf {
+        PathBuf::from("..")
+            .join("../indexer-neon/tests")
+            .join("testfile.dlt")
+    }
+
+    #[test]
+    fn test_parallel_indexing_produces_same_output() {
+        assert_same_as_sequential(&test_file(), "dlt_parallel_test");
+    }
+
+    #[test]
+    fn test_parallel_indexing_of_corrupted_file() {
+        let content = std::fs::read(test_file()).unwrap();
+        // find the start of all messages
+        let mut starts = vec![];
+        let mut rest: &[u8] = &content;
+        while !rest.is_empty() {
+            starts.push(content.len() - rest.len());
+            rest = dlt_message(rest, None, 0, None, None, true).unwrap().0;
+        }
+        // insert a truncated message and some garbage
+        let mut corrupted = content[..starts[30]].to_vec();
+        corrupted.extend_from_slice(&content[starts[50]..starts[50] + 30]);
+        corrupted.extend_from_slice(&content[starts[30]..starts[70]]);
+        corrupted.extend_from_slice(&[0xAB; 500]);
+        corrupted.extend_from_slice(&content[starts[70]..]);
+        let tmp_dir = TempDir::new("test_dir").expect("could not create temp dir");
+        let in_path = tmp_dir.path().join("dlt_parallel_corrupted.dlt");
+        std::fs::write(&in_path, &corrupted).unwrap();
+        assert_same_as_sequential(&in_path, "dlt_parallel_corrupted");
+    }
+}
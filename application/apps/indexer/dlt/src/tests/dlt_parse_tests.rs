@@ -205,6 +205,11 @@ mod tests {
             assert_eq!(expected, res);
         }
         #[test]
+        fn test_format_any_argument(argument in argument_strategy()) {
+            // fixed point values with any offset must not overflow
+            let _ = argument.to_string();
+        }
+        #[test]
         fn test_argument_to_bytes_to_argument(arg in argument_strategy(), endianness in any::<Endianness>()) {
             init_logging();
             let mut arg_bytes = if endianness == Endianness::Big {
@@ -231,6 +236,76 @@ mod tests {
             let expected: IResult<&[u8], Option<Message>> = Ok((b"----", Some(msg)));
             assert_eq!(expected, dlt_message(&msg_bytes, None, 0, None, None, false));
         }
+        #[test]
+        fn test_message_v2_to_bytes_to_message(msg in message_v2_strat()) {
+            init_logging();
+            let mut msg_bytes = msg.as_bytes();
+            msg_bytes.extend(b"----");
+            let expected: IResult<&[u8], Option<Message>> = Ok((b"----", Some(msg)));
+            assert_eq!(expected, dlt_message(&msg_bytes, None, 0, None, None, false));
+        }
+    }
+    #[test]
+    fn test_parse_v2_message() {
+        #[rustfmt::skip]
+        let raw: Vec<u8> = vec![
+            // header type: non verbose, ecu id, app and context id, version 2
+            0x4D,
+            // source location, tags, privacy level
+            0x07, 0x00, 0x00,
+            0x05, // message counter
+            0x00, 0x34, // length
+            0x00, 0x0F, 0x42, 0x40, 0x00, 0x00, 0x00, 0x00, 0x3C, // timestamp
+            0x00, 0x00, 0x00, 0x10, // message id
+            0x04, b'E', b'C', b'U', b'1',
+            0x03, b'A', b'P', b'P', 0x03, b'C', b'T', b'X',
+            0x08, b'm', b'a', b'i', b'n', b'.', b'c', b'p', b'p', 0x00, 0x00, 0x00, 0x2A,
+            0x01, 0x01, b'A', // tags
+            0x03, // privacy level
+            // payload
+            0x01, 0x02,
+        ];
+        let v2 = HeaderV2 {
+            content_info: ContentInfo::NonVerbose,
+            message_type: None,
+            timestamp: Some(DltTimeStampV2 {
+                seconds: 60,
+                nanoseconds: 1_000_000,
+            }),
+            app_id_context_id: Some(("APP".to_string(), "CTX".to_string())),
+            source_location: Some(SourceLocation {
+                file_name: "main.cpp".to_string(),
+                line_number: 42,
+            }),
+            tags: Some(vec!["A".to_string()]),
+            privacy_level: Some(3),
+        };
+        let expected = Message {
+            storage_header: None,
+            header: StandardHeader {
+                version: 2,
+                endianness: Endianness::Big,
+                has_extended_header: true,
+                message_counter: 5,
+                ecu_id: Some("ECU1".to_string()),
+                session_id: None,
+                timestamp: None,
+                payload_length: 2,
+                v2: Some(Box::new(v2.clone())),
+            },
+            extended_header: v2.extended_header(0),
+            payload: Payload2 {
+                payload_content: PayloadContent::NonVerbose(0x10, vec![0x01, 0x02]),
+            },
+            fibex_metadata: None,
+        };
+        assert_eq!(
+            Ok((&[][..], Some(expected.clone()))),
+            dlt_message(&raw, None, 0, None, None, false)
+        );
+        assert_eq!(raw, expected.as_bytes());
+        assert_eq!(Some(0x34), peek_message_length(&raw));
+        assert!(expected.to_string().contains("60.001000000"));
     }
     fn dump_to_file(msg_bytes: &[u8]) -> std::io::Result<()> {
         let home_dir = dirs::home_dir().expect("we need to have access to home-dir");
@@ -308,13 +383,15 @@ mod tests {
             &tx,
             None,
             None,
+            false,
+            false,
         );
     }
 
     #[test]
     fn test_parse_offending_argument() {
         let type_info = TypeInfo {
-            kind: TypeInfoKind::SignedFixedPoint(FloatWidth::Width64),
+            kind: TypeInfoKind::SignedFixedPoint(TypeLength::BitLength64),
             coding: StringCoding::UTF8,
             has_variable_info: true,
             has_trace_info: false,
@@ -340,7 +417,7 @@ mod tests {
     fn test2_parse_offending_argument() {
         let argument = Argument {
             type_info: TypeInfo {
-                kind: TypeInfoKind::SignedFixedPoint(FloatWidth::Width32),
+                kind: TypeInfoKind::SignedFixedPoint(TypeLength::BitLength32),
                 coding: StringCoding::UTF8,
                 has_variable_info: true,
                 has_trace_info: false,
@@ -361,6 +438,98 @@ mod tests {
         assert_eq!(expected, res);
     }
     #[test]
+    fn test_parse_float16_argument() {
+        let argument = Argument {
+            type_info: TypeInfo {
+                kind: TypeInfoKind::Float(FloatWidth::Width16),
+                coding: StringCoding::UTF8,
+                has_variable_info: false,
+                has_trace_info: false,
+            },
+            name: None,
+            unit: None,
+            fixed_point: None,
+            value: Value::F32(-1.5),
+        };
+        let mut argument_bytes = argument.as_bytes::<BigEndian>();
+        assert_eq!(vec![0x00, 0x00, 0x80, 0x82, 0xBE, 0x00], argument_bytes);
+        argument_bytes.extend(b"----");
+        let res: IResult<&[u8], Argument> = dlt_argument::<BigEndian>(&argument_bytes);
+        let expected: IResult<&[u8], Argument> = Ok((b"----", argument));
+        assert_eq!(expected, res);
+    }
+    #[test]
+    fn test_parse_128_bit_fixed_point_argument() {
+        let argument = Argument {
+            type_info: TypeInfo {
+                kind: TypeInfoKind::UnsignedFixedPoint(TypeLength::BitLength128),
+                coding: StringCoding::UTF8,
+                has_variable_info: true,
+                has_trace_info: false,
+            },
+            name: Some("speed".to_string()),
+            unit: Some("km/h".to_string()),
+            fixed_point: Some(FixedPoint {
+                quantization: 0.5,
+                offset: FixedPointValue::I128(-10),
+            }),
+            value: Value::U128(u128::MAX - 1),
+        };
+        let mut argument_bytes = argument.as_bytes::<LittleEndian>();
+        argument_bytes.extend(b"----");
+        let res: IResult<&[u8], Argument> = dlt_argument::<LittleEndian>(&argument_bytes);
+        let expected: IResult<&[u8], Argument> = Ok((b"----", argument));
+        assert_eq!(expected, res);
+    }
+    #[test]
+    fn test_fixed_point_with_negative_128_bit_offset() {
+        let argument = Argument {
+            type_info: TypeInfo {
+                kind: TypeInfoKind::SignedFixedPoint(TypeLength::BitLength128),
+                coding: StringCoding::UTF8,
+                has_variable_info: false,
+                has_trace_info: false,
+            },
+            name: None,
+            unit: None,
+            fixed_point: Some(FixedPoint {
+                quantization: 2.0,
+                offset: FixedPointValue::I128(-(1 << 70)),
+            }),
+            value: Value::I128(1 << 60),
+        };
+        let mut argument_bytes = argument.as_bytes::<BigEndian>();
+        argument_bytes.extend(b"----");
+        let res: IResult<&[u8], Argument> = dlt_argument::<BigEndian>(&argument_bytes);
+        let expected: IResult<&[u8], Argument> = Ok((b"----", argument.clone()));
+        assert_eq!(expected, res);
+        assert_eq!("-1178285777708197600000", argument.to_string());
+
+        let array = Argument {
+            type_info: TypeInfo {
+                kind: TypeInfoKind::Array(Box::new(TypeInfoKind::SignedFixedPoint(
+                    TypeLength::BitLength32,
+                ))),
+                ..argument.type_info
+            },
+            fixed_point: Some(FixedPoint {
+                quantization: 1.5,
+                offset: FixedPointValue::I32(-200),
+            }),
+            value: Value::Array {
+                dimensions: vec![3],
+                values: vec![Value::I32(-44), Value::I32(10), Value::I32(i32::MAX)],
+            },
+            ..argument
+        };
+        let mut argument_bytes = array.as_bytes::<LittleEndian>();
+        argument_bytes.extend(b"----");
+        let res: IResult<&[u8], Argument> = dlt_argument::<LittleEndian>(&argument_bytes);
+        let expected: IResult<&[u8], Argument> = Ok((b"----", array.clone()));
+        assert_eq!(expected, res);
+        assert_eq!("[-266, -185, 3221225270.5]", array.to_string());
+    }
+    #[test]
     fn test_parse_bool_argument() {
         {
             let type_info = TypeInfo {
@@ -468,7 +637,7 @@ mod tests {
             fixed_point: None,
             value: Value::U32(2_063_359_909),
             // type_info: TypeInfo {
-            //     kind: TypeInfoKind::UnsignedFixedPoint(FloatWidth::Width32),
+            //     kind: TypeInfoKind::UnsignedFixedPoint(TypeLength::BitLength32),
             //     coding: StringCoding::ASCII,
             //     has_variable_info: false,
             //     has_trace_info: false,
@@ -614,6 +783,132 @@ mod tests {
         assert_eq!(expected, res);
     }
     #[test]
+    fn test_parse_array_argument() {
+        #[rustfmt::skip]
+        let argument_bytes: Vec<u8> = vec![
+            // type info: array of u16 with variable info
+            0x42, 0x09, 0x00, 0x00,
+            // 2 dimensions: 2 x 3
+            0x02, 0x00, 0x02, 0x00, 0x03, 0x00,
+            // name and unit length
+            0x02, 0x00, 0x03, 0x00,
+            b'a', 0x0, b'c', b'm', 0x0,
+            // values
+            0x01, 0x00, 0x02, 0x00, 0x03, 0x00, 0x04, 0x00, 0x05, 0x00, 0x06, 0x00,
+            b'-', b'-', b'-', b'-',
+        ];
+        let argument = Argument {
+            type_info: TypeInfo {
+                kind: TypeInfoKind::Array(Box::new(TypeInfoKind::Unsigned(
+                    TypeLength::BitLength16,
+                ))),
+                coding: StringCoding::ASCII,
+                has_variable_info: true,
+                has_trace_info: false,
+            },
+            name: Some("a".to_string()),
+            unit: Some("cm".to_string()),
+            fixed_point: None,
+            value: Value::Array {
+                dimensions: vec![2, 3],
+                values: (1..=6u16).map(Value::U16).collect(),
+            },
+        };
+        let res: IResult<&[u8], Argument> = dlt_argument::<LittleEndian>(&argument_bytes);
+        let expected: IResult<&[u8], Argument> = Ok((b"----", argument.clone()));
+        assert_eq!(expected, res);
+        assert_eq!(
+            &argument_bytes[..argument_bytes.len() - 4],
+            &argument.as_bytes::<LittleEndian>()[..]
+        );
+        assert_eq!("a: [[1, 2, 3], [4, 5, 6]] cm", format!("{}", argument));
+    }
+    #[test]
+    fn test_parse_array_with_huge_dimensions() {
+        let array_bytes = |dimensions: &[u16]| {
+            // type info: array of u8 without variable info
+            let mut bytes = vec![0x41, 0x01, 0x00, 0x00, dimensions.len() as u8, 0x00];
+            for d in dimensions {
+                bytes.extend_from_slice(&d.to_le_bytes());
+            }
+            bytes.extend(b"----");
+            bytes
+        };
+        // the element count overflows
+        assert!(dlt_argument::<LittleEndian>(&array_bytes(&[0xFFFF; 5])).is_err());
+        // no elements, but the inner dimensions still overflow
+        let bytes = array_bytes(&[0, 0xFFFF, 0xFFFF, 0xFFFF, 0xFFFF, 0xFFFF]);
+        let (rest, argument) =
+            dlt_argument::<LittleEndian>(&bytes).expect("could not parse empty array");
+        assert_eq!(b"----", rest);
+        assert_eq!("[]", format!("{}", argument));
+    }
+    #[test]
+    fn test_parse_struct_argument() {
+        #[rustfmt::skip]
+        let argument_bytes: Vec<u8> = vec![
+            // type info: struct with variable info
+            0x00, 0x00, 0x48, 0x00,
+            // 2 entries
+            0x00, 0x02,
+            // name
+            0x00, 0x02, b's', 0x0,
+            // bool entry
+            0x00, 0x00, 0x00, 0x11, 0x01,
+            // array of 2 u8 entry
+            0x00, 0x00, 0x01, 0x41, 0x00, 0x01, 0x00, 0x02, 0x07, 0x08,
+            b'-', b'-', b'-', b'-',
+        ];
+        let bool_type = TypeInfo {
+            kind: TypeInfoKind::Bool,
+            coding: StringCoding::ASCII,
+            has_variable_info: false,
+            has_trace_info: false,
+        };
+        let array_type = TypeInfo {
+            kind: TypeInfoKind::Array(Box::new(TypeInfoKind::Unsigned(TypeLength::BitLength8))),
+            ..bool_type.clone()
+        };
+        let argument = Argument {
+            type_info: TypeInfo {
+                kind: TypeInfoKind::Struct,
+                coding: StringCoding::ASCII,
+                has_variable_info: true,
+                has_trace_info: false,
+            },
+            name: Some("s".to_string()),
+            unit: None,
+            fixed_point: None,
+            value: Value::Struct(vec![
+                Argument {
+                    type_info: bool_type,
+                    name: None,
+                    unit: None,
+                    fixed_point: None,
+                    value: Value::Bool(true),
+                },
+                Argument {
+                    type_info: array_type,
+                    name: None,
+                    unit: None,
+                    fixed_point: None,
+                    value: Value::Array {
+                        dimensions: vec![2],
+                        values: vec![Value::U8(7), Value::U8(8)],
+                    },
+                },
+            ]),
+        };
+        let res: IResult<&[u8], Argument> = dlt_argument::<BigEndian>(&argument_bytes);
+        let expected: IResult<&[u8], Argument> = Ok((b"----", argument.clone()));
+        assert_eq!(expected, res);
+        assert_eq!(
+            &argument_bytes[..argument_bytes.len() - 4],
+            &argument.as_bytes::<BigEndian>()[..]
+        );
+        assert_eq!("s: {true, [7, 8]}", format!("{}", argument));
+    }
+    #[test]
     fn test_parse_string_argument() {
         let type_info = TypeInfo {
             kind: TypeInfoKind::StringType,
@@ -696,7 +991,7 @@ mod tests {
     #[test]
     fn test_parse_fixed_point_argument() {
         let type_info = TypeInfo {
-            kind: TypeInfoKind::SignedFixedPoint(FloatWidth::Width32),
+            kind: TypeInfoKind::SignedFixedPoint(TypeLength::BitLength32),
             coding: StringCoding::UTF8,
             has_variable_info: true,
             has_trace_info: false,
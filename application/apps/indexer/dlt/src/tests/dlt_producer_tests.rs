@@ -0,0 +1,255 @@
+#[cfg(test)]
+mod tests {
+    use crate::dlt_parallel::*;
+    use crate::dlt_parse::*;
+    use crate::tests::{describe, index_into};
+    use crossbeam_channel as cc;
+    use indexer_base::chunks::ChunkResults;
+    use indexer_base::config::IndexingConfig;
+    use pretty_assertions::assert_eq;
+    use std::path::PathBuf;
+    use tempdir::TempDir;
+
+    /// index with the producer created by `create_producer` and return the
+    /// reported results, the output and the offset index
+    fn index<P, F>(
+        name: &str,
+        content_len: usize,
+        create_producer: F,
+    ) -> (Vec<String>, Vec<u8>, Option<Vec<u8>>)
+    where
+        P: MessageProducer + Unpin,
+        F: FnOnce(cc::Sender<ChunkResults>) -> P,
+    {
+        let tmp_dir = TempDir::new("test_dir").expect("could not create temp dir");
+        let out_path = tmp_dir.path().join(name);
+        index_into(&out_path, |tx| {
+            index_dlt_content(
+                IndexingConfig {
+                    tag: "TAG",
+                    chunk_size: 10,
+                    in_file: test_file(),
+                    out_path: &out_path,
+                    append: false,
+                },
+                0,
+                Some(content_len),
+                tx,
+                None,
+                None,
+                &mut create_producer(tx.clone()),
+            )
+        })
+    }
+
+    fn test_file() -> PathBuf {
+        PathBuf::from("..")
+            .join("../indexer-neon/tests")
+            .join("testfile.dlt")
+    }
+
+    #[test]
+    fn test_memory_producer_matches_file_producer() {
+        let content = std::fs::read(test_file()).unwrap();
+        let from_file = index("producer_file.out", content.len(), |tx| {
+            FileMessageProducer::new(&test_file(), None, 0, tx, true).unwrap()
+        });
+        let from_memory = index("producer_memory.out", content.len(), |tx| {
+            MemoryMessageProducer::new(&content, None, 0, tx, true)
+        });
+        assert_eq!(from_file.0, from_memory.0);
+        assert!(from_file.1 == from_memory.1, "output differs");
+        assert!(from_file.2 == from_memory.2, "offset index differs");
+        assert_eq!(105, String::from_utf8_lossy(&from_memory.1).lines().count());
+        let from_mapped_file = index("producer_mapped.out", content.len(), |tx| {
+            FileMessageProducer::memory_mapped(&test_file(), None, 0, tx, true).unwrap()
+        });
+        assert_eq!(from_file.0, from_mapped_file.0);
+        assert!(from_file.1 == from_mapped_file.1, "output differs");
+        assert!(from_file.2 == from_mapped_file.2, "offset index differs");
+    }
+
+    #[test]
+    fn test_index_memory_mapped_file() {
+        let index_file = |name: &str, memory_map: bool| {
+            let tmp_dir = TempDir::new("test_dir").expect("could not create temp dir");
+            let out_path = tmp_dir.path().join(name);
+            index_into(&out_path, |tx| {
+                create_index_and_mapping_dlt(
+                    IndexingConfig {
+                        tag: "TAG",
+                        chunk_size: 10,
+                        in_file: test_file(),
+                        out_path: &out_path,
+                        append: false,
+                    },
+                    indexer_base::input::input_size(&test_file()),
+                    None,
+                    tx,
+                    None,
+                    None,
+                    memory_map,
+                    false,
+                )
+            })
+        };
+        let buffered = index_file("producer_index_buffered.out", false);
+        let mapped = index_file("producer_index_mapped.out", true);
+        assert_eq!(buffered.0, mapped.0);
+        assert!(buffered.1 == mapped.1, "output differs");
+        assert_eq!(105, String::from_utf8_lossy(&mapped.1).lines().count());
+    }
+
+    #[test]
+    fn test_memory_producer_skips_garbage() {
+        let mut content = b"garbage".to_vec();
+        content.extend(std::fs::read(test_file()).unwrap());
+        let (tx, rx): (cc::Sender<ChunkResults>, cc::Receiver<ChunkResults>) = cc::unbounded();
+        let mut producer = MemoryMessageProducer::new(&content, None, 0, tx, true);
+        let mut messages = 0usize;
+        let mut consumed_total = 0usize;
+        loop {
+            let (consumed, next) = producer.produce_next_message(None);
+            if consumed == 0 {
+                break;
+            }
+            consumed_total += consumed;
+            if let Ok(Some(_)) = next {
+                messages += 1;
+            }
+        }
+        drop(producer);
+        assert_eq!(105, messages);
+        assert_eq!(content.len(), consumed_total);
+        let notifications: Vec<String> = rx.iter().map(describe).collect();
+        assert_eq!(
+            vec!["WARNING no valid message found: skipped 7 bytes (offset 0..7) None".to_string()],
+            notifications
+        );
+    }
+
+    #[test]
+    fn test_index_gzipped_file() {
+        use std::io::Write;
+        let content = std::fs::read(test_file()).unwrap();
+        let tmp_dir = TempDir::new("test_dir").expect("could not create temp dir");
+        let gz = tmp_dir.path().join("producer_gzipped.dlt.gz");
+        let mut encoder = flate2::write::GzEncoder::new(
+            std::fs::File::create(&gz).unwrap(),
+            flate2::Compression::default(),
+        );
+        encoder.write_all(&content).unwrap();
+        encoder.finish().unwrap();
+        let from_file = index("producer_plain.out", content.len(), |tx| {
+            FileMessageProducer::new(&test_file(), None, 0, tx, true).unwrap()
+        });
+
+        let out_path = tmp_dir.path().join("producer_gzipped.out");
+        let (results, out, offsets) = index_into(&out_path, |tx| {
+            create_index_and_mapping_dlt_parallel(
+                IndexingConfig {
+                    tag: "TAG",
+                    chunk_size: 10,
+                    in_file: gz.clone(),
+                    out_path: &out_path,
+                    append: false,
+                },
+                indexer_base::input::input_size(&gz),
+                None,
+                tx,
+                None,
+                vec![],
+                ParallelConfig {
+                    threads: 4,
+                    segment_size: 1000,
+                    memory_map: false,
+                    absolute_time: false,
+                },
+            )
+        });
+        let compressed_size = std::fs::metadata(&gz).unwrap().len();
+        // compressed content cannot be read by offset
+        assert_eq!(None, offsets);
+        assert!(from_file.1 == out, "output differs");
+        let (progress, results): (Vec<String>, Vec<String>) =
+            results.into_iter().partition(|r| r.starts_with("progress"));
+        let expected: Vec<String> = from_file
+            .0
+            .into_iter()
+            .filter(|r| !r.starts_with("progress"))
+            .collect();
+        assert_eq!(expected, results);
+        // progress is reported against the compressed size
+        assert_eq!(
+            Some(&format!("progress ({}, {})", compressed_size, compressed_size)),
+            progress.last()
+        );
+    }
+
+    /// index the test file written into a named pipe
+    #[cfg(unix)]
+    fn index_from_fifo(name: &str, threads: usize) -> Option<(Vec<String>, Vec<u8>)> {
+        let tmp_dir = TempDir::new("test_dir").expect("could not create temp dir");
+        let fifo = tmp_dir.path().join(format!("{}.fifo", name));
+        let out_path = tmp_dir.path().join(format!("{}.out", name));
+        let created = std::process::Command::new("mkfifo")
+            .arg(&fifo)
+            .status()
+            .map(|s| s.success())
+            .unwrap_or(false);
+        if !created {
+            return None;
+        }
+        let writer_fifo = fifo.clone();
+        let writer = std::thread::spawn(move || {
+            let content = std::fs::read(test_file()).unwrap();
+            std::fs::write(&writer_fifo, content).unwrap();
+        });
+        let (results, out, offsets) = index_into(&out_path, |tx| {
+            create_index_and_mapping_dlt_parallel(
+                IndexingConfig {
+                    tag: "TAG",
+                    chunk_size: 10,
+                    in_file: fifo.clone(),
+                    out_path: &out_path,
+                    append: false,
+                },
+                None,
+                None,
+                tx,
+                None,
+                vec![],
+                ParallelConfig {
+                    threads,
+                    segment_size: 1000,
+                    memory_map: false,
+                    absolute_time: false,
+                },
+            )
+        });
+        writer.join().unwrap();
+        // a pipe cannot be read again, so there is no offset index
+        assert_eq!(None, offsets);
+        Some((results, out))
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_index_from_named_pipe() {
+        let content = std::fs::read(test_file()).unwrap();
+        let from_file = index("producer_pipe_file.out", content.len(), |tx| {
+            FileMessageProducer::new(&test_file(), None, 0, tx, true).unwrap()
+        });
+        let expected_chunks: Vec<String> = from_file
+            .0
+            .into_iter()
+            .filter(|r| !r.starts_with("progress"))
+            .collect();
+        for threads in &[1usize, 4] {
+            if let Some((results, out)) = index_from_fifo("producer_pipe", *threads) {
+                assert_eq!(expected_chunks, results);
+                assert!(from_file.1 == out, "output differs");
+            }
+        }
+    }
+}
@@ -0,0 +1,129 @@
+#[cfg(test)]
+mod tests {
+    use crate::dlt_parse::{create_index_and_mapping_dlt, dlt_message};
+    use crate::dlt_recovery::*;
+    use crossbeam_channel as cc;
+    use indexer_base::chunks::ChunkResults;
+    use indexer_base::config::IndexingConfig;
+    use pretty_assertions::assert_eq;
+    use std::path::PathBuf;
+    use tempdir::TempDir;
+
+    fn test_file_content() -> (Vec<u8>, Vec<usize>) {
+        let content = std::fs::read(
+            PathBuf::from("..")
+                .join("../indexer-neon/tests")
+                .join("testfile.dlt"),
+        )
+        .unwrap();
+        let mut starts = vec![];
+        let mut rest: &[u8] = &content;
+        while !rest.is_empty() {
+            starts.push(content.len() - rest.len());
+            rest = dlt_message(rest, None, 0, None, None, true).unwrap().0;
+        }
+        (content, starts)
+    }
+
+    /// index the content and return the number of lines and the notifications
+    fn index_content(content: &[u8], name: &str) -> (usize, Vec<String>) {
+        let tmp_dir = TempDir::new("test_dir").expect("could not create temp dir");
+        let in_path = tmp_dir.path().join(format!("{}.dlt", name));
+        let out_path = tmp_dir.path().join(format!("{}.dlt.out", name));
+        std::fs::write(&in_path, content).unwrap();
+        let (tx, rx): (cc::Sender<ChunkResults>, cc::Receiver<ChunkResults>) = cc::unbounded();
+        create_index_and_mapping_dlt(
+            IndexingConfig {
+                tag: "TAG",
+                chunk_size: 500,
+                in_file: in_path.clone(),
+                out_path: &out_path,
+                append: false,
+            },
+            None,
+            None,
+            &tx,
+            None,
+            None,
+            false,
+            false,
+        )
+        .expect("indexing failed");
+        drop(tx);
+        let notifications = rx
+            .iter()
+            .filter_map(|r| r.err())
+            .map(|n| n.content)
+            .collect();
+        let lines = std::fs::read_to_string(&out_path).unwrap().lines().count();
+        (lines, notifications)
+    }
+
+    #[test]
+    fn test_truncated_last_message() {
+        let (content, starts) = test_file_content();
+        let cut = content.len() - 10;
+        let (lines, notifications) = index_content(&content[..cut], "dlt_recovery_truncated");
+        assert_eq!(starts.len() - 1, lines);
+        assert_eq!(
+            vec![format!(
+                "truncated message at end of input: skipped {} bytes (offset {}..{})",
+                cut - starts[starts.len() - 1],
+                starts[starts.len() - 1],
+                cut
+            )],
+            notifications
+        );
+    }
+
+    #[test]
+    fn test_corrupted_length_and_garbage() {
+        let (content, starts) = test_file_content();
+        let mut corrupted = content[..starts[71]].to_vec();
+        // the standard header of message 50 claims to be much longer
+        corrupted[starts[50] + 18] = 0x7F;
+        // garbage that contains a storage header pattern
+        corrupted.extend_from_slice(b"xxDLT\x01yyyyyyyyyyyyyyyyyyyyyyyy");
+        let garbage_end = corrupted.len();
+        corrupted.extend_from_slice(&content[starts[71]..]);
+        let (lines, notifications) = index_content(&corrupted, "dlt_recovery_corrupted");
+        assert_eq!(starts.len() - 1, lines);
+        assert_eq!(
+            vec![
+                format!(
+                    "no valid message found: skipped {} bytes (offset {}..{})",
+                    starts[51] - starts[50],
+                    starts[50],
+                    starts[51]
+                ),
+                format!(
+                    "no valid message found: skipped {} bytes (offset {}..{})",
+                    garbage_end - starts[71],
+                    starts[71],
+                    garbage_end
+                ),
+            ],
+            notifications
+        );
+    }
+
+    #[test]
+    fn test_next_plausible_storage_header() {
+        let (content, starts) = test_file_content();
+        let mut input = b"DLT\x01garbage".to_vec();
+        input.extend_from_slice(&content[starts[0]..starts[3]]);
+        assert_eq!(
+            Resync::Found(11),
+            next_plausible_storage_header(&input, true)
+        );
+        // the message after the garbage is cut off, more content could tell
+        assert_eq!(
+            Resync::NeedMore,
+            next_plausible_storage_header(&input[..20], false)
+        );
+        assert_eq!(
+            Resync::NotFound,
+            next_plausible_storage_header(b"no dlt here", true)
+        );
+    }
+}
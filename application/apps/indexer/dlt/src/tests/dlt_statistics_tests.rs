@@ -0,0 +1,197 @@
+#[cfg(test)]
+mod tests {
+    use crate::dlt::*;
+    use crate::dlt_parse::*;
+    use crate::fibex::*;
+    use crate::tests::TestMessage;
+    use crossbeam_channel as cc;
+    use indexer_base::progress::IndexingProgress;
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+    use std::path::PathBuf;
+    use std::rc::Rc;
+    use tempdir::TempDir;
+
+    const START: u64 = 1_600_000_000_000;
+
+    fn msg(app_id: &'static str, level: LogLevel, counter: u8, time: u64) -> Message {
+        TestMessage {
+            counter,
+            session_id: Some(1),
+            storage_time: Some(DltTimeStamp::from_ms(time)),
+            ..TestMessage::log(level, app_id, "CTX")
+        }
+        .build()
+    }
+
+    fn statistics(messages: &[Message]) -> serde_json::Value {
+        statistics_with_fibex(messages, None)
+    }
+
+    fn statistics_with_fibex(
+        messages: &[Message],
+        fibex: Option<Rc<FibexMetadata>>,
+    ) -> serde_json::Value {
+        let tmp_dir = TempDir::new("test_dir").expect("could not create temp dir");
+        let in_path = tmp_dir.path().join("dlt_statistics_timeline.dlt");
+        let content: Vec<u8> = messages.iter().flat_map(|m| m.as_bytes()).collect();
+        std::fs::write(&in_path, content).unwrap();
+        let (tx, rx): (
+            cc::Sender<StatisticsResults>,
+            cc::Receiver<StatisticsResults>,
+        ) = cc::unbounded();
+        get_dlt_file_info(&in_path, &tx, None, fibex, false).expect("could not get statistics");
+        drop(tx);
+        rx.iter()
+            .find_map(|r| match r {
+                Ok(IndexingProgress::GotItem { item }) => serde_json::to_value(item).ok(),
+                _ => None,
+            })
+            .expect("no statistics reported")
+    }
+
+    #[test]
+    fn test_timeline_grows_bucket_size() {
+        let messages = [
+            msg("APP1", LogLevel::Info, 0, START),
+            msg("APP1", LogLevel::Error, 1, START + 250_000),
+            // storage times are not always in order
+            msg("APP2", LogLevel::Error, 0, START + 10_500),
+            msg("APP1", LogLevel::Warn, 2, START - 5_000),
+            // a logger without real time clock starts in 1970
+            msg("APP2", LogLevel::Info, 1, 42_000),
+        ];
+        let stats = statistics(&messages);
+        assert_eq!(json!([START - 5_000, START + 250_000]), stats["time_range"]);
+        assert_eq!(json!(1), stats["implausible_storage_times"]);
+        let message_length = messages[0].as_bytes().len();
+        assert_eq!(
+            json!([["APP1", 3 * message_length], ["APP2", 2 * message_length]]),
+            stats["app_id_bytes"]
+        );
+        let timeline = &stats["timeline"];
+        // 1 s and 2 s buckets would be more than 100 to cover 255 s
+        assert_eq!(json!(4000), timeline["bucket_size"]);
+        let buckets = timeline["buckets"].as_array().unwrap();
+        assert_eq!(65, buckets.len());
+        assert_eq!(json!(START - 8_000), buckets[0]["start"]);
+        assert_eq!(json!(1), buckets[0]["levels"]["log_warning"]);
+        assert_eq!(json!(1), buckets[2]["levels"]["log_info"]);
+        assert_eq!(json!([["APP2", 1]]), buckets[4]["app_ids"]);
+        assert_eq!(json!(1), buckets[4]["levels"]["log_error"]);
+        assert_eq!(json!([["APP1", 1]]), buckets[64]["app_ids"]);
+        let total: u64 = buckets
+            .iter()
+            .flat_map(|b| b["app_ids"].as_array().unwrap().iter())
+            .map(|a| a[1].as_u64().unwrap())
+            .sum();
+        assert_eq!(4, total);
+    }
+
+    #[test]
+    fn test_statistics_of_memory_mapped_file() {
+        let test_file = PathBuf::from("..")
+            .join("../indexer-neon/tests")
+            .join("testfile.dlt");
+        let file_info = |memory_map: bool| {
+            let (tx, rx): (
+                cc::Sender<StatisticsResults>,
+                cc::Receiver<StatisticsResults>,
+            ) = cc::unbounded();
+            get_dlt_file_info(&test_file, &tx, None, None, memory_map)
+                .expect("could not get statistics");
+            drop(tx);
+            rx.iter()
+                .find_map(|r| match r {
+                    Ok(IndexingProgress::GotItem { item }) => serde_json::to_value(item).ok(),
+                    _ => None,
+                })
+                .expect("no statistics reported")
+        };
+        let mapped = file_info(true);
+        assert_eq!(file_info(false), mapped);
+        assert!(mapped["time_range"].is_array());
+    }
+
+    #[test]
+    fn test_statistics_of_truncated_file() {
+        let content = std::fs::read(
+            PathBuf::from("..")
+                .join("../indexer-neon/tests")
+                .join("testfile.dlt"),
+        )
+        .unwrap();
+        let tmp_dir = TempDir::new("test_dir").expect("could not create temp dir");
+        let in_path = tmp_dir.path().join("dlt_statistics_truncated.dlt");
+        std::fs::write(&in_path, &content[..content.len() - 10]).unwrap();
+        for memory_map in &[false, true] {
+            let (tx, rx): (
+                cc::Sender<StatisticsResults>,
+                cc::Receiver<StatisticsResults>,
+            ) = cc::unbounded();
+            get_dlt_file_info(&in_path, &tx, None, None, *memory_map)
+                .expect("could not get statistics");
+            drop(tx);
+            let notifications: Vec<String> = rx
+                .iter()
+                .filter_map(|r| r.err())
+                .map(|n| format!("{} {}", n.severity.as_str(), n.content))
+                .collect();
+            assert_eq!(1, notifications.len(), "{:?}", notifications);
+            assert!(
+                notifications[0].starts_with("WARNING truncated message at end of input: skipped")
+            );
+        }
+    }
+
+    #[test]
+    fn test_statistics_of_messages_beyond_the_read_buffer() {
+        // big messages over more than the 10 MB buffer of the reader straddle its window
+        let message = TestMessage {
+            payload: PayloadContent::Verbose(vec![Argument {
+                type_info: TypeInfo {
+                    kind: TypeInfoKind::StringType,
+                    coding: StringCoding::ASCII,
+                    has_variable_info: false,
+                    has_trace_info: false,
+                },
+                name: None,
+                unit: None,
+                fixed_point: None,
+                value: Value::StringVal("x".repeat(40_000)),
+            }]),
+            ..Default::default()
+        }
+        .build();
+        let count = 11 * 1024 * 1024 / message.as_bytes().len();
+        let messages = vec![message; count];
+        let stats = statistics(&messages);
+        assert_eq!(json!(count), stats["app_ids"][0][1]["log_info"]);
+    }
+
+    #[test]
+    fn test_ids_of_nonverbose_messages_from_fibex() {
+        let fibex = read_fibexes(vec![
+            PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/dlt-compu-methods.xml")
+        ])
+        .expect("can't parse fibex");
+        // without extended header only the fibex knows the ids and the level
+        let message = TestMessage {
+            endianness: Endianness::Little,
+            extended: None,
+            payload: PayloadContent::NonVerbose(100, vec![3, 0x1E, 0x69, 0, 0, 0, 42]),
+            storage_time: Some(DltTimeStamp::from_ms(START)),
+            ..Default::default()
+        }
+        .build();
+        let messages = [message.clone(), message];
+        let stats = statistics(&messages);
+        assert_eq!(json!("NONE"), stats["app_ids"][0][0]);
+        assert_eq!(json!(2), stats["app_ids"][0][1]["non_log"]);
+        let stats = statistics_with_fibex(&messages, Some(Rc::new(fibex)));
+        assert_eq!(json!("ENG"), stats["app_ids"][0][0]);
+        assert_eq!(json!(2), stats["app_ids"][0][1]["log_info"]);
+        assert_eq!(json!("STAT"), stats["context_ids"][0][0]);
+        assert_eq!(json!(2), stats["ecu_ids"][0][1]["log_info"]);
+    }
+}
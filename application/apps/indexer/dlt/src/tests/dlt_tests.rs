@@ -38,6 +38,7 @@ mod tests {
             ecu_id: Some("abc".to_string()),
             session_id: None,
             timestamp: Some(5),
+            v2: None,
         };
         assert_eq!(
             vec![
@@ -143,7 +144,7 @@ mod tests {
             has_trace_info: false,
         };
         let type_info2 = TypeInfo {
-            kind: TypeInfoKind::UnsignedFixedPoint(FloatWidth::Width32),
+            kind: TypeInfoKind::UnsignedFixedPoint(TypeLength::BitLength32),
             coding: StringCoding::ASCII,
             has_variable_info: false,
             has_trace_info: false,
@@ -378,6 +379,28 @@ mod tests {
         assert_eq!(expected, argument.as_bytes::<BigEndian>());
     }
     #[test]
+    fn test_convert_half_precision_floats() {
+        assert_eq!(0.0, f16_to_f32(0x0000));
+        assert_eq!(1.0, f16_to_f32(0x3C00));
+        assert_eq!(-2.0, f16_to_f32(0xC000));
+        assert_eq!(65504.0, f16_to_f32(0x7BFF));
+        assert_eq!(2f32.powi(-24), f16_to_f32(0x0001));
+        assert_eq!(f32::INFINITY, f16_to_f32(0x7C00));
+        assert!(f16_to_f32(0x7E00).is_nan());
+        // rounding to nearest even and overflow
+        assert_eq!(0x3C00, f32_to_f16(1.0 + 2f32.powi(-11)));
+        assert_eq!(0x3C02, f32_to_f16(1.0 + 3.0 * 2f32.powi(-11)));
+        assert_eq!(0x7C00, f32_to_f16(70000.0));
+        assert_eq!(0x0000, f32_to_f16(2f32.powi(-26)));
+        // every non NaN value survives a round trip
+        for bits in 0..=u16::MAX {
+            let value = f16_to_f32(bits);
+            if !value.is_nan() {
+                assert_eq!(bits, f32_to_f16(value), "{:04X}", bits);
+            }
+        }
+    }
+    #[test]
     fn test_convert_string_argument_to_bytes() {
         let type_info = TypeInfo {
             kind: TypeInfoKind::StringType,
@@ -423,7 +446,7 @@ mod tests {
     #[test]
     fn test_convert_fixedpoint_argument_to_bytes() {
         let type_info = TypeInfo {
-            kind: TypeInfoKind::SignedFixedPoint(FloatWidth::Width32),
+            kind: TypeInfoKind::SignedFixedPoint(TypeLength::BitLength32),
             coding: StringCoding::UTF8,
             has_variable_info: true,
             has_trace_info: false,
@@ -454,7 +477,7 @@ mod tests {
 
         // now without variable info
         let type_info = TypeInfo {
-            kind: TypeInfoKind::SignedFixedPoint(FloatWidth::Width32),
+            kind: TypeInfoKind::SignedFixedPoint(TypeLength::BitLength32),
             coding: StringCoding::UTF8,
             has_variable_info: false,
             has_trace_info: false,
@@ -0,0 +1,313 @@
+#[cfg(test)]
+mod tests {
+    use crate::dlt::*;
+    use crate::dlt_export::*;
+    use crate::dlt_parse::create_index_and_mapping_dlt;
+    use crate::dlt_time::*;
+    use crate::filtering::{DltFilterConfig, ProcessedDltFilterConfig};
+    use crate::tests::TestMessage;
+    use crossbeam_channel as cc;
+    use indexer_base::chunks::ChunkResults;
+    use indexer_base::config::IndexingConfig;
+    use indexer_base::progress::IndexingProgress;
+    use indexer_base::utils::PLUGIN_ID_SENTINAL;
+    use pretty_assertions::assert_eq;
+    use std::io::Write;
+    use std::path::{Path, PathBuf};
+    use tempdir::TempDir;
+
+    /// ECU start used for the test messages (in us since epoch)
+    const START: i64 = 1_500_000_000_000_000;
+
+    /// message sent `ticks` after `start` that was received `latency` us later
+    fn timed_msg(ecu_id: &'static str, start: i64, ticks: u32, latency: i64) -> Message {
+        let storage_time = start + i64::from(ticks) * 100 + latency;
+        TestMessage {
+            ecu_id: Some(ecu_id),
+            timestamp: Some(ticks),
+            storage_time: Some(DltTimeStamp {
+                seconds: (storage_time / 1_000_000) as u32,
+                microseconds: (storage_time % 1_000_000) as u32,
+            }),
+            ..Default::default()
+        }
+        .build()
+    }
+
+    fn micros(t: &DltTimeStamp) -> i64 {
+        i64::from(t.seconds) * 1_000_000 + i64::from(t.microseconds)
+    }
+
+    #[test]
+    fn test_start_is_anchored_by_minimum_latency() {
+        let messages = [
+            timed_msg("ECU1", START, 10_000, 30_000),
+            timed_msg("ECU1", START, 20_000, 2_000_000),
+            timed_msg("ECU1", START, 30_000, 0),
+            timed_msg("ECU1", START, 40_000, 5_000),
+        ];
+        let mut model = TimeModel::new();
+        let observed: Vec<i64> = messages
+            .iter()
+            .map(|m| micros(&model.observe(m).unwrap()))
+            .collect();
+        // while observing the estimate gets better with every message
+        assert_eq!(START + 1_000_000 + 30_000, observed[0]);
+        assert_eq!(START + 2_000_000 + 30_000, observed[1]);
+        assert_eq!(START + 3_000_000, observed[2]);
+        for (msg, ticks) in messages.iter().zip(&[10_000i64, 20_000, 30_000, 40_000]) {
+            assert_eq!(
+                START + ticks * 100,
+                micros(&model.absolute_time(msg).unwrap())
+            );
+        }
+    }
+
+    #[test]
+    fn test_restart_starts_new_lifecycle() {
+        let restart = START + 60_000_000;
+        let messages = [
+            timed_msg("ECU1", START, 100_000, 0),
+            timed_msg("ECU1", START, 200_000, 1_000),
+            // buffered message that arrives late is no restart
+            timed_msg("ECU1", START, 150_000, 9_000_000),
+            timed_msg("ECU1", restart, 50, 10),
+            timed_msg("ECU1", restart, 1_000, 0),
+            timed_msg("ECU2", START, 200, 0),
+        ];
+        let mut model = TimeModel::new();
+        for msg in &messages {
+            model.observe(msg);
+        }
+        let absolute: Vec<i64> = messages
+            .iter()
+            .map(|m| micros(&model.absolute_time(m).unwrap()))
+            .collect();
+        assert_eq!(
+            vec![
+                START + 10_000_000,
+                START + 20_000_000,
+                START + 15_000_000,
+                restart + 5_000,
+                restart + 100_000,
+                START + 20_000,
+            ],
+            absolute
+        );
+    }
+
+    #[test]
+    fn test_messages_without_timestamps() {
+        let mut msg = timed_msg("ECU1", START, 100, 0);
+        msg.header.timestamp = None;
+        let mut model = TimeModel::new();
+        assert_eq!(None, model.observe(&msg));
+        let mut msg = timed_msg("ECU1", START, 100, 0);
+        msg.storage_header = None;
+        assert_eq!(None, model.observe(&msg));
+        assert_eq!(None, model.absolute_time(&msg));
+    }
+
+    #[test]
+    fn test_export_absolute_time_column() {
+        let messages = [
+            timed_msg("ECU1", START, 10_000, 500_000),
+            timed_msg("ECU1", START, 20_000, 0),
+        ];
+        let tmp_dir = TempDir::new("test_dir").expect("could not create temp dir");
+        let in_path = tmp_dir.path().join("dlt_time_test_in.dlt");
+        let csv_path = tmp_dir.path().join("dlt_time_test_out.csv");
+        let in_bytes: Vec<u8> = messages.iter().flat_map(|m| m.as_bytes()).collect();
+        std::fs::write(&in_path, &in_bytes).expect("could not write input");
+        let (tx, _rx): (cc::Sender<ChunkResults>, cc::Receiver<ChunkResults>) = cc::unbounded();
+        export_dlt_messages(
+            &in_path,
+            &csv_path,
+            None,
+            DltExportConfig {
+                format: ExportFormat::Csv(vec![
+                    CsvColumn::from_name("timestamp").unwrap(),
+                    CsvColumn::from_name("abstime").unwrap(),
+                ]),
+                ..Default::default()
+            },
+            None,
+            &tx,
+            None,
+        )
+        .expect("csv export failed");
+        let csv = std::fs::read_to_string(&csv_path).expect("could not read csv");
+        // the first message is corrected with the start estimated from the second
+        assert_eq!(
+            format!(
+                "timestamp,abstime\n10000,{}\n20000,{}\n",
+                START + 1_000_000,
+                START + 2_000_000
+            ),
+            csv
+        );
+    }
+
+    #[test]
+    fn test_export_absolute_time_reads_file_twice() {
+        let messages = [
+            timed_msg("ECU1", START, 10_000, 500_000),
+            timed_msg("ECU1", START, 20_000, 0),
+        ];
+        let tmp_dir = TempDir::new("test_dir").expect("could not create temp dir");
+        let in_path = tmp_dir.path().join("dlt_time_test_twice_in.dlt");
+        let csv_path = tmp_dir.path().join("dlt_time_test_twice_out.csv");
+        let in_bytes: Vec<u8> = messages.iter().flat_map(|m| m.as_bytes()).collect();
+        std::fs::write(&in_path, &in_bytes).expect("could not write input");
+        let export = |shutdown_receiver| {
+            let (tx, rx): (cc::Sender<ChunkResults>, cc::Receiver<ChunkResults>) = cc::unbounded();
+            let exported = export_dlt_messages(
+                &in_path,
+                &csv_path,
+                None,
+                DltExportConfig {
+                    format: ExportFormat::Csv(vec![CsvColumn::from_name("abstime").unwrap()]),
+                    ..Default::default()
+                },
+                None,
+                &tx,
+                shutdown_receiver,
+            )
+            .expect("csv export failed");
+            drop(tx);
+            (
+                exported,
+                rx.iter().filter_map(Result::ok).collect::<Vec<_>>(),
+            )
+        };
+        let (exported, progress) = export(None);
+        assert_eq!(2, exported);
+        // both passes are reported as progress of the export
+        assert!(progress.iter().any(|p| match p {
+            IndexingProgress::Progress { ticks } => *ticks == (in_bytes.len(), 2 * in_bytes.len()),
+            _ => false,
+        }));
+        let (shutdown_tx, shutdown_rx) = cc::unbounded();
+        shutdown_tx.send(()).unwrap();
+        let (exported, progress) = export(Some(shutdown_rx));
+        assert_eq!(0, exported);
+        assert!(progress
+            .iter()
+            .any(|p| matches!(p, IndexingProgress::Stopped)));
+    }
+
+    /// index the file and return the last column of every message
+    fn indexed_absolute_times(
+        in_path: &Path,
+        out_path: &PathBuf,
+        dlt_filter: Option<DltFilterConfig>,
+        absolute_time: bool,
+    ) -> Vec<String> {
+        let (tx, _rx): (cc::Sender<ChunkResults>, cc::Receiver<ChunkResults>) = cc::unbounded();
+        create_index_and_mapping_dlt(
+            IndexingConfig {
+                tag: "TAG",
+                chunk_size: 10,
+                in_file: in_path.to_path_buf(),
+                out_path,
+                append: false,
+            },
+            None,
+            dlt_filter,
+            &tx,
+            None,
+            None,
+            false,
+            absolute_time,
+        )
+        .expect("indexing failed");
+        let out = std::fs::read_to_string(out_path).expect("could not read output");
+        out.lines()
+            .map(|line| {
+                let message = line.split(PLUGIN_ID_SENTINAL).next().unwrap();
+                message
+                    .rsplit(DLT_COLUMN_SENTINAL)
+                    .next()
+                    .unwrap()
+                    .to_string()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_index_absolute_time_column() {
+        let messages = [
+            timed_msg("ECU1", START, 10_000, 500_000),
+            timed_msg("ECU1", START, 20_000, 0),
+        ];
+        let tmp_dir = TempDir::new("test_dir").expect("could not create temp dir");
+        let in_path = tmp_dir.path().join("dlt_time_test_index.dlt");
+        let out_path = tmp_dir.path().join("dlt_time_test_index.out");
+        let in_bytes: Vec<u8> = messages.iter().flat_map(|m| m.as_bytes()).collect();
+        std::fs::write(&in_path, &in_bytes).expect("could not write input");
+        let with_time = indexed_absolute_times(&in_path, &out_path, None, true);
+        let without_time = indexed_absolute_times(&in_path, &out_path, None, false);
+        // the first message is corrected with the start estimated from the second
+        assert_eq!(
+            vec![
+                (START + 1_000_000).to_string(),
+                (START + 2_000_000).to_string()
+            ],
+            with_time
+        );
+        // the last column is the payload otherwise
+        assert!(without_time
+            .iter()
+            .all(|column| column.parse::<i64>().is_err()));
+    }
+
+    #[test]
+    fn test_absolute_time_of_filtered_stream() {
+        // only the message of the filtered app gives the right start
+        let mut filtered = timed_msg("ECU1", START, 10_000, 0);
+        filtered.extended_header.as_mut().unwrap().application_id = "OTHR".to_string();
+        let messages = [filtered, timed_msg("ECU1", START, 20_000, 500_000)];
+        let tmp_dir = TempDir::new("test_dir").expect("could not create temp dir");
+        // compressed files can only be read once
+        let in_path = tmp_dir.path().join("dlt_time_test_stream.dlt.gz");
+        let csv_path = tmp_dir.path().join("dlt_time_test_stream.csv");
+        let out_path = tmp_dir.path().join("dlt_time_test_stream.out");
+        let mut encoder = flate2::write::GzEncoder::new(
+            std::fs::File::create(&in_path).unwrap(),
+            flate2::Compression::default(),
+        );
+        for msg in &messages {
+            encoder.write_all(&msg.as_bytes()).unwrap();
+        }
+        encoder.finish().unwrap();
+        let (tx, _rx): (cc::Sender<ChunkResults>, cc::Receiver<ChunkResults>) = cc::unbounded();
+        export_dlt_messages(
+            &in_path,
+            &csv_path,
+            Some(ProcessedDltFilterConfig {
+                app_ids: Some(vec!["APP".to_string()].into_iter().collect()),
+                ..Default::default()
+            }),
+            DltExportConfig {
+                format: ExportFormat::Csv(vec![CsvColumn::from_name("abstime").unwrap()]),
+                ..Default::default()
+            },
+            None,
+            &tx,
+            None,
+        )
+        .expect("csv export failed");
+        let csv = std::fs::read_to_string(&csv_path).expect("could not read csv");
+        let indexed = indexed_absolute_times(
+            &in_path,
+            &out_path,
+            Some(DltFilterConfig {
+                app_ids: Some(vec!["APP".to_string()]),
+                ..Default::default()
+            }),
+            true,
+        );
+        assert_eq!(format!("abstime\n{}\n", START + 2_000_000), csv);
+        assert_eq!(vec![(START + 2_000_000).to_string()], indexed);
+    }
+}
@@ -1,13 +1,272 @@
 #[cfg(test)]
 mod tests {
     use std::path::PathBuf;
-    use crate::fibex::read_fibexes;
+    use std::rc::Rc;
+    use crate::dlt::*;
+    use crate::fibex::*;
+    use crate::tests::payload_column;
+    use pretty_assertions::assert_eq;
+    use tempdir::TempDir;
     #[test]
     fn test_fibex_parsing() {
-        let fibex = read_fibexes(&[
-            &PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/dlt-messages.xml")
+        let fibex = read_fibexes(vec![
+            PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/dlt-messages.xml")
         ])
         .expect("can't parse fibex");
         println!("{:?}", fibex);
     }
+
+    fn nonverbose_message(
+        fibex: Rc<FibexMetadata>,
+        (app_id, context_id): (&str, &str),
+        id: u32,
+        data: Vec<u8>,
+    ) -> Message {
+        Message::new(
+            MessageConfig {
+                version: 1,
+                endianness: Endianness::Big,
+                counter: 0,
+                ecu_id: Some("ECU1".to_string()),
+                session_id: None,
+                timestamp: None,
+                payload: Payload2 {
+                    payload_content: PayloadContent::NonVerbose(id, data),
+                },
+                extended_header_info: Some(ExtendedHeaderConfig {
+                    message_type: MessageType::Log(LogLevel::Info),
+                    app_id: app_id.to_string(),
+                    context_id: context_id.to_string(),
+                }),
+            },
+            Some(fibex),
+            None,
+        )
+    }
+
+    #[test]
+    fn test_128_bit_signals() {
+        let fibex = read_fibexes(vec![
+            PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/dlt-messages.xml")
+        ])
+        .expect("can't parse fibex");
+        let mut data = (1u128 << 100).to_be_bytes().to_vec();
+        data.extend_from_slice(&(-2i128).to_be_bytes());
+        assert_eq!(
+            format!(
+                "{s}checksums:  {s}{} {s}-2 ",
+                1u128 << 100,
+                s = DLT_ARGUMENT_SENTINAL
+            ),
+            payload_column(&nonverbose_message(
+                Rc::new(fibex),
+                ("DR", "CTX1"),
+                66,
+                data
+            ))
+        );
+    }
+
+    #[test]
+    fn test_compu_methods() {
+        let fibex = read_fibexes(vec![
+            PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/dlt-compu-methods.xml")
+        ])
+        .expect("can't parse fibex");
+        let fibex = Rc::new(fibex);
+        let values = |data: Vec<u8>| -> Vec<String> {
+            nonverbose_message(fibex.clone(), ("ENG", "STAT"), 100, data)
+                .nonverbose_arguments()
+                .expect("frame not decoded")
+                .iter()
+                .map(|arg| arg.to_string())
+                .collect()
+        };
+        assert_eq!(
+            vec!["state: ", "STATE_RUNNING", "temperature: ", "23.5 °C", "42"],
+            values(vec![3, 0x1E, 0x69, 0, 0, 0, 42])
+        );
+        // physical values stay numbers, only the names of raw values are texts
+        let arguments = nonverbose_message(
+            fibex.clone(),
+            ("ENG", "STAT"),
+            100,
+            vec![3, 0x1E, 0x69, 0, 0, 0, 42],
+        )
+        .nonverbose_arguments()
+        .expect("frame not decoded");
+        assert_eq!(
+            Value::StringVal("STATE_RUNNING".to_string()),
+            arguments[1].value
+        );
+        assert_eq!(Value::F64(23.5), arguments[3].value);
+        assert_eq!(Some("°C".to_string()), arguments[3].unit);
+        assert_eq!("23.5 °C", arguments[3].to_string());
+        // values without a name are shown as they are
+        assert_eq!(
+            vec!["state: ", "9", "temperature: ", "-54.35 °C", "7"],
+            values(vec![9, 0, 0, 0, 0, 0, 7])
+        );
+    }
+
+    #[test]
+    fn test_scale_linear_and_texttable() {
+        let method = CompuMethod {
+            category: CompuCategory::ScaleLinearAndTextTable,
+            scales: vec![
+                CompuScale {
+                    lower_limit: Some(0.0),
+                    upper_limit: Some(100.0),
+                    numerator: vec![0.0, 0.5],
+                    denominator: vec![1.0],
+                    ..Default::default()
+                },
+                CompuScale {
+                    lower_limit: Some(255.0),
+                    upper_limit: Some(255.0),
+                    text: Some("INVALID".to_string()),
+                    ..Default::default()
+                },
+            ],
+            unit: Some("%".to_string()),
+        };
+        assert_eq!(Some(PhysicalValue::Number(25.0)), method.convert(50.0));
+        assert_eq!(
+            Some(PhysicalValue::Text("INVALID".to_string())),
+            method.convert(255.0)
+        );
+        assert_eq!(None, method.convert(200.0));
+    }
+
+    /// element, id and line and column
+    type WarningLocation = (String, Option<String>, Option<(usize, usize)>);
+
+    fn warnings(report: &FibexReport) -> Vec<WarningLocation> {
+        report
+            .warnings
+            .iter()
+            .map(|w| (w.element.clone(), w.id.clone(), w.line_and_column))
+            .collect()
+    }
+
+    #[test]
+    fn test_skip_unusable_elements() {
+        let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/dlt-broken.xml");
+        let report = read_fibexes_with_report(vec![path.clone()]).expect("can't read fibex");
+        assert_eq!((1, 3), (report.frames, report.pdus));
+        let element = |element: &str, id: Option<&str>, line, column| {
+            (
+                element.to_string(),
+                id.map(str::to_string),
+                Some((line, column)),
+            )
+        };
+        assert_eq!(
+            vec![
+                element("PDU", Some("ID_6003"), 39, 70),
+                element("FRAME", Some("ID_202"), 103, 23),
+                element("SIGNAL", None, 114, 24),
+                element("CODING", Some("C_MODE"), 131, 24),
+                element("PDU", Some("ID_6002"), 22, 33),
+                element("FRAME", Some("ID_201"), 83, 34),
+            ],
+            warnings(&report)
+        );
+        assert_eq!(
+            format!(
+                "{}:22:33: PDU ID_6002: skipped: unsupported signal type S_FIXED_POINT",
+                path.display()
+            ),
+            report.warnings[4].to_string()
+        );
+        let fibex = Rc::new(report.metadata);
+        assert_eq!(
+            vec!["mode: ", "2", "7"],
+            nonverbose_message(fibex, ("GEAR", "MODE"), 200, vec![2, 7])
+                .nonverbose_arguments()
+                .expect("frame not decoded")
+                .iter()
+                .map(|arg| arg.to_string())
+                .collect::<Vec<String>>()
+        );
+    }
+
+    #[test]
+    fn test_keep_elements_before_xml_error() {
+        let content = std::fs::read_to_string(
+            PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/dlt-broken.xml"),
+        )
+        .unwrap();
+        // the end tag of the first FRAME does not match
+        let content = content.replacen("</fx:FRAME>", "</fx:FRAM>", 1);
+        let tmp_dir = TempDir::new("test_dir").expect("could not create temp dir");
+        let path = tmp_dir.path().join("fibex_xml_error.xml");
+        std::fs::write(&path, content).unwrap();
+        let report = read_fibexes_with_report(vec![path.clone()]);
+        let report = report.expect("can't read fibex");
+        // the signal S_MODE is defined after the error
+        assert_eq!((0, 2), (report.frames, report.pdus));
+        let stopped = report
+            .warnings
+            .iter()
+            .find(|w| w.reason.starts_with("reading of the file stopped"))
+            .expect("reading did not stop");
+        assert_eq!(Some(82), stopped.line_and_column.map(|(line, _)| line));
+    }
+
+    #[test]
+    fn test_merge_fibexes() {
+        let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/dlt-compu-methods.xml");
+        let content = std::fs::read_to_string(&path).unwrap();
+        // the same definitions in several files are no conflict
+        let report =
+            read_fibexes_with_report(vec![path.clone(), path.clone()]).expect("can't read fibex");
+        assert_eq!((1, 5), (report.frames, report.pdus));
+        assert!(report.warnings.is_empty());
+
+        let tmp_dir = TempDir::new("test_dir").expect("could not create temp dir");
+        let changed_path = tmp_dir.path().join("fibex_conflicts.xml");
+        std::fs::write(
+            &changed_path,
+            content
+                .replacen("<ho:DESC>state: </ho:DESC>", "<ho:DESC>mode: </ho:DESC>", 1)
+                .replacen("°C", "K", 1),
+        )
+        .unwrap();
+        let report = read_fibexes_with_report(vec![path.clone(), changed_path.clone()]);
+        let report = report.expect("can't read fibex");
+        assert_eq!((1, 5), (report.frames, report.pdus));
+        assert_eq!(
+            vec![
+                (
+                    "UNIT".to_string(),
+                    Some("U_DEGC".to_string()),
+                    "skipped: conflicts with an earlier definition".to_string()
+                ),
+                (
+                    "PDU".to_string(),
+                    Some("ID_5000".to_string()),
+                    format!(
+                        "skipped: conflicts with the definition in {}",
+                        path.display()
+                    )
+                ),
+            ],
+            report
+                .warnings
+                .iter()
+                .map(|w| (w.element.clone(), w.id.clone(), w.reason.clone()))
+                .collect::<Vec<(String, Option<String>, String)>>()
+        );
+        // the first definitions are used
+        assert_eq!(
+            vec!["state: ", "STATE_INIT", "temperature: ", "-54.35 °C", "0"],
+            nonverbose_message(Rc::new(report.metadata), ("ENG", "STAT"), 100, vec![0; 7])
+                .nonverbose_arguments()
+                .expect("frame not decoded")
+                .iter()
+                .map(|arg| arg.to_string())
+                .collect::<Vec<String>>()
+        );
+    }
 }
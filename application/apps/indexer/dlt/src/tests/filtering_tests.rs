@@ -0,0 +1,115 @@
+#[cfg(test)]
+mod tests {
+    use crate::dlt::*;
+    use crate::dlt_parse::*;
+    use crate::filtering::*;
+    use crate::tests::TestMessage;
+
+    /// true if the message is kept by the filter
+    fn passes(filter: &str, message: TestMessage) -> bool {
+        let config: DltFilterConfig = serde_json::from_str(filter).expect("invalid filter");
+        let config = process_filter_config(config);
+        let stored = message.storage_time.is_some();
+        let bytes = message.build().as_bytes();
+        let (rest, parsed) = dlt_message(&bytes, Some(&config), 0, None, None, stored)
+            .expect("could not parse message");
+        // left out messages are skipped as a whole
+        assert!(rest.is_empty());
+        parsed.is_some()
+    }
+
+    #[test]
+    fn test_exclusions_and_log_levels() {
+        let filter = r#"{
+            "min_log_level": 3,
+            "app_log_levels": { "NAV": 5 },
+            "context_log_levels": { "GPS": 2 },
+            "exclude_app_ids": ["NOIS"],
+            "exclude_context_ids": ["SPAM"],
+            "exclude_ecu_ids": ["ECU2"]
+        }"#;
+        let log = TestMessage::log;
+        assert!(passes(filter, log(LogLevel::Warn, "APP", "CTX")));
+        assert!(!passes(filter, log(LogLevel::Info, "APP", "CTX")));
+        assert!(passes(filter, log(LogLevel::Debug, "NAV", "CTX")));
+        assert!(!passes(filter, log(LogLevel::Verbose, "NAV", "CTX")));
+        // context levels take precedence over app levels
+        assert!(!passes(filter, log(LogLevel::Warn, "NAV", "GPS")));
+        assert!(passes(filter, log(LogLevel::Error, "NAV", "GPS")));
+        assert!(!passes(filter, log(LogLevel::Fatal, "NOIS", "CTX")));
+        assert!(!passes(filter, log(LogLevel::Fatal, "APP", "SPAM")));
+        assert!(!passes(
+            filter,
+            TestMessage {
+                ecu_id: Some("ECU2"),
+                ..log(LogLevel::Fatal, "APP", "CTX")
+            }
+        ));
+    }
+
+    #[test]
+    fn test_message_types_and_verbosity() {
+        let network_trace = || TestMessage {
+            extended: Some((
+                MessageType::NetworkTrace(NetworkTraceType::Can),
+                "APP",
+                "CTX",
+            )),
+            ..Default::default()
+        };
+        let filter = r#"{ "exclude_message_types": ["control", "network_trace"] }"#;
+        assert!(passes(filter, TestMessage::default()));
+        assert!(!passes(filter, network_trace()));
+        let filter = r#"{ "message_types": ["network_trace"] }"#;
+        assert!(!passes(filter, TestMessage::default()));
+        assert!(passes(filter, network_trace()));
+
+        let non_verbose = || TestMessage {
+            payload: PayloadContent::NonVerbose(1, vec![1, 2, 3]),
+            ..Default::default()
+        };
+        assert!(passes(r#"{ "verbose": false }"#, non_verbose()));
+        assert!(!passes(r#"{ "verbose": true }"#, non_verbose()));
+        assert!(passes(r#"{ "verbose": true }"#, TestMessage::default()));
+    }
+
+    #[test]
+    fn test_time_range_and_missing_headers() {
+        let filter = r#"{ "from_timestamp": 1000000, "to_timestamp": 2000000 }"#;
+        let at = |time: Option<u64>| TestMessage {
+            storage_time: time.map(DltTimeStamp::from_ms),
+            ..Default::default()
+        };
+        assert!(passes(filter, at(Some(1_000_000))));
+        assert!(passes(filter, at(Some(2_000_000))));
+        assert!(!passes(filter, at(Some(999_999))));
+        assert!(!passes(filter, at(Some(2_000_001))));
+        assert!(!passes(filter, at(None)));
+
+        // the ECU id of the storage header is used if the message has none
+        let without_ecu_id = |time: Option<u64>| TestMessage {
+            ecu_id: None,
+            storage_time: time.map(DltTimeStamp::from_ms),
+            ..Default::default()
+        };
+        assert!(!passes(r#"{ "ecu_ids": ["ECU1"] }"#, without_ecu_id(None)));
+        assert!(passes(
+            r#"{ "ecu_ids": ["STOR"] }"#,
+            without_ecu_id(Some(1))
+        ));
+
+        let without_extended_header = || TestMessage {
+            extended: None,
+            payload: PayloadContent::NonVerbose(1, vec![1, 2, 3]),
+            ..Default::default()
+        };
+        assert!(passes(
+            r#"{ "app_ids": ["APP"] }"#,
+            without_extended_header()
+        ));
+        assert!(!passes(
+            r#"{ "skip_without_extended_header": true }"#,
+            without_extended_header()
+        ));
+    }
+}
@@ -2,3 +2,129 @@
 mod dlt_tests;
 mod dlt_parse_tests;
 mod fibex_tests;
+mod arxml_tests;
+mod dlt_net_tests;
+mod dlt_export_tests;
+mod dlt_control_tests;
+mod dlt_offsets_tests;
+mod dlt_parallel_tests;
+mod dlt_recovery_tests;
+mod dlt_producer_tests;
+mod dlt_time_tests;
+mod dlt_lifecycle_tests;
+mod dlt_counter_tests;
+mod dlt_statistics_tests;
+mod someip_tests;
+mod can_tests;
+mod filtering_tests;
+
+use crate::dlt::*;
+use crate::dlt_offsets::offset_index_path;
+use crossbeam_channel as cc;
+use failure::Error;
+use indexer_base::chunks::ChunkResults;
+use indexer_base::progress::IndexingProgress;
+use std::path::Path;
+
+/// a reported indexing result as comparable text
+fn describe(result: ChunkResults) -> String {
+    match result {
+        Ok(IndexingProgress::GotItem { item }) => format!("chunk {:?} {:?}", item.r, item.b),
+        Ok(IndexingProgress::Progress { ticks }) => format!("progress {:?}", ticks),
+        Ok(IndexingProgress::Processed { bytes }) => format!("processed {}", bytes),
+        Ok(IndexingProgress::Stopped) => "stopped".to_string(),
+        Ok(IndexingProgress::Finished) => "finished".to_string(),
+        Err(n) => format!("{} {} {:?}", n.severity.as_str(), n.content, n.line),
+    }
+}
+
+/// the payload column of the message
+fn payload_column(message: &Message) -> String {
+    message
+        .to_string()
+        .rsplit(DLT_COLUMN_SENTINAL)
+        .next()
+        .unwrap()
+        .to_string()
+}
+
+/// run an indexing into `out_path` and return the reported results, the output
+/// and the offset index (if one was written)
+fn index_into<F>(out_path: &Path, run: F) -> (Vec<String>, Vec<u8>, Option<Vec<u8>>)
+where
+    F: FnOnce(&cc::Sender<ChunkResults>) -> Result<(), Error>,
+{
+    let (tx, rx): (cc::Sender<ChunkResults>, cc::Receiver<ChunkResults>) = cc::unbounded();
+    run(&tx).expect("indexing failed");
+    drop(tx);
+    let results = rx.iter().map(describe).collect();
+    let out = std::fs::read(out_path).unwrap();
+    let offsets = std::fs::read(offset_index_path(out_path)).ok();
+    (results, out, offsets)
+}
+
+/// a message for the tests, fields that are not set are a verbose info log
+/// without arguments of `APP`/`CTX` from `ECU1` that was stored at 1000 s
+/// the ECU of the storage header is the ECU of the header (or `STOR`)
+struct TestMessage {
+    endianness: Endianness,
+    counter: u8,
+    ecu_id: Option<&'static str>,
+    session_id: Option<u32>,
+    timestamp: Option<u32>,
+    /// message type, application and context of the extended header
+    extended: Option<(MessageType, &'static str, &'static str)>,
+    payload: PayloadContent,
+    storage_time: Option<DltTimeStamp>,
+}
+impl Default for TestMessage {
+    fn default() -> Self {
+        TestMessage {
+            endianness: Endianness::Big,
+            counter: 0,
+            ecu_id: Some("ECU1"),
+            session_id: None,
+            timestamp: None,
+            extended: Some((MessageType::Log(LogLevel::Info), "APP", "CTX")),
+            payload: PayloadContent::Verbose(vec![]),
+            storage_time: Some(DltTimeStamp::from_ms(1_000_000)),
+        }
+    }
+}
+impl TestMessage {
+    fn log(level: LogLevel, app_id: &'static str, context_id: &'static str) -> TestMessage {
+        TestMessage {
+            extended: Some((MessageType::Log(level), app_id, context_id)),
+            ..Default::default()
+        }
+    }
+
+    fn build(self) -> Message {
+        let ecu_id = self.ecu_id;
+        Message::new(
+            MessageConfig {
+                version: 1,
+                endianness: self.endianness,
+                counter: self.counter,
+                ecu_id: ecu_id.map(str::to_string),
+                session_id: self.session_id,
+                timestamp: self.timestamp,
+                payload: Payload2 {
+                    payload_content: self.payload,
+                },
+                extended_header_info: self.extended.map(|(message_type, app_id, context_id)| {
+                    ExtendedHeaderConfig {
+                        message_type,
+                        app_id: app_id.to_string(),
+                        context_id: context_id.to_string(),
+                    }
+                }),
+            },
+            None,
+            self.storage_time.map(|timestamp| StorageHeader {
+                timestamp,
+                ecu_id: ecu_id.unwrap_or("STOR").to_string(),
+            }),
+        )
+    }
+}
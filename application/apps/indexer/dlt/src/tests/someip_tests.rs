@@ -0,0 +1,221 @@
+#[cfg(test)]
+mod tests {
+    use crate::dlt::*;
+    use crate::fibex::*;
+    use crate::someip::*;
+    use crate::tests::payload_column;
+    use pretty_assertions::assert_eq;
+    use std::path::PathBuf;
+    use std::rc::Rc;
+
+    fn read_services() -> FibexReport {
+        read_fibexes_with_report(vec![
+            PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/someip-services.json")
+        ])
+        .expect("can't read service mapping")
+    }
+
+    fn someip_bytes(method_id: u16, message_type: u8, payload: &[u8]) -> Vec<u8> {
+        let mut bytes = vec![0x12, 0x34];
+        bytes.extend(&method_id.to_be_bytes());
+        bytes.extend(&(8 + payload.len() as u32).to_be_bytes());
+        bytes.extend(&[0x00, 0x07, 0x00, 0x2A, 0x01, 0x03, message_type, 0x00]);
+        bytes.extend(payload);
+        bytes
+    }
+
+    fn network_trace(
+        payload_content: PayloadContent,
+        metadata: Option<Rc<FibexMetadata>>,
+    ) -> Message {
+        Message::new(
+            MessageConfig {
+                version: 1,
+                endianness: Endianness::Big,
+                counter: 0,
+                ecu_id: Some("ECU1".to_string()),
+                session_id: None,
+                timestamp: Some(5),
+                payload: Payload2 { payload_content },
+                extended_header_info: Some(ExtendedHeaderConfig {
+                    message_type: MessageType::NetworkTrace(NetworkTraceType::Someip),
+                    app_id: "NET".to_string(),
+                    context_id: "SIP".to_string(),
+                }),
+            },
+            metadata,
+            None,
+        )
+    }
+
+    fn raw_arguments(parts: &[&[u8]]) -> PayloadContent {
+        PayloadContent::Verbose(
+            parts
+                .iter()
+                .map(|part| Argument {
+                    type_info: TypeInfo {
+                        kind: TypeInfoKind::Raw,
+                        coding: StringCoding::ASCII,
+                        has_variable_info: false,
+                        has_trace_info: false,
+                    },
+                    name: None,
+                    unit: None,
+                    fixed_point: None,
+                    value: Value::Raw(part.to_vec()),
+                })
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn test_decode_someip_header() {
+        let bytes = someip_bytes(0x0001, 0x20 | 0x01, &[0xAB, 0xCD, 0xEF]);
+        let message = decode_someip_message(&bytes, None).expect("not decoded");
+        assert_eq!(
+            SomeipHeader {
+                service_id: 0x1234,
+                method_id: 0x0001,
+                length: 11,
+                client_id: 0x0007,
+                session_id: 0x002A,
+                protocol_version: 1,
+                interface_version: 3,
+                message_type: SomeipMessageType::RequestNoReturn,
+                tp: true,
+                return_code: ReturnCode::Ok,
+            },
+            message.header
+        );
+        assert_eq!(vec![0xAB, 0xCD, 0xEF], message.payload);
+        assert_eq!(None, message.parameters);
+        // too short for the header
+        assert_eq!(None, decode_someip_message(&bytes[..15], None));
+
+        // header and payload are traced as separate raw arguments
+        let trace = network_trace(raw_arguments(&[&bytes[..16], &bytes[16..]]), None);
+        assert_eq!(
+            "SOME/IP 0x1234.0x0001 REQUEST_NO_RETURN (TP) client 0x0007 session 0x002A E_OK\u{5}[AB, CD, EF]",
+            payload_column(&trace)
+        );
+    }
+
+    #[test]
+    fn test_resolve_names_and_parameters() {
+        let metadata = Rc::new(read_services().metadata);
+        let mut payload = vec![0x0B, 0xB8, 0, 0, 0, 9];
+        payload.extend(&[0xEF, 0xBB, 0xBF]);
+        payload.extend(b"manual\0");
+        let request = network_trace(
+            raw_arguments(&[&someip_bytes(0x0001, 0x00, &payload)]),
+            Some(metadata.clone()),
+        );
+        assert_eq!(
+            "SOME/IP Engine.setSpeed REQUEST client 0x0007 session 0x002A E_OK\u{5}rpm: 3000\u{5}reason: manual",
+            payload_column(&request)
+        );
+        let response = network_trace(
+            raw_arguments(&[&someip_bytes(0x0001, 0x80, &[1])]),
+            Some(metadata.clone()),
+        );
+        let someip_message = response.someip_message().expect("not decoded");
+        assert_eq!(Some("setSpeed"), someip_message.method_name.as_deref());
+        assert_eq!(
+            vec!["true"],
+            someip_message
+                .parameters
+                .expect("parameters not decoded")
+                .iter()
+                .map(|p| p.value.to_string())
+                .collect::<Vec<_>>()
+        );
+        // segments of SOME/IP-TP start with the TP header, so the parameters
+        // are not decoded
+        let segment = network_trace(
+            raw_arguments(&[&someip_bytes(0x0001, 0x20, &payload)]),
+            Some(metadata.clone()),
+        );
+        let someip_message = segment.someip_message().expect("not decoded");
+        assert_eq!(Some("setSpeed"), someip_message.method_name.as_deref());
+        assert_eq!(None, someip_message.parameters);
+        // a payload that doesn't match the parameters is shown as it is
+        let event = network_trace(
+            raw_arguments(&[&someip_bytes(0x8001, 0x02, &[0x41, 0xB4])]),
+            Some(metadata),
+        );
+        assert_eq!(
+            "SOME/IP Engine.temperature NOTIFICATION client 0x0007 session 0x002A E_OK\u{5}[41, B4]",
+            payload_column(&event)
+        );
+    }
+
+    #[test]
+    fn test_nonverbose_network_trace() {
+        let bytes = someip_bytes(0x0001, 0x80, &[0]);
+        // header and payload, each with their length, the message id
+        // consists of the length and the service id
+        let mut data = bytes[2..16].to_vec();
+        data.extend(&[0x00, 0x01, 0x00]);
+        let trace = network_trace(
+            PayloadContent::NonVerbose(0x0010_1234, data),
+            Some(Rc::new(read_services().metadata)),
+        );
+        let someip_message = trace.someip_message().expect("not decoded");
+        assert_eq!(0x1234, someip_message.header.service_id);
+        assert_eq!(vec![0], someip_message.payload);
+        assert_eq!(
+            "SOME/IP Engine.setSpeed RESPONSE client 0x0007 session 0x002A E_OK\u{5}accepted: false",
+            payload_column(&trace)
+        );
+        // the payload is only split if the first part has the length of the header
+        let payload = PayloadContent::NonVerbose(0x0002_AABB, vec![0x00, 0x01, 0xCC]);
+        assert_eq!(
+            Some(vec![0x00, 0x02, 0xAA, 0xBB, 0x00, 0x01, 0xCC]),
+            network_trace_bytes(&payload, Endianness::Big, SOMEIP_HEADER_LENGTH)
+        );
+        // other network traces are not decoded
+        let mut ipc = trace.clone();
+        ipc.extended_header.as_mut().unwrap().message_type =
+            MessageType::NetworkTrace(NetworkTraceType::Ipc);
+        assert_eq!(None, ipc.someip_message());
+    }
+
+    #[test]
+    fn test_service_mapping_warnings() {
+        let report = read_services();
+        assert_eq!(1, report.services);
+        assert_eq!(
+            Some("Engine"),
+            report.metadata.service(0x1234).map(|s| s.name.as_str())
+        );
+        assert_eq!(
+            vec![(
+                "service",
+                "0x1234",
+                "skipped: conflicts with an earlier definition"
+            )],
+            report
+                .warnings
+                .iter()
+                .map(|w| (
+                    w.element.as_str(),
+                    w.id.as_deref().unwrap_or_default(),
+                    w.reason.as_str()
+                ))
+                .collect::<Vec<_>>()
+        );
+        let report = read_fibexes_with_report(vec![
+            PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/someip-broken.json")
+        ])
+        .expect("can't read service mapping");
+        assert_eq!(0, report.services);
+        assert_eq!(1, report.warnings.len());
+        assert_eq!(
+            Some(4),
+            report.warnings[0].line_and_column.map(|(line, _)| line)
+        );
+        assert!(report.warnings[0]
+            .reason
+            .starts_with("reading of the file stopped: unknown variant `uint24`"));
+    }
+}
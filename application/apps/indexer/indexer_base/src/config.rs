@@ -45,3 +45,13 @@ pub struct MulticastInfo {
     pub multiaddr: String,
     pub interface: Option<String>,
 }
+/// tcp client config
+/// connects to a dlt-daemon (or any other dlt server) that sends dlt messages
+/// as a plain stream without storage headers.
+/// `address` is the ip address or hostname of the server, the dlt-daemon
+/// default `port` is 3490
+#[derive(Serialize, Deserialize, Debug)]
+pub struct TcpConfig {
+    pub address: String,
+    pub port: String,
+}
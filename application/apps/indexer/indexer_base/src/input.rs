@@ -0,0 +1,371 @@
+// Copyright (c) 2020 E.S.R.Labs. All rights reserved.
+//
+// NOTICE:  All information contained herein is, and remains
+// the property of E.S.R.Labs and its suppliers, if any.
+// The intellectual and technical concepts contained herein are
+// proprietary to E.S.R.Labs and its suppliers and may be covered
+// by German and Foreign Patents, patents in process, and are protected
+// by trade secret or copyright law.
+// Dissemination of this information or reproduction of this material
+// is strictly forbidden unless prior written permission is obtained
+// from E.S.R.Labs.
+//! Input sources that are given by a path
+//!
+//! Besides regular files an input can be a named pipe or `-` for stdin,
+//! e.g. `ssh ecu cat /var/log/trace.dlt | indexer_cli dlt -i - ...`.
+//! Such inputs can only be read once from start to end and their size is
+//! not known in advance.
+//!
+//! Inputs compressed with gzip, xz or zstd are detected by their magic bytes
+//! and decompressed while reading. A member of a zip archive is selected
+//! with `archive.zip!member`, an archive with a single file can be given
+//! without a member. Decompressed inputs can not be seeked, their size is
+//! the size of the compressed data.
+use std::fs;
+use std::io::{self, BufRead, BufReader, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+/// input path that stands for stdin
+pub const STDIN_PATH: &str = "-";
+/// separates the path of a zip archive from the member that should be read
+pub const ZIP_MEMBER_SEPARATOR: char = '!';
+
+/// enough bytes to detect all supported compression formats
+const MAGIC_LENGTH: usize = 6;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Compression {
+    Gzip,
+    Xz,
+    Zstd,
+    Zip,
+}
+
+impl Compression {
+    /// detect the compression format from the first bytes of an input
+    pub fn detect(magic: &[u8]) -> Option<Compression> {
+        if magic.starts_with(&[0x1F, 0x8B]) {
+            Some(Compression::Gzip)
+        } else if magic.starts_with(&[0xFD, 0x37, 0x7A, 0x58, 0x5A, 0x00]) {
+            Some(Compression::Xz)
+        } else if magic.starts_with(&[0x28, 0xB5, 0x2F, 0xFD]) {
+            Some(Compression::Zstd)
+        } else if magic.starts_with(&[0x50, 0x4B, 0x03, 0x04]) {
+            Some(Compression::Zip)
+        } else {
+            None
+        }
+    }
+}
+
+/// number of bytes that were read from the (compressed) source of an input
+/// it is shared with the reader so progress can be reported against the
+/// size of the source while the decompressed content is processed
+#[derive(Debug, Clone, Default)]
+pub struct SourcePosition(Arc<AtomicUsize>);
+
+impl SourcePosition {
+    pub fn get(&self) -> usize {
+        self.0.load(Ordering::Relaxed)
+    }
+    fn advance(&self, bytes: usize) {
+        self.0.fetch_add(bytes, Ordering::Relaxed);
+    }
+}
+
+/// counts the bytes read from the wrapped reader
+struct CountingReader<R> {
+    inner: R,
+    position: SourcePosition,
+}
+
+impl<R: Read> Read for CountingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.position.advance(n);
+        Ok(n)
+    }
+}
+
+pub fn is_stdin(path: &Path) -> bool {
+    path.as_os_str() == STDIN_PATH
+}
+
+/// splits `archive.zip!member` into the archive and the member
+/// only if the path itself does not exist but the archive does
+fn split_zip_member(path: &Path) -> Option<(PathBuf, String)> {
+    if path.exists() {
+        return None;
+    }
+    let s = path.to_str()?;
+    let pos = s.rfind(ZIP_MEMBER_SEPARATOR)?;
+    let archive = PathBuf::from(&s[..pos]);
+    if archive.is_file() {
+        Some((archive, s[pos + 1..].to_string()))
+    } else {
+        None
+    }
+}
+
+/// the file that has to exist for an input, for a zip member this is the archive
+pub fn source_path(path: &Path) -> PathBuf {
+    match split_zip_member(path) {
+        Some((archive, _)) => archive,
+        None => path.to_path_buf(),
+    }
+}
+
+/// the compression of a regular file, `None` for uncompressed files
+pub fn file_compression(path: &Path) -> Option<Compression> {
+    if split_zip_member(path).is_some() {
+        return Some(Compression::Zip);
+    }
+    if !fs::metadata(path).ok()?.is_file() {
+        return None;
+    }
+    let mut magic = Vec::with_capacity(MAGIC_LENGTH);
+    fs::File::open(path)
+        .ok()?
+        .take(MAGIC_LENGTH as u64)
+        .read_to_end(&mut magic)
+        .ok()?;
+    Compression::detect(&magic)
+}
+
+/// true if the input is an uncompressed regular file that can be read
+/// at any position (e.g. memory mapped or split into segments)
+pub fn is_random_access(path: &Path) -> bool {
+    !is_stdin(path)
+        && fs::metadata(path).map(|md| md.is_file()).unwrap_or(false)
+        && file_compression(path).is_none()
+}
+
+/// the size of the input if it is known in advance
+/// for stdin and named pipes only the processed bytes can be counted
+/// for compressed files this is the compressed size
+pub fn input_size(path: &Path) -> Option<usize> {
+    if is_stdin(path) {
+        return None;
+    }
+    if let Some((archive, member)) = split_zip_member(path) {
+        return zip_member(&archive, Some(&member))
+            .ok()
+            .map(|m| m.compressed_size as usize);
+    }
+    let size = fs::metadata(path)
+        .ok()
+        .filter(|md| md.is_file())
+        .map(|md| md.len() as usize)?;
+    if file_compression(path) == Some(Compression::Zip) {
+        return zip_member(path, None)
+            .ok()
+            .map(|m| m.compressed_size as usize);
+    }
+    Some(size)
+}
+
+pub enum Input {
+    Stream(BufReader<Box<dyn Read + Send>>),
+    File(fs::File),
+    /// content of a compressed file or stream, read while it is decompressed
+    Decompressed {
+        reader: Box<dyn Read + Send>,
+        position: SourcePosition,
+    },
+}
+
+impl Input {
+    /// the position in the compressed source for decompressed inputs
+    pub fn source_position(&self) -> Option<SourcePosition> {
+        match self {
+            Input::Decompressed { position, .. } => Some(position.clone()),
+            _ => None,
+        }
+    }
+}
+
+/// open stdin, a named pipe or a regular file for reading
+/// compressed content is decompressed transparently
+pub fn open_input(path: &Path) -> io::Result<Input> {
+    if is_stdin(path) {
+        return open_stream(Box::new(io::stdin()));
+    }
+    if let Some((archive, member)) = split_zip_member(path) {
+        return open_zip_member(&archive, Some(&member));
+    }
+    let mut file = fs::File::open(path)?;
+    if !file.metadata()?.is_file() {
+        return open_stream(Box::new(file));
+    }
+    let mut magic = Vec::with_capacity(MAGIC_LENGTH);
+    (&mut file)
+        .take(MAGIC_LENGTH as u64)
+        .read_to_end(&mut magic)?;
+    file.seek(SeekFrom::Start(0))?;
+    match Compression::detect(&magic) {
+        None => Ok(Input::File(file)),
+        Some(Compression::Zip) => open_zip_member(path, None),
+        Some(compression) => decompress(compression, file),
+    }
+}
+
+fn open_stream(stream: Box<dyn Read + Send>) -> io::Result<Input> {
+    let mut reader = BufReader::new(stream);
+    match Compression::detect(reader.fill_buf()?) {
+        None => Ok(Input::Stream(reader)),
+        Some(compression) => decompress(compression, reader),
+    }
+}
+
+fn counting<R: Read>(inner: R) -> (CountingReader<R>, SourcePosition) {
+    let position = SourcePosition::default();
+    let reader = CountingReader {
+        inner,
+        position: position.clone(),
+    };
+    (reader, position)
+}
+
+fn decompress<R: Read + Send + 'static>(compression: Compression, source: R) -> io::Result<Input> {
+    let (counting, position) = counting(source);
+    let reader: Box<dyn Read + Send> = match compression {
+        Compression::Gzip => Box::new(flate2::read::MultiGzDecoder::new(counting)),
+        Compression::Xz => Box::new(xz2::read::XzDecoder::new_multi_decoder(counting)),
+        Compression::Zstd => Box::new(zstd::stream::read::Decoder::new(counting)?),
+        Compression::Zip => {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "zip archives can only be read from a file",
+            ))
+        }
+    };
+    Ok(Input::Decompressed { reader, position })
+}
+
+struct ZipMember {
+    data_start: u64,
+    compressed_size: u64,
+    deflated: bool,
+}
+
+fn zip_error(e: zip::result::ZipError) -> io::Error {
+    match e {
+        zip::result::ZipError::Io(e) => e,
+        e => io::Error::new(io::ErrorKind::InvalidData, e.to_string()),
+    }
+}
+
+/// find a member of a zip archive, without a name the archive has to
+/// contain exactly one file
+fn zip_member(archive_path: &Path, name: Option<&str>) -> io::Result<ZipMember> {
+    let mut archive = zip::ZipArchive::new(fs::File::open(archive_path)?).map_err(zip_error)?;
+    let index = match name {
+        Some(name) => (0..archive.len())
+            .find(|i| {
+                archive
+                    .by_index_raw(*i)
+                    .map(|f| f.name() == name)
+                    .unwrap_or(false)
+            })
+            .ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::NotFound,
+                    format!("{} not found in {}", name, archive_path.display()),
+                )
+            })?,
+        None => {
+            let files: Vec<(usize, String)> = (0..archive.len())
+                .filter_map(|i| {
+                    let f = archive.by_index_raw(i).ok()?;
+                    if f.is_file() {
+                        Some((i, f.name().to_string()))
+                    } else {
+                        None
+                    }
+                })
+                .collect();
+            match files.as_slice() {
+                [(i, _)] => *i,
+                _ => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidInput,
+                        format!(
+                            "{} contains {} files, select one with {}{}<member>: {}",
+                            archive_path.display(),
+                            files.len(),
+                            archive_path.display(),
+                            ZIP_MEMBER_SEPARATOR,
+                            files
+                                .iter()
+                                .map(|(_, name)| name.as_str())
+                                .collect::<Vec<&str>>()
+                                .join(", ")
+                        ),
+                    ))
+                }
+            }
+        }
+    };
+    let member = archive.by_index_raw(index).map_err(zip_error)?;
+    let deflated = match member.compression() {
+        zip::CompressionMethod::Stored => false,
+        zip::CompressionMethod::Deflated => true,
+        method => {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "{} in {} uses unsupported compression {}",
+                    member.name(),
+                    archive_path.display(),
+                    method
+                ),
+            ))
+        }
+    };
+    Ok(ZipMember {
+        data_start: member.data_start(),
+        compressed_size: member.compressed_size(),
+        deflated,
+    })
+}
+
+fn open_zip_member(archive_path: &Path, name: Option<&str>) -> io::Result<Input> {
+    let member = zip_member(archive_path, name)?;
+    let mut file = fs::File::open(archive_path)?;
+    file.seek(SeekFrom::Start(member.data_start))?;
+    let (data, position) = counting(file.take(member.compressed_size));
+    let reader: Box<dyn Read + Send> = if member.deflated {
+        Box::new(flate2::read::DeflateDecoder::new(data))
+    } else {
+        Box::new(data)
+    };
+    Ok(Input::Decompressed { reader, position })
+}
+
+impl Read for Input {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self {
+            Input::Stream(stream) => stream.read(buf),
+            Input::File(file) => file.read(buf),
+            Input::Decompressed { reader, .. } => reader.read(buf),
+        }
+    }
+}
+
+impl Seek for Input {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        match self {
+            Input::Stream(_) => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "cannot seek in a stream",
+            )),
+            Input::File(file) => file.seek(pos),
+            Input::Decompressed { .. } => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "cannot seek in decompressed content",
+            )),
+        }
+    }
+}
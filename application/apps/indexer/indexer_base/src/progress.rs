@@ -1,5 +1,6 @@
 use serde::Serialize;
 use crate::chunks::*;
+use crate::input::SourcePosition;
 use crossbeam_channel as cc;
 
 #[derive(Serialize, Debug, PartialEq)]
@@ -22,6 +23,8 @@ pub type IndexingResults<T> = std::result::Result<IndexingProgress<T>, Notificat
 pub enum IndexingProgress<T> {
     GotItem { item: T },
     Progress { ticks: (usize, usize) },
+    /// progress when the total size of the input is not known (e.g. stdin)
+    Processed { bytes: usize },
     Stopped,
     Finished,
 }
@@ -31,11 +34,16 @@ pub struct Notification {
     pub line: Option<usize>,
 }
 
+/// without a known total size, progress is reported whenever this many
+/// more bytes were processed
+const PROCESSED_BYTES_REPORT_INTERVAL: usize = 1024 * 1024;
+
 pub struct ProgressReporter {
     update_channel: cc::Sender<ChunkResults>,
     processed_bytes: usize,
     progress_percentage: usize,
     total: Option<usize>,
+    source_position: Option<SourcePosition>,
 }
 impl ProgressReporter {
     pub fn new(total: Option<usize>, update_channel: cc::Sender<ChunkResults>) -> ProgressReporter {
@@ -44,22 +52,41 @@ impl ProgressReporter {
             processed_bytes: 0,
             progress_percentage: 0,
             total,
+            source_position: None,
         }
     }
+    /// report progress with the position in the compressed source instead
+    /// of the consumed (decompressed) bytes
+    pub fn track_source(&mut self, position: SourcePosition) {
+        self.source_position = Some(position);
+    }
     pub fn make_progress(&mut self, consumed: usize) {
         self.processed_bytes += consumed;
         if let Some(file_size) = self.total {
+            let processed = match &self.source_position {
+                Some(position) => position.get(),
+                None => self.processed_bytes,
+            };
             let new_progress_percentage: usize =
-                (self.processed_bytes as f64 / file_size as f64 * 100.0).round() as usize;
+                (processed as f64 / file_size as f64 * 100.0).round() as usize;
             if new_progress_percentage != self.progress_percentage {
                 self.progress_percentage = new_progress_percentage;
                 match self.update_channel.send(Ok(IndexingProgress::Progress {
-                    ticks: (self.processed_bytes, file_size),
+                    ticks: (processed, file_size),
                 })) {
                     Ok(()) => (),
                     Err(e) => warn!("could not send: {}", e),
                 }
             }
+        } else if self.processed_bytes / PROCESSED_BYTES_REPORT_INTERVAL
+            != (self.processed_bytes - consumed) / PROCESSED_BYTES_REPORT_INTERVAL
+        {
+            match self.update_channel.send(Ok(IndexingProgress::Processed {
+                bytes: self.processed_bytes,
+            })) {
+                Ok(()) => (),
+                Err(e) => warn!("could not send: {}", e),
+            }
         }
     }
 }
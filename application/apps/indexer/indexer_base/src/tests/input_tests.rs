@@ -0,0 +1,117 @@
+#[cfg(test)]
+mod tests {
+    use crate::input::*;
+    use std::io::{Read, Write};
+    use std::path::{Path, PathBuf};
+    use tempdir::TempDir;
+
+    const CONTENT: &str = "first line\nsecond line\nthird line\n";
+
+    fn read_all(path: &Path) -> String {
+        let mut content = String::new();
+        open_input(path)
+            .expect("could not open input")
+            .read_to_string(&mut content)
+            .expect("could not read input");
+        content
+    }
+
+    fn write_zip(path: &Path, members: &[(&str, zip::CompressionMethod)]) {
+        let mut zip = zip::ZipWriter::new(std::fs::File::create(path).unwrap());
+        for (name, method) in members {
+            zip.start_file(
+                *name,
+                zip::write::FileOptions::default().compression_method(*method),
+            )
+            .unwrap();
+            zip.write_all(format!("{}: {}", name, CONTENT).as_bytes())
+                .unwrap();
+        }
+        zip.finish().unwrap();
+    }
+
+    #[test]
+    fn test_detect_compression() {
+        assert_eq!(
+            Some(Compression::Gzip),
+            Compression::detect(&[0x1F, 0x8B, 8])
+        );
+        assert_eq!(
+            Some(Compression::Xz),
+            Compression::detect(&[0xFD, b'7', b'z', b'X', b'Z', 0])
+        );
+        assert_eq!(
+            Some(Compression::Zstd),
+            Compression::detect(&[0x28, 0xB5, 0x2F, 0xFD, 0])
+        );
+        assert_eq!(Some(Compression::Zip), Compression::detect(b"PK\x03\x04"));
+        assert_eq!(None, Compression::detect(b"DLT\x01"));
+        assert_eq!(None, Compression::detect(&[0x1F]));
+    }
+
+    #[test]
+    fn test_read_compressed_files() {
+        let tmp_dir = TempDir::new("test_dir").expect("could not create temp dir");
+        let plain = tmp_dir.path().join("input.txt");
+        std::fs::write(&plain, CONTENT).unwrap();
+
+        let gz = tmp_dir.path().join("input.txt.gz");
+        let mut encoder = flate2::write::GzEncoder::new(
+            std::fs::File::create(&gz).unwrap(),
+            flate2::Compression::default(),
+        );
+        encoder.write_all(CONTENT.as_bytes()).unwrap();
+        encoder.finish().unwrap();
+
+        let xz = tmp_dir.path().join("input.txt.xz");
+        let mut encoder = xz2::write::XzEncoder::new(std::fs::File::create(&xz).unwrap(), 6);
+        encoder.write_all(CONTENT.as_bytes()).unwrap();
+        encoder.finish().unwrap();
+
+        let zst = tmp_dir.path().join("input.txt.zst");
+        std::fs::write(&zst, zstd::encode_all(CONTENT.as_bytes(), 0).unwrap()).unwrap();
+
+        assert!(is_random_access(&plain));
+        for path in &[&gz, &xz, &zst] {
+            assert_eq!(CONTENT, read_all(path), "content of {:?}", path);
+            assert!(!is_random_access(path));
+            assert_eq!(
+                Some(std::fs::metadata(path).unwrap().len() as usize),
+                input_size(path)
+            );
+            // the whole compressed file is read in the end
+            let mut input = open_input(path).unwrap();
+            let position = input.source_position().expect("no source position");
+            std::io::copy(&mut input, &mut std::io::sink()).unwrap();
+            assert_eq!(input_size(path), Some(position.get()));
+        }
+    }
+
+    #[test]
+    fn test_read_zip_members() {
+        let tmp_dir = TempDir::new("test_dir").expect("could not create temp dir");
+        let single = tmp_dir.path().join("single.zip");
+        write_zip(&single, &[("a.txt", zip::CompressionMethod::Deflated)]);
+        assert_eq!(format!("a.txt: {}", CONTENT), read_all(&single));
+        assert!(!is_random_access(&single));
+
+        let multiple = tmp_dir.path().join("multiple.zip");
+        write_zip(
+            &multiple,
+            &[
+                ("a.txt", zip::CompressionMethod::Deflated),
+                ("b.txt", zip::CompressionMethod::Stored),
+            ],
+        );
+        let member = |name: &str| PathBuf::from(format!("{}!{}", multiple.display(), name));
+        assert_eq!(format!("a.txt: {}", CONTENT), read_all(&member("a.txt")));
+        assert_eq!(format!("b.txt: {}", CONTENT), read_all(&member("b.txt")));
+        assert_eq!(multiple, source_path(&member("b.txt")));
+        assert!(input_size(&member("b.txt")).is_some());
+        assert!(open_input(&member("c.txt")).is_err());
+        match open_input(&multiple) {
+            Err(e) => assert!(e.to_string().contains("a.txt, b.txt"), "{}", e),
+            Ok(_) => panic!("a member has to be selected"),
+        }
+    }
+}
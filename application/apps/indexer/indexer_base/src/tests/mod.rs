@@ -1,2 +1,4 @@
 #[macro_use]
 mod utils_tests;
+mod progress_tests;
+mod input_tests;
@@ -0,0 +1,50 @@
+#[cfg(test)]
+mod tests {
+    use crate::chunks::ChunkResults;
+    use crate::input::*;
+    use crate::progress::*;
+    use crossbeam_channel as cc;
+    use std::path::Path;
+    use tempdir::TempDir;
+
+    #[test]
+    fn test_progress_without_total_reports_processed_bytes() {
+        let (tx, rx): (cc::Sender<ChunkResults>, cc::Receiver<ChunkResults>) = cc::unbounded();
+        let mut reporter = ProgressReporter::new(None, tx);
+        for _ in 0..5 {
+            reporter.make_progress(512 * 1024);
+        }
+        drop(reporter);
+        let reported: Vec<usize> = rx
+            .iter()
+            .map(|r| match r {
+                Ok(IndexingProgress::Processed { bytes }) => bytes,
+                _ => panic!("only processed bytes expected"),
+            })
+            .collect();
+        assert_eq!(vec![1024 * 1024, 2 * 1024 * 1024], reported);
+    }
+
+    #[test]
+    fn test_input_size() {
+        let tmp_dir = TempDir::new("test_dir").expect("could not create temp dir");
+        let path = tmp_dir.path().join("input.txt");
+        std::fs::write(&path, b"12345").unwrap();
+        assert_eq!(Some(5), input_size(&path));
+        assert!(is_stdin(Path::new(STDIN_PATH)));
+        assert_eq!(None, input_size(Path::new(STDIN_PATH)));
+        assert_eq!(None, input_size(&tmp_dir.path().join("missing.txt")));
+        #[cfg(unix)]
+        {
+            let fifo = tmp_dir.path().join("fifo");
+            let created = std::process::Command::new("mkfifo")
+                .arg(&fifo)
+                .status()
+                .map(|s| s.success())
+                .unwrap_or(false);
+            if created {
+                assert_eq!(None, input_size(&fifo));
+            }
+        }
+    }
+}
@@ -21,6 +21,7 @@ use dlt::dlt_parse::StatisticsResults;
 use indexer_base::chunks::{serialize_chunks, Chunk, ChunkResults};
 use indexer_base::config::*;
 use indexer_base::error_reporter::*;
+use indexer_base::input;
 use crossbeam_channel::unbounded;
 use crossbeam_channel as cc;
 
@@ -70,6 +71,23 @@ fn init_logging() -> Result<()> {
 
     Ok(())
 }
+/// the files used to decode non verbose messages and network traces
+fn fibex_arg() -> Arg<'static, 'static> {
+    Arg::with_name("fibex")
+        .long("fibex")
+        .value_name("FIBEX")
+        .multiple(true)
+        .number_of_values(1)
+        .help("fibex file used to decode non verbose messages, ARXML (.arxml), SOME/IP service mapping (.json) or CAN database (.dbc) (repeatable)")
+}
+
+/// opt-in because a mapped file must not be truncated while it is read
+fn mmap_arg() -> Arg<'static, 'static> {
+    Arg::with_name("mmap")
+        .long("mmap")
+        .help("read the file memory mapped (faster, but the file must not change while it is read)")
+}
+
 fn main() {
     init_logging().expect("logging has to be in place");
     let start = Instant::now();
@@ -90,7 +108,7 @@ fn main() {
                     Arg::with_name("input")
                         .short("i")
                         .long("input")
-                        .help("Sets the input file to be indexed")
+                        .help("Sets the input file to be indexed (\"-\" for stdin, may be compressed)")
                         .required(true)
                         .index(1),
                 )
@@ -259,7 +277,7 @@ fn main() {
                     Arg::with_name("input")
                         .short("i")
                         .long("input")
-                        .help("the DLT file to parse")
+                        .help("the DLT file to parse (\"-\" for stdin, may be compressed)")
                         .required(true)
                         .index(1),
                 )
@@ -312,6 +330,25 @@ fn main() {
                         .short("s")
                         .long("stdout")
                         .help("put out chunk information on stdout"),
+                )
+                .arg(
+                    Arg::with_name("threads")
+                        .short("j")
+                        .long("threads")
+                        .value_name("THREADS")
+                        .help("number of threads used for parsing (1 for sequential indexing)")
+                        .required(false)
+                        .default_value("1"),
+                )
+                .arg(fibex_arg())
+                .arg(mmap_arg())
+                .arg(
+                    Arg::with_name("abstime")
+                        .long("abstime")
+                        .help(
+                            "add the send time (us since epoch, corrected per ECU) \
+                             as last column, files are read twice for this",
+                        ),
                 ),
         )
         .subcommand(
@@ -369,6 +406,142 @@ fn main() {
                         .short("s")
                         .long("stdout")
                         .help("put out chunk information on stdout"),
+                )
+                .arg(fibex_arg()),
+        )
+        .subcommand(
+            SubCommand::with_name("dlt-tcp")
+                .about("handling dlt input from a tcp connection (e.g. a dlt-daemon)")
+                .arg(
+                    Arg::with_name("address")
+                        .short("a")
+                        .long("address")
+                        .help("ip address or hostname of the dlt server")
+                        .required(true)
+                        .index(1),
+                )
+                .arg(
+                    Arg::with_name("port")
+                        .short("p")
+                        .long("port")
+                        .help("port of the dlt server")
+                        .required(false)
+                        .default_value("3490"),
+                )
+                .arg(
+                    Arg::with_name("ecu_id")
+                        .short("e")
+                        .long("ecu")
+                        .value_name("ECU_ID")
+                        .help("ecu id used in the storage header of received messages")
+                        .required(false)
+                        .default_value("TCP"),
+                )
+                .arg(
+                    Arg::with_name("tag")
+                        .short("t")
+                        .long("tag")
+                        .value_name("TAG")
+                        .help("tag for each log entry")
+                        .required(true),
+                )
+                .arg(
+                    Arg::with_name("output")
+                        .short("o")
+                        .long("out")
+                        .value_name("OUT")
+                        .required(true)
+                        .help("Output file"),
+                )
+                .arg(
+                    Arg::with_name("filter_config")
+                        .short("f")
+                        .long("filter")
+                        .value_name("FILTER_CONFIG")
+                        .help("json file that defines dlt filter settings"),
+                )
+                .arg(fibex_arg()),
+        )
+        .subcommand(
+            SubCommand::with_name("dlt-export")
+                .about("export (filtered) dlt messages to a new dlt, json lines or csv file")
+                .arg(
+                    Arg::with_name("input")
+                        .short("i")
+                        .long("input")
+                        .help("the DLT file to read from (\"-\" for stdin, may be compressed)")
+                        .required(true)
+                        .index(1),
+                )
+                .arg(
+                    Arg::with_name("output")
+                        .short("o")
+                        .long("out")
+                        .value_name("OUT")
+                        .required(true)
+                        .help("Output file"),
+                )
+                .arg(
+                    Arg::with_name("format")
+                        .long("format")
+                        .value_name("FORMAT")
+                        .possible_values(&["dlt", "jsonl", "csv"])
+                        .default_value("dlt")
+                        .help("binary dlt messages, one json object per line or csv"),
+                )
+                .arg(
+                    Arg::with_name("columns")
+                        .long("columns")
+                        .value_name("COLUMNS")
+                        .help(
+                            "comma separated csv columns (index,time,ecu,counter,session,\
+                             timestamp,app,context,type,payload,abstime), defaults to all \
+                             but abstime (send time in us since epoch, corrected per ECU)",
+                        ),
+                )
+                .arg(fibex_arg())
+                .arg(
+                    Arg::with_name("filter_config")
+                        .short("f")
+                        .long("filter")
+                        .value_name("FILTER_CONFIG")
+                        .help("json file that defines dlt filter settings"),
+                )
+                .arg(
+                    Arg::with_name("from_line")
+                        .long("from-line")
+                        .value_name("LINE")
+                        .help("first line to export (lines of filtered messages, starting at 0)"),
+                )
+                .arg(
+                    Arg::with_name("to_line")
+                        .long("to-line")
+                        .value_name("LINE")
+                        .help("last line to export (inclusive)"),
+                )
+                .arg(
+                    Arg::with_name("from_time")
+                        .long("from-time")
+                        .value_name("MS")
+                        .help("only export messages received at or after this time (ms since epoch)"),
+                )
+                .arg(
+                    Arg::with_name("to_time")
+                        .long("to-time")
+                        .value_name("MS")
+                        .help("only export messages received at or before this time (ms since epoch)"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("dlt-lifecycles")
+                .about("find the lifecycles of the ECUs in a dlt file")
+                .arg(
+                    Arg::with_name("input")
+                        .short("i")
+                        .long("input")
+                        .help("the DLT file to parse (\"-\" for stdin, may be compressed)")
+                        .required(true)
+                        .index(1),
                 ),
         )
         .subcommand(
@@ -378,7 +551,7 @@ fn main() {
                     Arg::with_name("input")
                         .short("i")
                         .long("input")
-                        .help("the DLT file to parse")
+                        .help("the DLT file to parse (\"-\" for stdin, may be compressed)")
                         .required(true)
                         .index(1),
                 )
@@ -387,6 +560,19 @@ fn main() {
                         .short("s")
                         .long("stdout")
                         .help("put out chunk information on stdout"),
+                )
+                .arg(fibex_arg())
+                .arg(mmap_arg()),
+        )
+        .subcommand(
+            SubCommand::with_name("fibex-check")
+                .about("check which parts of FIBEX or ARXML files can be used to decode non-verbose messages")
+                .arg(
+                    Arg::with_name("input")
+                        .help("the FIBEX and ARXML files, SOME/IP service mappings and CAN databases to check")
+                        .required(true)
+                        .multiple(true)
+                        .index(1),
                 ),
         )
         .get_matches();
@@ -405,10 +591,96 @@ fn main() {
         handle_dlt_subcommand(matches, start, use_stderr_for_status_updates)
     } else if let Some(matches) = matches.subcommand_matches("dlt-udp") {
         handle_dlt_udp_subcommand(matches)
+    } else if let Some(matches) = matches.subcommand_matches("dlt-tcp") {
+        handle_dlt_tcp_subcommand(matches)
+    } else if let Some(matches) = matches.subcommand_matches("dlt-export") {
+        handle_dlt_export_subcommand(matches, start)
+    } else if let Some(matches) = matches.subcommand_matches("dlt-lifecycles") {
+        handle_dlt_lifecycles_subcommand(matches, start)
     } else if let Some(matches) = matches.subcommand_matches("dlt-stats") {
         handle_dlt_stats_subcommand(matches, start, use_stderr_for_status_updates)
     } else if let Some(matches) = matches.subcommand_matches("discover") {
         handle_discover_subcommand(matches)
+    } else if let Some(matches) = matches.subcommand_matches("fibex-check") {
+        handle_fibex_check_subcommand(matches)
+    }
+
+    /// the size of the input, reading from stdin or a named pipe we only know
+    /// how many bytes we processed
+    fn source_size(input: &path::Path) -> Option<usize> {
+        if !input::is_stdin(input) && !input::source_path(input).exists() {
+            report_error(format!("could not open {:?}", input));
+            std::process::exit(2);
+        }
+        input::input_size(input)
+    }
+
+    /// paths of the indexed output and the mapping file
+    /// they are derived from the input unless we read from stdin
+    fn output_paths(input: &str, output: Option<&str>) -> (path::PathBuf, path::PathBuf) {
+        let base = if input::is_stdin(path::Path::new(input)) {
+            match output {
+                Some(output) => output,
+                None => {
+                    report_error("an output file is needed when reading from stdin");
+                    std::process::exit(2);
+                }
+            }
+        } else {
+            input
+        };
+        let out_path = match output {
+            Some(output) => path::PathBuf::from(output),
+            None => path::PathBuf::from(base.to_string() + ".out"),
+        };
+        (out_path, path::PathBuf::from(base.to_string() + ".map.json"))
+    }
+
+    /// the dlt filter settings of the json file given with `--filter`
+    fn filter_config(matches: &clap::ArgMatches) -> Option<dlt::filtering::DltFilterConfig> {
+        let config_path = path::PathBuf::from(matches.value_of("filter_config")?);
+        let mut cnf_file = match fs::File::open(&config_path) {
+            Ok(file) => file,
+            Err(_) => {
+                report_error(format!("could not open filter config {:?}", config_path));
+                std::process::exit(2)
+            }
+        };
+        match dlt::filtering::read_filter_options(&mut cnf_file) {
+            Ok(filter_config) => Some(filter_config),
+            Err(e) => {
+                report_error(format!("invalid filter config {:?}: {}", config_path, e));
+                std::process::exit(2)
+            }
+        }
+    }
+
+    /// the files of all `--fibex` arguments
+    fn fibex_paths(matches: &clap::ArgMatches) -> Vec<path::PathBuf> {
+        matches
+            .values_of("fibex")
+            .map(|paths| paths.map(path::PathBuf::from).collect())
+            .unwrap_or_default()
+    }
+
+    /// the merged metadata of the fibex files, elements that can't be used
+    /// (or that conflict with those of another file) are reported as warnings
+    fn load_fibex(fibex_paths: &[path::PathBuf]) -> Option<std::rc::Rc<dlt::fibex::FibexMetadata>> {
+        if fibex_paths.is_empty() {
+            return None;
+        }
+        match dlt::fibex::read_fibexes_with_report(fibex_paths.to_vec()) {
+            Ok(report) => {
+                for warning in &report.warnings {
+                    report_warning(warning.to_string());
+                }
+                Some(std::rc::Rc::new(report.metadata))
+            }
+            Err(e) => {
+                report_error(format!("could not read fibex {:?}: {}", fibex_paths, e));
+                std::process::exit(3)
+            }
+        }
     }
 
     fn handle_index_subcommand(
@@ -421,24 +693,11 @@ fn main() {
             let file_path = path::PathBuf::from(file);
             let tag = matches.value_of("tag").expect("tag must be present");
             let tag_string = tag.to_string();
-            let fallback_out = file.to_string() + ".out";
-            let out_path = path::PathBuf::from(
-                matches
-                    .value_of("output")
-                    .unwrap_or_else(|| fallback_out.as_str()),
-            );
-            let mapping_out_path: path::PathBuf =
-                path::PathBuf::from(file.to_string() + ".map.json");
+            let (out_path, mapping_out_path) = output_paths(file, matches.value_of("output"));
             let chunk_size = value_t_or_exit!(matches.value_of("chunk_size"), usize);
 
             let source_file_size = if status_updates {
-                Some(match fs::metadata(file) {
-                    Ok(file_meta) => file_meta.len() as usize,
-                    Err(_) => {
-                        report_error("could not find out size of source file");
-                        std::process::exit(2);
-                    }
-                })
+                source_size(&file_path)
             } else {
                 None
             };
@@ -494,6 +753,9 @@ fn main() {
                             (ticks.0 as f64 / ticks.1 as f64) * 100.0
                         );
                     }
+                    Ok(Ok(IndexingProgress::Processed { bytes })) => {
+                        trace!("progress... ({} bytes)", bytes);
+                    }
                     Ok(Ok(IndexingProgress::GotItem { item: chunk })) => {
                         chunks.push(chunk);
                     }
@@ -608,78 +870,58 @@ fn main() {
     ) {
         debug!("handle_dlt_subcommand");
         if let (Some(file_name), Some(tag)) = (matches.value_of("input"), matches.value_of("tag")) {
-            let filter_conf: Option<dlt::filtering::DltFilterConfig> = match matches
-                .value_of("filter_config")
-            {
-                Some(filter_config_file_name) => {
-                    let config_path = path::PathBuf::from(filter_config_file_name);
-                    let mut cnf_file = match fs::File::open(&config_path) {
-                        Ok(file) => file,
-                        Err(_) => {
-                            report_error(format!("could not open filter config {:?}", config_path));
-                            std::process::exit(2)
-                        }
-                    };
-                    dlt::filtering::read_filter_options(&mut cnf_file).ok()
-                }
-                None => None,
-            };
+            let filter_conf = filter_config(matches);
+            let fibex_paths = fibex_paths(matches);
             let append: bool = matches.is_present("append");
+            let file_path = path::PathBuf::from(file_name);
             let source_file_size = if status_updates {
-                Some(match fs::metadata(file_name) {
-                    Ok(file_meta) => file_meta.len() as usize,
-                    Err(_) => {
-                        report_error("could not find out size of source file");
-                        std::process::exit(2);
-                    }
-                })
+                source_size(&file_path)
             } else {
                 None
             };
-            let fallback_out = file_name.to_string() + ".out";
-            let out_path = path::PathBuf::from(
-                matches
-                    .value_of("output")
-                    .unwrap_or_else(|| fallback_out.as_str()),
-            );
-            let file_path = path::PathBuf::from(file_name);
-            let mapping_out_path: path::PathBuf =
-                path::PathBuf::from(file_name.to_string() + ".map.json");
+            let (out_path, mapping_out_path) = output_paths(file_name, matches.value_of("output"));
 
             let (tx, rx): (cc::Sender<ChunkResults>, cc::Receiver<ChunkResults>) = unbounded();
             let chunk_size = value_t_or_exit!(matches.value_of("chunk_size"), usize);
             let tag_string = tag.to_string();
+            let threads = value_t_or_exit!(matches.value_of("threads"), usize);
+            let memory_map = matches.is_present("mmap");
+            let absolute_time = matches.is_present("abstime");
             thread::spawn(move || {
-                if let Err(why) = dlt::dlt_parse::create_index_and_mapping_dlt(
-                    IndexingConfig {
-                        tag: tag_string.as_str(),
-                        chunk_size,
-                        in_file: file_path,
-                        out_path: &out_path,
-                        append,
-                    },
+                let indexing_config = IndexingConfig {
+                    tag: tag_string.as_str(),
+                    chunk_size,
+                    in_file: file_path,
+                    out_path: &out_path,
+                    append,
+                };
+                if threads > 1 {
+                    if let Err(why) = dlt::dlt_parallel::create_index_and_mapping_dlt_parallel(
+                        indexing_config,
+                        source_file_size,
+                        filter_conf,
+                        &tx,
+                        None,
+                        fibex_paths,
+                        dlt::dlt_parallel::ParallelConfig {
+                            threads,
+                            segment_size: dlt::dlt_parallel::DEFAULT_SEGMENT_SIZE,
+                            memory_map,
+                            absolute_time,
+                        },
+                    ) {
+                        report_error(format!("couldn't process: {}", why));
+                        std::process::exit(2)
+                    }
+                } else if let Err(why) = dlt::dlt_parse::create_index_and_mapping_dlt(
+                    indexing_config,
                     source_file_size,
                     filter_conf,
                     &tx,
                     None,
-                    // dlt::filtering::DltFilterConfig {
-                    //     min_log_level: verbosity_log_level,
-                    //     components: None,
-                    // },
-                    Some(std::rc::Rc::new(
-                        dlt::fibex::read_fibexes(vec![std::path::PathBuf::from(env!(
-                            "CARGO_MANIFEST_DIR"
-                        ))
-                        .join("../dlt/tests/dlt-messages.xml")])
-                        .unwrap_or_else(|_e| {
-                            report_error(format!(
-                                "could not open {:?}",
-                                std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"))
-                                    .join("../dlt/tests/dlt-messages.xml")
-                            ));
-                            std::process::exit(3);
-                        }),
-                    )),
+                    load_fibex(&fibex_paths),
+                    memory_map,
+                    absolute_time,
                 ) {
                     report_error(format!("couldn't process: {}", why));
                     std::process::exit(2)
@@ -711,6 +953,9 @@ fn main() {
                             (ticks.0 as f64 / ticks.1 as f64) * 100.0
                         );
                     }
+                    Ok(Ok(IndexingProgress::Processed { bytes })) => {
+                        trace!("progress... ({} bytes)", bytes);
+                    }
                     Ok(Ok(IndexingProgress::GotItem { item: chunk })) => {
                         chunks.push(chunk);
                     }
@@ -740,22 +985,8 @@ fn main() {
             matches.value_of("tag"),
             matches.value_of("output"),
         ) {
-            let filter_conf: Option<dlt::filtering::DltFilterConfig> = match matches
-                .value_of("filter_config")
-            {
-                Some(filter_config_file_name) => {
-                    let config_path = path::PathBuf::from(filter_config_file_name);
-                    let mut cnf_file = match fs::File::open(&config_path) {
-                        Ok(file) => file,
-                        Err(_) => {
-                            report_error(format!("could not open filter config {:?}", config_path));
-                            std::process::exit(2)
-                        }
-                    };
-                    dlt::filtering::read_filter_options(&mut cnf_file).ok()
-                }
-                None => None,
-            };
+            let filter_conf = filter_config(matches);
+            let fibex_paths = fibex_paths(matches);
             let out_path = path::PathBuf::from(output);
             let mapping_out_path: path::PathBuf =
                 path::PathBuf::from(output.to_string() + ".map.json");
@@ -781,20 +1012,7 @@ fn main() {
                     filter_conf,
                     &tx,
                     shutdown_channel.1,
-                    Some(std::rc::Rc::new(
-                        dlt::fibex::read_fibexes(vec![std::path::PathBuf::from(env!(
-                            "CARGO_MANIFEST_DIR"
-                        ))
-                        .join("../dlt/tests/dlt-messages.xml")])
-                        .unwrap_or_else(|_e| {
-                            report_error(format!(
-                                "could not open {:?}",
-                                std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"))
-                                    .join("../dlt/tests/dlt-messages.xml")
-                            ));
-                            std::process::exit(3);
-                        }),
-                    )),
+                    load_fibex(&fibex_paths),
                 ) {
                     report_error(format!("couldn't process: {}", why));
                     std::process::exit(2)
@@ -817,6 +1035,9 @@ fn main() {
                             (ticks.0 as f64 / ticks.1 as f64) * 100.0
                         );
                     }
+                    Ok(Ok(IndexingProgress::Processed { bytes })) => {
+                        trace!("progress... ({} bytes)", bytes);
+                    }
                     Ok(Ok(IndexingProgress::GotItem { item: chunk })) => {
                         chunks.push(chunk);
                     }
@@ -840,6 +1061,194 @@ fn main() {
         }
     }
 
+    fn handle_dlt_tcp_subcommand(matches: &clap::ArgMatches) {
+        debug!("handle_dlt_tcp_subcommand");
+        if let (Some(address), Some(port), Some(ecu_id), Some(tag), Some(output)) = (
+            matches.value_of("address"),
+            matches.value_of("port"),
+            matches.value_of("ecu_id"),
+            matches.value_of("tag"),
+            matches.value_of("output"),
+        ) {
+            let filter_conf = filter_config(matches);
+            let fibex_paths = fibex_paths(matches);
+            let out_path = path::PathBuf::from(output);
+            let mapping_out_path: path::PathBuf =
+                path::PathBuf::from(output.to_string() + ".map.json");
+
+            let (tx, rx): (cc::Sender<ChunkResults>, cc::Receiver<ChunkResults>) = unbounded();
+            let shutdown_channel = async_std::sync::channel(1);
+            let tag_string = tag.to_string();
+            let ecu_id_string = ecu_id.to_string();
+            let tcp_conf = TcpConfig {
+                address: address.to_string(),
+                port: port.to_string(),
+            };
+            thread::spawn(move || {
+                if let Err(why) = dlt::dlt_parse::create_index_and_mapping_dlt_from_tcp(
+                    tcp_conf,
+                    tag_string.as_str(),
+                    ecu_id_string,
+                    &out_path,
+                    filter_conf,
+                    &tx,
+                    shutdown_channel.1,
+                    load_fibex(&fibex_paths),
+                ) {
+                    report_error(format!("couldn't process: {}", why));
+                    std::process::exit(2)
+                }
+            });
+            let mut chunks: Vec<Chunk> = vec![];
+            loop {
+                match rx.recv() {
+                    Err(why) => {
+                        report_error(format!("couldn't process: {}", why));
+                        std::process::exit(2)
+                    }
+                    Ok(Ok(IndexingProgress::Finished)) => {
+                        let _ = serialize_chunks(&chunks, &mapping_out_path);
+                        break;
+                    }
+                    Ok(Ok(IndexingProgress::GotItem { item: chunk })) => {
+                        if chunk.r == (0, 0) && chunk.b == (0, 0) {
+                            println!("connected to {}:{}", address, port);
+                        } else {
+                            chunks.push(chunk);
+                        }
+                    }
+                    Ok(Err(Notification {
+                        severity,
+                        content,
+                        line,
+                    })) => {
+                        if severity == Severity::WARNING {
+                            report_warning_ln(content, line);
+                        } else {
+                            report_error_ln(content, line);
+                        }
+                    }
+                    Ok(_) => (),
+                }
+            }
+
+            println!("done done");
+            std::process::exit(0)
+        }
+    }
+
+    fn handle_dlt_export_subcommand(matches: &clap::ArgMatches, start: std::time::Instant) {
+        debug!("handle_dlt_export_subcommand");
+        if let (Some(file_name), Some(output)) =
+            (matches.value_of("input"), matches.value_of("output"))
+        {
+            let filter_conf = filter_config(matches);
+            let fibex_paths = fibex_paths(matches);
+            let line_range = if matches.is_present("from_line") || matches.is_present("to_line") {
+                let from = if matches.is_present("from_line") {
+                    value_t_or_exit!(matches.value_of("from_line"), usize)
+                } else {
+                    0
+                };
+                let to = if matches.is_present("to_line") {
+                    value_t_or_exit!(matches.value_of("to_line"), usize)
+                } else {
+                    usize::MAX
+                };
+                Some((from, to))
+            } else {
+                None
+            };
+            let time_range = if matches.is_present("from_time") || matches.is_present("to_time") {
+                let from = if matches.is_present("from_time") {
+                    value_t_or_exit!(matches.value_of("from_time"), u64)
+                } else {
+                    0
+                };
+                let to = if matches.is_present("to_time") {
+                    value_t_or_exit!(matches.value_of("to_time"), u64)
+                } else {
+                    u64::MAX
+                };
+                Some((from, to))
+            } else {
+                None
+            };
+            let format = match matches.value_of("format") {
+                Some("jsonl") => dlt::dlt_export::ExportFormat::JsonLines,
+                Some("csv") => {
+                    let columns = match matches.value_of("columns") {
+                        Some(names) => names
+                            .split(',')
+                            .map(|name| {
+                                dlt::dlt_export::CsvColumn::from_name(name.trim())
+                                    .unwrap_or_else(|| {
+                                        report_error(format!("unknown csv column: {}", name));
+                                        std::process::exit(2)
+                                    })
+                            })
+                            .collect(),
+                        None => dlt::dlt_export::DEFAULT_CSV_COLUMNS.to_vec(),
+                    };
+                    dlt::dlt_export::ExportFormat::Csv(columns)
+                }
+                _ => dlt::dlt_export::ExportFormat::Dlt,
+            };
+            let file_path = path::PathBuf::from(file_name);
+            let out_path = path::PathBuf::from(output);
+            let (tx, rx): (cc::Sender<ChunkResults>, cc::Receiver<ChunkResults>) = unbounded();
+            let export_thread = thread::spawn(move || {
+                let fibex_metadata = load_fibex(&fibex_paths);
+                dlt::dlt_export::export_dlt_messages(
+                    &file_path,
+                    &out_path,
+                    filter_conf.map(dlt::filtering::process_filter_config),
+                    dlt::dlt_export::DltExportConfig {
+                        line_range,
+                        time_range,
+                        format,
+                    },
+                    fibex_metadata,
+                    &tx,
+                    None,
+                )
+            });
+            loop {
+                match rx.recv() {
+                    // channel closes when the export failed before it could finish
+                    Err(_) => break,
+                    Ok(Ok(IndexingProgress::Finished)) => break,
+                    Ok(Err(Notification {
+                        severity,
+                        content,
+                        line,
+                    })) => {
+                        if severity == Severity::WARNING {
+                            report_warning_ln(content, line);
+                        } else {
+                            report_error_ln(content, line);
+                        }
+                    }
+                    Ok(_) => (),
+                }
+            }
+            match export_thread.join() {
+                Ok(Ok(exported)) => {
+                    duration_report(start, format!("exported {} messages", exported));
+                    std::process::exit(0)
+                }
+                Ok(Err(why)) => {
+                    report_error(format!("couldn't export: {}", why));
+                    std::process::exit(2)
+                }
+                Err(_) => {
+                    report_error("export thread panicked");
+                    std::process::exit(2)
+                }
+            }
+        }
+    }
+
     fn handle_discover_subcommand(matches: &clap::ArgMatches) {
         if let Some(test_string) = matches.value_of("input-string") {
             match detect_timestamp_in_string(test_string, None) {
@@ -882,6 +1291,9 @@ fn main() {
                     Ok(Ok(IndexingProgress::Progress { ticks: t })) => {
                         trace!("progress... ({:.1} %)", (t.0 as f64 / t.1 as f64) * 100.0);
                     }
+                    Ok(Ok(IndexingProgress::Processed { bytes })) => {
+                        trace!("progress... ({} bytes)", bytes);
+                    }
                     Ok(Ok(IndexingProgress::Finished)) => {
                         trace!("finished...");
                         break;
@@ -951,6 +1363,9 @@ fn main() {
                     Ok(Ok(IndexingProgress::Progress { ticks: t })) => {
                         trace!("progress... ({:.1} %)", (t.0 as f64 / t.1 as f64) * 100.0);
                     }
+                    Ok(Ok(IndexingProgress::Processed { bytes })) => {
+                        trace!("progress... ({} bytes)", bytes);
+                    }
                     Ok(Ok(IndexingProgress::Finished)) => {
                         trace!("finished...");
                         break;
@@ -983,6 +1398,61 @@ fn main() {
         }
     }
 
+    fn handle_dlt_lifecycles_subcommand(matches: &clap::ArgMatches, start: std::time::Instant) {
+        let file_name = matches.value_of("input").expect("input must be present");
+        let file_path = path::PathBuf::from(file_name);
+        source_size(&file_path);
+        let (tx, rx): (
+            cc::Sender<dlt::dlt_lifecycle::LifecycleResults>,
+            cc::Receiver<dlt::dlt_lifecycle::LifecycleResults>,
+        ) = unbounded();
+
+        thread::spawn(move || {
+            if let Err(why) = dlt::dlt_lifecycle::get_dlt_lifecycles(&file_path, &tx, None) {
+                report_error(format!("couldn't find lifecycles: {}", why));
+                std::process::exit(2)
+            }
+        });
+        loop {
+            match rx.recv() {
+                Ok(Ok(IndexingProgress::GotItem { item: lifecycles })) => {
+                    for lifecycle in &lifecycles {
+                        match serde_json::to_string(lifecycle) {
+                            Ok(json) => println!("{}", json),
+                            Err(e) => {
+                                report_error(format!("serializing {:?} failed: {}", lifecycle, e));
+                                std::process::exit(2)
+                            }
+                        }
+                    }
+                    duration_report(start, format!("found {} lifecycles", lifecycles.len()));
+                }
+                Ok(Ok(IndexingProgress::Progress { ticks: t })) => {
+                    trace!("progress... ({:.1} %)", (t.0 as f64 / t.1 as f64) * 100.0);
+                }
+                Ok(Ok(IndexingProgress::Processed { bytes })) => {
+                    trace!("progress... ({} bytes)", bytes);
+                }
+                Ok(Ok(IndexingProgress::Finished)) | Ok(Ok(IndexingProgress::Stopped)) => break,
+                Ok(Err(Notification {
+                    severity,
+                    content,
+                    line,
+                })) => {
+                    if severity == Severity::WARNING {
+                        report_warning_ln(content, line);
+                    } else {
+                        report_error_ln(content, line);
+                    }
+                }
+                Err(_) => {
+                    report_error("couldn't process");
+                    std::process::exit(2)
+                }
+            }
+        }
+    }
+
     fn handle_dlt_stats_subcommand(
         matches: &clap::ArgMatches,
         start: std::time::Instant,
@@ -990,27 +1460,23 @@ fn main() {
     ) {
         let file_name = matches.value_of("input").expect("input must be present");
         let file_path = path::PathBuf::from(file_name);
-        let f = match fs::File::open(&file_path) {
-            Ok(file) => file,
-            Err(_) => {
-                report_error(format!("could not open {:?}", file_path));
-                std::process::exit(2)
-            }
-        };
-        let source_file_size = match f.metadata() {
-            Ok(file_meta) => file_meta.len() as usize,
-            Err(_) => {
-                report_error("could not find out size of source file");
-                std::process::exit(2);
-            }
-        };
+        let source_file_size = source_size(&file_path);
+        let fibex_paths = fibex_paths(matches);
+        let memory_map = matches.is_present("mmap");
         let (tx, rx): (
             cc::Sender<StatisticsResults>,
             cc::Receiver<StatisticsResults>,
         ) = unbounded();
 
         thread::spawn(move || {
-            if let Err(why) = dlt::dlt_parse::get_dlt_file_info(&file_path, &tx, None) {
+            let fibex_metadata = load_fibex(&fibex_paths);
+            if let Err(why) = dlt::dlt_parse::get_dlt_file_info(
+                &file_path,
+                &tx,
+                None,
+                fibex_metadata,
+                memory_map,
+            ) {
                 report_error(format!("couldn't collect statistics: {}", why));
                 std::process::exit(2)
             }
@@ -1027,7 +1493,7 @@ fn main() {
                             std::process::exit(2)
                         }
                     }
-                    if status_updates {
+                    if let (true, Some(source_file_size)) = (status_updates, source_file_size) {
                         let file_size_in_mb = source_file_size as f64 / 1024.0 / 1024.0;
                         let elapsed = start.elapsed();
                         let ms = elapsed.as_millis();
@@ -1042,6 +1508,9 @@ fn main() {
                 Ok(Ok(IndexingProgress::Progress { ticks: t })) => {
                     trace!("progress... ({:.1} %)", (t.0 as f64 / t.1 as f64) * 100.0);
                 }
+                Ok(Ok(IndexingProgress::Processed { bytes })) => {
+                    trace!("progress... ({} bytes)", bytes);
+                }
                 Ok(Ok(IndexingProgress::Finished)) => {
                     trace!("finished...");
                     break;
@@ -1069,6 +1538,38 @@ fn main() {
             }
         }
     }
+
+    /// prints the elements of the FIBEX files that can't be used, exits with 1
+    /// if there are any
+    fn handle_fibex_check_subcommand(matches: &clap::ArgMatches) {
+        let files: Vec<path::PathBuf> = matches
+            .values_of("input")
+            .expect("input must be present")
+            .map(path::PathBuf::from)
+            .collect();
+        match dlt::fibex::read_fibexes_with_report(files) {
+            Ok(report) => {
+                for warning in &report.warnings {
+                    println!("{}", warning);
+                }
+                println!(
+                    "{} frames, {} PDUs, {} SOME/IP services, {} CAN messages usable, {} warnings",
+                    report.frames,
+                    report.pdus,
+                    report.services,
+                    report.can_messages,
+                    report.warnings.len()
+                );
+                if !report.warnings.is_empty() {
+                    std::process::exit(1)
+                }
+            }
+            Err(e) => {
+                report_error(format!("could not read fibex: {}", e));
+                std::process::exit(2)
+            }
+        }
+    }
 }
 
 fn duration_report(start: std::time::Instant, report: String) {
@@ -0,0 +1,45 @@
+use dlt::dlt::*;
+use std::path::PathBuf;
+use std::process::Command;
+use tempdir::TempDir;
+
+#[test]
+fn test_index_with_arxml() {
+    let tmp_dir = TempDir::new("cli_test").expect("could not create temp dir");
+    let in_path = tmp_dir.path().join("non_verbose.dlt");
+    let out_path = tmp_dir.path().join("non_verbose.dlt.out");
+    // a non verbose message without extended header: Heartbeat with uptime
+    let message = Message::new(
+        MessageConfig {
+            version: 1,
+            endianness: Endianness::Big,
+            counter: 0,
+            ecu_id: Some("ECU1".to_string()),
+            session_id: None,
+            timestamp: None,
+            payload: Payload2 {
+                payload_content: PayloadContent::NonVerbose(200, vec![0, 1, 0x51, 0x80]),
+            },
+            extended_header_info: None,
+        },
+        None,
+        Some(StorageHeader {
+            timestamp: DltTimeStamp::from_ms(1_000_000),
+            ecu_id: "ECU1".to_string(),
+        }),
+    );
+    std::fs::write(&in_path, message.as_bytes()).expect("could not write input");
+    let arxml = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../dlt/tests/dlt-messages.arxml");
+    let status = Command::new(env!("CARGO_BIN_EXE_indexer_cli"))
+        .arg("dlt")
+        .arg(&in_path)
+        .args(["-t", "TAG", "-o"])
+        .arg(&out_path)
+        .arg("--fibex")
+        .arg(&arxml)
+        .status()
+        .expect("could not run indexer_cli");
+    assert!(status.success());
+    let out = std::fs::read_to_string(&out_path).expect("could not read output");
+    assert!(out.contains("86400"), "uptime not decoded: {:?}", out);
+}
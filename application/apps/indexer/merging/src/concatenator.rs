@@ -12,6 +12,7 @@
 use indexer_base::chunks::ChunkResults;
 use failure::err_msg;
 use indexer_base::chunks::ChunkFactory;
+use indexer_base::input::{input_size, open_input, SourcePosition};
 use indexer_base::progress::IndexingProgress;
 use indexer_base::utils;
 use serde::{Deserialize, Serialize};
@@ -106,14 +107,13 @@ pub fn concat_files(
 
     let combined_source_file_size = concat_inputs.iter().try_fold(0, |acc, i| {
         let f = &PathBuf::from(i.path.clone());
-        match fs::metadata(f) {
-            Ok(metadata) => Ok(acc + metadata.len()),
-            Err(e) => Err(err_msg(format!(
-                "error getting size of file {:?} ({})",
-                f, e
-            ))),
+        match input_size(f) {
+            Some(size) => Ok(acc + size),
+            None => Err(err_msg(format!("error getting size of file {:?}", f))),
         }
     })?;
+    // bytes of the inputs that were completely processed
+    let mut finished_bytes = 0;
     let mut progress_percentage = 0usize;
     for input in concat_inputs {
         if let Some(rx) = shutdown_rx.as_ref() {
@@ -127,16 +127,23 @@ pub fn concat_files(
                 _ => (),
             }
         };
-        let f: fs::File = fs::File::open(input.path)?;
-        let mut reader: BufReader<&std::fs::File> = BufReader::new(&f);
+        let path = PathBuf::from(&input.path);
+        let f = open_input(&path)?;
+        // for compressed inputs the progress is taken from the position in the source
+        let source_position = f.source_position();
+        let mut reader = BufReader::new(f);
+        let mut file_bytes = 0;
         let mut buf = vec![];
         while let Ok(len) = reader.read_until(b'\n', &mut buf) {
             if len == 0 {
                 // no more content
                 break;
             };
-            let original_line_length = len;
-            processed_bytes += original_line_length;
+            file_bytes += len;
+            processed_bytes = finished_bytes
+                + source_position
+                    .as_ref()
+                    .map_or(file_bytes, SourcePosition::get);
             let s = unsafe { std::str::from_utf8_unchecked(&buf) };
             let trimmed_line = s.trim_matches(utils::is_newline);
 
@@ -163,16 +170,18 @@ pub fn concat_files(
             if new_progress_percentage != progress_percentage {
                 progress_percentage = new_progress_percentage;
                 let _ = update_channel.send(Ok(IndexingProgress::Progress {
-                    ticks: (processed_bytes, combined_source_file_size as usize),
+                    ticks: (processed_bytes, combined_source_file_size),
                 }));
             }
             buf = vec![];
         }
+        finished_bytes += input_size(&path).unwrap_or(file_bytes);
+        processed_bytes = finished_bytes;
     }
     buf_writer.flush()?;
 
     let _ = update_channel.send(Ok(IndexingProgress::Progress {
-        ticks: (processed_bytes, combined_source_file_size as usize),
+        ticks: (processed_bytes, combined_source_file_size),
     }));
 
     let _ = update_channel.send(Ok(IndexingProgress::Finished));
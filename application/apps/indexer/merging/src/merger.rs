@@ -13,6 +13,7 @@ use failure::err_msg;
 use indexer_base::chunks::ChunkFactory;
 use indexer_base::chunks::ChunkResults;
 use indexer_base::error_reporter::*;
+use indexer_base::input::{input_size, open_input, Input, SourcePosition};
 use indexer_base::progress::IndexingProgress;
 use indexer_base::timedline::*;
 use indexer_base::utils;
@@ -53,7 +54,7 @@ pub struct MergerInput {
     pub tag: String,
 }
 pub struct TimedLineIter<'a> {
-    reader: BufReader<fs::File>,
+    reader: BufReader<Input>,
     tag: &'a str,
     regex: Regex,
     year: Option<i32>,
@@ -64,7 +65,7 @@ pub struct TimedLineIter<'a> {
 }
 impl<'a> TimedLineIter<'a> {
     pub fn new(
-        fh: fs::File,
+        fh: Input,
         tag: &'a str,
         regex: Regex,
         year: Option<i32>,
@@ -184,8 +185,7 @@ pub fn merge_and_sort_files(
         // let kind: RegexKind = detect_timestamp_regex(&input.path)?;
         // let r: &Regex = &REGEX_REGISTRY[&kind];
         let r = lookup_regex_for_format_str(&input.format)?;
-        let f: fs::File = fs::File::open(input.path)?;
-        let mut reader: BufReader<&std::fs::File> = BufReader::new(&f);
+        let mut reader = BufReader::new(open_input(&input.path)?);
         let mut buf = vec![];
         let mut last_timestamp: i64 = 0;
         while let Ok(len) = reader.read_until(b'\n', &mut buf) {
@@ -293,35 +293,40 @@ pub fn merge_files_iter(
 
     let mut progress_percentage = 0usize;
     // create a peekable iterator for all file inputs
-    let mut readers: Vec<Peekable<TimedLineIter>> = merger_inputs
+    // for compressed inputs the progress is taken from the position in the source
+    let (mut readers, source_positions): (
+        Vec<Peekable<TimedLineIter>>,
+        Vec<Option<SourcePosition>>,
+    ) = merger_inputs
         .iter()
         .map(|input| {
-            fs::File::open(&input.path)
+            open_input(&input.path)
                 .map_err(failure::Error::from)
                 .and_then(|f| {
                     let r: Regex = lookup_regex_for_format_str(&input.format)?;
-                    Ok(TimedLineIter::new(
-                        f,
-                        input.tag.as_str(),
-                        r,
-                        input.year,
-                        input.offset,
-                        line_nr,
-                    )
-                    .peekable())
+                    let source_position = f.source_position();
+                    Ok((
+                        TimedLineIter::new(
+                            f,
+                            input.tag.as_str(),
+                            r,
+                            input.year,
+                            input.offset,
+                            line_nr,
+                        )
+                        .peekable(),
+                        source_position,
+                    ))
                 })
         })
         .filter_map(Result::ok) // TODO better error handling
-        .collect();
+        .unzip();
     // MergerInput
     let combined_source_file_size = merger_inputs.iter().try_fold(0, |acc, i| {
         let f = &i.path.clone();
-        match fs::metadata(f) {
-            Ok(metadata) => Ok(acc + metadata.len()),
-            Err(e) => Err(err_msg(format!(
-                "error getting size of file {:?} ({})",
-                f, e
-            ))),
+        match input_size(f) {
+            Some(size) => Ok(acc + size),
+            None => Err(err_msg(format!("error getting size of file {:?}", f))),
         }
     })?;
 
@@ -354,7 +359,9 @@ pub fn merge_files_iter(
             // we found a line with a minimal timestamp
             if let Some(line) = readers[min_index].next() {
                 // important: keep track of how many bytes we processed
-                processed_bytes += line.original_length;
+                if source_positions[min_index].is_none() {
+                    processed_bytes += line.original_length;
+                }
                 let trimmed_len = line.content.len();
                 if trimmed_len > 0 {
                     let additional_bytes = utils::create_tagged_line(
@@ -388,13 +395,19 @@ pub fn merge_files_iter(
                         update_channel.send(Ok(IndexingProgress::GotItem { item: chunk }))?;
                     }
 
+                    let processed_bytes = processed_bytes
+                        + source_positions
+                            .iter()
+                            .flatten()
+                            .map(SourcePosition::get)
+                            .sum::<usize>();
                     let new_progress_percentage: usize =
                         (processed_bytes as f64 / combined_source_file_size as f64 * 100.0).round()
                             as usize;
                     if new_progress_percentage != progress_percentage {
                         progress_percentage = new_progress_percentage;
                         update_channel.send(Ok(IndexingProgress::Progress {
-                            ticks: (processed_bytes, combined_source_file_size as usize),
+                            ticks: (processed_bytes, combined_source_file_size),
                         }))?;
                     }
                 }
@@ -89,7 +89,8 @@ mod tests {
                         content,
                     );
                 }
-                Ok(Ok(IndexingProgress::Progress { ticks: _t })) => {
+                Ok(Ok(IndexingProgress::Progress { .. }))
+                | Ok(Ok(IndexingProgress::Processed { .. })) => {
                     trace!("progress...");
                 }
                 Ok(Ok(IndexingProgress::GotItem { item })) => {
@@ -15,6 +15,7 @@ use crate::parse;
 use failure::{err_msg, Error};
 use indexer_base::chunks::{ChunkFactory};
 use indexer_base::config::IndexingConfig;
+use indexer_base::input::{input_size, open_input};
 use indexer_base::progress::*;
 use indexer_base::utils;
 use parse::detect_timestamp_in_string;
@@ -66,8 +67,8 @@ pub fn index_file(
     let (out_file, current_out_file_size) =
         utils::get_out_file_and_size(config.append, &config.out_path)?;
 
-    let f = match fs::File::open(&config.in_file) {
-        Ok(file) => file,
+    let f = match open_input(&config.in_file) {
+        Ok(input) => input,
         Err(e) => {
             eprint!("could not open {:?}", config.in_file);
             let _ = update_channel.try_send(Err(Notification {
@@ -78,21 +79,22 @@ pub fn index_file(
             return Err(err_msg(format!("could not open file ({})", e)));
         }
     };
-    let source_file_size: Option<usize> = fs::metadata(&config.in_file)
-        .ok()
-        .map(|md| md.len() as usize);
+    let source_file_size: Option<usize> = input_size(&config.in_file);
 
     let mut chunk_count = 0usize;
     let mut last_byte_index = 0usize;
     let mut chunk_factory = ChunkFactory::new(config.chunk_size, current_out_file_size);
 
+    let source_position = f.source_position();
     let mut reader = BufReader::new(f);
     let mut line_nr = initial_line_nr;
     let mut buf_writer = BufWriter::with_capacity(10 * 1024 * 1024, out_file);
 
     let mut buf = vec![];
-    let mut processed_bytes = 0usize;
-    let mut progress_percentage = 0usize;
+    let mut progress_reporter = ProgressReporter::new(source_file_size, update_channel.clone());
+    if let Some(position) = source_position {
+        progress_reporter.track_source(position);
+    }
     let mut stopped = false;
     while let Ok(len) = reader.read_until(b'\n', &mut buf) {
         if stopped {
@@ -103,7 +105,6 @@ pub fn index_file(
         let trimmed_line = s.trim_matches(utils::is_newline);
         let trimmed_len = trimmed_line.len();
         let had_newline = trimmed_len != len;
-        processed_bytes += len;
         if len == 0 {
             // no more content
             break;
@@ -159,18 +160,8 @@ pub fn index_file(
                 }
                 None => false,
             };
-
-            if let Some(file_size) = source_file_size {
-                let new_progress_percentage: usize =
-                    (processed_bytes as f64 / file_size as f64 * 100.0).round() as usize;
-                if new_progress_percentage != progress_percentage {
-                    progress_percentage = new_progress_percentage;
-                    update_channel.send(Ok(IndexingProgress::Progress {
-                        ticks: (processed_bytes, file_size),
-                    }))?;
-                }
-            }
         }
+        progress_reporter.make_progress(len);
         buf = vec![];
     }
     if stopped {
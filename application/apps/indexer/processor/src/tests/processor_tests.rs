@@ -66,7 +66,8 @@ mod tests {
                         content,
                     );
                 }
-                Ok(Ok(IndexingProgress::Progress { ticks: _t })) => {
+                Ok(Ok(IndexingProgress::Progress { .. }))
+                | Ok(Ok(IndexingProgress::Processed { .. })) => {
                     trace!("progress...");
                 }
                 Ok(Ok(IndexingProgress::GotItem { item: chunk })) => {
@@ -130,7 +131,8 @@ mod tests {
                     assert_eq!(0, chunks.len(), "empty file should produce 0 chunks");
                     break;
                 }
-                Ok(Ok(IndexingProgress::Progress { ticks: _t })) => {
+                Ok(Ok(IndexingProgress::Progress { .. }))
+                | Ok(Ok(IndexingProgress::Processed { .. })) => {
                     println!("progress...");
                 }
                 Ok(Err(Notification {
@@ -195,7 +197,8 @@ mod tests {
                     assert_eq!(0, chunks[0].r.0, "first chunk row should start with 0");
                     break;
                 }
-                Ok(Ok(IndexingProgress::Progress { ticks: _t })) => {
+                Ok(Ok(IndexingProgress::Progress { .. }))
+                | Ok(Ok(IndexingProgress::Processed { .. })) => {
                     println!("progress...");
                 }
                 Ok(Err(Notification {
@@ -333,7 +336,8 @@ mod tests {
                     assert_eq!(true, chunks_fit_together(&chunks), "chunks need to fit");
                     break;
                 }
-                Ok(Ok(IndexingProgress::Progress { ticks: _t })) => {
+                Ok(Ok(IndexingProgress::Progress { .. }))
+                | Ok(Ok(IndexingProgress::Processed { .. })) => {
                     trace!("progress...");
                 }
                 Ok(Ok(IndexingProgress::GotItem { item: chunk })) => {
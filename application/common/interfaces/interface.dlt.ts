@@ -27,8 +27,22 @@ export interface DltFilterConf {
 	app_ids?: Array<string>;
 	ecu_ids?: Array<string>;
 	context_ids?: Array<string>;
+	exclude_app_ids?: Array<string>;
+	exclude_ecu_ids?: Array<string>;
+	exclude_context_ids?: Array<string>;
+	app_log_levels?: { [app_id: string]: DltLogLevel };
+	context_log_levels?: { [context_id: string]: DltLogLevel };
+	message_types?: Array<DltMessageTypeFilter>;
+	exclude_message_types?: Array<DltMessageTypeFilter>;
+	verbose?: boolean;
+	/// ms since epoch, compared with the storage header
+	from_timestamp?: number;
+	to_timestamp?: number;
+	skip_without_extended_header?: boolean;
 }
 
+export type DltMessageTypeFilter = 'log' | 'app_trace' | 'network_trace' | 'control' | 'unknown';
+
 export enum DltLogLevel {
 	Fatal = 0x1 << 4,
 	Error = 0x2 << 4,
@@ -54,6 +68,48 @@ export interface StatisticInfo {
 	context_ids: Array<[string, LevelDistribution]>;
 	ecu_ids: Array<[string, LevelDistribution]>;
 	contained_non_verbose: boolean;
+	lost_messages: Array<LostMessages>;
+	/// first and last storage time in ms since epoch
+	time_range?: [number, number];
+	timeline: Timeline;
+	/// messages left out of time_range and timeline (storage time before 2000)
+	implausible_storage_times: number;
+	app_id_bytes: Array<[string, number]>;
+}
+
+/// message counts over the time range of a file, bucket_size is in ms
+export interface Timeline {
+	bucket_size: number;
+	buckets: Array<TimeBucket>;
+}
+
+export interface TimeBucket {
+	start: number;
+	levels: LevelDistribution;
+	app_ids: Array<[string, number]>;
+}
+
+/// gaps in the message counter of one sender
+export interface LostMessages {
+	ecu_id?: string;
+	session_id?: number;
+	app_id?: string;
+	context_id?: string;
+	gaps: number;
+	lost: number;
+}
+
+/// lines, offsets and times (ms since epoch) of one run of an ECU
+export interface EcuLifecycle {
+	ecu_id: string;
+	start: 'FirstMessage' | 'TimestampReset' | 'SessionRestart';
+	start_line: number;
+	end_line: number;
+	start_offset: number;
+	end_offset: number;
+	start_time?: number;
+	end_time?: number;
+	message_count: number;
 }
 
 export interface IFibexConfig {